@@ -0,0 +1,317 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Matching of [`Multiaddr`]s against protocol-stack patterns.
+
+use libp2p_identity::PeerId;
+use multiaddr::{Multiaddr, Protocol};
+use std::{fmt, str::FromStr};
+
+/// A pattern describing the protocol stack of a [`Multiaddr`].
+///
+/// Patterns are written like multiaddresses, except that any protocol value may be replaced by
+/// `_` to match an arbitrary value, e.g. `/ip4/_/tcp/_` or `/dns4/example.com/tcp/_/ws`. The
+/// values matched by `_` are returned as [`Captures`].
+///
+/// A pattern has to match the whole address. As a special case, a trailing `/p2p/<peer-id>` of
+/// the address is accepted even if the pattern does not mention it. Use [`Captures::peer_id`] to
+/// extract it.
+///
+/// ```
+/// # use libp2p_core::{AddressPattern, Multiaddr};
+/// let pattern: AddressPattern = "/ip4/_/tcp/_".parse().unwrap();
+/// let address: Multiaddr = "/ip4/127.0.0.1/tcp/4001".parse().unwrap();
+///
+/// assert!(pattern.matches(&address));
+/// assert!(!pattern.matches(&"/ip4/127.0.0.1/udp/4001/quic-v1".parse().unwrap()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressPattern {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// Matches exactly this protocol, including its value.
+    Exact(Protocol<'static>),
+    /// Matches any value of the protocol with the given tag.
+    Any(String),
+}
+
+impl AddressPattern {
+    /// Returns whether the given address matches this pattern.
+    pub fn matches(&self, address: &Multiaddr) -> bool {
+        self.captures(address).is_some()
+    }
+
+    /// Matches the given address against this pattern, returning the protocols matched by `_`.
+    ///
+    /// Returns `None` if the address does not match.
+    pub fn captures<'a>(&self, address: &'a Multiaddr) -> Option<Captures<'a>> {
+        let mut protocols = address.iter();
+        let mut captured = Vec::new();
+
+        for segment in &self.segments {
+            let protocol = protocols.next()?;
+
+            match segment {
+                Segment::Exact(expected) if *expected == protocol => {}
+                Segment::Any(tag) if tag == protocol.tag() => captured.push(protocol),
+                _ => return None,
+            }
+        }
+
+        let peer_id = match protocols.next() {
+            None => None,
+            Some(Protocol::P2p(peer_id)) if protocols.next().is_none() => Some(peer_id),
+            Some(_) => return None,
+        };
+
+        let peer_id = peer_id.or_else(|| {
+            captured.iter().rev().find_map(|p| match p {
+                Protocol::P2p(peer_id) => Some(*peer_id),
+                _ => None,
+            })
+        });
+
+        Some(Captures {
+            protocols: captured,
+            peer_id,
+        })
+    }
+}
+
+impl FromStr for AddressPattern {
+    type Err = ParsePatternError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let mut parts = pattern.split('/');
+
+        if parts.next() != Some("") {
+            return Err(ParsePatternError::InvalidPattern);
+        }
+
+        let mut segments = Vec::new();
+
+        while let Some(tag) = parts.next() {
+            if tag.is_empty() {
+                return Err(ParsePatternError::InvalidPattern);
+            }
+
+            let protocol = match Protocol::from_str_parts(std::iter::once(tag)) {
+                Ok(protocol) => protocol,
+                Err(multiaddr::Error::InvalidProtocolString) => {
+                    let value = parts.next().ok_or(ParsePatternError::MissingValue)?;
+
+                    if value == "_" {
+                        segments.push(Segment::Any(tag.to_owned()));
+                        continue;
+                    }
+
+                    Protocol::from_str_parts([tag, value].into_iter())?
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            segments.push(Segment::Exact(protocol.acquire()));
+        }
+
+        if segments.is_empty() {
+            return Err(ParsePatternError::InvalidPattern);
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl fmt::Display for AddressPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            match segment {
+                Segment::Exact(protocol) => write!(f, "{protocol}")?,
+                Segment::Any(tag) => write!(f, "/{tag}/_")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The protocols of a [`Multiaddr`] matched by the `_` placeholders of an [`AddressPattern`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures<'a> {
+    protocols: Vec<Protocol<'a>>,
+    peer_id: Option<PeerId>,
+}
+
+impl<'a> Captures<'a> {
+    /// Returns the protocol matched by the `index`-th placeholder of the pattern.
+    pub fn get(&self, index: usize) -> Option<&Protocol<'a>> {
+        self.protocols.get(index)
+    }
+
+    /// Returns an iterator over the captured protocols, in the order of the placeholders.
+    pub fn iter(&self) -> impl Iterator<Item = &Protocol<'a>> {
+        self.protocols.iter()
+    }
+
+    /// Returns the number of captured protocols.
+    pub fn len(&self) -> usize {
+        self.protocols.len()
+    }
+
+    /// Returns whether the pattern contained no placeholders.
+    pub fn is_empty(&self) -> bool {
+        self.protocols.is_empty()
+    }
+
+    /// Returns the peer ID of the matched address, if it ends in `/p2p/<peer-id>`.
+    pub fn peer_id(&self) -> Option<PeerId> {
+        self.peer_id
+    }
+}
+
+/// Matches `address` against `pattern`, returning the protocols matched by `_`.
+///
+/// Returns `Ok(None)` if the address does not match. See [`AddressPattern`] for the pattern
+/// syntax.
+pub fn matches<'a>(
+    address: &'a Multiaddr,
+    pattern: &str,
+) -> Result<Option<Captures<'a>>, ParsePatternError> {
+    Ok(pattern.parse::<AddressPattern>()?.captures(address))
+}
+
+/// Error when parsing an [`AddressPattern`].
+#[derive(Debug, thiserror::Error)]
+pub enum ParsePatternError {
+    #[error("pattern must be a non-empty sequence of `/`-separated protocols")]
+    InvalidPattern,
+    #[error("unknown protocol `{0}`")]
+    UnknownProtocol(String),
+    #[error("protocol is missing its value")]
+    MissingValue,
+    #[error("invalid protocol value")]
+    InvalidValue(#[source] multiaddr::Error),
+}
+
+impl From<multiaddr::Error> for ParsePatternError {
+    fn from(error: multiaddr::Error) -> Self {
+        match error {
+            multiaddr::Error::UnknownProtocolString(tag) => ParsePatternError::UnknownProtocol(tag),
+            other => ParsePatternError::InvalidValue(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn captures<'a>(address: &'a Multiaddr, pattern: &str) -> Option<Captures<'a>> {
+        matches(address, pattern).unwrap()
+    }
+
+    #[test]
+    fn matches_protocol_stack() {
+        let address: Multiaddr = "/ip4/192.0.2.1/tcp/4001".parse().unwrap();
+
+        assert!(captures(&address, "/ip4/_/tcp/_").is_some());
+        assert!(captures(&address, "/ip4/192.0.2.1/tcp/_").is_some());
+        assert!(captures(&address, "/ip4/192.0.2.1/tcp/4001").is_some());
+        assert!(captures(&address, "/ip4/192.0.2.2/tcp/_").is_none());
+        assert!(captures(&address, "/ip6/_/tcp/_").is_none());
+        assert!(captures(&address, "/ip4/_").is_none());
+        assert!(captures(&address, "/ip4/_/tcp/_/ws").is_none());
+    }
+
+    #[test]
+    fn captures_placeholders_in_order() {
+        let address: Multiaddr = "/dns4/example.com/tcp/443/wss".parse().unwrap();
+        let captures = captures(&address, "/dns4/_/tcp/_/wss").unwrap();
+
+        assert_eq!(captures.len(), 2);
+        assert_eq!(captures.get(0), Some(&Protocol::Dns4("example.com".into())));
+        assert_eq!(captures.get(1), Some(&Protocol::Tcp(443)));
+        assert_eq!(captures.peer_id(), None);
+    }
+
+    #[test]
+    fn extracts_trailing_peer_id() {
+        let peer_id = PeerId::random();
+        let address: Multiaddr = format!("/ip4/192.0.2.1/udp/4001/quic-v1/p2p/{peer_id}")
+            .parse()
+            .unwrap();
+
+        let implicit = captures(&address, "/ip4/_/udp/_/quic-v1").unwrap();
+        assert_eq!(implicit.len(), 2);
+        assert_eq!(implicit.peer_id(), Some(peer_id));
+
+        let explicit = captures(&address, "/ip4/_/udp/_/quic-v1/p2p/_").unwrap();
+        assert_eq!(explicit.len(), 3);
+        assert_eq!(explicit.get(2), Some(&Protocol::P2p(peer_id)));
+        assert_eq!(explicit.peer_id(), Some(peer_id));
+
+        let relayed: Multiaddr = format!("/ip4/192.0.2.1/tcp/4001/p2p/{peer_id}/p2p-circuit")
+            .parse()
+            .unwrap();
+        assert!(captures(&relayed, "/ip4/_/tcp/_").is_none());
+        assert_eq!(
+            captures(&relayed, "/ip4/_/tcp/_/p2p/_/p2p-circuit")
+                .unwrap()
+                .peer_id(),
+            Some(peer_id)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(matches!(
+            "".parse::<AddressPattern>(),
+            Err(ParsePatternError::InvalidPattern)
+        ));
+        assert!(matches!(
+            "ip4/_".parse::<AddressPattern>(),
+            Err(ParsePatternError::InvalidPattern)
+        ));
+        assert!(matches!(
+            "/ip4".parse::<AddressPattern>(),
+            Err(ParsePatternError::MissingValue)
+        ));
+        assert!(matches!(
+            "/foo/_".parse::<AddressPattern>(),
+            Err(ParsePatternError::UnknownProtocol(_))
+        ));
+        assert!(matches!(
+            "/tcp/foo".parse::<AddressPattern>(),
+            Err(ParsePatternError::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let pattern = "/ip4/_/tcp/4001/ws/p2p/_";
+
+        assert_eq!(
+            pattern.parse::<AddressPattern>().unwrap().to_string(),
+            pattern
+        );
+    }
+}
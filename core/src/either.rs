@@ -18,12 +18,13 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use crate::muxing::StreamMuxerEvent;
+use crate::muxing::{SendDatagramError, StreamMuxerEvent};
 use crate::{
     muxing::StreamMuxer,
     transport::{ListenerId, Transport, TransportError, TransportEvent},
     Multiaddr,
 };
+use bytes::Bytes;
 use either::Either;
 use futures::prelude::*;
 use pin_project::pin_project;
@@ -85,6 +86,20 @@ where
             future::Either::Right(inner) => inner.poll(cx).map_err(Either::Right),
         }
     }
+
+    fn send_datagram(self: Pin<&mut Self>, data: Bytes) -> Result<(), SendDatagramError> {
+        match self.as_pin_mut() {
+            future::Either::Left(inner) => inner.send_datagram(data),
+            future::Either::Right(inner) => inner.send_datagram(data),
+        }
+    }
+
+    fn max_datagram_size(&self) -> Option<usize> {
+        match self {
+            future::Either::Left(inner) => inner.max_datagram_size(),
+            future::Either::Right(inner) => inner.max_datagram_size(),
+        }
+    }
 }
 
 /// Implements `Future` and dispatches all method calls to either `First` or `Second`.
@@ -47,16 +47,23 @@ pub type Negotiated<T> = multistream_select::Negotiated<T>;
 
 mod translation;
 
+pub mod address_pattern;
+
 pub mod connection;
 pub mod either;
+pub mod multiaddr_builder;
+pub mod multiaddr_ext;
 pub mod muxing;
 pub mod peer_record;
 pub mod signed_envelope;
 pub mod transport;
 pub mod upgrade;
 
+pub use address_pattern::AddressPattern;
 pub use connection::{ConnectedPoint, Endpoint};
 pub use multiaddr::Multiaddr;
+pub use multiaddr_builder::MultiaddrBuilder;
+pub use multiaddr_ext::MultiaddrExt;
 pub use multihash;
 pub use muxing::StreamMuxer;
 pub use peer_record::PeerRecord;
@@ -0,0 +1,376 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Typed construction of [`Multiaddr`]s.
+//!
+//! See [`MultiaddrExt::builder`](crate::MultiaddrExt::builder).
+
+use libp2p_identity::PeerId;
+use multiaddr::{Multiaddr, Protocol};
+use multihash::Multihash;
+use std::borrow::Cow;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Builds a [`Multiaddr`] component by component, validating the order of the protocols.
+///
+/// Created via [`MultiaddrExt::builder`](crate::MultiaddrExt::builder). The first protocol
+/// that cannot follow the previous one is reported by [`MultiaddrBuilder::build`], all
+/// protocols added after it are ignored.
+///
+/// ```
+/// # use libp2p_core::{Multiaddr, MultiaddrExt};
+/// # use std::net::Ipv4Addr;
+/// let address = Multiaddr::builder()
+///     .ip4(Ipv4Addr::LOCALHOST)
+///     .tcp(4001)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(address, "/ip4/127.0.0.1/tcp/4001".parse::<Multiaddr>().unwrap());
+/// assert!(Multiaddr::builder().tcp(4001).build().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiaddrBuilder {
+    address: Multiaddr,
+    layer: Layer,
+    error: Option<InvalidProtocolOrder>,
+}
+
+/// The protocol most recently added to a [`MultiaddrBuilder`], determining which protocols may
+/// follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layer {
+    Empty,
+    Network,
+    Dnsaddr,
+    Memory,
+    Tcp,
+    Tls,
+    Ws,
+    Udp,
+    Quic,
+    WebTransport,
+    WebRtcDirect,
+    Peer,
+    Circuit,
+    RelayedPeer,
+}
+
+impl Layer {
+    /// Returns the layer after appending `protocol`, if it may follow this layer.
+    fn next(self, protocol: &Protocol<'_>) -> Option<Layer> {
+        use Layer::*;
+
+        let next = match (self, protocol) {
+            (Empty, Protocol::Ip4(_) | Protocol::Ip6(_)) => Network,
+            (Empty, Protocol::Dns(_) | Protocol::Dns4(_) | Protocol::Dns6(_)) => Network,
+            (Empty, Protocol::Dnsaddr(_)) => Dnsaddr,
+            (Empty, Protocol::Memory(_)) => Memory,
+            (Network, Protocol::Tcp(_)) => Tcp,
+            (Network, Protocol::Udp(_)) => Udp,
+            (Tcp, Protocol::Tls) => Tls,
+            (Tcp | Tls, Protocol::Ws(_)) | (Tcp, Protocol::Wss(_)) => Ws,
+            (Udp, Protocol::QuicV1) => Quic,
+            (Udp, Protocol::WebRTCDirect) => WebRtcDirect,
+            (Quic, Protocol::WebTransport) => WebTransport,
+            (WebTransport, Protocol::Certhash(_)) => WebTransport,
+            (WebRtcDirect, Protocol::Certhash(_)) => WebRtcDirect,
+            (
+                Empty | Dnsaddr | Memory | Tcp | Tls | Ws | Quic | WebTransport | WebRtcDirect,
+                Protocol::P2p(_),
+            ) => Peer,
+            (Peer, Protocol::P2pCircuit) => Circuit,
+            (Circuit, Protocol::P2p(_)) => RelayedPeer,
+            _ => return None,
+        };
+
+        Some(next)
+    }
+}
+
+impl MultiaddrBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            address: Multiaddr::empty(),
+            layer: Layer::Empty,
+            error: None,
+        }
+    }
+
+    /// Appends `/ip4/<addr>`.
+    pub fn ip4(self, addr: Ipv4Addr) -> Self {
+        self.push(Protocol::Ip4(addr))
+    }
+
+    /// Appends `/ip6/<addr>`.
+    pub fn ip6(self, addr: Ipv6Addr) -> Self {
+        self.push(Protocol::Ip6(addr))
+    }
+
+    /// Appends `/dns/<name>`.
+    pub fn dns(self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.push(Protocol::Dns(name.into()))
+    }
+
+    /// Appends `/dns4/<name>`.
+    pub fn dns4(self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.push(Protocol::Dns4(name.into()))
+    }
+
+    /// Appends `/dns6/<name>`.
+    pub fn dns6(self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.push(Protocol::Dns6(name.into()))
+    }
+
+    /// Appends `/dnsaddr/<name>`.
+    pub fn dnsaddr(self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.push(Protocol::Dnsaddr(name.into()))
+    }
+
+    /// Appends `/memory/<port>`.
+    pub fn memory(self, port: u64) -> Self {
+        self.push(Protocol::Memory(port))
+    }
+
+    /// Appends `/tcp/<port>`.
+    pub fn tcp(self, port: u16) -> Self {
+        self.push(Protocol::Tcp(port))
+    }
+
+    /// Appends `/udp/<port>`.
+    pub fn udp(self, port: u16) -> Self {
+        self.push(Protocol::Udp(port))
+    }
+
+    /// Appends `/tls`.
+    pub fn tls(self) -> Self {
+        self.push(Protocol::Tls)
+    }
+
+    /// Appends `/ws`.
+    pub fn ws(self) -> Self {
+        self.push(Protocol::Ws(Cow::Borrowed("/")))
+    }
+
+    /// Appends `/wss`.
+    pub fn wss(self) -> Self {
+        self.push(Protocol::Wss(Cow::Borrowed("/")))
+    }
+
+    /// Appends `/quic-v1`.
+    pub fn quic_v1(self) -> Self {
+        self.push(Protocol::QuicV1)
+    }
+
+    /// Appends `/webtransport`.
+    pub fn webtransport(self) -> Self {
+        self.push(Protocol::WebTransport)
+    }
+
+    /// Appends `/webrtc-direct`.
+    pub fn webrtc_direct(self) -> Self {
+        self.push(Protocol::WebRTCDirect)
+    }
+
+    /// Appends `/certhash/<hash>`.
+    pub fn certhash(self, hash: Multihash<64>) -> Self {
+        self.push(Protocol::Certhash(hash))
+    }
+
+    /// Appends `/p2p/<peer-id>`.
+    pub fn p2p(self, peer_id: PeerId) -> Self {
+        self.push(Protocol::P2p(peer_id))
+    }
+
+    /// Appends `/p2p-circuit`.
+    pub fn p2p_circuit(self) -> Self {
+        self.push(Protocol::P2pCircuit)
+    }
+
+    /// Returns the built address or the first protocol that was added out of order.
+    pub fn build(self) -> Result<Multiaddr, InvalidProtocolOrder> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.address),
+        }
+    }
+
+    fn push(mut self, protocol: Protocol<'static>) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+
+        match self.layer.next(&protocol) {
+            Some(layer) => {
+                self.layer = layer;
+                self.address.push(protocol);
+            }
+            None => {
+                self.error = Some(InvalidProtocolOrder {
+                    protocol: protocol.tag(),
+                    previous: self.address.iter().last().map(|p| p.tag()),
+                });
+            }
+        }
+
+        self
+    }
+}
+
+/// A protocol was added to a [`MultiaddrBuilder`] in a position it cannot appear in.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("`/{protocol}` cannot follow {}", previous.map(|p| format!("`/{p}`")).unwrap_or_else(|| "the start of an address".to_owned()))]
+pub struct InvalidProtocolOrder {
+    protocol: &'static str,
+    previous: Option<&'static str>,
+}
+
+impl InvalidProtocolOrder {
+    /// The name of the protocol that was added out of order.
+    pub fn protocol(&self) -> &'static str {
+        self.protocol
+    }
+
+    /// The name of the protocol it was added after, if any.
+    pub fn previous(&self) -> Option<&'static str> {
+        self.previous
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MultiaddrExt;
+
+    fn parse(address: &str) -> Multiaddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn builds_common_addresses() {
+        let peer_id = PeerId::random();
+
+        assert_eq!(
+            Multiaddr::builder()
+                .ip4(Ipv4Addr::LOCALHOST)
+                .tcp(4001)
+                .p2p(peer_id)
+                .build()
+                .unwrap(),
+            parse(&format!("/ip4/127.0.0.1/tcp/4001/p2p/{peer_id}"))
+        );
+        assert_eq!(
+            Multiaddr::builder()
+                .ip6(Ipv6Addr::LOCALHOST)
+                .udp(4001)
+                .quic_v1()
+                .build()
+                .unwrap(),
+            parse("/ip6/::1/udp/4001/quic-v1")
+        );
+        assert_eq!(
+            Multiaddr::builder()
+                .dns4("example.com")
+                .tcp(443)
+                .tls()
+                .ws()
+                .build()
+                .unwrap(),
+            parse("/dns4/example.com/tcp/443/tls/ws")
+        );
+        assert_eq!(
+            Multiaddr::builder().memory(1).build().unwrap(),
+            parse("/memory/1")
+        );
+    }
+
+    #[test]
+    fn builds_relayed_addresses() {
+        let relay = PeerId::random();
+        let destination = PeerId::random();
+
+        assert_eq!(
+            Multiaddr::builder()
+                .ip4(Ipv4Addr::LOCALHOST)
+                .tcp(4001)
+                .p2p(relay)
+                .p2p_circuit()
+                .p2p(destination)
+                .build()
+                .unwrap(),
+            parse(&format!(
+                "/ip4/127.0.0.1/tcp/4001/p2p/{relay}/p2p-circuit/p2p/{destination}"
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_orderings() {
+        let error = Multiaddr::builder().tcp(4001).build().unwrap_err();
+        assert_eq!(error.protocol(), "tcp");
+        assert_eq!(error.previous(), None);
+
+        let error = Multiaddr::builder()
+            .ip4(Ipv4Addr::LOCALHOST)
+            .quic_v1()
+            .build()
+            .unwrap_err();
+        assert_eq!(error.protocol(), "quic-v1");
+        assert_eq!(error.previous(), Some("ip4"));
+
+        let error = Multiaddr::builder()
+            .ip4(Ipv4Addr::LOCALHOST)
+            .tcp(4001)
+            .p2p(PeerId::random())
+            .tcp(4002)
+            .build()
+            .unwrap_err();
+        assert_eq!(error.protocol(), "tcp");
+        assert_eq!(error.previous(), Some("p2p"));
+
+        assert!(Multiaddr::builder()
+            .ip4(Ipv4Addr::LOCALHOST)
+            .ip4(Ipv4Addr::LOCALHOST)
+            .build()
+            .is_err());
+        assert!(Multiaddr::builder()
+            .ip4(Ipv4Addr::LOCALHOST)
+            .udp(4001)
+            .p2p_circuit()
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn reports_first_invalid_protocol() {
+        let error = Multiaddr::builder()
+            .udp(4001)
+            .ip4(Ipv4Addr::LOCALHOST)
+            .tcp(4001)
+            .build()
+            .unwrap_err();
+
+        assert_eq!(error.protocol(), "udp");
+        assert_eq!(
+            error.to_string(),
+            "`/udp` cannot follow the start of an address"
+        );
+    }
+}
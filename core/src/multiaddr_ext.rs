@@ -0,0 +1,229 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Normalization of [`Multiaddr`]s, e.g. for deduplicating addresses.
+
+use crate::MultiaddrBuilder;
+use libp2p_identity::PeerId;
+use multiaddr::{Multiaddr, Protocol};
+use std::borrow::Cow;
+
+/// Extension methods for [`Multiaddr`].
+pub trait MultiaddrExt {
+    /// Returns the canonical form of this address.
+    ///
+    /// Two addresses referring to the same endpoint in different notations have the same
+    /// canonical form. Canonicalization
+    ///
+    /// - expands the aliases `/wss` and `/https` into `/tls/ws` and `/tls/http`,
+    /// - replaces IPv4-mapped IPv6 addresses by their IPv4 address,
+    /// - lower-cases DNS names and removes their trailing dot,
+    /// - sorts consecutive `/certhash` components, whose order has no meaning.
+    ///
+    /// A trailing `/p2p/<peer-id>` is kept, use [`MultiaddrExt::split_peer_id`] to separate it.
+    /// Canonicalization is idempotent.
+    ///
+    /// ```
+    /// # use libp2p_core::{multiaddr_ext::MultiaddrExt, Multiaddr};
+    /// let address: Multiaddr = "/dns4/Example.COM/tcp/443/wss".parse().unwrap();
+    ///
+    /// assert_eq!(
+    ///     address.canonical(),
+    ///     "/dns4/example.com/tcp/443/tls/ws".parse::<Multiaddr>().unwrap()
+    /// );
+    /// ```
+    fn canonical(&self) -> Multiaddr;
+
+    /// Returns the peer ID of a trailing `/p2p/<peer-id>`, if any.
+    fn peer_id(&self) -> Option<PeerId>;
+
+    /// Separates a trailing `/p2p/<peer-id>` from this address.
+    fn split_peer_id(&self) -> (Multiaddr, Option<PeerId>);
+
+    /// Returns a [`MultiaddrBuilder`] for constructing an address from typed components.
+    fn builder() -> MultiaddrBuilder
+    where
+        Self: Sized;
+}
+
+impl MultiaddrExt for Multiaddr {
+    fn canonical(&self) -> Multiaddr {
+        let mut canonical = Multiaddr::with_capacity(self.len());
+        let mut certhashes = Vec::new();
+
+        for protocol in self.iter() {
+            if let Protocol::Certhash(hash) = protocol {
+                certhashes.push(hash);
+                continue;
+            }
+            push_certhashes(&mut canonical, &mut certhashes);
+
+            match protocol {
+                Protocol::Wss(path) => {
+                    canonical.push(Protocol::Tls);
+                    canonical.push(Protocol::Ws(path));
+                }
+                Protocol::Https => {
+                    canonical.push(Protocol::Tls);
+                    canonical.push(Protocol::Http);
+                }
+                Protocol::Ip6(ip) => match ip.to_ipv4_mapped() {
+                    Some(ip) => canonical.push(Protocol::Ip4(ip)),
+                    None => canonical.push(Protocol::Ip6(ip)),
+                },
+                Protocol::Dns(name) => canonical.push(Protocol::Dns(canonical_name(name))),
+                Protocol::Dns4(name) => canonical.push(Protocol::Dns4(canonical_name(name))),
+                Protocol::Dns6(name) => canonical.push(Protocol::Dns6(canonical_name(name))),
+                Protocol::Dnsaddr(name) => canonical.push(Protocol::Dnsaddr(canonical_name(name))),
+                protocol => canonical.push(protocol),
+            }
+        }
+        push_certhashes(&mut canonical, &mut certhashes);
+
+        canonical
+    }
+
+    fn peer_id(&self) -> Option<PeerId> {
+        match self.iter().last()? {
+            Protocol::P2p(peer_id) => Some(peer_id),
+            _ => None,
+        }
+    }
+
+    fn split_peer_id(&self) -> (Multiaddr, Option<PeerId>) {
+        let mut address = self.clone();
+
+        match address.pop() {
+            Some(Protocol::P2p(peer_id)) => (address, Some(peer_id)),
+            _ => (self.clone(), None),
+        }
+    }
+
+    fn builder() -> MultiaddrBuilder {
+        MultiaddrBuilder::new()
+    }
+}
+
+fn push_certhashes(address: &mut Multiaddr, certhashes: &mut Vec<multihash::Multihash<64>>) {
+    certhashes.sort_by_key(|hash| hash.to_bytes());
+
+    for hash in certhashes.drain(..) {
+        address.push(Protocol::Certhash(hash));
+    }
+}
+
+fn canonical_name(name: Cow<'_, str>) -> Cow<'_, str> {
+    let trimmed = name.strip_suffix('.').unwrap_or(&name);
+
+    if trimmed.len() == name.len() && !name.bytes().any(|b| b.is_ascii_uppercase()) {
+        return name;
+    }
+
+    Cow::Owned(trimmed.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEER_ID: &str = "12D3KooWGRUVh7ugC5D3EEsRzCFfPLu2LwbNGNi4mHF8wqbq1BT4";
+
+    fn canonical(address: &str) -> Multiaddr {
+        address.parse::<Multiaddr>().unwrap().canonical()
+    }
+
+    #[test]
+    fn expands_aliases() {
+        assert_eq!(
+            canonical("/dns/example.com/tcp/443/wss"),
+            canonical("/dns/example.com/tcp/443/tls/ws")
+        );
+        assert_eq!(
+            canonical("/dns/example.com/tcp/443/https"),
+            "/dns/example.com/tcp/443/tls/http".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn normalizes_ip_addresses_and_dns_names() {
+        assert_eq!(
+            canonical("/ip6/::ffff:192.0.2.1/tcp/4001"),
+            "/ip4/192.0.2.1/tcp/4001".parse().unwrap()
+        );
+        assert_eq!(
+            canonical("/ip6/2001:db8::1/tcp/4001"),
+            "/ip6/2001:db8::1/tcp/4001".parse().unwrap()
+        );
+        assert_eq!(
+            canonical("/dns4/Example.COM./tcp/4001"),
+            "/dns4/example.com/tcp/4001".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn sorts_certhashes() {
+        let hash1 = "uEiDDq4_xNyDorZBH3TlGazyJdOWSwvo4PUo5YHFMrvDE8g";
+        let hash2 = "uEiAkH5a4DPGKUuOBjYw0CgwjvcJCJMD2K_1aluKR_tpevQ";
+
+        assert_eq!(
+            canonical(&format!(
+                "/ip4/192.0.2.1/udp/4001/quic-v1/webtransport/certhash/{hash1}/certhash/{hash2}"
+            )),
+            canonical(&format!(
+                "/ip4/192.0.2.1/udp/4001/quic-v1/webtransport/certhash/{hash2}/certhash/{hash1}"
+            ))
+        );
+    }
+
+    #[test]
+    fn canonicalization_is_idempotent() {
+        for address in [
+            "/ip4/192.0.2.1/tcp/4001",
+            "/ip6/::ffff:192.0.2.1/udp/4001/quic-v1",
+            "/dns4/Example.COM./tcp/443/wss/p2p/12D3KooWGRUVh7ugC5D3EEsRzCFfPLu2LwbNGNi4mHF8wqbq1BT4",
+            "/dnsaddr/BOOTSTRAP.libp2p.io/https",
+            "/ip4/192.0.2.1/udp/4001/quic-v1/webtransport/certhash/uEiDDq4_xNyDorZBH3TlGazyJdOWSwvo4PUo5YHFMrvDE8g/certhash/uEiAkH5a4DPGKUuOBjYw0CgwjvcJCJMD2K_1aluKR_tpevQ",
+        ] {
+            let once = canonical(address);
+
+            assert_eq!(once.canonical(), once, "{address}");
+        }
+    }
+
+    #[test]
+    fn extracts_trailing_peer_id() {
+        let peer_id: PeerId = PEER_ID.parse().unwrap();
+        let address: Multiaddr = format!("/dns4/example.com/tcp/1/p2p/{PEER_ID}")
+            .parse()
+            .unwrap();
+
+        assert_eq!(address.peer_id(), Some(peer_id));
+        assert_eq!(
+            address.split_peer_id(),
+            ("/dns4/example.com/tcp/1".parse().unwrap(), Some(peer_id))
+        );
+
+        let relayed: Multiaddr = format!("/ip4/192.0.2.1/tcp/1/p2p/{PEER_ID}/p2p-circuit")
+            .parse()
+            .unwrap();
+        assert_eq!(relayed.peer_id(), None);
+        assert_eq!(relayed.split_peer_id(), (relayed.clone(), None));
+    }
+}
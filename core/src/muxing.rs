@@ -50,10 +50,12 @@
 //! The upgrade process will take ownership of the connection, which makes it possible for the
 //! implementation of `StreamMuxer` to control everything that happens on the wire.
 
+use bytes::Bytes;
 use futures::{task::Context, task::Poll, AsyncRead, AsyncWrite};
 use multiaddr::Multiaddr;
 use std::future::Future;
 use std::pin::Pin;
+use std::{fmt, io};
 
 pub use self::boxed::StreamMuxerBox;
 pub use self::boxed::SubstreamBox;
@@ -109,6 +111,25 @@ pub trait StreamMuxer {
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Result<StreamMuxerEvent, Self::Error>>;
+
+    /// Sends an unreliable datagram to the remote.
+    ///
+    /// Delivery is not guaranteed and datagrams may arrive out of order. The remote reports them
+    /// as [`StreamMuxerEvent::DatagramReceived`].
+    ///
+    /// The default implementation fails with [`SendDatagramError::Unsupported`].
+    fn send_datagram(self: Pin<&mut Self>, data: Bytes) -> Result<(), SendDatagramError> {
+        let _ = data;
+        Err(SendDatagramError::Unsupported)
+    }
+
+    /// Returns the maximum size of a datagram that can currently be sent, or `None` if datagrams
+    /// are not supported.
+    ///
+    /// The default implementation returns `None`.
+    fn max_datagram_size(&self) -> Option<usize> {
+        None
+    }
 }
 
 /// An event produced by a [`StreamMuxer`].
@@ -116,6 +137,38 @@ pub trait StreamMuxer {
 pub enum StreamMuxerEvent {
     /// The address of the remote has changed.
     AddressChange(Multiaddr),
+    /// A datagram has been received from the remote, see [`StreamMuxer::send_datagram`].
+    DatagramReceived(Bytes),
+}
+
+/// Error of [`StreamMuxer::send_datagram`].
+#[derive(Debug)]
+pub enum SendDatagramError {
+    /// Datagrams are not supported by the muxer, or not enabled locally or by the remote.
+    Unsupported,
+    /// The datagram is larger than [`StreamMuxer::max_datagram_size`].
+    TooLarge,
+    /// The connection failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for SendDatagramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendDatagramError::Unsupported => write!(f, "Datagrams are not supported"),
+            SendDatagramError::TooLarge => write!(f, "Datagram exceeds the maximum size"),
+            SendDatagramError::Io(e) => write!(f, "Failed to send datagram: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SendDatagramError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SendDatagramError::Unsupported | SendDatagramError::TooLarge => None,
+            SendDatagramError::Io(e) => Some(e),
+        }
+    }
 }
 
 /// Extension trait for [`StreamMuxer`].
@@ -158,6 +211,14 @@ pub trait StreamMuxerExt: StreamMuxer + Sized {
         Pin::new(self).poll_close(cx)
     }
 
+    /// Convenience function for calling [`StreamMuxer::send_datagram`] for [`StreamMuxer`]s that are `Unpin`.
+    fn send_datagram_unpin(&mut self, data: Bytes) -> Result<(), SendDatagramError>
+    where
+        Self: Unpin,
+    {
+        Pin::new(self).send_datagram(data)
+    }
+
     /// Returns a future for closing this [`StreamMuxer`].
     fn close(self) -> Close<Self> {
         Close(self)
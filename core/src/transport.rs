@@ -29,20 +29,24 @@ use futures::prelude::*;
 use multiaddr::Multiaddr;
 use std::{
     error::Error,
-    fmt,
+    fmt, io,
     pin::Pin,
     sync::atomic::{AtomicUsize, Ordering},
     task::{Context, Poll},
 };
 
 pub mod and_then;
+pub mod chaos;
 pub mod choice;
 pub mod dummy;
 pub mod global_only;
 pub mod map;
 pub mod map_err;
+pub mod map_listen_addr;
 pub mod memory;
+pub mod rate_limited;
 pub mod timeout;
+pub mod trace;
 pub mod upgrade;
 
 mod boxed;
@@ -204,6 +208,102 @@ pub trait Transport {
         map_err::MapErr::new(self, f)
     }
 
+    /// Applies a function on the listen addresses reported by the transport.
+    ///
+    /// Only the addresses in [`TransportEvent::NewAddress`] and [`TransportEvent::AddressExpired`]
+    /// are rewritten; the address passed to [`Transport::listen_on`] is used unchanged to bind the
+    /// listener. This is useful when running behind a NAT with a static port mapping, where the
+    /// address that other peers can reach differs from the local one.
+    ///
+    /// ```
+    /// # use libp2p_core::{multiaddr::Protocol, transport::MemoryTransport, Transport};
+    /// # use std::net::Ipv4Addr;
+    /// let transport = MemoryTransport::default().map_listen_addr(|addr| {
+    ///     addr.replace(0, |protocol| match protocol {
+    ///         Protocol::Ip4(ip) if ip.is_private() => Some(Protocol::Ip4(Ipv4Addr::new(203, 0, 113, 7))),
+    ///         _ => None,
+    ///     })
+    ///     .unwrap_or(addr)
+    /// });
+    /// ```
+    fn map_listen_addr<F>(self, f: F) -> map_listen_addr::MapListenAddr<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Multiaddr) -> Multiaddr,
+    {
+        map_listen_addr::MapListenAddr::new(self, f)
+    }
+
+    /// Limits the bandwidth of the connections created by the transport.
+    ///
+    /// Reads and writes on every connection are throttled with a token bucket, according to the
+    /// limit per connection and the limit shared by all connections in `config`. Apply it to the
+    /// raw transport, i.e. before [`Transport::upgrade`], to limit the bandwidth of the
+    /// connection including all protocols running on it.
+    ///
+    /// ```
+    /// # use libp2p_core::{transport::{rate_limited::RateLimitConfig, MemoryTransport}, Transport};
+    /// # use std::num::NonZeroU64;
+    /// let transport = MemoryTransport::default().rate_limited(RateLimitConfig {
+    ///     per_connection_bps: NonZeroU64::new(1024 * 1024),
+    ///     global_bps: NonZeroU64::new(10 * 1024 * 1024),
+    /// });
+    /// ```
+    fn rate_limited(self, config: rate_limited::RateLimitConfig) -> rate_limited::RateLimited<Self>
+    where
+        Self: Sized,
+        Self::Output: AsyncRead + AsyncWrite,
+    {
+        rate_limited::RateLimited::new(self, config)
+    }
+
+    /// Injects failures into the connections created by the transport, for testing.
+    ///
+    /// Connections can be dropped after a number of bytes, be delayed on establishment and be
+    /// reset randomly, according to `config`. The random failures are reproducible given the
+    /// seed in `config`. Apply it to the raw transport, i.e. before [`Transport::upgrade`], to
+    /// test how the protocols running on the connection cope with an unreliable network.
+    ///
+    /// ```
+    /// # use libp2p_core::{transport::{chaos::ChaosConfig, MemoryTransport}, Transport};
+    /// let transport = MemoryTransport::default().with_chaos(ChaosConfig {
+    ///     seed: 42,
+    ///     reset_probability: 0.01,
+    ///     ..Default::default()
+    /// });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`ChaosConfig::reset_probability`](chaos::ChaosConfig::reset_probability) is
+    /// not within `0.0..=1.0`.
+    fn with_chaos(self, config: chaos::ChaosConfig) -> chaos::Chaos<Self>
+    where
+        Self: Sized,
+        Self::Output: AsyncRead + AsyncWrite,
+    {
+        chaos::Chaos::new(self, config)
+    }
+
+    /// Captures the bytes read from and written to the connections created by the transport.
+    ///
+    /// Apply it to the raw transport, i.e. before [`Transport::upgrade`], to capture the bytes on
+    /// the wire. Use [`Authenticated::traced`](upgrade::Authenticated::traced) to capture the
+    /// bytes after decryption instead. See [`trace`] for the format of the capture file.
+    ///
+    /// ```no_run
+    /// # use libp2p_core::{transport::{trace::Tracer, MemoryTransport}, Transport};
+    /// let transport = MemoryTransport::default().traced(Tracer::create("wire.trace")?);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    fn traced(self, tracer: trace::Tracer) -> trace::Traced<Self>
+    where
+        Self: Sized,
+        Self::Output: AsyncRead + AsyncWrite,
+    {
+        trace::Traced::new(self, tracer)
+    }
+
     /// Adds a fallback transport that is used when encountering errors
     /// while establishing inbound or outbound connections.
     ///
@@ -560,3 +660,63 @@ where
         }
     }
 }
+
+/// A failure to resolve a name of a dialed address, e.g. the domain of a `/dns4` address.
+///
+/// Transports resolving names, like the one of `libp2p-dns`, report it as part of their
+/// errors, allowing to tell resolution failures apart from other failures via [`find_error`].
+#[derive(Debug)]
+pub struct NameResolutionError(Box<dyn Error + Send + Sync>);
+
+impl NameResolutionError {
+    pub fn new(error: impl Error + Send + Sync + 'static) -> Self {
+        Self(Box::new(error))
+    }
+
+    /// The error of the resolver.
+    pub fn get_ref(&self) -> &(dyn Error + Send + Sync + 'static) {
+        self.0.as_ref()
+    }
+
+    /// The error of the resolver, if it is of type `E`.
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+}
+
+impl fmt::Display for NameResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Name resolution failed")
+    }
+}
+
+impl Error for NameResolutionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+/// Searches the given error and its sources for an error of type `E`.
+///
+/// This also looks into the [`io::Error`]s produced by boxing a transport,
+/// e.g. the ones reported by a `Swarm` for failed connections.
+pub fn find_error<'a, E: Error + 'static>(error: &'a (dyn Error + 'static)) -> Option<&'a E> {
+    let mut current = Some(error);
+
+    while let Some(error) = current {
+        if let Some(error) = error.downcast_ref::<E>() {
+            return Some(error);
+        }
+
+        // `io::Error::source` skips the error it wraps.
+        current = match error
+            .downcast_ref::<io::Error>()
+            .and_then(io::Error::get_ref)
+        {
+            Some(inner) => Some(inner),
+            None => error.source(),
+        };
+    }
+
+    None
+}
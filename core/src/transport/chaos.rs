@@ -0,0 +1,359 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Transports that inject failures into their connections, for testing.
+//!
+//! See [`Transport::with_chaos`].
+
+use crate::{
+    transport::{ListenerId, TransportError, TransportEvent},
+    Multiaddr, Transport,
+};
+use futures::{prelude::*, ready};
+use futures_timer::Delay;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    cmp, io,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// Failures injected by a [`Chaos`] transport.
+///
+/// The default configuration injects no failures.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ChaosConfig {
+    /// Seed of the random number generator deciding about connection resets.
+    ///
+    /// Given the same seed and the same sequence of connections and I/O operations, the same
+    /// failures are injected.
+    pub seed: u64,
+    /// Number of bytes after which a connection fails with [`io::ErrorKind::ConnectionAborted`].
+    ///
+    /// The bytes read from and written to the connection are counted together.
+    pub drop_after_bytes: Option<u64>,
+    /// Delay until a dialed or accepted connection is handed out.
+    pub handshake_delay: Duration,
+    /// Probability of a connection being reset after each successful read or write, failing the
+    /// following reads and writes with [`io::ErrorKind::ConnectionReset`].
+    ///
+    /// Must be within `0.0..=1.0`.
+    pub reset_probability: f64,
+}
+
+/// See [`Transport::with_chaos`].
+#[derive(Debug)]
+#[pin_project::pin_project]
+pub struct Chaos<T> {
+    #[pin]
+    transport: T,
+    config: ChaosConfig,
+    /// Seeds the random number generator of every new connection.
+    rng: StdRng,
+}
+
+impl<T> Chaos<T> {
+    pub(crate) fn new(transport: T, config: ChaosConfig) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&config.reset_probability),
+            "reset probability must be within 0.0..=1.0"
+        );
+
+        Chaos {
+            transport,
+            config,
+            rng: StdRng::seed_from_u64(config.seed),
+        }
+    }
+}
+
+fn new_future<F>(inner: F, config: &ChaosConfig, rng: &mut StdRng) -> ChaosFuture<F> {
+    ChaosFuture {
+        inner,
+        delay: Delay::new(config.handshake_delay),
+        faults: Some(Faults {
+            remaining_bytes: config.drop_after_bytes,
+            reset_probability: config.reset_probability,
+            rng: StdRng::seed_from_u64(rng.gen()),
+            failed: None,
+        }),
+    }
+}
+
+impl<T> Transport for Chaos<T>
+where
+    T: Transport,
+    T::Output: AsyncRead + AsyncWrite,
+{
+    type Output = ChaosStream<T::Output>;
+    type Error = T::Error;
+    type ListenerUpgrade = ChaosFuture<T::ListenerUpgrade>;
+    type Dial = ChaosFuture<T::Dial>;
+
+    fn listen_on(
+        &mut self,
+        id: ListenerId,
+        addr: Multiaddr,
+    ) -> Result<(), TransportError<Self::Error>> {
+        self.transport.listen_on(id, addr)
+    }
+
+    fn remove_listener(&mut self, id: ListenerId) -> bool {
+        self.transport.remove_listener(id)
+    }
+
+    fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let inner = self.transport.dial(addr)?;
+        Ok(new_future(inner, &self.config, &mut self.rng))
+    }
+
+    fn dial_as_listener(
+        &mut self,
+        addr: Multiaddr,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let inner = self.transport.dial_as_listener(addr)?;
+        Ok(new_future(inner, &self.config, &mut self.rng))
+    }
+
+    fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
+        self.transport.address_translation(server, observed)
+    }
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+        let this = self.project();
+        let event = ready!(this.transport.poll(cx));
+
+        Poll::Ready(event.map_upgrade(|inner| new_future(inner, this.config, this.rng)))
+    }
+}
+
+/// Future resolving to a [`ChaosStream`], see [`Transport::with_chaos`].
+#[pin_project::pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct ChaosFuture<F> {
+    #[pin]
+    inner: F,
+    delay: Delay,
+    faults: Option<Faults>,
+}
+
+impl<F, S, E> Future for ChaosFuture<F>
+where
+    F: TryFuture<Ok = S, Error = E>,
+{
+    type Output = Result<ChaosStream<S>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        ready!(this.delay.poll_unpin(cx));
+        let inner = ready!(this.inner.try_poll(cx))?;
+        let faults = this
+            .faults
+            .take()
+            .expect("ChaosFuture polled after completion");
+
+        Poll::Ready(Ok(ChaosStream { inner, faults }))
+    }
+}
+
+/// Connection of a [`Chaos`] transport.
+///
+/// Reads and writes fail according to the [`ChaosConfig`] of the transport.
+#[pin_project::pin_project]
+pub struct ChaosStream<S> {
+    #[pin]
+    inner: S,
+    faults: Faults,
+}
+
+impl<S: AsyncRead> AsyncRead for ChaosStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let allowed = this.faults.allowance(buf.len())?;
+        let num_bytes = ready!(this.inner.poll_read(cx, &mut buf[..allowed]))?;
+        this.faults.consume(num_bytes);
+
+        Poll::Ready(Ok(num_bytes))
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for ChaosStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let allowed = this.faults.allowance(buf.len())?;
+        let num_bytes = ready!(this.inner.poll_write(cx, &buf[..allowed]))?;
+        this.faults.consume(num_bytes);
+
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// The failures of a single connection.
+struct Faults {
+    remaining_bytes: Option<u64>,
+    reset_probability: f64,
+    rng: StdRng,
+    /// The kind of failure once the connection failed.
+    failed: Option<io::ErrorKind>,
+}
+
+impl Faults {
+    /// Returns how many of `max` bytes may be transferred, or the injected failure.
+    fn allowance(&mut self, max: usize) -> io::Result<usize> {
+        if self.failed.is_none() && max > 0 && self.remaining_bytes == Some(0) {
+            self.failed = Some(io::ErrorKind::ConnectionAborted);
+        }
+
+        if let Some(kind) = self.failed {
+            return Err(io::Error::new(kind, "failure injected by chaos transport"));
+        }
+
+        Ok(self
+            .remaining_bytes
+            .map_or(max, |remaining| cmp::min(max as u64, remaining) as usize))
+    }
+
+    /// Records a successful read or write of `num_bytes` and decides whether to reset the
+    /// connection.
+    ///
+    /// Deciding only once per completed operation keeps the failures independent of how often
+    /// the connection is polled.
+    fn consume(&mut self, num_bytes: usize) {
+        if let Some(remaining) = self.remaining_bytes.as_mut() {
+            *remaining = remaining.saturating_sub(num_bytes as u64);
+        }
+
+        if num_bytes > 0
+            && self.reset_probability > 0.0
+            && self.rng.gen_bool(self.reset_probability)
+        {
+            self.failed = Some(io::ErrorKind::ConnectionReset);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+    use futures::{future, AsyncReadExt, AsyncWriteExt, StreamExt};
+
+    /// Sends `num_bytes` in chunks of one byte and returns the number of bytes sent before the
+    /// connection failed, together with the failure.
+    fn send(config: ChaosConfig, num_bytes: usize) -> (usize, Option<io::ErrorKind>) {
+        let mut listener = MemoryTransport::default().boxed();
+        let mut dialer = MemoryTransport::default().with_chaos(config);
+
+        listener
+            .listen_on(ListenerId::next(), "/memory/0".parse().unwrap())
+            .unwrap();
+
+        futures::executor::block_on(async {
+            let addr = listener
+                .select_next_some()
+                .await
+                .into_new_address()
+                .unwrap();
+
+            let receive = async {
+                let (upgrade, _) = listener.select_next_some().await.into_incoming().unwrap();
+                let mut stream = upgrade.await.unwrap();
+                let mut received = Vec::new();
+                let _ = stream.read_to_end(&mut received).await;
+            };
+            let send = async {
+                let mut stream = dialer.dial(addr).unwrap().await.unwrap();
+                for sent in 0..num_bytes {
+                    if let Err(e) = stream.write_all(&[1]).await {
+                        return (sent, Some(e.kind()));
+                    }
+                }
+                stream.close().await.unwrap();
+                (num_bytes, None)
+            };
+
+            future::join(receive, send).await.1
+        })
+    }
+
+    #[test]
+    fn connection_is_dropped_after_bytes() {
+        let config = ChaosConfig {
+            drop_after_bytes: Some(10),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            send(config, 100),
+            (10, Some(io::ErrorKind::ConnectionAborted))
+        );
+    }
+
+    #[test]
+    fn resets_are_reproducible_given_seed() {
+        let config = |seed| ChaosConfig {
+            seed,
+            reset_probability: 0.05,
+            ..Default::default()
+        };
+
+        let (sent, failure) = send(config(1), 1000);
+        assert_eq!(failure, Some(io::ErrorKind::ConnectionReset));
+        assert_eq!(send(config(1), 1000), (sent, failure));
+        assert_ne!(send(config(2), 1000).0, sent);
+    }
+
+    #[test]
+    fn connection_is_delayed() {
+        let config = ChaosConfig {
+            handshake_delay: Duration::from_millis(200),
+            ..Default::default()
+        };
+
+        let start = instant::Instant::now();
+        send(config, 1);
+        assert!(start.elapsed() >= config.handshake_delay);
+    }
+
+    #[test]
+    fn no_failures_by_default() {
+        assert_eq!(send(ChaosConfig::default(), 100), (100, None));
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::transport::{ListenerId, Transport, TransportError, TransportEvent};
+use multiaddr::Multiaddr;
+use std::{pin::Pin, task::Context, task::Poll};
+
+/// See `Transport::map_listen_addr`.
+#[derive(Debug, Copy, Clone)]
+#[pin_project::pin_project]
+pub struct MapListenAddr<T, F> {
+    #[pin]
+    transport: T,
+    map: F,
+}
+
+impl<T, F> MapListenAddr<T, F> {
+    /// Internal function that builds a `MapListenAddr`.
+    pub(crate) fn new(transport: T, map: F) -> MapListenAddr<T, F> {
+        MapListenAddr { transport, map }
+    }
+}
+
+impl<T, F> Transport for MapListenAddr<T, F>
+where
+    T: Transport,
+    F: Fn(Multiaddr) -> Multiaddr,
+{
+    type Output = T::Output;
+    type Error = T::Error;
+    type ListenerUpgrade = T::ListenerUpgrade;
+    type Dial = T::Dial;
+
+    fn listen_on(
+        &mut self,
+        id: ListenerId,
+        addr: Multiaddr,
+    ) -> Result<(), TransportError<Self::Error>> {
+        self.transport.listen_on(id, addr)
+    }
+
+    fn remove_listener(&mut self, id: ListenerId) -> bool {
+        self.transport.remove_listener(id)
+    }
+
+    fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.transport.dial(addr)
+    }
+
+    fn dial_as_listener(
+        &mut self,
+        addr: Multiaddr,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.transport.dial_as_listener(addr)
+    }
+
+    fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
+        self.transport.address_translation(server, observed)
+    }
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+        let this = self.project();
+        let map = &*this.map;
+        this.transport.poll(cx).map(|event| match event {
+            TransportEvent::NewAddress {
+                listener_id,
+                listen_addr,
+            } => TransportEvent::NewAddress {
+                listener_id,
+                listen_addr: map(listen_addr),
+            },
+            TransportEvent::AddressExpired {
+                listener_id,
+                listen_addr,
+            } => TransportEvent::AddressExpired {
+                listener_id,
+                listen_addr: map(listen_addr),
+            },
+            event => event,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+    use futures::StreamExt;
+    use multiaddr::Protocol;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn rewrites_reported_listen_address() {
+        let public: Multiaddr = "/ip4/203.0.113.7".parse().unwrap();
+        let mut transport = MemoryTransport::default()
+            .map_listen_addr(move |addr| public.iter().chain(addr.iter()).collect())
+            .boxed();
+
+        transport
+            .listen_on(ListenerId::next(), "/memory/0".parse().unwrap())
+            .unwrap();
+
+        let listen_addr = futures::executor::block_on(transport.select_next_some())
+            .into_new_address()
+            .unwrap();
+
+        let mut iter = listen_addr.iter();
+        assert_eq!(
+            iter.next(),
+            Some(Protocol::Ip4(Ipv4Addr::new(203, 0, 113, 7)))
+        );
+        assert!(matches!(iter.next(), Some(Protocol::Memory(port)) if port != 0));
+    }
+}
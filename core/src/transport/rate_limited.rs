@@ -0,0 +1,379 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Transports that limit the bandwidth of their connections.
+//!
+//! See [`Transport::rate_limited`].
+
+use crate::{
+    transport::{ListenerId, TransportError, TransportEvent},
+    Multiaddr, Transport,
+};
+use futures::{prelude::*, ready};
+use futures_timer::Delay;
+use instant::Instant;
+use parking_lot::Mutex;
+use std::{
+    cmp, io,
+    num::NonZeroU64,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// Bandwidth limits of a [`RateLimited`] transport, in bytes per second.
+///
+/// Reading and writing are limited independently, i.e. a limit of 1 MB/s allows a connection to
+/// receive and send 1 MB/s each. A limit of `None` means unlimited.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// Limit applying to each connection on its own.
+    pub per_connection_bps: Option<NonZeroU64>,
+    /// Limit shared by all connections of the transport.
+    pub global_bps: Option<NonZeroU64>,
+}
+
+/// See [`Transport::rate_limited`].
+#[derive(Debug)]
+#[pin_project::pin_project]
+pub struct RateLimited<T> {
+    #[pin]
+    transport: T,
+    limits: Limits,
+}
+
+impl<T> RateLimited<T> {
+    pub(crate) fn new(transport: T, config: RateLimitConfig) -> Self {
+        let global = || {
+            config
+                .global_bps
+                .map(|bps| Arc::new(Mutex::new(TokenBucket::new(bps))))
+        };
+
+        RateLimited {
+            transport,
+            limits: Limits {
+                per_connection_bps: config.per_connection_bps,
+                global_read: global(),
+                global_write: global(),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Limits {
+    per_connection_bps: Option<NonZeroU64>,
+    global_read: Option<Arc<Mutex<TokenBucket>>>,
+    global_write: Option<Arc<Mutex<TokenBucket>>>,
+}
+
+impl Limits {
+    /// Creates the read and write [`Limiter`] of a new connection.
+    fn limiters(&self) -> (Limiter, Limiter) {
+        let limiter = |global: &Option<Arc<Mutex<TokenBucket>>>| Limiter {
+            connection: self.per_connection_bps.map(TokenBucket::new),
+            global: global.clone(),
+            delay: None,
+        };
+
+        (limiter(&self.global_read), limiter(&self.global_write))
+    }
+}
+
+impl<T> Transport for RateLimited<T>
+where
+    T: Transport,
+    T::Output: AsyncRead + AsyncWrite,
+{
+    type Output = RateLimitedStream<T::Output>;
+    type Error = T::Error;
+    type ListenerUpgrade = RateLimitedFuture<T::ListenerUpgrade>;
+    type Dial = RateLimitedFuture<T::Dial>;
+
+    fn listen_on(
+        &mut self,
+        id: ListenerId,
+        addr: Multiaddr,
+    ) -> Result<(), TransportError<Self::Error>> {
+        self.transport.listen_on(id, addr)
+    }
+
+    fn remove_listener(&mut self, id: ListenerId) -> bool {
+        self.transport.remove_listener(id)
+    }
+
+    fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let inner = self.transport.dial(addr)?;
+        Ok(RateLimitedFuture::new(inner, self.limits.limiters()))
+    }
+
+    fn dial_as_listener(
+        &mut self,
+        addr: Multiaddr,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let inner = self.transport.dial_as_listener(addr)?;
+        Ok(RateLimitedFuture::new(inner, self.limits.limiters()))
+    }
+
+    fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
+        self.transport.address_translation(server, observed)
+    }
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+        let this = self.project();
+        let event = ready!(this.transport.poll(cx));
+
+        Poll::Ready(
+            event.map_upgrade(|inner| RateLimitedFuture::new(inner, this.limits.limiters())),
+        )
+    }
+}
+
+/// Future resolving to a [`RateLimitedStream`], see [`Transport::rate_limited`].
+#[pin_project::pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct RateLimitedFuture<F> {
+    #[pin]
+    inner: F,
+    limiters: Option<(Limiter, Limiter)>,
+}
+
+impl<F> RateLimitedFuture<F> {
+    fn new(inner: F, limiters: (Limiter, Limiter)) -> Self {
+        RateLimitedFuture {
+            inner,
+            limiters: Some(limiters),
+        }
+    }
+}
+
+impl<F, S, E> Future for RateLimitedFuture<F>
+where
+    F: TryFuture<Ok = S, Error = E>,
+{
+    type Output = Result<RateLimitedStream<S>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let inner = ready!(this.inner.try_poll(cx))?;
+        let (read, write) = this
+            .limiters
+            .take()
+            .expect("RateLimitedFuture polled after completion");
+
+        Poll::Ready(Ok(RateLimitedStream { inner, read, write }))
+    }
+}
+
+/// Connection of a [`RateLimited`] transport.
+///
+/// Reads and writes are delayed once the connection exhausted its bandwidth.
+#[pin_project::pin_project]
+pub struct RateLimitedStream<S> {
+    #[pin]
+    inner: S,
+    read: Limiter,
+    write: Limiter,
+}
+
+impl<S: AsyncRead> AsyncRead for RateLimitedStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let allowed = ready!(this.read.poll_allowance(cx, buf.len()));
+        let num_bytes = ready!(this.inner.poll_read(cx, &mut buf[..allowed]))?;
+        this.read.consume(num_bytes);
+
+        Poll::Ready(Ok(num_bytes))
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for RateLimitedStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let allowed = ready!(this.write.poll_allowance(cx, buf.len()));
+        let num_bytes = ready!(this.inner.poll_write(cx, &buf[..allowed]))?;
+        this.write.consume(num_bytes);
+
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Limits one direction of a connection.
+struct Limiter {
+    connection: Option<TokenBucket>,
+    global: Option<Arc<Mutex<TokenBucket>>>,
+    /// Wakes up the task once the buckets are refilled.
+    delay: Option<Delay>,
+}
+
+impl Limiter {
+    /// Returns how many of `max` bytes may be transferred, waiting for the buckets to refill if
+    /// they are empty.
+    fn poll_allowance(&mut self, cx: &mut Context<'_>, max: usize) -> Poll<usize> {
+        if max == 0 {
+            return Poll::Ready(0);
+        }
+
+        loop {
+            if let Some(delay) = self.delay.as_mut() {
+                ready!(delay.poll_unpin(cx));
+                self.delay = None;
+            }
+
+            let now = Instant::now();
+            let mut allowed = max;
+            let mut wait = Duration::ZERO;
+
+            let mut global = self.global.as_ref().map(|g| g.lock());
+            for bucket in self.connection.iter_mut().chain(global.as_deref_mut()) {
+                match bucket.available(now) {
+                    0 => wait = cmp::max(wait, bucket.time_until_available()),
+                    available => allowed = cmp::min(allowed, available),
+                }
+            }
+
+            if wait.is_zero() {
+                return Poll::Ready(allowed);
+            }
+
+            self.delay = Some(Delay::new(wait));
+        }
+    }
+
+    fn consume(&mut self, num_bytes: usize) {
+        if let Some(bucket) = self.connection.as_mut() {
+            bucket.consume(num_bytes);
+        }
+        if let Some(bucket) = self.global.as_ref() {
+            bucket.lock().consume(num_bytes);
+        }
+    }
+}
+
+/// Token bucket refilled at `rate` bytes per second, holding at most one second worth of tokens.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(bps: NonZeroU64) -> Self {
+        let rate = bps.get() as f64;
+
+        TokenBucket {
+            rate,
+            tokens: rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Refills the bucket and returns the number of whole tokens in it.
+    fn available(&mut self, now: Instant) -> usize {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate).min(self.rate);
+        self.last_refill = now;
+
+        self.tokens.max(0.0) as usize
+    }
+
+    /// Time until the bucket holds at least one whole token again.
+    fn time_until_available(&self) -> Duration {
+        Duration::from_secs_f64((1.0 - self.tokens).max(0.0) / self.rate)
+    }
+
+    fn consume(&mut self, num_bytes: usize) {
+        self.tokens -= num_bytes as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+    use futures::{future, AsyncReadExt, AsyncWriteExt, StreamExt};
+
+    #[test]
+    fn transfer_takes_at_least_limit() {
+        const RATE: u64 = 64 * 1024;
+        const PAYLOAD: usize = 3 * RATE as usize;
+
+        let config = RateLimitConfig {
+            per_connection_bps: NonZeroU64::new(RATE),
+            global_bps: None,
+        };
+        let mut listener = MemoryTransport::default().rate_limited(config).boxed();
+        let mut dialer = MemoryTransport::default().rate_limited(config);
+
+        listener
+            .listen_on(ListenerId::next(), "/memory/0".parse().unwrap())
+            .unwrap();
+
+        futures::executor::block_on(async {
+            let addr = listener
+                .select_next_some()
+                .await
+                .into_new_address()
+                .unwrap();
+
+            let start = Instant::now();
+            let receive = async {
+                let (upgrade, _) = listener.select_next_some().await.into_incoming().unwrap();
+                let mut stream = upgrade.await.unwrap();
+                let mut received = Vec::new();
+                stream.read_to_end(&mut received).await.unwrap();
+                received
+            };
+            let send = async {
+                let mut stream = dialer.dial(addr).unwrap().await.unwrap();
+                stream.write_all(&vec![1; PAYLOAD]).await.unwrap();
+                stream.close().await.unwrap();
+            };
+
+            let (received, ()) = future::join(receive, send).await;
+
+            assert_eq!(received.len(), PAYLOAD);
+            // The first second worth of bytes is available right away.
+            assert!(start.elapsed() >= Duration::from_secs(2));
+        });
+    }
+}
@@ -0,0 +1,515 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Capturing the bytes of connections to a file, for debugging.
+//!
+//! A [`Tracer`] writes the bytes read from and written to connections to a capture file. It can
+//! be installed at two [`TracePoint`]s:
+//!
+//! - [`Transport::traced`] on the raw transport, capturing the bytes on the wire, i.e. the
+//!   handshake of the security upgrade followed by the encrypted bytes.
+//! - [`Authenticated::traced`](crate::transport::upgrade::Authenticated::traced) after the
+//!   security upgrade, capturing the decrypted bytes, i.e. everything the multiplexer sees.
+//!
+//! Clones of a [`Tracer`] write to the same file, so one tracer can be installed at both points.
+//! A [`Tracer::disabled`] tracer writes nothing and only costs a branch per read and write.
+//!
+//! ```
+//! # use libp2p_core::{transport::{trace::Tracer, MemoryTransport}, Transport};
+//! let tracer = std::env::var_os("LIBP2P_TRACE_FILE")
+//!     .map(Tracer::create)
+//!     .transpose()
+//!     .expect("to create trace file")
+//!     .unwrap_or_else(Tracer::disabled);
+//! let transport = MemoryTransport::default().traced(tracer);
+//! ```
+//!
+//! # File format
+//!
+//! The file starts with the 8 bytes [`MAGIC`] followed by a sequence of records. All integers are
+//! big-endian. Each record consists of:
+//!
+//! | Field         | Size              | Description                                          |
+//! |---------------|-------------------|------------------------------------------------------|
+//! | timestamp     | 8 bytes           | Microseconds since the [`Tracer`] was created.       |
+//! | connection    | 8 bytes           | Identifier of the connection, unique per [`Tracer`]. |
+//! | trace point   | 1 byte            | `0` for [`TracePoint::Wire`], `1` for [`TracePoint::Decrypted`]. |
+//! | kind          | 1 byte            | See [`RecordKind`].                                  |
+//! | length        | 4 bytes           | Length of the payload.                               |
+//! | payload       | `length` bytes    | See [`RecordKind`].                                  |
+//!
+//! Records are buffered and written synchronously, so tracing should not be enabled in
+//! production. Reads and writes of more than [`u32::MAX`] bytes, which don't fit a record, are
+//! skipped.
+
+use crate::{
+    transport::{ListenerId, TransportError, TransportEvent},
+    Multiaddr, Transport,
+};
+use futures::{prelude::*, ready};
+use instant::Instant;
+use parking_lot::Mutex;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+/// Magic bytes at the start of every capture file, including the format version.
+pub const MAGIC: [u8; 8] = *b"l2ptrc01";
+
+/// Where a [`Tracer`] captures the bytes of a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TracePoint {
+    /// The bytes on the wire, see [`Transport::traced`].
+    Wire,
+    /// The bytes after decryption by the security upgrade, see
+    /// [`Authenticated::traced`](crate::transport::upgrade::Authenticated::traced).
+    Decrypted,
+}
+
+/// Kind of a record in a capture file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+    /// A connection was established. The payload is a UTF-8 description of the connection, i.e.
+    /// `dialer` or `listener`, the remote address and, for [`TracePoint::Decrypted`], the
+    /// [`PeerId`](libp2p_identity::PeerId) of the remote, separated by spaces.
+    Open = 0,
+    /// Bytes were read from the connection. The payload contains the bytes.
+    Read = 1,
+    /// Bytes were written to the connection. The payload contains the bytes.
+    Write = 2,
+    /// The connection was dropped. The payload is empty.
+    Close = 3,
+}
+
+/// Writes the bytes of connections to a capture file.
+///
+/// See the [module-level documentation](self).
+#[derive(Clone, Default)]
+pub struct Tracer {
+    writer: Option<Arc<Writer>>,
+}
+
+struct Writer {
+    file: Mutex<BufWriter<File>>,
+    start: Instant,
+    next_connection: AtomicU64,
+}
+
+impl Tracer {
+    /// Creates a tracer writing to the file at the given path, truncating it if it exists.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(&MAGIC)?;
+
+        Ok(Tracer {
+            writer: Some(Arc::new(Writer {
+                file: Mutex::new(file),
+                start: Instant::now(),
+                next_connection: AtomicU64::new(0),
+            })),
+        })
+    }
+
+    /// Creates a tracer that does not capture anything.
+    pub fn disabled() -> Self {
+        Tracer { writer: None }
+    }
+
+    /// Returns whether the tracer captures connections.
+    pub fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Writes all buffered records to the file.
+    pub fn flush(&self) -> io::Result<()> {
+        match &self.writer {
+            Some(writer) => writer.file.lock().flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Wraps a new connection, recording an [`RecordKind::Open`] record with the given
+    /// description.
+    pub(crate) fn stream<S>(
+        &self,
+        stream: S,
+        point: TracePoint,
+        description: impl FnOnce() -> String,
+    ) -> TracedStream<S> {
+        let connection = self.writer.as_ref().map(|writer| {
+            let connection = ConnectionTrace {
+                writer: writer.clone(),
+                id: writer.next_connection.fetch_add(1, Ordering::Relaxed),
+                point,
+            };
+            connection.record(RecordKind::Open, description().as_bytes());
+            connection
+        });
+
+        TracedStream {
+            inner: stream,
+            connection,
+        }
+    }
+}
+
+impl std::fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tracer")
+            .field("enabled", &self.is_enabled())
+            .finish()
+    }
+}
+
+impl Writer {
+    fn record(&self, connection: u64, point: TracePoint, kind: RecordKind, payload: &[u8]) {
+        if let Err(e) = self.write_record(connection, point, kind, payload) {
+            tracing::debug!(%connection, "Failed to write trace record: {e}");
+        }
+    }
+
+    fn write_record(
+        &self,
+        connection: u64,
+        point: TracePoint,
+        kind: RecordKind,
+        payload: &[u8],
+    ) -> io::Result<()> {
+        let timestamp = u64::try_from(self.start.elapsed().as_micros()).unwrap_or(u64::MAX);
+        let point = match point {
+            TracePoint::Wire => 0u8,
+            TracePoint::Decrypted => 1u8,
+        };
+        let length = u32::try_from(payload.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "payload of {} bytes exceeds the maximum record length",
+                    payload.len()
+                ),
+            )
+        })?;
+
+        let mut file = self.file.lock();
+        file.write_all(&timestamp.to_be_bytes())?;
+        file.write_all(&connection.to_be_bytes())?;
+        file.write_all(&[point, kind as u8])?;
+        file.write_all(&length.to_be_bytes())?;
+        file.write_all(payload)
+    }
+}
+
+/// The state of a [`TracedStream`] of an enabled [`Tracer`].
+struct ConnectionTrace {
+    writer: Arc<Writer>,
+    id: u64,
+    point: TracePoint,
+}
+
+impl ConnectionTrace {
+    fn record(&self, kind: RecordKind, payload: &[u8]) {
+        self.writer.record(self.id, self.point, kind, payload)
+    }
+}
+
+impl Drop for ConnectionTrace {
+    fn drop(&mut self) {
+        self.record(RecordKind::Close, &[]);
+        if let Err(e) = self.writer.file.lock().flush() {
+            tracing::debug!(connection=%self.id, "Failed to flush trace file: {e}");
+        }
+    }
+}
+
+/// See [`Transport::traced`].
+#[derive(Debug)]
+#[pin_project::pin_project]
+pub struct Traced<T> {
+    #[pin]
+    transport: T,
+    tracer: Tracer,
+}
+
+impl<T> Traced<T> {
+    pub(crate) fn new(transport: T, tracer: Tracer) -> Self {
+        Traced { transport, tracer }
+    }
+}
+
+impl<T> Transport for Traced<T>
+where
+    T: Transport,
+    T::Output: AsyncRead + AsyncWrite,
+{
+    type Output = TracedStream<T::Output>;
+    type Error = T::Error;
+    type ListenerUpgrade = TracedFuture<T::ListenerUpgrade>;
+    type Dial = TracedFuture<T::Dial>;
+
+    fn listen_on(
+        &mut self,
+        id: ListenerId,
+        addr: Multiaddr,
+    ) -> Result<(), TransportError<Self::Error>> {
+        self.transport.listen_on(id, addr)
+    }
+
+    fn remove_listener(&mut self, id: ListenerId) -> bool {
+        self.transport.remove_listener(id)
+    }
+
+    fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let tracer = self.tracer.clone();
+        let description = tracer.is_enabled().then(|| format!("dialer {addr}"));
+        let inner = self.transport.dial(addr)?;
+        Ok(TracedFuture::new(inner, tracer, description))
+    }
+
+    fn dial_as_listener(
+        &mut self,
+        addr: Multiaddr,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        let tracer = self.tracer.clone();
+        let description = tracer.is_enabled().then(|| format!("listener {addr}"));
+        let inner = self.transport.dial_as_listener(addr)?;
+        Ok(TracedFuture::new(inner, tracer, description))
+    }
+
+    fn address_translation(&self, server: &Multiaddr, observed: &Multiaddr) -> Option<Multiaddr> {
+        self.transport.address_translation(server, observed)
+    }
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+        let this = self.project();
+        let event = ready!(this.transport.poll(cx));
+        let description = match &event {
+            TransportEvent::Incoming { send_back_addr, .. } if this.tracer.is_enabled() => {
+                Some(format!("listener {send_back_addr}"))
+            }
+            _ => None,
+        };
+
+        Poll::Ready(
+            event.map_upgrade(|inner| TracedFuture::new(inner, this.tracer.clone(), description)),
+        )
+    }
+}
+
+/// Future resolving to a [`TracedStream`], see [`Transport::traced`].
+#[pin_project::pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct TracedFuture<F> {
+    #[pin]
+    inner: F,
+    tracer: Tracer,
+    /// Description of the connection, only formatted if the [`Tracer`] is enabled.
+    description: Option<String>,
+}
+
+impl<F> TracedFuture<F> {
+    fn new(inner: F, tracer: Tracer, description: Option<String>) -> Self {
+        TracedFuture {
+            inner,
+            tracer,
+            description,
+        }
+    }
+}
+
+impl<F, S, E> Future for TracedFuture<F>
+where
+    F: TryFuture<Ok = S, Error = E>,
+{
+    type Output = Result<TracedStream<S>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let inner = ready!(this.inner.try_poll(cx))?;
+        let description = this.description.take();
+
+        Poll::Ready(Ok(this.tracer.stream(inner, TracePoint::Wire, || {
+            description.expect("description of an enabled tracer")
+        })))
+    }
+}
+
+/// Connection whose bytes are captured by a [`Tracer`].
+#[pin_project::pin_project]
+pub struct TracedStream<S> {
+    #[pin]
+    inner: S,
+    connection: Option<ConnectionTrace>,
+}
+
+impl<S: AsyncRead> AsyncRead for TracedStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let num_bytes = ready!(this.inner.poll_read(cx, buf))?;
+        if let Some(connection) = this.connection {
+            connection.record(RecordKind::Read, &buf[..num_bytes]);
+        }
+
+        Poll::Ready(Ok(num_bytes))
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for TracedStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let num_bytes = ready!(this.inner.poll_write(cx, buf))?;
+        if let Some(connection) = this.connection {
+            connection.record(RecordKind::Write, &buf[..num_bytes]);
+        }
+
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+    use futures::{future, AsyncReadExt, AsyncWriteExt, StreamExt};
+
+    #[derive(Debug, PartialEq)]
+    struct Record {
+        connection: u64,
+        point: u8,
+        kind: u8,
+        payload: Vec<u8>,
+    }
+
+    fn parse(file: &[u8]) -> Vec<Record> {
+        assert_eq!(file[..8], MAGIC);
+
+        let mut records = Vec::new();
+        let mut rest = &file[8..];
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[18..22].try_into().unwrap()) as usize;
+            records.push(Record {
+                connection: u64::from_be_bytes(rest[8..16].try_into().unwrap()),
+                point: rest[16],
+                kind: rest[17],
+                payload: rest[22..22 + length].to_vec(),
+            });
+            rest = &rest[22 + length..];
+        }
+        records
+    }
+
+    #[test]
+    fn captures_bytes_of_both_directions() {
+        let path = std::env::temp_dir().join(format!("libp2p-trace-{}", rand::random::<u64>()));
+        let tracer = Tracer::create(&path).unwrap();
+
+        let mut listener = MemoryTransport::default().boxed();
+        let mut dialer = MemoryTransport::default().traced(tracer.clone());
+
+        listener
+            .listen_on(ListenerId::next(), "/memory/0".parse().unwrap())
+            .unwrap();
+
+        futures::executor::block_on(async {
+            let addr = listener
+                .select_next_some()
+                .await
+                .into_new_address()
+                .unwrap();
+
+            let echo = async {
+                let (upgrade, _) = listener.select_next_some().await.into_incoming().unwrap();
+                let mut stream = upgrade.await.unwrap();
+                let mut buf = [0; 4];
+                stream.read_exact(&mut buf).await.unwrap();
+                stream.write_all(b"pong").await.unwrap();
+            };
+            let ping = async {
+                let mut stream = dialer.dial(addr.clone()).unwrap().await.unwrap();
+                stream.write_all(b"ping").await.unwrap();
+                let mut buf = [0; 4];
+                stream.read_exact(&mut buf).await.unwrap();
+            };
+
+            future::join(echo, ping).await;
+
+            let records = parse(&std::fs::read(&path).unwrap());
+            std::fs::remove_file(&path).unwrap();
+
+            let record = |kind: RecordKind, payload: &[u8]| Record {
+                connection: 0,
+                point: 0,
+                kind: kind as u8,
+                payload: payload.to_vec(),
+            };
+            assert_eq!(
+                records,
+                vec![
+                    record(RecordKind::Open, format!("dialer {addr}").as_bytes()),
+                    record(RecordKind::Write, b"ping"),
+                    record(RecordKind::Read, b"pong"),
+                    record(RecordKind::Close, &[]),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn disabled_tracer_passes_bytes_through() {
+        let tracer = Tracer::disabled();
+        let stream = tracer.stream(
+            futures::io::Cursor::new(b"hello".to_vec()),
+            TracePoint::Wire,
+            || unreachable!("disabled tracer should not describe connections"),
+        );
+        assert!(stream.connection.is_none());
+
+        let mut stream = stream;
+        let mut buf = Vec::new();
+        futures::executor::block_on(stream.read_to_end(&mut buf)).unwrap();
+        assert_eq!(buf, b"hello");
+    }
+}
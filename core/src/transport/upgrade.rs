@@ -26,15 +26,21 @@ use crate::{
     connection::ConnectedPoint,
     muxing::{StreamMuxer, StreamMuxerBox},
     transport::{
-        and_then::AndThen, boxed::boxed, timeout::TransportTimeout, ListenerId, Transport,
-        TransportError, TransportEvent,
+        and_then::AndThen,
+        boxed::boxed,
+        map::Map,
+        map_err::MapErr,
+        timeout::TransportTimeout,
+        trace::{TracePoint, TracedStream, Tracer},
+        ListenerId, Transport, TransportError, TransportEvent,
     },
     upgrade::{
         self, apply_inbound, apply_outbound, InboundConnectionUpgrade, InboundUpgradeApply,
-        OutboundConnectionUpgrade, OutboundUpgradeApply, UpgradeError,
+        NegotiationError, OutboundConnectionUpgrade, OutboundUpgradeApply, UpgradeError,
     },
     Negotiated,
 };
+use either::Either;
 use futures::{prelude::*, ready};
 use libp2p_identity::PeerId;
 use multiaddr::Multiaddr;
@@ -93,23 +99,34 @@ where
     ///
     ///   * I/O upgrade: `C -> (PeerId, D)`.
     ///   * Transport output: `C -> (PeerId, D)`
+    ///
+    /// Errors of the upgrade are reported as [`UpgradeStepError`]s of the
+    /// [`UpgradeStep::Authentication`] step.
+    #[allow(clippy::type_complexity)]
     pub fn authenticate<C, D, U, E>(
         self,
         upgrade: U,
-    ) -> Authenticated<AndThen<T, impl FnOnce(C, ConnectedPoint) -> Authenticate<C, U> + Clone>>
+    ) -> Authenticated<
+        MapErr<
+            AndThen<T, impl FnOnce(C, ConnectedPoint) -> Authenticate<C, U> + Clone>,
+            fn(Either<T::Error, UpgradeStepError>) -> UpgradedTransportError<T::Error>,
+        >,
+    >
     where
         T: Transport<Output = C>,
         C: AsyncRead + AsyncWrite + Unpin,
         D: AsyncRead + AsyncWrite + Unpin,
         U: InboundConnectionUpgrade<Negotiated<C>, Output = (PeerId, D), Error = E>,
         U: OutboundConnectionUpgrade<Negotiated<C>, Output = (PeerId, D), Error = E> + Clone,
-        E: Error + 'static,
+        E: Error + Send + Sync + 'static,
     {
         let version = self.version;
         Authenticated(Builder::new(
-            self.inner.and_then(move |conn, endpoint| Authenticate {
-                inner: upgrade::apply(conn, upgrade, endpoint, version),
-            }),
+            self.inner
+                .and_then(move |conn, endpoint| Authenticate {
+                    inner: upgrade::apply(conn, upgrade, endpoint, version),
+                })
+                .map_err(UpgradedTransportError::from as fn(_) -> _),
             version,
         ))
     }
@@ -129,21 +146,19 @@ where
     inner: EitherUpgrade<C, U>,
 }
 
-impl<C, U> Future for Authenticate<C, U>
+impl<C, U, D, E> Future for Authenticate<C, U>
 where
     C: AsyncRead + AsyncWrite + Unpin,
-    U: InboundConnectionUpgrade<Negotiated<C>>
-        + OutboundConnectionUpgrade<
-            Negotiated<C>,
-            Output = <U as InboundConnectionUpgrade<Negotiated<C>>>::Output,
-            Error = <U as InboundConnectionUpgrade<Negotiated<C>>>::Error,
-        >,
+    U: InboundConnectionUpgrade<Negotiated<C>, Output = D, Error = E>,
+    U: OutboundConnectionUpgrade<Negotiated<C>, Output = D, Error = E>,
+    E: Error + Send + Sync + 'static,
 {
-    type Output = <EitherUpgrade<C, U> as Future>::Output;
+    type Output = Result<D, UpgradeStepError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
         Future::poll(this.inner, cx)
+            .map_err(|e| UpgradeStepError::new(UpgradeStep::Authentication, e))
     }
 }
 
@@ -167,14 +182,17 @@ where
     C: AsyncRead + AsyncWrite + Unpin,
     U: InboundConnectionUpgrade<Negotiated<C>, Output = M, Error = E>,
     U: OutboundConnectionUpgrade<Negotiated<C>, Output = M, Error = E>,
+    E: Error + Send + Sync + 'static,
 {
-    type Output = Result<(PeerId, M), UpgradeError<E>>;
+    type Output = Result<(PeerId, M), UpgradeStepError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
         let m = match ready!(Future::poll(this.upgrade, cx)) {
             Ok(m) => m,
-            Err(err) => return Poll::Ready(Err(err)),
+            Err(err) => {
+                return Poll::Ready(Err(UpgradeStepError::new(UpgradeStep::Multiplexing, err)))
+            }
         };
         let i = this
             .peer_id
@@ -218,6 +236,42 @@ where
         ))
     }
 
+    /// Captures the bytes read from and written to the authenticated connections.
+    ///
+    /// The bytes are captured after decryption by the security upgrade, i.e. they contain the
+    /// multiplexer negotiation and frames. Use [`Transport::traced`] on the raw transport to
+    /// capture the bytes on the wire instead. See [`trace`](crate::transport::trace) for the
+    /// format of the capture file.
+    ///
+    /// ## Transitions
+    ///
+    ///   * I/O upgrade: `C -> TracedStream<C>`.
+    ///   * Transport output: `(PeerId, C) -> (PeerId, TracedStream<C>)`.
+    #[allow(clippy::type_complexity)]
+    pub fn traced<C>(
+        self,
+        tracer: Tracer,
+    ) -> Authenticated<
+        Map<T, impl FnOnce((PeerId, C), ConnectedPoint) -> (PeerId, TracedStream<C>) + Clone>,
+    >
+    where
+        T: Transport<Output = (PeerId, C)>,
+        C: AsyncRead + AsyncWrite + Unpin,
+    {
+        Authenticated(Builder::new(
+            self.0.inner.map(move |(peer, stream), endpoint| {
+                let stream = tracer.stream(stream, TracePoint::Decrypted, || match endpoint {
+                    ConnectedPoint::Dialer { address, .. } => format!("dialer {address} {peer}"),
+                    ConnectedPoint::Listener { send_back_addr, .. } => {
+                        format!("listener {send_back_addr} {peer}")
+                    }
+                });
+                (peer, stream)
+            }),
+            self.0.version,
+        ))
+    }
+
     /// Upgrades the transport with a (sub)stream multiplexer.
     ///
     /// The supplied upgrade receives the I/O resource `C` and must
@@ -228,26 +282,40 @@ where
     ///
     ///   * I/O upgrade: `C -> M`.
     ///   * Transport output: `(PeerId, C) -> (PeerId, M)`.
+    ///
+    /// Errors of the upgrade are reported as [`UpgradeStepError`]s of the
+    /// [`UpgradeStep::Multiplexing`] step.
+    #[allow(clippy::type_complexity)]
     pub fn multiplex<C, M, U, E>(
         self,
         upgrade: U,
-    ) -> Multiplexed<AndThen<T, impl FnOnce((PeerId, C), ConnectedPoint) -> Multiplex<C, U> + Clone>>
+    ) -> Multiplexed<
+        MapErr<
+            AndThen<T, impl FnOnce((PeerId, C), ConnectedPoint) -> Multiplex<C, U> + Clone>,
+            fn(Either<T::Error, UpgradeStepError>) -> UpgradedTransportError<T::Error>,
+        >,
+    >
     where
         T: Transport<Output = (PeerId, C)>,
         C: AsyncRead + AsyncWrite + Unpin,
         M: StreamMuxer,
         U: InboundConnectionUpgrade<Negotiated<C>, Output = M, Error = E>,
         U: OutboundConnectionUpgrade<Negotiated<C>, Output = M, Error = E> + Clone,
-        E: Error + 'static,
+        E: Error + Send + Sync + 'static,
     {
         let version = self.0.version;
-        Multiplexed(self.0.inner.and_then(move |(i, c), endpoint| {
-            let upgrade = upgrade::apply(c, upgrade, endpoint, version);
-            Multiplex {
-                peer_id: Some(i),
-                upgrade,
-            }
-        }))
+        Multiplexed(
+            self.0
+                .inner
+                .and_then(move |(i, c), endpoint| {
+                    let upgrade = upgrade::apply(c, upgrade, endpoint, version);
+                    Multiplex {
+                        peer_id: Some(i),
+                        upgrade,
+                    }
+                })
+                .map_err(UpgradedTransportError::from as fn(_) -> _),
+        )
     }
 
     /// Like [`Authenticated::multiplex`] but accepts a function which returns the upgrade.
@@ -261,27 +329,41 @@ where
     ///
     ///   * I/O upgrade: `C -> M`.
     ///   * Transport output: `(PeerId, C) -> (PeerId, M)`.
+    ///
+    /// Errors of the upgrade are reported as [`UpgradeStepError`]s of the
+    /// [`UpgradeStep::Multiplexing`] step.
+    #[allow(clippy::type_complexity)]
     pub fn multiplex_ext<C, M, U, E, F>(
         self,
         up: F,
-    ) -> Multiplexed<AndThen<T, impl FnOnce((PeerId, C), ConnectedPoint) -> Multiplex<C, U> + Clone>>
+    ) -> Multiplexed<
+        MapErr<
+            AndThen<T, impl FnOnce((PeerId, C), ConnectedPoint) -> Multiplex<C, U> + Clone>,
+            fn(Either<T::Error, UpgradeStepError>) -> UpgradedTransportError<T::Error>,
+        >,
+    >
     where
         T: Transport<Output = (PeerId, C)>,
         C: AsyncRead + AsyncWrite + Unpin,
         M: StreamMuxer,
         U: InboundConnectionUpgrade<Negotiated<C>, Output = M, Error = E>,
         U: OutboundConnectionUpgrade<Negotiated<C>, Output = M, Error = E> + Clone,
-        E: Error + 'static,
+        E: Error + Send + Sync + 'static,
         F: for<'a> FnOnce(&'a PeerId, &'a ConnectedPoint) -> U + Clone,
     {
         let version = self.0.version;
-        Multiplexed(self.0.inner.and_then(move |(peer_id, c), endpoint| {
-            let upgrade = upgrade::apply(c, up(&peer_id, &endpoint), endpoint, version);
-            Multiplex {
-                peer_id: Some(peer_id),
-                upgrade,
-            }
-        }))
+        Multiplexed(
+            self.0
+                .inner
+                .and_then(move |(peer_id, c), endpoint| {
+                    let upgrade = upgrade::apply(c, up(&peer_id, &endpoint), endpoint, version);
+                    Multiplex {
+                        peer_id: Some(peer_id),
+                        upgrade,
+                    }
+                })
+                .map_err(UpgradedTransportError::from as fn(_) -> _),
+        )
     }
 }
 
@@ -370,6 +452,143 @@ where
     }
 }
 
+/// A step of the transport upgrade process configured through a [`Builder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpgradeStep {
+    /// The authentication of the remote, see [`Builder::authenticate`].
+    Authentication,
+    /// The negotiation of a stream multiplexer, see [`Authenticated::multiplex`].
+    Multiplexing,
+}
+
+impl fmt::Display for UpgradeStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpgradeStep::Authentication => write!(f, "Authentication"),
+            UpgradeStep::Multiplexing => write!(f, "Multiplexing"),
+        }
+    }
+}
+
+/// Error of an [`UpgradeStep`].
+///
+/// The error of the applied upgrade is type-erased, such that this error can be recovered
+/// from a boxed transport through [`UpgradeStepError::find`]. Use
+/// `downcast_ref` on [`UpgradeStepError::upgrade_error`] to access
+/// the concrete error, e.g. of the security protocol.
+#[derive(Debug)]
+pub struct UpgradeStepError {
+    step: UpgradeStep,
+    error: UpgradeError<Box<dyn Error + Send + Sync>>,
+}
+
+impl UpgradeStepError {
+    fn new<E>(step: UpgradeStep, error: UpgradeError<E>) -> Self
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        UpgradeStepError {
+            step,
+            error: error.map_err(|e| Box::new(e) as Box<_>),
+        }
+    }
+
+    /// The step of the upgrade process that failed.
+    pub fn step(&self) -> UpgradeStep {
+        self.step
+    }
+
+    /// The error of negotiating the protocol of this step, e.g. if the remote
+    /// supports none of our security protocols.
+    pub fn negotiation_error(&self) -> Option<&NegotiationError> {
+        match &self.error {
+            UpgradeError::Select(e) => Some(e),
+            UpgradeError::Apply(_) => None,
+        }
+    }
+
+    /// The error of the upgrade applied after the protocol was negotiated,
+    /// e.g. a failed security handshake.
+    pub fn upgrade_error(&self) -> Option<&(dyn Error + Send + Sync + 'static)> {
+        match &self.error {
+            UpgradeError::Select(_) => None,
+            UpgradeError::Apply(e) => Some(e.as_ref()),
+        }
+    }
+
+    /// Searches the given error and its sources for an [`UpgradeStepError`].
+    ///
+    /// This also looks into the [`io::Error`](std::io::Error)s produced by boxing a transport,
+    /// e.g. the ones reported by a `Swarm` for failed connections.
+    pub fn find<'a>(error: &'a (dyn Error + 'static)) -> Option<&'a UpgradeStepError> {
+        super::find_error(error)
+    }
+}
+
+impl fmt::Display for UpgradeStepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            UpgradeError::Select(_) => write!(f, "{} protocol negotiation failed", self.step),
+            UpgradeError::Apply(_) => write!(f, "{} upgrade failed", self.step),
+        }
+    }
+}
+
+impl Error for UpgradeStepError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.error {
+            UpgradeError::Select(e) => Some(e),
+            UpgradeError::Apply(e) => Some(e.as_ref()),
+        }
+    }
+}
+
+/// Error of a transport upgraded through [`Builder::authenticate`] or
+/// [`Authenticated::multiplex`].
+#[derive(Debug)]
+pub enum UpgradedTransportError<TErr> {
+    /// Error in the underlying transport.
+    ///
+    /// Displayed as and reporting the sources of the error of the underlying transport, as if it
+    /// wasn't wrapped.
+    Transport(TErr),
+    /// Error while upgrading a connection of the underlying transport.
+    Upgrade(UpgradeStepError),
+}
+
+impl<TErr> From<Either<TErr, UpgradeStepError>> for UpgradedTransportError<TErr> {
+    fn from(error: Either<TErr, UpgradeStepError>) -> Self {
+        match error {
+            Either::Left(e) => UpgradedTransportError::Transport(e),
+            Either::Right(e) => UpgradedTransportError::Upgrade(e),
+        }
+    }
+}
+
+impl<TErr> fmt::Display for UpgradedTransportError<TErr>
+where
+    TErr: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpgradedTransportError::Transport(e) => write!(f, "{e}"),
+            UpgradedTransportError::Upgrade(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl<TErr> Error for UpgradedTransportError<TErr>
+where
+    TErr: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UpgradedTransportError::Transport(e) => e.source(),
+            UpgradedTransportError::Upgrade(e) => Some(e),
+        }
+    }
+}
+
 /// An inbound or outbound upgrade.
 type EitherUpgrade<C, U> = future::Either<InboundUpgradeApply<C, U>, OutboundUpgradeApply<C, U>>;
 
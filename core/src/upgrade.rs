@@ -61,6 +61,7 @@ mod apply;
 mod denied;
 mod either;
 mod error;
+mod fallback;
 mod pending;
 mod ready;
 mod select;
@@ -72,7 +73,8 @@ pub(crate) use error::UpgradeError;
 use futures::future::Future;
 
 pub use self::{
-    denied::DeniedUpgrade, pending::PendingUpgrade, ready::ReadyUpgrade, select::SelectUpgrade,
+    denied::DeniedUpgrade, fallback::FallbackUpgrade, pending::PendingUpgrade, ready::ReadyUpgrade,
+    select::SelectUpgrade,
 };
 pub use crate::Negotiated;
 pub use multistream_select::{NegotiatedComplete, NegotiationError, ProtocolError, Version};
@@ -75,7 +75,7 @@ where
 }
 
 /// Future returned by `apply_inbound`. Drives the upgrade process.
-pub struct InboundUpgradeApply<C, U>
+pub(crate) struct InboundUpgradeApply<C, U>
 where
     C: AsyncRead + AsyncWrite + Unpin,
     U: InboundConnectionUpgrade<Negotiated<C>>,
@@ -158,7 +158,7 @@ where
 }
 
 /// Future returned by `apply_outbound`. Drives the upgrade process.
-pub struct OutboundUpgradeApply<C, U>
+pub(crate) struct OutboundUpgradeApply<C, U>
 where
     C: AsyncRead + AsyncWrite + Unpin,
     U: OutboundConnectionUpgrade<Negotiated<C>>,
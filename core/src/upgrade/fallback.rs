@@ -0,0 +1,86 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeInfo};
+use futures::future;
+use std::vec;
+use void::Void;
+
+/// Implementation of [`UpgradeInfo`], [`InboundUpgrade`] and [`OutboundUpgrade`] that supports
+/// several protocols in order of preference and directly yields the substream together with the
+/// negotiated protocol.
+///
+/// When dialing, the protocols are proposed to the remote in the given order and the first one it
+/// supports is chosen. When listening, multistream-select lets the dialer decide: the first
+/// proposal of the remote that is contained in the list is accepted. Listing the newest version of
+/// a protocol first thus makes two peers that both support it agree on that version, while still
+/// falling back to older versions for peers that do not.
+#[derive(Debug, Clone)]
+pub struct FallbackUpgrade<P> {
+    protocols: Vec<P>,
+}
+
+impl<P> FallbackUpgrade<P> {
+    /// Creates a new [`FallbackUpgrade`] supporting the given protocols, most preferred first.
+    pub fn new(protocols: impl IntoIterator<Item = P>) -> Self {
+        Self {
+            protocols: protocols.into_iter().collect(),
+        }
+    }
+}
+
+impl<P> UpgradeInfo for FallbackUpgrade<P>
+where
+    P: AsRef<str> + Clone,
+{
+    type Info = P;
+    type InfoIter = vec::IntoIter<P>;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        self.protocols.clone().into_iter()
+    }
+}
+
+impl<C, P> InboundUpgrade<C> for FallbackUpgrade<P>
+where
+    P: AsRef<str> + Clone,
+{
+    type Output = (C, P);
+    type Error = Void;
+    type Future = future::Ready<Result<Self::Output, Self::Error>>;
+
+    fn upgrade_inbound(self, stream: C, protocol: Self::Info) -> Self::Future {
+        future::ready(Ok((stream, protocol)))
+    }
+}
+
+impl<C, P> OutboundUpgrade<C> for FallbackUpgrade<P>
+where
+    P: AsRef<str> + Clone,
+{
+    type Output = (C, P);
+    type Error = Void;
+    type Future = future::Ready<Result<Self::Output, Self::Error>>;
+
+    fn upgrade_outbound(self, stream: C, protocol: Self::Info) -> Self::Future {
+        future::ready(Ok((stream, protocol)))
+    }
+}
+
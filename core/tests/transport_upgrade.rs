@@ -19,7 +19,9 @@
 // DEALINGS IN THE SOFTWARE.
 
 use futures::prelude::*;
-use libp2p_core::transport::{ListenerId, MemoryTransport, Transport};
+use libp2p_core::transport::trace::{RecordKind, Tracer, MAGIC};
+use libp2p_core::transport::upgrade::{UpgradeStep, UpgradeStepError};
+use libp2p_core::transport::{ListenerId, MemoryTransport, Transport, TransportError};
 use libp2p_core::upgrade::{
     self, InboundConnectionUpgrade, OutboundConnectionUpgrade, UpgradeInfo,
 };
@@ -128,3 +130,135 @@ fn upgrade_pipeline() {
     async_std::task::spawn(server);
     async_std::task::block_on(client);
 }
+
+#[test]
+fn failed_authentication_is_reported_as_upgrade_step() {
+    let listener_keys = identity::Keypair::generate_ed25519();
+    let mut listener_transport = MemoryTransport::default()
+        .upgrade(upgrade::Version::V1)
+        .authenticate(
+            noise::Config::new(&listener_keys)
+                .unwrap()
+                .with_prologue(b"listener".to_vec()),
+        )
+        .multiplex(MplexConfig::default())
+        .boxed();
+
+    let dialer_keys = identity::Keypair::generate_ed25519();
+    let mut dialer_transport = MemoryTransport::default()
+        .upgrade(upgrade::Version::V1)
+        .authenticate(
+            noise::Config::new(&dialer_keys)
+                .unwrap()
+                .with_prologue(b"dialer".to_vec()),
+        )
+        .multiplex(MplexConfig::default())
+        .boxed();
+
+    let listen_addr = Multiaddr::from(Protocol::Memory(random::<u64>()));
+
+    listener_transport
+        .listen_on(ListenerId::next(), listen_addr.clone())
+        .unwrap();
+
+    let server = async move {
+        loop {
+            let Some((upgrade, _send_back_addr)) =
+                listener_transport.select_next_some().await.into_incoming()
+            else {
+                continue;
+            };
+            let _ = upgrade.await;
+        }
+    };
+
+    let client = async move {
+        let error = match dialer_transport.dial(listen_addr) {
+            Ok(dial) => dial.await.unwrap_err(),
+            Err(TransportError::Other(e)) => e,
+            Err(TransportError::MultiaddrNotSupported(_)) => panic!("Memory address not supported"),
+        };
+
+        let error = UpgradeStepError::find(&error).expect("error of an upgrade step");
+        assert_eq!(error.step(), UpgradeStep::Authentication);
+        assert!(error.negotiation_error().is_none());
+        assert!(error
+            .upgrade_error()
+            .is_some_and(|e| e.is::<noise::Error>()));
+    };
+
+    async_std::task::spawn(server);
+    async_std::task::block_on(client);
+}
+
+#[test]
+fn traces_wire_and_decrypted_bytes() {
+    let path = std::env::temp_dir().join(format!("libp2p-trace-{}", random::<u64>()));
+    let tracer = Tracer::create(&path).unwrap();
+
+    let listener_keys = identity::Keypair::generate_ed25519();
+    let mut listener_transport = MemoryTransport::default()
+        .upgrade(upgrade::Version::V1)
+        .authenticate(noise::Config::new(&listener_keys).unwrap())
+        .multiplex(MplexConfig::default())
+        .boxed();
+
+    let dialer_keys = identity::Keypair::generate_ed25519();
+    let mut dialer_transport = MemoryTransport::default()
+        .traced(tracer.clone())
+        .upgrade(upgrade::Version::V1)
+        .authenticate(noise::Config::new(&dialer_keys).unwrap())
+        .traced(tracer.clone())
+        .multiplex(MplexConfig::default())
+        .boxed();
+
+    let listen_addr = Multiaddr::from(Protocol::Memory(random::<u64>()));
+    listener_transport
+        .listen_on(ListenerId::next(), listen_addr.clone())
+        .unwrap();
+
+    let server = async move {
+        loop {
+            let Some((upgrade, _send_back_addr)) =
+                listener_transport.select_next_some().await.into_incoming()
+            else {
+                continue;
+            };
+            let _ = upgrade.await;
+        }
+    };
+
+    let client = async move {
+        dialer_transport.dial(listen_addr).unwrap().await.unwrap();
+    };
+
+    async_std::task::spawn(server);
+    async_std::task::block_on(client);
+    tracer.flush().unwrap();
+
+    let file = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(file[..8], MAGIC);
+
+    // Concatenates the payloads of all writes at the given trace point.
+    let written = |trace_point: u8| {
+        let mut written = Vec::new();
+        let mut rest = &file[8..];
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[18..22].try_into().unwrap()) as usize;
+            if rest[16] == trace_point && rest[17] == RecordKind::Write as u8 {
+                written.extend_from_slice(&rest[22..22 + length]);
+            }
+            rest = &rest[22 + length..];
+        }
+        written
+    };
+    let contains =
+        |haystack: &[u8], needle: &[u8]| haystack.windows(needle.len()).any(|w| w == needle);
+
+    let wire = written(0);
+    let decrypted = written(1);
+    assert!(contains(&wire, b"/noise"));
+    assert!(!contains(&wire, b"/mplex/6.7.0"));
+    assert!(contains(&decrypted, b"/mplex/6.7.0"));
+}
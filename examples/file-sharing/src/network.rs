@@ -271,6 +271,7 @@ impl EventLoop {
                 request_response::Message::Response {
                     request_id,
                     response,
+                    ..
                 } => {
                     let _ = self
                         .pending_request_file
@@ -278,6 +279,9 @@ impl EventLoop {
                         .expect("Request to still be pending.")
                         .send(Ok(response.0));
                 }
+                request_response::Message::ResponseStream { .. } => {
+                    unreachable!("streaming responses are not enabled")
+                }
             },
             SwarmEvent::Behaviour(BehaviourEvent::RequestResponse(
                 request_response::Event::OutboundFailure {
@@ -39,9 +39,31 @@ async fn main() -> Result<(), Box<dyn Error>> {
             yamux::Config::default,
         )?
         .with_behaviour(|_| ping::Behaviour::default())?
-        .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(Duration::from_secs(u64::MAX)))
+        .with_swarm_config(|cfg| {
+            cfg.with_idle_connection_timeout(Duration::from_secs(u64::MAX))
+                .with_bandwidth_accounting(true)
+        })
         .build();
 
+    // Log the throughput of all connections once per second.
+    let bandwidth = swarm
+        .bandwidth()
+        .expect("bandwidth accounting to be enabled");
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        let mut last = bandwidth.total();
+        loop {
+            interval.tick().await;
+            let (inbound, outbound) = bandwidth.total();
+            tracing::info!(
+                inbound_mb_per_s = (inbound - last.0) as f64 / 1_000_000.0,
+                outbound_mb_per_s = (outbound - last.1) as f64 / 1_000_000.0,
+                "Bandwidth"
+            );
+            last = (inbound, outbound);
+        }
+    });
+
     // Tell the swarm to listen on all interfaces and a random, OS-assigned
     // port.
     swarm.listen_on("/ip4/0.0.0.0/tcp/0".parse()?)?;
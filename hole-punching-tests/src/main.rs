@@ -111,10 +111,13 @@ async fn main() -> Result<()> {
                     .await?;
             }
             (
-                SwarmEvent::Behaviour(BehaviourEvent::Dcutr(dcutr::Event {
-                    remote_peer_id,
-                    result: Ok(connection_id),
-                })),
+                SwarmEvent::Behaviour(BehaviourEvent::Dcutr(
+                    dcutr::Event::DirectConnectionUpgradeSucceeded {
+                        remote_peer_id,
+                        direct_connection_id: connection_id,
+                        ..
+                    },
+                )),
                 _,
                 _,
             ) => {
@@ -136,11 +139,13 @@ async fn main() -> Result<()> {
                 return Ok(());
             }
             (
-                SwarmEvent::Behaviour(BehaviourEvent::Dcutr(dcutr::Event {
-                    remote_peer_id,
-                    result: Err(error),
-                    ..
-                })),
+                SwarmEvent::Behaviour(BehaviourEvent::Dcutr(
+                    dcutr::Event::DirectConnectionUpgradeFailed {
+                        remote_peer_id,
+                        error,
+                        ..
+                    },
+                )),
                 _,
                 _,
             ) => {
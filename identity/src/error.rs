@@ -77,7 +77,12 @@ impl DecodingError {
         }
     }
 
-    #[cfg(all(feature = "rsa", not(target_arch = "wasm32")))]
+    #[cfg(any(
+        feature = "ecdsa",
+        feature = "secp256k1",
+        feature = "ed25519",
+        feature = "rsa"
+    ))]
     pub(crate) fn encoding_unsupported(key_type: &'static str) -> Self {
         Self {
             msg: format!("encoding {key_type} key to Protobuf is unsupported"),
@@ -107,7 +112,6 @@ pub struct SigningError {
 
 /// An error during encoding of key material.
 impl SigningError {
-    #[cfg(all(feature = "rsa", not(target_arch = "wasm32")))]
     pub(crate) fn new<S: ToString>(msg: S) -> Self {
         Self {
             msg: msg.to_string(),
@@ -115,10 +119,9 @@ impl SigningError {
         }
     }
 
-    #[cfg(all(feature = "rsa", not(target_arch = "wasm32")))]
-    pub(crate) fn source(self, source: impl Error + Send + Sync + 'static) -> Self {
+    pub(crate) fn source(self, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
         Self {
-            source: Some(Box::new(source)),
+            source: Some(source.into()),
             ..self
         }
     }
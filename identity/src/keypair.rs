@@ -60,6 +60,8 @@ use crate::secp256k1;
 #[cfg(feature = "ecdsa")]
 use crate::ecdsa;
 use crate::KeyType;
+use std::fmt;
+use std::sync::Arc;
 
 /// Identity keypair of a node.
 ///
@@ -98,6 +100,34 @@ enum KeyPairInner {
     /// An ECDSA keypair.
     #[cfg(feature = "ecdsa")]
     Ecdsa(ecdsa::Keypair),
+    /// A keypair whose private key is held by an external [`Signer`].
+    External(ExternalKeypair),
+}
+
+/// Produces signatures on behalf of a [`Keypair`] without exposing its private key.
+///
+/// Implement this trait for keys that are held outside of process memory, e.g. in an HSM, and
+/// construct a [`Keypair`] from it via [`Keypair::from_signer`].
+pub trait Signer: Send + Sync + 'static {
+    /// Sign a message with the private key corresponding to the public key of the [`Keypair`].
+    ///
+    /// The signature must be valid according to the key type, i.e. it must pass
+    /// [`PublicKey::verify`].
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+#[derive(Clone)]
+struct ExternalKeypair {
+    public: PublicKey,
+    signer: Arc<dyn Signer>,
+}
+
+impl fmt::Debug for ExternalKeypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExternalKeypair")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Keypair {
@@ -125,6 +155,21 @@ impl Keypair {
         }
     }
 
+    /// Construct a keypair from a public key and an external [`Signer`] holding the
+    /// corresponding private key.
+    ///
+    /// The private key never enters process memory. As a result, the keypair can neither be
+    /// encoded via [`Keypair::to_protobuf_encoding`] nor be used to derive secrets via
+    /// [`Keypair::derive_secret`].
+    pub fn from_signer(public: PublicKey, signer: impl Signer) -> Keypair {
+        Keypair {
+            keypair: KeyPairInner::External(ExternalKeypair {
+                public,
+                signer: Arc::new(signer),
+            }),
+        }
+    }
+
     #[cfg(feature = "ed25519")]
     pub fn try_into_ed25519(self) -> Result<ed25519::Keypair, OtherVariantError> {
         self.try_into()
@@ -189,6 +234,10 @@ impl Keypair {
             KeyPairInner::Secp256k1(ref pair) => Ok(pair.secret().sign(msg)),
             #[cfg(feature = "ecdsa")]
             KeyPairInner::Ecdsa(ref pair) => Ok(pair.secret().sign(msg)),
+            KeyPairInner::External(ref pair) => pair
+                .signer
+                .sign(msg)
+                .map_err(|e| SigningError::new("external signer failed").source(e)),
         }
     }
 
@@ -211,6 +260,7 @@ impl Keypair {
             KeyPairInner::Ecdsa(ref pair) => PublicKey {
                 publickey: PublicKeyInner::Ecdsa(pair.public().clone()),
             },
+            KeyPairInner::External(ref pair) => pair.public.clone(),
         }
     }
 
@@ -242,6 +292,9 @@ impl Keypair {
                     Type: proto::KeyType::ECDSA,
                     Data: data.secret().encode_der(),
                 },
+                KeyPairInner::External(_) => {
+                    return Err(DecodingError::encoding_unsupported("external signer"))
+                }
             };
 
             let mut buf = Vec::with_capacity(pk.get_size());
@@ -340,12 +393,14 @@ impl Keypair {
             KeyPairInner::Secp256k1(_) => KeyType::Secp256k1,
             #[cfg(feature = "ecdsa")]
             KeyPairInner::Ecdsa(_) => KeyType::Ecdsa,
+            KeyPairInner::External(ref pair) => pair.public.key_type(),
         }
     }
 
     /// Deterministically derive a new secret from this [`Keypair`], taking into account the provided domain.
     ///
-    /// This works for all key types except RSA where it returns `None`.
+    /// This works for all key types except RSA and keypairs backed by an external [`Signer`]
+    /// where it returns `None`.
     ///
     /// # Example
     ///
@@ -402,6 +457,7 @@ impl Keypair {
                     .try_into()
                     .expect("Ecdsa's private key should be 32 bytes"),
             ),
+            KeyPairInner::External(_) => None,
         }
     }
 }
@@ -455,6 +511,7 @@ impl TryInto<ed25519::Keypair> for Keypair {
             KeyPairInner::Secp256k1(_) => Err(OtherVariantError::new(crate::KeyType::Secp256k1)),
             #[cfg(feature = "ecdsa")]
             KeyPairInner::Ecdsa(_) => Err(OtherVariantError::new(crate::KeyType::Ecdsa)),
+            KeyPairInner::External(pair) => Err(OtherVariantError::new(pair.public.key_type())),
         }
     }
 }
@@ -472,6 +529,7 @@ impl TryInto<ecdsa::Keypair> for Keypair {
             KeyPairInner::Rsa(_) => Err(OtherVariantError::new(crate::KeyType::RSA)),
             #[cfg(feature = "secp256k1")]
             KeyPairInner::Secp256k1(_) => Err(OtherVariantError::new(crate::KeyType::Secp256k1)),
+            KeyPairInner::External(pair) => Err(OtherVariantError::new(pair.public.key_type())),
         }
     }
 }
@@ -489,6 +547,7 @@ impl TryInto<secp256k1::Keypair> for Keypair {
             KeyPairInner::Rsa(_) => Err(OtherVariantError::new(crate::KeyType::RSA)),
             #[cfg(feature = "ecdsa")]
             KeyPairInner::Ecdsa(_) => Err(OtherVariantError::new(crate::KeyType::Ecdsa)),
+            KeyPairInner::External(pair) => Err(OtherVariantError::new(pair.public.key_type())),
         }
     }
 }
@@ -506,6 +565,7 @@ impl TryInto<rsa::Keypair> for Keypair {
             KeyPairInner::Secp256k1(_) => Err(OtherVariantError::new(crate::KeyType::Secp256k1)),
             #[cfg(feature = "ecdsa")]
             KeyPairInner::Ecdsa(_) => Err(OtherVariantError::new(crate::KeyType::Ecdsa)),
+            KeyPairInner::External(pair) => Err(OtherVariantError::new(pair.public.key_type())),
         }
     }
 }
@@ -889,6 +949,34 @@ mod tests {
         assert_eq!(private_key.key_type(), tpe)
     }
 
+    #[test]
+    #[cfg(all(feature = "ed25519", feature = "rand"))]
+    fn keypair_from_signer_signs_with_external_key() {
+        struct LocalSigner(Keypair);
+
+        impl Signer for LocalSigner {
+            fn sign(
+                &self,
+                msg: &[u8],
+            ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+                Ok(self.0.sign(msg)?)
+            }
+        }
+
+        let inner = Keypair::generate_ed25519();
+        let keypair = Keypair::from_signer(inner.public(), LocalSigner(inner.clone()));
+
+        let msg = b"hello world";
+        let sig = keypair.sign(msg).unwrap();
+
+        assert_eq!(keypair.public(), inner.public());
+        assert_eq!(keypair.key_type(), KeyType::Ed25519);
+        assert!(keypair.public().verify(msg, &sig));
+        assert!(keypair.to_protobuf_encoding().is_err());
+        assert!(keypair.derive_secret(b"domain").is_none());
+        assert!(keypair.try_into_ed25519().is_err());
+    }
+
     #[test]
     #[cfg(feature = "peerid")]
     fn keypair_from_protobuf_encoding() {
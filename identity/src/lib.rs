@@ -110,7 +110,7 @@ impl From<&PublicKey> for proto::PublicKey {
 }
 
 pub use error::{DecodingError, OtherVariantError, SigningError};
-pub use keypair::{Keypair, PublicKey};
+pub use keypair::{Keypair, PublicKey, Signer};
 #[cfg(feature = "peerid")]
 pub use peer_id::{ParseError, PeerId};
 
@@ -20,8 +20,9 @@
 
 #![allow(deprecated)]
 
-use crate::core::muxing::{StreamMuxer, StreamMuxerEvent};
+use crate::core::muxing::{SendDatagramError, StreamMuxer, StreamMuxerEvent};
 
+use bytes::Bytes;
 use futures::{
     io::{IoSlice, IoSliceMut},
     prelude::*,
@@ -100,6 +101,15 @@ where
         let this = self.project();
         this.inner.poll_close(cx)
     }
+
+    fn send_datagram(self: Pin<&mut Self>, data: Bytes) -> Result<(), SendDatagramError> {
+        let this = self.project();
+        this.inner.send_datagram(data)
+    }
+
+    fn max_datagram_size(&self) -> Option<usize> {
+        self.inner.max_datagram_size()
+    }
 }
 
 /// Allows obtaining the average bandwidth of the streams.
@@ -513,6 +513,404 @@ mod tests {
             .build();
     }
 
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "tls",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    async fn noise_only_peer_connects_to_tls_noise_peer() {
+        use futures::StreamExt;
+        use libp2p_swarm::SwarmEvent;
+        use std::time::Duration;
+
+        let mut listener = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                (libp2p_tls::Config::new, libp2p_noise::Config::new),
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(10)))
+            .build();
+        let mut dialer = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(10)))
+            .build();
+
+        listener
+            .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+            .unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await {
+                break address;
+            }
+        };
+        dialer.dial(addr).unwrap();
+
+        let listener_peer_id = *listener.local_peer_id();
+        let established = async {
+            loop {
+                futures::select! {
+                    event = dialer.select_next_some() => match event {
+                        SwarmEvent::ConnectionEstablished { peer_id, .. } => break peer_id,
+                        SwarmEvent::OutgoingConnectionError { error, .. } => {
+                            panic!("dial failed: {error}")
+                        }
+                        _ => {}
+                    },
+                    _ = listener.select_next_some() => {}
+                }
+            }
+        };
+        let peer_id = tokio::time::timeout(Duration::from_secs(10), established)
+            .await
+            .expect("connection to be established");
+
+        assert_eq!(peer_id, listener_peer_id);
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "tls",
+        feature = "noise",
+        feature = "yamux",
+        feature = "secp256k1",
+        feature = "ed25519"
+    ))]
+    async fn secp256k1_peer_connects_to_ed25519_peer() {
+        use futures::StreamExt;
+        use libp2p_identity::Keypair;
+        use libp2p_swarm::{Swarm, SwarmEvent};
+        use std::time::Duration;
+
+        fn build(keypair: Keypair, tls: bool) -> Swarm<libp2p_swarm::dummy::Behaviour> {
+            let builder = SwarmBuilder::with_existing_identity(keypair).with_tokio();
+            let builder = if tls {
+                builder
+                    .with_tcp(
+                        Default::default(),
+                        libp2p_tls::Config::new,
+                        libp2p_yamux::Config::default,
+                    )
+                    .unwrap()
+                    .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+                    .unwrap()
+            } else {
+                builder
+                    .with_tcp(
+                        Default::default(),
+                        libp2p_noise::Config::new,
+                        libp2p_yamux::Config::default,
+                    )
+                    .unwrap()
+                    .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+                    .unwrap()
+            };
+            builder
+                .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(10)))
+                .build()
+        }
+
+        for tls in [false, true] {
+            let secp256k1 = Keypair::generate_secp256k1();
+            let secp256k1_peer_id = secp256k1.public().to_peer_id();
+            let mut listener = build(secp256k1, tls);
+            let mut dialer = build(Keypair::generate_ed25519(), tls);
+            assert_eq!(*listener.local_peer_id(), secp256k1_peer_id);
+
+            listener
+                .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+                .unwrap();
+            let addr = loop {
+                if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await
+                {
+                    break address;
+                }
+            };
+            dialer.dial(addr).unwrap();
+
+            let established = async {
+                loop {
+                    futures::select! {
+                        event = dialer.select_next_some() => match event {
+                            SwarmEvent::ConnectionEstablished { peer_id, .. } => break peer_id,
+                            SwarmEvent::OutgoingConnectionError { error, .. } => {
+                                panic!("dial failed: {error}")
+                            }
+                            _ => {}
+                        },
+                        _ = listener.select_next_some() => {}
+                    }
+                }
+            };
+            let peer_id = tokio::time::timeout(Duration::from_secs(10), established)
+                .await
+                .expect("connection to be established");
+
+            assert_eq!(peer_id, secp256k1_peer_id);
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux"
+    ))]
+    async fn tcp_connection_timeout() {
+        use futures::StreamExt;
+        use libp2p_core::transport::{
+            timeout::TransportTimeoutError, upgrade::UpgradedTransportError, TransportError,
+        };
+        use libp2p_swarm::{DialError, SwarmEvent};
+        use std::time::{Duration, Instant};
+
+        let timeout = Duration::from_millis(500);
+
+        // Accepts TCP connections but never completes the security handshake.
+        let silent_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = silent_listener.local_addr().unwrap().port();
+
+        let mut swarm = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .with_connection_timeout(timeout)
+            .build();
+
+        let start = Instant::now();
+        swarm
+            .dial(
+                format!("/ip4/127.0.0.1/tcp/{port}")
+                    .parse::<crate::Multiaddr>()
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), swarm.select_next_some())
+            .await
+            .expect("dial to time out before the test does");
+
+        let elapsed = start.elapsed();
+
+        let SwarmEvent::OutgoingConnectionError {
+            error: DialError::Transport(errors),
+            ..
+        } = event
+        else {
+            panic!("Unexpected event: {event:?}")
+        };
+        let [(_, TransportError::Other(error))] = &errors[..] else {
+            panic!("Unexpected errors: {errors:?}")
+        };
+        // The error of the TCP transport, upgraded by the builder.
+        type TcpError = UpgradedTransportError<UpgradedTransportError<std::io::Error>>;
+        assert!(matches!(
+            error
+                .get_ref()
+                .and_then(|e| e.downcast_ref::<TransportTimeoutError<TcpError>>()),
+            Some(TransportTimeoutError::Timeout)
+        ));
+        assert!(elapsed >= timeout);
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        unix,
+        feature = "tokio",
+        feature = "uds",
+        feature = "noise",
+        feature = "yamux",
+        feature = "ping"
+    ))]
+    async fn uds() {
+        use futures::StreamExt;
+        use libp2p_swarm::SwarmEvent;
+
+        let new_swarm = || {
+            SwarmBuilder::with_new_identity()
+                .with_tokio()
+                .with_uds(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+                .unwrap()
+                .with_behaviour(|_| libp2p_ping::Behaviour::default())
+                .unwrap()
+                .with_swarm_config(|cfg| {
+                    cfg.with_idle_connection_timeout(std::time::Duration::from_secs(5))
+                })
+                .build()
+        };
+        let mut listener = new_swarm();
+        let mut dialer = new_swarm();
+        let listener_peer_id = *listener.local_peer_id();
+
+        let path = std::env::temp_dir().join(format!("libp2p-uds-{listener_peer_id}.sock"));
+        let addr = crate::Multiaddr::empty().with(crate::multiaddr::Protocol::Unix(
+            path.to_string_lossy().into_owned().into(),
+        ));
+        listener.listen_on(addr.clone()).unwrap();
+        loop {
+            if let SwarmEvent::NewListenAddr { .. } = listener.select_next_some().await {
+                break;
+            }
+        }
+        tokio::spawn(async move {
+            loop {
+                listener.select_next_some().await;
+            }
+        });
+
+        dialer.dial(addr).unwrap();
+        let (peer, rtt) = loop {
+            match dialer.select_next_some().await {
+                SwarmEvent::Behaviour(libp2p_ping::Event {
+                    peer,
+                    result: Ok(rtt),
+                    ..
+                }) => break (peer, rtt),
+                SwarmEvent::Behaviour(libp2p_ping::Event {
+                    result: Err(error), ..
+                }) => panic!("{error:?}"),
+                SwarmEvent::OutgoingConnectionError { error, .. } => panic!("{error:?}"),
+                _ => {}
+            }
+        };
+
+        assert_eq!(peer, listener_peer_id);
+        assert!(rtt > std::time::Duration::ZERO);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "noise",
+        feature = "yamux",
+        feature = "ping"
+    ))]
+    async fn memory() {
+        use futures::StreamExt;
+        use libp2p_swarm::SwarmEvent;
+
+        let new_swarm = || {
+            SwarmBuilder::with_new_identity()
+                .with_tokio()
+                .with_memory(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+                .unwrap()
+                .with_behaviour(|_| libp2p_ping::Behaviour::default())
+                .unwrap()
+                .with_swarm_config(|cfg| {
+                    cfg.with_idle_connection_timeout(std::time::Duration::from_secs(5))
+                })
+                .build()
+        };
+        let mut listener = new_swarm();
+        let mut dialer = new_swarm();
+        let listener_peer_id = *listener.local_peer_id();
+
+        listener.listen_on("/memory/0".parse().unwrap()).unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await {
+                break address;
+            }
+        };
+        tokio::spawn(async move {
+            loop {
+                listener.select_next_some().await;
+            }
+        });
+
+        dialer.dial(addr).unwrap();
+        let event = loop {
+            match dialer.select_next_some().await {
+                SwarmEvent::Behaviour(event) => break event,
+                SwarmEvent::OutgoingConnectionError { error, .. } => panic!("{error:?}"),
+                _ => {}
+            }
+        };
+
+        assert_eq!(event.peer, listener_peer_id);
+        assert!(event.result.is_ok());
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tls",
+        feature = "noise",
+        feature = "yamux",
+        feature = "dns",
+        feature = "websocket",
+    ))]
+    async fn websocket() {
+        let _ = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_websocket(
+                (libp2p_tls::Config::new, libp2p_noise::Config::new),
+                libp2p_yamux::Config::default,
+            )
+            .await
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+    }
+
+    #[tokio::test]
+    #[cfg(all(
+        feature = "tokio",
+        feature = "tcp",
+        feature = "noise",
+        feature = "yamux",
+        feature = "dns",
+        feature = "websocket",
+    ))]
+    async fn tcp_websocket_listens_on_both() {
+        let mut swarm = SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                Default::default(),
+                libp2p_noise::Config::new,
+                libp2p_yamux::Config::default,
+            )
+            .unwrap()
+            .with_websocket(libp2p_noise::Config::new, libp2p_yamux::Config::default)
+            .await
+            .unwrap()
+            .with_behaviour(|_| libp2p_swarm::dummy::Behaviour)
+            .unwrap()
+            .build();
+
+        swarm
+            .listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap())
+            .unwrap();
+        swarm
+            .listen_on("/ip4/127.0.0.1/tcp/0/ws".parse().unwrap())
+            .unwrap();
+    }
+
     #[tokio::test]
     #[cfg(all(
         feature = "tokio",
@@ -6,12 +6,14 @@ mod behaviour;
 mod build;
 mod dns;
 mod identity;
+mod memory;
 mod other_transport;
 mod provider;
 mod quic;
 mod relay;
 mod swarm;
 mod tcp;
+mod uds;
 mod websocket;
 
 use bandwidth_logging::*;
@@ -31,7 +33,12 @@ use super::select_muxer::SelectMuxerUpgrade;
 use super::select_security::SelectSecurityUpgrade;
 use super::SwarmBuilder;
 
-use libp2p_core::{muxing::StreamMuxerBox, Transport};
+use futures::{AsyncRead, AsyncWrite, Future};
+use libp2p_core::{
+    muxing::{StreamMuxer, StreamMuxerBox},
+    upgrade::{InboundConnectionUpgrade, OutboundConnectionUpgrade},
+    Negotiated, Transport, UpgradeInfo,
+};
 use libp2p_identity::Keypair;
 
 #[allow(unreachable_pub)]
@@ -111,6 +118,130 @@ where
     }
 }
 
+/// A security upgrade, as passed to e.g. [`SwarmBuilder::with_memory`], for connections of type
+/// `C`.
+///
+/// Bundles the bounds of the resulting upgrade, so transports upgraded with it can be used as
+/// [`AuthenticatedMultiplexedTransport`].
+#[allow(unreachable_pub)]
+pub trait SecurityUpgrade<C>: IntoSecurityUpgrade<C, Upgrade = Self::U> {
+    type Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static;
+    type E: std::error::Error + Send + Sync + 'static;
+    type U: InboundConnectionUpgrade<
+            Negotiated<C>,
+            Output = (libp2p_identity::PeerId, Self::Stream),
+            Error = Self::E,
+            Future = Self::InboundFuture,
+        > + OutboundConnectionUpgrade<
+            Negotiated<C>,
+            Output = (libp2p_identity::PeerId, Self::Stream),
+            Error = Self::E,
+            Future = Self::OutboundFuture,
+        > + UpgradeInfo<Info = Self::Info, InfoIter = Self::InfoIter>
+        + Clone
+        + Send
+        + 'static;
+    type InboundFuture: Future<Output = Result<(libp2p_identity::PeerId, Self::Stream), Self::E>>
+        + Send;
+    type OutboundFuture: Future<Output = Result<(libp2p_identity::PeerId, Self::Stream), Self::E>>
+        + Send;
+    type Info: AsRef<str> + Clone + Send;
+    type InfoIter: IntoIterator<Item = Self::Info, IntoIter = Self::InfoIntoIter>;
+    type InfoIntoIter: Iterator<Item = Self::Info> + Send;
+}
+
+impl<C, T, Stream, E> SecurityUpgrade<C> for T
+where
+    T: IntoSecurityUpgrade<C>,
+    T::Upgrade: InboundConnectionUpgrade<
+            Negotiated<C>,
+            Output = (libp2p_identity::PeerId, Stream),
+            Error = E,
+        > + OutboundConnectionUpgrade<
+            Negotiated<C>,
+            Output = (libp2p_identity::PeerId, Stream),
+            Error = E,
+        > + Clone
+        + Send
+        + 'static,
+    <T::Upgrade as InboundConnectionUpgrade<Negotiated<C>>>::Future: Send,
+    <T::Upgrade as OutboundConnectionUpgrade<Negotiated<C>>>::Future: Send,
+    <T::Upgrade as UpgradeInfo>::Info: Send,
+    <<T::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+    Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    type Stream = Stream;
+    type E = E;
+    type U = T::Upgrade;
+    type InboundFuture = <T::Upgrade as InboundConnectionUpgrade<Negotiated<C>>>::Future;
+    type OutboundFuture = <T::Upgrade as OutboundConnectionUpgrade<Negotiated<C>>>::Future;
+    type Info = <T::Upgrade as UpgradeInfo>::Info;
+    type InfoIter = <T::Upgrade as UpgradeInfo>::InfoIter;
+    type InfoIntoIter = <<T::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter;
+}
+
+/// A multiplexer upgrade, as passed to e.g. [`SwarmBuilder::with_memory`], for secured
+/// connections of type `C`.
+///
+/// Bundles the bounds of the resulting upgrade, so transports upgraded with it can be used as
+/// [`AuthenticatedMultiplexedTransport`].
+#[allow(unreachable_pub)]
+pub trait MultiplexerUpgrade<C>: IntoMultiplexerUpgrade<C, Upgrade = Self::U> {
+    type Muxer: StreamMuxer<Substream = Self::Substream, Error = Self::MuxerError> + Send + 'static;
+    type Substream: AsyncRead + AsyncWrite + Send + 'static;
+    type MuxerError: std::error::Error + Send + Sync + 'static;
+    type E: std::error::Error + Send + Sync + 'static;
+    type U: InboundConnectionUpgrade<
+            Negotiated<C>,
+            Output = Self::Muxer,
+            Error = Self::E,
+            Future = Self::InboundFuture,
+        > + OutboundConnectionUpgrade<
+            Negotiated<C>,
+            Output = Self::Muxer,
+            Error = Self::E,
+            Future = Self::OutboundFuture,
+        > + UpgradeInfo<Info = Self::Info, InfoIter = Self::InfoIter>
+        + Clone
+        + Send
+        + 'static;
+    type InboundFuture: Future<Output = Result<Self::Muxer, Self::E>> + Send;
+    type OutboundFuture: Future<Output = Result<Self::Muxer, Self::E>> + Send;
+    type Info: AsRef<str> + Clone + Send;
+    type InfoIter: IntoIterator<Item = Self::Info, IntoIter = Self::InfoIntoIter>;
+    type InfoIntoIter: Iterator<Item = Self::Info> + Send;
+}
+
+impl<C, T, Muxer, E> MultiplexerUpgrade<C> for T
+where
+    T: IntoMultiplexerUpgrade<C>,
+    T::Upgrade: InboundConnectionUpgrade<Negotiated<C>, Output = Muxer, Error = E>
+        + OutboundConnectionUpgrade<Negotiated<C>, Output = Muxer, Error = E>
+        + Clone
+        + Send
+        + 'static,
+    <T::Upgrade as InboundConnectionUpgrade<Negotiated<C>>>::Future: Send,
+    <T::Upgrade as OutboundConnectionUpgrade<Negotiated<C>>>::Future: Send,
+    <T::Upgrade as UpgradeInfo>::Info: Send,
+    <<T::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter: Send,
+    Muxer: StreamMuxer + Send + 'static,
+    Muxer::Substream: Send + 'static,
+    Muxer::Error: Send + Sync + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    type Muxer = Muxer;
+    type Substream = Muxer::Substream;
+    type MuxerError = Muxer::Error;
+    type E = E;
+    type U = T::Upgrade;
+    type InboundFuture = <T::Upgrade as InboundConnectionUpgrade<Negotiated<C>>>::Future;
+    type OutboundFuture = <T::Upgrade as OutboundConnectionUpgrade<Negotiated<C>>>::Future;
+    type Info = <T::Upgrade as UpgradeInfo>::Info;
+    type InfoIter = <T::Upgrade as UpgradeInfo>::InfoIter;
+    type InfoIntoIter = <<T::Upgrade as UpgradeInfo>::InfoIter as IntoIterator>::IntoIter;
+}
+
 pub trait AuthenticatedMultiplexedTransport:
     Transport<
         Error = Self::E,
@@ -4,23 +4,51 @@ use super::*;
 use crate::SwarmBuilder;
 use libp2p_core::Transport;
 use libp2p_swarm::Swarm;
+use std::time::Duration;
 
 pub struct BuildPhase<T, B> {
     pub(crate) behaviour: B,
     pub(crate) transport: T,
     pub(crate) swarm_config: libp2p_swarm::Config,
+    pub(crate) connection_timeout: Duration,
 }
 
-const CONNECTION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// The default of [`SwarmBuilder::with_connection_timeout`], used by `with_swarm_config`.
+#[cfg(any(
+    all(
+        not(target_arch = "wasm32"),
+        any(feature = "async-std", feature = "tokio")
+    ),
+    all(target_arch = "wasm32", feature = "wasm-bindgen")
+))]
+pub(crate) const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
 
 impl<Provider, T: AuthenticatedMultiplexedTransport, B: libp2p_swarm::NetworkBehaviour>
     SwarmBuilder<Provider, BuildPhase<T, B>>
 {
+    /// Sets the timeout for establishing a connection, i.e. for the transport and the security and
+    /// multiplexer upgrades to complete.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn with_connection_timeout(mut self, timeout: Duration) -> Self {
+        self.phase.connection_timeout = timeout;
+        self
+    }
+
+    /// Sets a [`ConnectionFilter`](libp2p_swarm::ConnectionFilter) deciding which inbound
+    /// connections are accepted, before any upgrade is applied to them.
+    ///
+    /// See [`libp2p_swarm::Config::with_connection_filter`].
+    pub fn with_connection_filter(mut self, filter: impl libp2p_swarm::ConnectionFilter) -> Self {
+        self.phase.swarm_config = self.phase.swarm_config.with_connection_filter(filter);
+        self
+    }
+
     pub fn build(self) -> Swarm<B> {
         Swarm::new(
             libp2p_core::transport::timeout::TransportTimeout::new(
                 self.phase.transport,
-                CONNECTION_TIMEOUT,
+                self.phase.connection_timeout,
             )
             .boxed(),
             self.phase.behaviour,
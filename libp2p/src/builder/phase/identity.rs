@@ -5,10 +5,18 @@ use std::marker::PhantomData;
 pub struct IdentityPhase {}
 
 impl SwarmBuilder<NoProviderSpecified, IdentityPhase> {
+    /// Use a newly generated Ed25519 identity.
+    ///
+    /// Use [`SwarmBuilder::with_existing_identity`] to use a different key type, e.g. a
+    /// [`Keypair::generate_secp256k1`](libp2p_identity::Keypair::generate_secp256k1).
     pub fn with_new_identity() -> SwarmBuilder<NoProviderSpecified, ProviderPhase> {
         SwarmBuilder::with_existing_identity(libp2p_identity::Keypair::generate_ed25519())
     }
 
+    /// Use the given identity.
+    ///
+    /// The local [`PeerId`](libp2p_identity::PeerId) is derived from its public key and the
+    /// Noise and TLS handshakes authenticate with it, whatever its key type.
     pub fn with_existing_identity(
         keypair: libp2p_identity::Keypair,
     ) -> SwarmBuilder<NoProviderSpecified, ProviderPhase> {
@@ -18,4 +26,19 @@ impl SwarmBuilder<NoProviderSpecified, IdentityPhase> {
             phase: ProviderPhase {},
         }
     }
+
+    /// Use an identity whose private key is held by an external [`Signer`](libp2p_identity::Signer),
+    /// e.g. an HSM.
+    ///
+    /// The local [`PeerId`](libp2p_identity::PeerId) is derived from `public_key`. All signing
+    /// operations, e.g. during the Noise and TLS handshakes or when signing peer records, are
+    /// delegated to `signer`.
+    pub fn with_external_identity(
+        public_key: libp2p_identity::PublicKey,
+        signer: impl libp2p_identity::Signer,
+    ) -> SwarmBuilder<NoProviderSpecified, ProviderPhase> {
+        SwarmBuilder::with_existing_identity(libp2p_identity::Keypair::from_signer(
+            public_key, signer,
+        ))
+    }
 }
@@ -0,0 +1,97 @@
+use super::*;
+use crate::SwarmBuilder;
+use libp2p_core::{muxing::StreamMuxerBox, transport::MemoryTransport, Transport};
+use std::marker::PhantomData;
+
+impl<Provider, T: AuthenticatedMultiplexedTransport>
+    SwarmBuilder<Provider, OtherTransportPhase<T>>
+{
+    /// Adds an in-process [`MemoryTransport`], listening on and dialing `/memory/<port>`
+    /// addresses without using any OS sockets.
+    ///
+    /// Connections are upgraded with the given security and multiplexer upgrades, exactly like
+    /// with [`SwarmBuilder::with_tcp`], which makes this transport well suited for fast and
+    /// deterministic tests. Listening on `/memory/0` picks an unused port.
+    ///
+    /// ``` rust
+    /// # use libp2p::SwarmBuilder;
+    /// # use std::error::Error;
+    /// # #[cfg(all(feature = "tokio", feature = "noise", feature = "yamux"))]
+    /// # async fn build_swarm() -> Result<(), Box<dyn Error>> {
+    /// let swarm = SwarmBuilder::with_new_identity()
+    ///     .with_tokio()
+    ///     .with_memory(libp2p_noise::Config::new, libp2p_yamux::Config::default)?
+    /// # ;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_memory<SecUpgrade, MuxUpgrade>(
+        self,
+        security_upgrade: SecUpgrade,
+        multiplexer_upgrade: MuxUpgrade,
+    ) -> Result<
+        SwarmBuilder<Provider, OtherTransportPhase<impl AuthenticatedMultiplexedTransport>>,
+        SecUpgrade::Error,
+    >
+    where
+        SecUpgrade: SecurityUpgrade<libp2p_core::transport::memory::Channel<Vec<u8>>>,
+        MuxUpgrade: MultiplexerUpgrade<SecUpgrade::Stream>,
+    {
+        let memory = MemoryTransport::default()
+            .upgrade(libp2p_core::upgrade::Version::V1Lazy)
+            .authenticate(security_upgrade.into_security_upgrade(&self.keypair)?)
+            .multiplex(multiplexer_upgrade.into_multiplexer_upgrade())
+            .map(|(p, c), _| (p, StreamMuxerBox::new(c)));
+
+        Ok(SwarmBuilder {
+            phase: OtherTransportPhase {
+                transport: self
+                    .phase
+                    .transport
+                    .or_transport(memory)
+                    .map(|either, _| either.into_inner()),
+            },
+            keypair: self.keypair,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<Provider> SwarmBuilder<Provider, TcpPhase> {
+    /// See [`SwarmBuilder::with_memory`].
+    pub fn with_memory<SecUpgrade, MuxUpgrade>(
+        self,
+        security_upgrade: SecUpgrade,
+        multiplexer_upgrade: MuxUpgrade,
+    ) -> Result<
+        SwarmBuilder<Provider, OtherTransportPhase<impl AuthenticatedMultiplexedTransport>>,
+        SecUpgrade::Error,
+    >
+    where
+        SecUpgrade: SecurityUpgrade<libp2p_core::transport::memory::Channel<Vec<u8>>>,
+        MuxUpgrade: MultiplexerUpgrade<SecUpgrade::Stream>,
+    {
+        self.without_tcp()
+            .without_quic()
+            .with_memory(security_upgrade, multiplexer_upgrade)
+    }
+}
+
+impl<Provider, T: AuthenticatedMultiplexedTransport> SwarmBuilder<Provider, QuicPhase<T>> {
+    /// See [`SwarmBuilder::with_memory`].
+    pub fn with_memory<SecUpgrade, MuxUpgrade>(
+        self,
+        security_upgrade: SecUpgrade,
+        multiplexer_upgrade: MuxUpgrade,
+    ) -> Result<
+        SwarmBuilder<Provider, OtherTransportPhase<impl AuthenticatedMultiplexedTransport>>,
+        SecUpgrade::Error,
+    >
+    where
+        SecUpgrade: SecurityUpgrade<libp2p_core::transport::memory::Channel<Vec<u8>>>,
+        MuxUpgrade: MultiplexerUpgrade<SecUpgrade::Stream>,
+    {
+        self.without_quic()
+            .with_memory(security_upgrade, multiplexer_upgrade)
+    }
+}
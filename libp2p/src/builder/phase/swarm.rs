@@ -20,6 +20,7 @@ macro_rules! impl_with_swarm_config {
                         behaviour: self.phase.behaviour,
                         transport: self.phase.transport,
                         swarm_config: constructor($config),
+                        connection_timeout: CONNECTION_TIMEOUT,
                     },
                     keypair: self.keypair,
                     phantom: std::marker::PhantomData,
@@ -27,6 +28,18 @@ macro_rules! impl_with_swarm_config {
             }
 
             // Shortcuts
+            pub fn with_connection_timeout(
+                self,
+                timeout: std::time::Duration,
+            ) -> SwarmBuilder<$providerPascalCase, BuildPhase<T, B>>
+            where
+                B: libp2p_swarm::NetworkBehaviour,
+                T: AuthenticatedMultiplexedTransport,
+            {
+                self.with_swarm_config(std::convert::identity)
+                    .with_connection_timeout(timeout)
+            }
+
             pub fn build(self) -> libp2p_swarm::Swarm<B>
             where
                 B: libp2p_swarm::NetworkBehaviour,
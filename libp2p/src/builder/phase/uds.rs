@@ -0,0 +1,113 @@
+use super::*;
+use crate::SwarmBuilder;
+#[cfg(all(unix, not(target_arch = "wasm32"), feature = "uds"))]
+use libp2p_core::{muxing::StreamMuxerBox, Transport};
+use std::marker::PhantomData;
+
+macro_rules! impl_uds_builder {
+    ($providerKebabCase:literal, $providerPascalCase:ty, $udsConfig:ty, $udsStream:ty) => {
+        #[cfg(all(unix, not(target_arch = "wasm32"), feature = "uds", feature = $providerKebabCase))]
+        impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<$providerPascalCase, OtherTransportPhase<T>> {
+            /// Adds a Unix domain socket transport, listening on and dialing `/unix/...` addresses.
+            ///
+            /// The security and multiplexer upgrades are specified like for
+            /// [`SwarmBuilder::with_tcp`].
+            ///
+            /// ``` rust
+            /// # use libp2p::SwarmBuilder;
+            /// # use std::error::Error;
+            /// # async fn build_swarm() -> Result<(), Box<dyn Error>> {
+            /// let swarm = SwarmBuilder::with_new_identity()
+            ///     .with_tokio()
+            ///     .with_uds(libp2p_noise::Config::new, libp2p_yamux::Config::default)?
+            /// # ;
+            /// # Ok(())
+            /// # }
+            /// ```
+            pub fn with_uds<SecUpgrade, MuxUpgrade>(
+                self,
+                security_upgrade: SecUpgrade,
+                multiplexer_upgrade: MuxUpgrade,
+            ) -> Result<
+                SwarmBuilder<$providerPascalCase, OtherTransportPhase<impl AuthenticatedMultiplexedTransport>>,
+                SecUpgrade::Error,
+            >
+            where
+                SecUpgrade: SecurityUpgrade<$udsStream>,
+                MuxUpgrade: MultiplexerUpgrade<SecUpgrade::Stream>,
+            {
+                let uds = <$udsConfig>::new()
+                    .upgrade(libp2p_core::upgrade::Version::V1Lazy)
+                    .authenticate(security_upgrade.into_security_upgrade(&self.keypair)?)
+                    .multiplex(multiplexer_upgrade.into_multiplexer_upgrade())
+                    .map(|(p, c), _| (p, StreamMuxerBox::new(c)));
+
+                Ok(SwarmBuilder {
+                    phase: OtherTransportPhase {
+                        transport: self
+                            .phase
+                            .transport
+                            .or_transport(uds)
+                            .map(|either, _| either.into_inner()),
+                    },
+                    keypair: self.keypair,
+                    phantom: PhantomData,
+                })
+            }
+        }
+
+        #[cfg(all(unix, not(target_arch = "wasm32"), feature = "uds", feature = $providerKebabCase))]
+        impl SwarmBuilder<$providerPascalCase, TcpPhase> {
+            /// See [`SwarmBuilder::with_uds`].
+            pub fn with_uds<SecUpgrade, MuxUpgrade>(
+                self,
+                security_upgrade: SecUpgrade,
+                multiplexer_upgrade: MuxUpgrade,
+            ) -> Result<
+                SwarmBuilder<$providerPascalCase, OtherTransportPhase<impl AuthenticatedMultiplexedTransport>>,
+                SecUpgrade::Error,
+            >
+            where
+                SecUpgrade: SecurityUpgrade<$udsStream>,
+                MuxUpgrade: MultiplexerUpgrade<SecUpgrade::Stream>,
+            {
+                self.without_tcp()
+                    .without_quic()
+                    .with_uds(security_upgrade, multiplexer_upgrade)
+            }
+        }
+
+        #[cfg(all(unix, not(target_arch = "wasm32"), feature = "uds", feature = $providerKebabCase))]
+        impl<T: AuthenticatedMultiplexedTransport> SwarmBuilder<$providerPascalCase, QuicPhase<T>> {
+            /// See [`SwarmBuilder::with_uds`].
+            pub fn with_uds<SecUpgrade, MuxUpgrade>(
+                self,
+                security_upgrade: SecUpgrade,
+                multiplexer_upgrade: MuxUpgrade,
+            ) -> Result<
+                SwarmBuilder<$providerPascalCase, OtherTransportPhase<impl AuthenticatedMultiplexedTransport>>,
+                SecUpgrade::Error,
+            >
+            where
+                SecUpgrade: SecurityUpgrade<$udsStream>,
+                MuxUpgrade: MultiplexerUpgrade<SecUpgrade::Stream>,
+            {
+                self.without_quic()
+                    .with_uds(security_upgrade, multiplexer_upgrade)
+            }
+        }
+    };
+}
+
+impl_uds_builder!(
+    "async-std",
+    super::provider::AsyncStd,
+    libp2p_uds::UdsConfig,
+    libp2p_uds::UnixStream
+);
+impl_uds_builder!(
+    "tokio",
+    super::provider::Tokio,
+    libp2p_uds::TokioUdsConfig,
+    libp2p_uds::TokioUnixStream
+);
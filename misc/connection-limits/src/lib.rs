@@ -18,7 +18,7 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use libp2p_core::{ConnectedPoint, Endpoint, Multiaddr};
+use libp2p_core::{multiaddr::Protocol, ConnectedPoint, Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use libp2p_swarm::{
     behaviour::{ConnectionEstablished, DialFailure, ListenFailure},
@@ -27,6 +27,7 @@ use libp2p_swarm::{
 };
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::net::IpAddr;
 use std::task::{Context, Poll};
 use void::Void;
 
@@ -66,6 +67,8 @@ pub struct Behaviour {
     established_inbound_connections: HashSet<ConnectionId>,
     established_outbound_connections: HashSet<ConnectionId>,
     established_per_peer: HashMap<PeerId, HashSet<ConnectionId>>,
+    pending_inbound_per_ip: HashMap<IpAddr, HashSet<ConnectionId>>,
+    established_per_ip: HashMap<IpAddr, HashSet<ConnectionId>>,
 }
 
 impl Behaviour {
@@ -77,6 +80,8 @@ impl Behaviour {
             established_inbound_connections: Default::default(),
             established_outbound_connections: Default::default(),
             established_per_peer: Default::default(),
+            pending_inbound_per_ip: Default::default(),
+            established_per_ip: Default::default(),
         }
     }
 
@@ -90,24 +95,95 @@ impl Behaviour {
         let current = current as u32;
 
         if current >= limit {
-            return Err(ConnectionDenied::new(Exceeded { limit, kind }));
+            return Err(ConnectionDenied::new(Exceeded {
+                limit,
+                kind,
+                ip: None,
+            }));
         }
 
         Ok(())
     }
 }
 
+fn check_limit_per_ip(
+    limit: Option<u32>,
+    connections: &HashMap<IpAddr, HashSet<ConnectionId>>,
+    address: &Multiaddr,
+    kind: Kind,
+) -> Result<(), ConnectionDenied> {
+    let (Some(limit), Some(ip)) = (limit, ip_of(address)) else {
+        return Ok(());
+    };
+    let current = connections.get(&ip).map_or(0, HashSet::len) as u32;
+
+    if current >= limit {
+        return Err(ConnectionDenied::new(Exceeded {
+            limit,
+            kind,
+            ip: Some(ip),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Returns the IP address of the given address, i.e. its first `ip4` or `ip6` component.
+fn ip_of(address: &Multiaddr) -> Option<IpAddr> {
+    address.iter().find_map(|protocol| match protocol {
+        Protocol::Ip4(ip) => Some(IpAddr::from(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::from(ip)),
+        _ => None,
+    })
+}
+
+fn insert_per_ip(
+    connections: &mut HashMap<IpAddr, HashSet<ConnectionId>>,
+    address: &Multiaddr,
+    connection_id: ConnectionId,
+) {
+    if let Some(ip) = ip_of(address) {
+        connections.entry(ip).or_default().insert(connection_id);
+    }
+}
+
+fn remove_per_ip(
+    connections: &mut HashMap<IpAddr, HashSet<ConnectionId>>,
+    address: &Multiaddr,
+    connection_id: ConnectionId,
+) {
+    let Some(ip) = ip_of(address) else {
+        return;
+    };
+
+    if let Some(ids) = connections.get_mut(&ip) {
+        ids.remove(&connection_id);
+        if ids.is_empty() {
+            connections.remove(&ip);
+        }
+    }
+}
+
 /// A connection limit has been exceeded.
 #[derive(Debug, Clone, Copy)]
 pub struct Exceeded {
     limit: u32,
     kind: Kind,
+    ip: Option<IpAddr>,
 }
 
 impl Exceeded {
     pub fn limit(&self) -> u32 {
         self.limit
     }
+
+    /// The remote IP address whose connections exceeded a per-IP limit.
+    ///
+    /// Returns `None` if a limit other than [`ConnectionLimits::with_max_pending_per_ip`] or
+    /// [`ConnectionLimits::with_max_established_per_ip`] was exceeded.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.ip
+    }
 }
 
 impl fmt::Display for Exceeded {
@@ -116,7 +192,13 @@ impl fmt::Display for Exceeded {
             f,
             "connection limit exceeded: at most {} {} are allowed",
             self.limit, self.kind
-        )
+        )?;
+
+        if let Some(ip) = self.ip {
+            write!(f, " ({ip})")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -128,6 +210,8 @@ enum Kind {
     EstablishedOutgoing,
     EstablishedPerPeer,
     EstablishedTotal,
+    PendingIncomingPerIp,
+    EstablishedPerIp,
 }
 
 impl fmt::Display for Kind {
@@ -139,6 +223,8 @@ impl fmt::Display for Kind {
             Kind::EstablishedOutgoing => write!(f, "established outgoing connections"),
             Kind::EstablishedPerPeer => write!(f, "established connections per peer"),
             Kind::EstablishedTotal => write!(f, "established connections"),
+            Kind::PendingIncomingPerIp => write!(f, "pending incoming connections per IP"),
+            Kind::EstablishedPerIp => write!(f, "established connections per IP"),
         }
     }
 }
@@ -154,6 +240,8 @@ pub struct ConnectionLimits {
     max_established_outgoing: Option<u32>,
     max_established_per_peer: Option<u32>,
     max_established_total: Option<u32>,
+    max_pending_per_ip: Option<u32>,
+    max_established_per_ip: Option<u32>,
 }
 
 impl ConnectionLimits {
@@ -198,6 +286,25 @@ impl ConnectionLimits {
         self.max_established_per_peer = limit;
         self
     }
+
+    /// Configures the maximum number of concurrently incoming connections being established
+    /// from the same remote IP address.
+    ///
+    /// Outgoing connections are not limited, as their remote address is not known before they
+    /// are established. Connections over addresses without an IP component are not limited.
+    pub fn with_max_pending_per_ip(mut self, limit: Option<u32>) -> Self {
+        self.max_pending_per_ip = limit;
+        self
+    }
+
+    /// Configures the maximum number of concurrent established connections with the same remote
+    /// IP address, regardless of direction (incoming or outgoing).
+    ///
+    /// Connections over addresses without an IP component are not limited.
+    pub fn with_max_established_per_ip(mut self, limit: Option<u32>) -> Self {
+        self.max_established_per_ip = limit;
+        self
+    }
 }
 
 impl NetworkBehaviour for Behaviour {
@@ -208,15 +315,22 @@ impl NetworkBehaviour for Behaviour {
         &mut self,
         connection_id: ConnectionId,
         _: &Multiaddr,
-        _: &Multiaddr,
+        remote_addr: &Multiaddr,
     ) -> Result<(), ConnectionDenied> {
         self.check_limit(
             self.limits.max_pending_incoming,
             self.pending_inbound_connections.len(),
             Kind::PendingIncoming,
         )?;
+        check_limit_per_ip(
+            self.limits.max_pending_per_ip,
+            &self.pending_inbound_per_ip,
+            remote_addr,
+            Kind::PendingIncomingPerIp,
+        )?;
 
         self.pending_inbound_connections.insert(connection_id);
+        insert_per_ip(&mut self.pending_inbound_per_ip, remote_addr, connection_id);
 
         Ok(())
     }
@@ -226,9 +340,10 @@ impl NetworkBehaviour for Behaviour {
         connection_id: ConnectionId,
         peer: PeerId,
         _: &Multiaddr,
-        _: &Multiaddr,
+        remote_addr: &Multiaddr,
     ) -> Result<THandler<Self>, ConnectionDenied> {
         self.pending_inbound_connections.remove(&connection_id);
+        remove_per_ip(&mut self.pending_inbound_per_ip, remote_addr, connection_id);
 
         self.check_limit(
             self.limits.max_established_incoming,
@@ -250,6 +365,13 @@ impl NetworkBehaviour for Behaviour {
             Kind::EstablishedTotal,
         )?;
 
+        check_limit_per_ip(
+            self.limits.max_established_per_ip,
+            &self.established_per_ip,
+            remote_addr,
+            Kind::EstablishedPerIp,
+        )?;
+
         Ok(dummy::ConnectionHandler)
     }
 
@@ -275,7 +397,7 @@ impl NetworkBehaviour for Behaviour {
         &mut self,
         connection_id: ConnectionId,
         peer: PeerId,
-        _: &Multiaddr,
+        addr: &Multiaddr,
         _: Endpoint,
     ) -> Result<THandler<Self>, ConnectionDenied> {
         self.pending_outbound_connections.remove(&connection_id);
@@ -300,6 +422,13 @@ impl NetworkBehaviour for Behaviour {
             Kind::EstablishedTotal,
         )?;
 
+        check_limit_per_ip(
+            self.limits.max_established_per_ip,
+            &self.established_per_ip,
+            addr,
+            Kind::EstablishedPerIp,
+        )?;
+
         Ok(dummy::ConnectionHandler)
     }
 
@@ -308,8 +437,14 @@ impl NetworkBehaviour for Behaviour {
             FromSwarm::ConnectionClosed(ConnectionClosed {
                 peer_id,
                 connection_id,
+                endpoint,
                 ..
             }) => {
+                remove_per_ip(
+                    &mut self.established_per_ip,
+                    endpoint.get_remote_address(),
+                    connection_id,
+                );
                 self.established_inbound_connections.remove(&connection_id);
                 self.established_outbound_connections.remove(&connection_id);
                 self.established_per_peer
@@ -336,12 +471,26 @@ impl NetworkBehaviour for Behaviour {
                     .entry(peer_id)
                     .or_default()
                     .insert(connection_id);
+                insert_per_ip(
+                    &mut self.established_per_ip,
+                    endpoint.get_remote_address(),
+                    connection_id,
+                );
             }
             FromSwarm::DialFailure(DialFailure { connection_id, .. }) => {
                 self.pending_outbound_connections.remove(&connection_id);
             }
-            FromSwarm::ListenFailure(ListenFailure { connection_id, .. }) => {
+            FromSwarm::ListenFailure(ListenFailure {
+                connection_id,
+                send_back_addr,
+                ..
+            }) => {
                 self.pending_inbound_connections.remove(&connection_id);
+                remove_per_ip(
+                    &mut self.pending_inbound_per_ip,
+                    send_back_addr,
+                    connection_id,
+                );
             }
             _ => {}
         }
@@ -476,6 +625,109 @@ mod tests {
         quickcheck(prop as fn(_));
     }
 
+    #[test]
+    fn max_established_per_ip() {
+        let limit = 2;
+
+        let mut swarm1 = Swarm::new_ephemeral(|_| {
+            Behaviour::new(ConnectionLimits::default().with_max_established_per_ip(Some(limit)))
+        });
+        let mut swarm2 = Swarm::new_ephemeral(|_| Behaviour::new(ConnectionLimits::default()));
+
+        async_std::task::block_on(async {
+            let (_, tcp_addr) = swarm1.listen().with_memory_addr_external().await;
+            let peer1 = *swarm1.local_peer_id();
+
+            // Connections over addresses without an IP are not limited.
+            swarm2.connect(&mut swarm1).await;
+
+            for _ in 0..=limit {
+                swarm2
+                    .dial(
+                        DialOpts::peer_id(peer1)
+                            .condition(PeerCondition::Always)
+                            .addresses(vec![tcp_addr.clone()])
+                            .build(),
+                    )
+                    .unwrap();
+            }
+            async_std::task::spawn(swarm2.loop_on_next());
+
+            let mut established = 0;
+            let cause = loop {
+                match swarm1.next_swarm_event().await {
+                    SwarmEvent::ConnectionEstablished { .. } => established += 1,
+                    SwarmEvent::IncomingConnectionError {
+                        error: ListenError::Denied { cause },
+                        ..
+                    } => break cause,
+                    _ => {}
+                }
+            };
+
+            assert_eq!(established, limit);
+            let exceeded = cause.downcast::<Exceeded>().unwrap();
+            assert_eq!(exceeded.limit(), limit);
+            assert_eq!(exceeded.ip(), Some("127.0.0.1".parse().unwrap()));
+        });
+    }
+
+    #[test]
+    fn max_pending_per_ip() {
+        let limit = 2;
+        let mut behaviour =
+            super::Behaviour::new(ConnectionLimits::default().with_max_pending_per_ip(Some(limit)));
+        let local_addr: Multiaddr = "/ip4/127.0.0.1/tcp/4001".parse().unwrap();
+        let remote_addr: Multiaddr = "/ip4/198.51.100.1/tcp/12345".parse().unwrap();
+        let pending: Vec<_> = (1..=limit as usize)
+            .map(ConnectionId::new_unchecked)
+            .collect();
+
+        for connection_id in &pending {
+            behaviour
+                .handle_pending_inbound_connection(*connection_id, &local_addr, &remote_addr)
+                .expect("connection within limit");
+        }
+
+        let exceeded = behaviour
+            .handle_pending_inbound_connection(
+                ConnectionId::new_unchecked(100),
+                &local_addr,
+                &remote_addr,
+            )
+            .expect_err("connection exceeding limit")
+            .downcast::<Exceeded>()
+            .unwrap();
+        assert_eq!(exceeded.limit(), limit);
+        assert_eq!(exceeded.ip(), Some("198.51.100.1".parse().unwrap()));
+
+        // Other IPs are not affected.
+        behaviour
+            .handle_pending_inbound_connection(
+                ConnectionId::new_unchecked(101),
+                &local_addr,
+                &"/ip4/198.51.100.2/tcp/12345".parse().unwrap(),
+            )
+            .expect("connection from other IP");
+
+        // A connection that is no longer pending frees up its slot.
+        behaviour
+            .handle_established_inbound_connection(
+                pending[0],
+                PeerId::random(),
+                &local_addr,
+                &remote_addr,
+            )
+            .unwrap();
+        behaviour
+            .handle_pending_inbound_connection(
+                ConnectionId::new_unchecked(102),
+                &local_addr,
+                &remote_addr,
+            )
+            .expect("connection within limit after another one was established");
+    }
+
     /// Another sibling [`NetworkBehaviour`] implementation might deny established connections in
     /// [`handle_established_outbound_connection`] or [`handle_established_inbound_connection`].
     /// [`Behaviour`] must not increase the established counters in
@@ -1,4 +1,5 @@
 use crate::protocol_stack;
+use bytes::Bytes;
 use futures::{
     future::{MapOk, TryFutureExt},
     io::{IoSlice, IoSliceMut},
@@ -6,7 +7,7 @@ use futures::{
     ready,
 };
 use libp2p_core::{
-    muxing::{StreamMuxer, StreamMuxerEvent},
+    muxing::{SendDatagramError, StreamMuxer, StreamMuxerEvent},
     transport::{ListenerId, TransportError, TransportEvent},
     Multiaddr,
 };
@@ -235,6 +236,15 @@ where
         let this = self.project();
         this.inner.poll_close(cx)
     }
+
+    fn send_datagram(self: Pin<&mut Self>, data: Bytes) -> Result<(), SendDatagramError> {
+        let this = self.project();
+        this.inner.send_datagram(data)
+    }
+
+    fn max_datagram_size(&self) -> Option<usize> {
+        self.inner.max_datagram_size()
+    }
 }
 
 /// Wraps around an [`AsyncRead`] + [`AsyncWrite`] and logs the bandwidth that goes through it.
@@ -49,6 +49,9 @@ struct EventLabels {
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelValue)]
 enum EventType {
+    HolePunchStarted,
+    ConnectReceived,
+    SyncSent,
     DirectConnectionUpgradeSucceeded,
     DirectConnectionUpgradeFailed,
 }
@@ -56,14 +59,15 @@ enum EventType {
 impl From<&libp2p_dcutr::Event> for EventType {
     fn from(event: &libp2p_dcutr::Event) -> Self {
         match event {
-            libp2p_dcutr::Event {
-                remote_peer_id: _,
-                result: Ok(_),
-            } => EventType::DirectConnectionUpgradeSucceeded,
-            libp2p_dcutr::Event {
-                remote_peer_id: _,
-                result: Err(_),
-            } => EventType::DirectConnectionUpgradeFailed,
+            libp2p_dcutr::Event::HolePunchStarted { .. } => EventType::HolePunchStarted,
+            libp2p_dcutr::Event::ConnectReceived { .. } => EventType::ConnectReceived,
+            libp2p_dcutr::Event::SyncSent { .. } => EventType::SyncSent,
+            libp2p_dcutr::Event::DirectConnectionUpgradeSucceeded { .. } => {
+                EventType::DirectConnectionUpgradeSucceeded
+            }
+            libp2p_dcutr::Event::DirectConnectionUpgradeFailed { .. } => {
+                EventType::DirectConnectionUpgradeFailed
+            }
         }
     }
 }
@@ -207,7 +207,8 @@ impl super::Recorder<libp2p_kad::Event> for Metrics {
                         }
                         Ok(libp2p_kad::GetProvidersOk::FinishedWithNoAdditionalRecord {
                             ..
-                        }) => {}
+                        })
+                        | Ok(libp2p_kad::GetProvidersOk::Cancelled { .. }) => {}
                         Err(error) => {
                             self.query_result_get_providers_error
                                 .get_or_create(&error.into())
@@ -54,11 +54,13 @@ enum EventType {
     ReservationReqDenied,
     ReservationReqDenyFailed,
     ReservationTimedOut,
+    ReservationLimitPerPeerReached,
     CircuitReqDenied,
     CircuitReqDenyFailed,
     CircuitReqOutboundConnectFailed,
     CircuitReqAccepted,
     CircuitReqAcceptFailed,
+    CircuitLimitPerPeerReached,
     CircuitClosed,
 }
 
@@ -76,6 +78,9 @@ impl From<&libp2p_relay::Event> for EventType {
                 EventType::ReservationReqDenyFailed
             }
             libp2p_relay::Event::ReservationTimedOut { .. } => EventType::ReservationTimedOut,
+            libp2p_relay::Event::ReservationLimitPerPeerReached { .. } => {
+                EventType::ReservationLimitPerPeerReached
+            }
             libp2p_relay::Event::CircuitReqDenied { .. } => EventType::CircuitReqDenied,
             #[allow(deprecated)]
             libp2p_relay::Event::CircuitReqOutboundConnectFailed { .. } => {
@@ -86,6 +91,9 @@ impl From<&libp2p_relay::Event> for EventType {
             libp2p_relay::Event::CircuitReqAccepted { .. } => EventType::CircuitReqAccepted,
             #[allow(deprecated)]
             libp2p_relay::Event::CircuitReqAcceptFailed { .. } => EventType::CircuitReqAcceptFailed,
+            libp2p_relay::Event::CircuitLimitPerPeerReached { .. } => {
+                EventType::CircuitLimitPerPeerReached
+            }
             libp2p_relay::Event::CircuitClosed { .. } => EventType::CircuitClosed,
         }
     }
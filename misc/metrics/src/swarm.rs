@@ -283,6 +283,8 @@ impl<TBvEv> super::Recorder<SwarmEvent<TBvEv>> for Metrics {
                         record(OutgoingConnectionError::DialPeerConditionFalse)
                     }
                     DialError::Aborted => record(OutgoingConnectionError::Aborted),
+                    DialError::Cancelled => record(OutgoingConnectionError::Cancelled),
+                    DialError::Dns(_) => record(OutgoingConnectionError::Dns),
                     DialError::WrongPeerId { .. } => record(OutgoingConnectionError::WrongPeerId),
                     DialError::Denied { .. } => record(OutgoingConnectionError::Denied),
                 };
@@ -408,6 +410,8 @@ enum OutgoingConnectionError {
     NoAddresses,
     DialPeerConditionFalse,
     Aborted,
+    Cancelled,
+    Dns,
     WrongPeerId,
     TransportMultiaddrNotSupported,
     TransportOther,
@@ -287,6 +287,20 @@ impl Behaviour {
         self.as_client().on_new_address();
     }
 
+    /// Immediately probe the local addresses for external reachability, instead of waiting for
+    /// the next scheduled probe.
+    ///
+    /// The result is reported as [`Event::OutboundProbe`] and, if the assumed NAT status flips,
+    /// as [`Event::StatusChanged`].
+    /// Does nothing if a probe is already in flight.
+    pub fn probe_now(&mut self) {
+        if !self.ongoing_outbound.is_empty() {
+            tracing::debug!("Not starting probe: another probe is already in flight");
+            return;
+        }
+        self.schedule_probe.reset(Duration::ZERO);
+    }
+
     fn as_client(&mut self) -> AsClient {
         AsClient {
             inner: &mut self.inner,
@@ -462,7 +476,11 @@ impl NetworkBehaviour for Behaviour {
                         | request_response::Event::InboundFailure { .. } => {
                             self.as_server().handle_event(event)
                         }
-                        request_response::Event::ResponseSent { .. } => VecDeque::new(),
+                        request_response::Event::ResponseSent { .. }
+                        | request_response::Event::Message {
+                            message: request_response::Message::ResponseStream { .. },
+                            ..
+                        } => VecDeque::new(),
                     };
 
                     self.pending_actions.extend(actions);
@@ -110,6 +110,7 @@ impl<'a> HandleInnerEvent for AsClient<'a> {
                     request_response::Message::Response {
                         request_id,
                         response,
+                        ..
                     },
             } => {
                 tracing::debug!(?response, "Outbound dial-back request returned response");
@@ -153,6 +154,7 @@ impl<'a> HandleInnerEvent for AsClient<'a> {
                 peer,
                 error,
                 request_id,
+                ..
             } => {
                 tracing::debug!(
                     %peer,
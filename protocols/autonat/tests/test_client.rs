@@ -217,6 +217,56 @@ async fn test_confidence() {
     }
 }
 
+#[async_std::test]
+async fn test_probe_now() {
+    let mut client = Swarm::new_ephemeral(|key| {
+        Behaviour::new(
+            key.public().to_peer_id(),
+            Config {
+                retry_interval: Duration::from_secs(60),
+                refresh_interval: Duration::from_secs(60),
+                only_global_ips: false,
+                throttle_server_period: Duration::ZERO,
+                boot_delay: Duration::from_secs(60),
+                ..Default::default()
+            },
+        )
+    });
+    let (server_id, addr, _) = new_server_swarm().await;
+    client.behaviour_mut().add_server(server_id, Some(addr));
+    client.listen().with_memory_addr_external().await;
+
+    client.behaviour_mut().probe_now();
+
+    let status_changed = async {
+        match client.next_behaviour_event().await {
+            Event::OutboundProbe(OutboundProbeEvent::Request { peer, .. }) => {
+                assert_eq!(peer, server_id);
+            }
+            other => panic!("Unexpected behaviour event: {other:?}."),
+        }
+
+        // A probe is in flight, thus this is a no-op.
+        client.behaviour_mut().probe_now();
+
+        loop {
+            match client.next_behaviour_event().await {
+                Event::OutboundProbe(OutboundProbeEvent::Response { peer, .. }) => {
+                    assert_eq!(peer, server_id);
+                }
+                Event::StatusChanged { old, new } => break (old, new),
+                other => panic!("Unexpected behaviour event: {other:?}."),
+            }
+        }
+    };
+    let (old, new) = async_std::future::timeout(Duration::from_secs(10), status_changed)
+        .await
+        .expect("status to change before the next scheduled probe");
+
+    assert_eq!(old, NatStatus::Unknown);
+    assert!(new.is_public());
+}
+
 #[async_std::test]
 async fn test_throttle_server_period() {
     let mut client = Swarm::new_ephemeral(|key| {
@@ -22,6 +22,7 @@
 
 use crate::{handler, protocol};
 use either::Either;
+use instant::Instant;
 use libp2p_core::connection::ConnectedPoint;
 use libp2p_core::multiaddr::Protocol;
 use libp2p_core::{Endpoint, Multiaddr};
@@ -37,16 +38,60 @@ use lru::LruCache;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::num::NonZeroUsize;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use thiserror::Error;
 use void::Void;
 
 pub(crate) const MAX_NUMBER_OF_UPGRADE_ATTEMPTS: u8 = 3;
 
 /// The events produced by the [`Behaviour`].
+///
+/// Each hole-punch attempt on a relayed connection starts with [`Event::HolePunchStarted`] and
+/// ends with either [`Event::DirectConnectionUpgradeSucceeded`] or
+/// [`Event::DirectConnectionUpgradeFailed`]. `elapsed` is the time since the start of the attempt.
 #[derive(Debug)]
-pub struct Event {
-    pub remote_peer_id: PeerId,
-    pub result: Result<ConnectionId, Error>,
+pub enum Event {
+    /// A hole-punch attempt started, i.e. we began the DCUtR handshake with the remote.
+    HolePunchStarted {
+        remote_peer_id: PeerId,
+        relayed_connection_id: ConnectionId,
+    },
+    /// The remote's `CONNECT` message, carrying its address candidates, has been received.
+    ConnectReceived {
+        remote_peer_id: PeerId,
+        relayed_connection_id: ConnectionId,
+        elapsed: Duration,
+    },
+    /// Our `SYNC` message has been sent.
+    ///
+    /// Only reported by the node initiating the handshake, i.e. the listener of the relayed
+    /// connection.
+    SyncSent {
+        remote_peer_id: PeerId,
+        relayed_connection_id: ConnectionId,
+        elapsed: Duration,
+    },
+    /// A direct connection to the remote has been established.
+    ///
+    /// Use `direct_address` in place of `relayed_address` to prefer the direct path when dialing
+    /// the remote again, see also [`Behaviour::direct_address`].
+    DirectConnectionUpgradeSucceeded {
+        remote_peer_id: PeerId,
+        relayed_connection_id: ConnectionId,
+        direct_connection_id: ConnectionId,
+        /// The remote address of the relayed connection.
+        relayed_address: Multiaddr,
+        /// The remote address of the direct connection.
+        direct_address: Multiaddr,
+        elapsed: Duration,
+    },
+    /// The hole-punch attempt failed.
+    DirectConnectionUpgradeFailed {
+        remote_peer_id: PeerId,
+        relayed_connection_id: ConnectionId,
+        error: Error,
+        elapsed: Duration,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -79,6 +124,20 @@ pub struct Behaviour {
     /// Indexed by the [`ConnectionId`] of the relayed connection and
     /// the [`PeerId`] we are trying to establish a direct connection to.
     outgoing_direct_connection_attempts: HashMap<(ConnectionId, PeerId), u8>,
+
+    /// When the ongoing hole-punch attempt on a relayed connection started.
+    attempt_started: HashMap<ConnectionId, Instant>,
+
+    /// The remote addresses of all relayed connections.
+    relayed_addresses: HashMap<ConnectionId, Multiaddr>,
+
+    /// The remote address of the relayed connection being upgraded by each pending direct
+    /// connection.
+    pending_relayed_addresses: HashMap<ConnectionId, Multiaddr>,
+
+    /// The relayed and direct remote address of every direct connection established by
+    /// the upgrade of a relayed connection.
+    upgraded_addresses: HashMap<ConnectionId, (Multiaddr, Multiaddr)>,
 }
 
 impl Behaviour {
@@ -89,9 +148,65 @@ impl Behaviour {
             address_candidates: Candidates::new(local_peer_id),
             direct_to_relayed_connections: Default::default(),
             outgoing_direct_connection_attempts: Default::default(),
+            attempt_started: Default::default(),
+            relayed_addresses: Default::default(),
+            pending_relayed_addresses: Default::default(),
+            upgraded_addresses: Default::default(),
         }
     }
 
+    /// Returns the remote address of a direct connection that replaced a relayed connection with
+    /// the given remote address, if that direct connection is still established.
+    pub fn direct_address(&self, relayed_address: &Multiaddr) -> Option<&Multiaddr> {
+        self.upgraded_addresses
+            .values()
+            .find(|(relayed, _)| relayed == relayed_address)
+            .map(|(_, direct)| direct)
+    }
+
+    /// Time since the start of the hole-punch attempt on the given relayed connection.
+    fn elapsed(&self, relayed_connection_id: ConnectionId) -> Duration {
+        self.attempt_started
+            .get(&relayed_connection_id)
+            .map(Instant::elapsed)
+            .unwrap_or_default()
+    }
+
+    fn hole_punch_failed(
+        &mut self,
+        remote_peer_id: PeerId,
+        relayed_connection_id: ConnectionId,
+        error: InnerError,
+    ) {
+        let elapsed = self.elapsed(relayed_connection_id);
+        self.attempt_started.remove(&relayed_connection_id);
+
+        self.queued_events.push_back(ToSwarm::GenerateEvent(
+            Event::DirectConnectionUpgradeFailed {
+                remote_peer_id,
+                relayed_connection_id,
+                error: Error { inner: error },
+                elapsed,
+            },
+        ));
+    }
+
+    /// Remembers the address of the relayed connection, which may be closed before the direct
+    /// connection is established.
+    fn track_direct_connection_attempt(
+        &mut self,
+        direct_connection_id: ConnectionId,
+        relayed_connection_id: ConnectionId,
+    ) {
+        let relayed_address = self
+            .relayed_addresses
+            .get(&relayed_connection_id)
+            .expect("Relayed connection to be tracked.")
+            .clone();
+        self.pending_relayed_addresses
+            .insert(direct_connection_id, relayed_address);
+    }
+
     fn observed_addresses(&self) -> Vec<Multiaddr> {
         self.address_candidates.iter().cloned().collect()
     }
@@ -104,6 +219,9 @@ impl Behaviour {
             ..
         }: DialFailure,
     ) {
+        self.pending_relayed_addresses
+            .remove(&failed_direct_connection);
+
         let Some(peer_id) = peer_id else {
             return;
         };
@@ -122,19 +240,19 @@ impl Behaviour {
             return;
         };
 
+        let relayed_connection_id = *relayed_connection_id;
         if *attempt < MAX_NUMBER_OF_UPGRADE_ATTEMPTS {
             self.queued_events.push_back(ToSwarm::NotifyHandler {
-                handler: NotifyHandler::One(*relayed_connection_id),
+                handler: NotifyHandler::One(relayed_connection_id),
                 peer_id,
                 event: Either::Left(handler::relayed::Command::Connect),
             })
         } else {
-            self.queued_events.extend([ToSwarm::GenerateEvent(Event {
-                remote_peer_id: peer_id,
-                result: Err(Error {
-                    inner: InnerError::AttemptsExceeded(MAX_NUMBER_OF_UPGRADE_ATTEMPTS),
-                }),
-            })]);
+            self.hole_punch_failed(
+                peer_id,
+                relayed_connection_id,
+                InnerError::AttemptsExceeded(MAX_NUMBER_OF_UPGRADE_ATTEMPTS),
+            );
         }
     }
 
@@ -147,7 +265,12 @@ impl Behaviour {
             ..
         }: ConnectionClosed,
     ) {
-        if !connected_point.is_relayed() {
+        if connected_point.is_relayed() {
+            self.attempt_started.remove(&connection_id);
+            self.relayed_addresses.remove(&connection_id);
+        } else {
+            self.upgraded_addresses.remove(&connection_id);
+
             let connections = self
                 .direct_connections
                 .get_mut(&peer_id)
@@ -175,6 +298,9 @@ impl NetworkBehaviour for Behaviour {
         remote_addr: &Multiaddr,
     ) -> Result<THandler<Self>, ConnectionDenied> {
         if is_relayed(local_addr) {
+            self.relayed_addresses
+                .insert(connection_id, remote_addr.clone());
+
             let connected_point = ConnectedPoint::Listener {
                 local_addr: local_addr.clone(),
                 send_back_addr: remote_addr.clone(),
@@ -208,6 +334,8 @@ impl NetworkBehaviour for Behaviour {
         role_override: Endpoint,
     ) -> Result<THandler<Self>, ConnectionDenied> {
         if is_relayed(addr) {
+            self.relayed_addresses.insert(connection_id, addr.clone());
+
             return Ok(Either::Left(handler::relayed::Handler::new(
                 ConnectedPoint::Dialer {
                     address: addr.clone(),
@@ -234,10 +362,26 @@ impl NetworkBehaviour for Behaviour {
                 );
             }
 
-            self.queued_events.extend([ToSwarm::GenerateEvent(Event {
-                remote_peer_id: peer,
-                result: Ok(connection_id),
-            })]);
+            let elapsed = self.elapsed(relayed_connection_id);
+            self.attempt_started.remove(&relayed_connection_id);
+
+            let relayed_address = self
+                .pending_relayed_addresses
+                .remove(&connection_id)
+                .expect("Direct connection attempt to be tracked.");
+            self.upgraded_addresses
+                .insert(connection_id, (relayed_address.clone(), addr.clone()));
+
+            self.queued_events.extend([ToSwarm::GenerateEvent(
+                Event::DirectConnectionUpgradeSucceeded {
+                    remote_peer_id: peer,
+                    relayed_connection_id,
+                    direct_connection_id: connection_id,
+                    relayed_address,
+                    direct_address: addr.clone(),
+                    elapsed,
+                },
+            )]);
         }
         Ok(Either::Right(dummy::ConnectionHandler))
     }
@@ -260,6 +404,31 @@ impl NetworkBehaviour for Behaviour {
         };
 
         match handler_event {
+            Either::Left(handler::relayed::Event::HolePunchStarted) => {
+                self.attempt_started
+                    .insert(relayed_connection_id, Instant::now());
+                self.queued_events
+                    .push_back(ToSwarm::GenerateEvent(Event::HolePunchStarted {
+                        remote_peer_id: event_source,
+                        relayed_connection_id,
+                    }));
+            }
+            Either::Left(handler::relayed::Event::ConnectReceived) => {
+                self.queued_events
+                    .push_back(ToSwarm::GenerateEvent(Event::ConnectReceived {
+                        remote_peer_id: event_source,
+                        relayed_connection_id,
+                        elapsed: self.elapsed(relayed_connection_id),
+                    }));
+            }
+            Either::Left(handler::relayed::Event::SyncSent) => {
+                self.queued_events
+                    .push_back(ToSwarm::GenerateEvent(Event::SyncSent {
+                        remote_peer_id: event_source,
+                        relayed_connection_id,
+                        elapsed: self.elapsed(relayed_connection_id),
+                    }));
+            }
             Either::Left(handler::relayed::Event::InboundConnectNegotiated { remote_addrs }) => {
                 tracing::debug!(target=%event_source, addresses=?remote_addrs, "Attempting to hole-punch as dialer");
 
@@ -272,23 +441,25 @@ impl NetworkBehaviour for Behaviour {
 
                 self.direct_to_relayed_connections
                     .insert(maybe_direct_connection_id, relayed_connection_id);
+                self.track_direct_connection_attempt(
+                    maybe_direct_connection_id,
+                    relayed_connection_id,
+                );
                 self.queued_events.push_back(ToSwarm::Dial { opts });
             }
             Either::Left(handler::relayed::Event::InboundConnectFailed { error }) => {
-                self.queued_events.push_back(ToSwarm::GenerateEvent(Event {
-                    remote_peer_id: event_source,
-                    result: Err(Error {
-                        inner: InnerError::InboundError(error),
-                    }),
-                }));
+                self.hole_punch_failed(
+                    event_source,
+                    relayed_connection_id,
+                    InnerError::InboundError(error),
+                );
             }
             Either::Left(handler::relayed::Event::OutboundConnectFailed { error }) => {
-                self.queued_events.push_back(ToSwarm::GenerateEvent(Event {
-                    remote_peer_id: event_source,
-                    result: Err(Error {
-                        inner: InnerError::OutboundError(error),
-                    }),
-                }));
+                self.hole_punch_failed(
+                    event_source,
+                    relayed_connection_id,
+                    InnerError::OutboundError(error),
+                );
 
                 // Maybe treat these as transient and retry?
             }
@@ -305,6 +476,10 @@ impl NetworkBehaviour for Behaviour {
 
                 self.direct_to_relayed_connections
                     .insert(maybe_direct_connection_id, relayed_connection_id);
+                self.track_direct_connection_attempt(
+                    maybe_direct_connection_id,
+                    relayed_connection_id,
+                );
                 *self
                     .outgoing_direct_connection_attempts
                     .entry((relayed_connection_id, event_source))
@@ -21,9 +21,11 @@
 //! [`ConnectionHandler`] handling relayed connection potentially upgraded to a direct connection.
 
 use crate::behaviour::MAX_NUMBER_OF_UPGRADE_ATTEMPTS;
+use crate::protocol::Progress;
 use crate::{protocol, PROTOCOL_NAME};
 use either::Either;
-use futures::future;
+use futures::channel::mpsc;
+use futures::{future, StreamExt};
 use libp2p_core::multiaddr::Multiaddr;
 use libp2p_core::upgrade::{DeniedUpgrade, ReadyUpgrade};
 use libp2p_core::ConnectedPoint;
@@ -48,12 +50,24 @@ pub enum Command {
 
 #[derive(Debug)]
 pub enum Event {
+    HolePunchStarted,
+    ConnectReceived,
+    SyncSent,
     InboundConnectNegotiated { remote_addrs: Vec<Multiaddr> },
     OutboundConnectNegotiated { remote_addrs: Vec<Multiaddr> },
     InboundConnectFailed { error: inbound::Error },
     OutboundConnectFailed { error: outbound::Error },
 }
 
+impl From<Progress> for Event {
+    fn from(progress: Progress) -> Self {
+        match progress {
+            Progress::ConnectReceived => Event::ConnectReceived,
+            Progress::SyncSent => Event::SyncSent,
+        }
+    }
+}
+
 pub struct Handler {
     endpoint: ConnectedPoint,
     /// Queue of events to return when polled.
@@ -75,10 +89,18 @@ pub struct Handler {
     holepunch_candidates: Vec<Multiaddr>,
 
     attempts: u8,
+
+    /// Progress reported by the ongoing DCUtR handshakes.
+    progress_sender: mpsc::Sender<Progress>,
+    progress_receiver: mpsc::Receiver<Progress>,
 }
 
 impl Handler {
     pub fn new(endpoint: ConnectedPoint, holepunch_candidates: Vec<Multiaddr>) -> Self {
+        // Each handshake reports at most two steps and at most one inbound and one outbound handshake
+        // are ongoing at a time.
+        let (progress_sender, progress_receiver) = mpsc::channel(4);
+
         Self {
             endpoint,
             queued_events: Default::default(),
@@ -86,9 +108,36 @@ impl Handler {
             outbound_stream: futures_bounded::FuturesSet::new(Duration::from_secs(10), 1),
             holepunch_candidates,
             attempts: 0,
+            progress_sender,
+            progress_receiver,
         }
     }
 
+    /// Reports the outcome of a handshake, after any progress it reported before completing.
+    fn report_outcome(
+        &mut self,
+        event: Event,
+    ) -> Poll<
+        ConnectionHandlerEvent<
+            <Self as ConnectionHandler>::OutboundProtocol,
+            <Self as ConnectionHandler>::OutboundOpenInfo,
+            <Self as ConnectionHandler>::ToBehaviour,
+        >,
+    > {
+        while let Ok(Some(progress)) = self.progress_receiver.try_next() {
+            self.queued_events
+                .push_back(ConnectionHandlerEvent::NotifyBehaviour(progress.into()));
+        }
+        self.queued_events
+            .push_back(ConnectionHandlerEvent::NotifyBehaviour(event));
+
+        Poll::Ready(
+            self.queued_events
+                .pop_front()
+                .expect("to have just pushed an event"),
+        )
+    }
+
     fn on_fully_negotiated_inbound(
         &mut self,
         FullyNegotiatedInbound {
@@ -105,6 +154,7 @@ impl Handler {
                     .try_push(inbound::handshake(
                         stream,
                         self.holepunch_candidates.clone(),
+                        self.progress_sender.clone(),
                     ))
                     .is_err()
                 {
@@ -113,6 +163,10 @@ impl Handler {
                     );
                 }
                 self.attempts += 1;
+                self.queued_events
+                    .push_back(ConnectionHandlerEvent::NotifyBehaviour(
+                        Event::HolePunchStarted,
+                    ));
             }
             // A connection listener denies all incoming substreams, thus none can ever be fully negotiated.
             future::Either::Right(output) => void::unreachable(output),
@@ -137,6 +191,7 @@ impl Handler {
             .try_push(outbound::handshake(
                 stream,
                 self.holepunch_candidates.clone(),
+                self.progress_sender.clone(),
             ))
             .is_err()
         {
@@ -208,6 +263,10 @@ impl ConnectionHandler for Handler {
                     .push_back(ConnectionHandlerEvent::OutboundSubstreamRequest {
                         protocol: SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL_NAME), ()),
                     });
+                self.queued_events
+                    .push_back(ConnectionHandlerEvent::NotifyBehaviour(
+                        Event::HolePunchStarted,
+                    ));
                 self.attempts += 1;
             }
         }
@@ -233,48 +292,40 @@ impl ConnectionHandler for Handler {
             return Poll::Ready(event);
         }
 
+        if let Poll::Ready(Some(progress)) = self.progress_receiver.poll_next_unpin(cx) {
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(progress.into()));
+        }
+
         match self.inbound_stream.poll_unpin(cx) {
             Poll::Ready(Ok(Ok(addresses))) => {
-                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
-                    Event::InboundConnectNegotiated {
-                        remote_addrs: addresses,
-                    },
-                ))
+                return self.report_outcome(Event::InboundConnectNegotiated {
+                    remote_addrs: addresses,
+                })
             }
             Poll::Ready(Ok(Err(error))) => {
-                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
-                    Event::InboundConnectFailed { error },
-                ))
+                return self.report_outcome(Event::InboundConnectFailed { error })
             }
             Poll::Ready(Err(futures_bounded::Timeout { .. })) => {
-                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
-                    Event::InboundConnectFailed {
-                        error: inbound::Error::Io(io::ErrorKind::TimedOut.into()),
-                    },
-                ))
+                return self.report_outcome(Event::InboundConnectFailed {
+                    error: inbound::Error::Io(io::ErrorKind::TimedOut.into()),
+                })
             }
             Poll::Pending => {}
         }
 
         match self.outbound_stream.poll_unpin(cx) {
             Poll::Ready(Ok(Ok(addresses))) => {
-                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
-                    Event::OutboundConnectNegotiated {
-                        remote_addrs: addresses,
-                    },
-                ))
+                return self.report_outcome(Event::OutboundConnectNegotiated {
+                    remote_addrs: addresses,
+                })
             }
             Poll::Ready(Ok(Err(error))) => {
-                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
-                    Event::OutboundConnectFailed { error },
-                ))
+                return self.report_outcome(Event::OutboundConnectFailed { error })
             }
             Poll::Ready(Err(futures_bounded::Timeout { .. })) => {
-                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
-                    Event::OutboundConnectFailed {
-                        error: outbound::Error::Io(io::ErrorKind::TimedOut.into()),
-                    },
-                ))
+                return self.report_outcome(Event::OutboundConnectFailed {
+                    error: outbound::Error::Io(io::ErrorKind::TimedOut.into()),
+                })
             }
             Poll::Pending => {}
         }
@@ -25,3 +25,12 @@ pub(crate) mod outbound;
 pub const PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/libp2p/dcutr");
 
 const MAX_MESSAGE_SIZE_BYTES: usize = 4096;
+
+/// Steps of an ongoing DCUtR handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Progress {
+    /// The remote's `CONNECT` message has been received.
+    ConnectReceived,
+    /// Our `SYNC` message has been sent.
+    SyncSent,
+}
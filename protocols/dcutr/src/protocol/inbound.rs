@@ -19,7 +19,9 @@
 // DEALINGS IN THE SOFTWARE.
 
 use crate::proto;
+use crate::protocol::Progress;
 use asynchronous_codec::Framed;
+use futures::channel::mpsc;
 use futures::prelude::*;
 use libp2p_core::{multiaddr::Protocol, Multiaddr};
 use libp2p_swarm::Stream;
@@ -30,6 +32,7 @@ use thiserror::Error;
 pub(crate) async fn handshake(
     stream: Stream,
     candidates: Vec<Multiaddr>,
+    mut progress: mpsc::Sender<Progress>,
 ) -> Result<Vec<Multiaddr>, Error> {
     let mut stream = Framed::new(
         stream,
@@ -69,6 +72,8 @@ pub(crate) async fn handshake(
         return Err(Error::Protocol(ProtocolViolation::UnexpectedTypeSync));
     }
 
+    let _ = progress.try_send(Progress::ConnectReceived);
+
     let msg = proto::HolePunch {
         type_pb: proto::Type::CONNECT,
         ObsAddrs: candidates.into_iter().map(|a| a.to_vec()).collect(),
@@ -19,8 +19,10 @@
 // DEALINGS IN THE SOFTWARE.
 
 use crate::proto;
+use crate::protocol::Progress;
 use crate::PROTOCOL_NAME;
 use asynchronous_codec::Framed;
+use futures::channel::mpsc;
 use futures::prelude::*;
 use futures_timer::Delay;
 use instant::Instant;
@@ -33,6 +35,7 @@ use thiserror::Error;
 pub(crate) async fn handshake(
     stream: Stream,
     candidates: Vec<Multiaddr>,
+    mut progress: mpsc::Sender<Progress>,
 ) -> Result<Vec<Multiaddr>, Error> {
     let mut stream = Framed::new(
         stream,
@@ -59,6 +62,8 @@ pub(crate) async fn handshake(
         return Err(Error::Protocol(ProtocolViolation::UnexpectedTypeSync));
     }
 
+    let _ = progress.try_send(Progress::ConnectReceived);
+
     if ObsAddrs.is_empty() {
         return Err(Error::Protocol(ProtocolViolation::NoAddresses));
     }
@@ -90,6 +95,8 @@ pub(crate) async fn handshake(
 
     stream.send(msg).await?;
 
+    let _ = progress.try_send(Progress::SyncSent);
+
     Delay::new(rtt / 2).await;
 
     Ok(obs_addrs)
@@ -74,28 +74,69 @@ async fn connect() {
 
     let dst_addr = dst_tcp_addr.with(Protocol::P2p(dst_peer_id));
 
-    let established_conn_id = src
-        .wait(move |e| match e {
+    let mut established_conn_id = None;
+    let mut timeline = Vec::new();
+    let reported_conn_id = loop {
+        match src.next_swarm_event().await {
             SwarmEvent::ConnectionEstablished {
                 endpoint,
                 connection_id,
                 ..
-            } => (*endpoint.get_remote_address() == dst_addr).then_some(connection_id),
-            _ => None,
-        })
-        .await;
-
-    let reported_conn_id = src
-        .wait(move |e| match e {
-            SwarmEvent::Behaviour(ClientEvent::Dcutr(dcutr::Event {
-                result: Ok(connection_id),
-                ..
-            })) => Some(connection_id),
-            _ => None,
-        })
-        .await;
+            } if *endpoint.get_remote_address() == dst_addr => {
+                established_conn_id = Some(connection_id);
+            }
+            SwarmEvent::Behaviour(ClientEvent::Dcutr(
+                dcutr::Event::DirectConnectionUpgradeSucceeded {
+                    direct_connection_id,
+                    relayed_address,
+                    direct_address,
+                    ..
+                },
+            )) => {
+                assert_eq!(relayed_address, dst_relayed_addr);
+                assert_eq!(direct_address, dst_addr);
+                break direct_connection_id;
+            }
+            SwarmEvent::Behaviour(ClientEvent::Dcutr(event)) => timeline.push(event),
+            _ => {}
+        }
+    };
+    let established_conn_id = match established_conn_id {
+        Some(id) => id,
+        None => {
+            let dst_addr = dst_addr.clone();
+            src.wait(move |e| match e {
+                SwarmEvent::ConnectionEstablished {
+                    endpoint,
+                    connection_id,
+                    ..
+                } => (*endpoint.get_remote_address() == dst_addr).then_some(connection_id),
+                _ => None,
+            })
+            .await
+        }
+    };
 
     assert_eq!(established_conn_id, reported_conn_id);
+    assert_eq!(
+        src.behaviour().dcutr.direct_address(&dst_relayed_addr),
+        Some(&dst_addr)
+    );
+
+    // `src` is the dialer of the relayed connection and thus responds to the handshake.
+    match &timeline[..] {
+        [dcutr::Event::HolePunchStarted {
+            remote_peer_id,
+            relayed_connection_id: started_on,
+        }, dcutr::Event::ConnectReceived {
+            relayed_connection_id: received_on,
+            ..
+        }, ..] => {
+            assert_eq!(*remote_peer_id, dst_peer_id);
+            assert_eq!(started_on, received_on);
+        }
+        other => panic!("Unexpected hole-punch timeline: {other:?}"),
+    }
 }
 
 fn build_relay() -> Swarm<Relay> {
@@ -29,7 +29,7 @@ use std::{
     time::Duration,
 };
 
-use futures::StreamExt;
+use futures::{channel::mpsc, StreamExt};
 use futures_ticker::Ticker;
 use prometheus_client::registry::Registry;
 use rand::{seq::SliceRandom, thread_rng};
@@ -53,6 +53,7 @@ use crate::mcache::MessageCache;
 use crate::metrics::{Churn, Config as MetricsConfig, Inclusion, Metrics, Penalty};
 use crate::peer_score::{PeerScore, PeerScoreParams, PeerScoreThresholds, RejectReason};
 use crate::protocol::SIGNING_PREFIX;
+use crate::rate_limiter::PublishRateLimiter;
 use crate::subscription_filter::{AllowAllSubscriptionFilter, TopicSubscriptionFilter};
 use crate::time_cache::DuplicateCache;
 use crate::topic::{Hasher, Topic, TopicHash};
@@ -62,6 +63,7 @@ use crate::types::{
     SubscriptionAction,
 };
 use crate::types::{PeerConnections, PeerKind, Rpc};
+use crate::validation::{ValidationHandle, ValidationResult};
 use crate::{rpc_proto::proto, TopicScoreParams};
 use crate::{PublishError, SubscriptionError, ValidationError};
 use instant::SystemTime;
@@ -314,6 +316,9 @@ pub struct Behaviour<D = IdentityTransform, F = AllowAllSubscriptionFilter> {
     /// Counts the number of `IWANT` that we sent the each peer since the last heartbeat.
     count_sent_iwant: HashMap<PeerId, usize>,
 
+    /// Counts the number of `IWANT` received from each peer since the last heartbeat.
+    count_received_iwant: HashMap<PeerId, usize>,
+
     /// Keeps track of IWANT messages that we are awaiting to send.
     /// This is used to prevent sending duplicate IWANT messages for the same message.
     pending_iwant_msgs: HashSet<MessageId>,
@@ -322,6 +327,10 @@ pub struct Behaviour<D = IdentityTransform, F = AllowAllSubscriptionFilter> {
     /// our own messages back if the messages are anonymous or use a random author.
     published_message_ids: DuplicateCache<MessageId>,
 
+    /// Tracks the rate at which peers publish messages on each topic, if a
+    /// [`Config::publish_rate_limit`] is set.
+    publish_rate_limiter: Option<PublishRateLimiter>,
+
     /// The filter used to handle message subscriptions.
     subscription_filter: F,
 
@@ -332,6 +341,12 @@ pub struct Behaviour<D = IdentityTransform, F = AllowAllSubscriptionFilter> {
 
     /// Keep track of a set of internal metrics relating to gossipsub.
     metrics: Option<Metrics>,
+
+    /// Handed out to [`ValidationHandle`]s to report the outcome of a validation.
+    validation_sender: mpsc::Sender<ValidationResult>,
+
+    /// Receives the validation results reported through [`ValidationHandle`]s.
+    validation_receiver: mpsc::Receiver<ValidationResult>,
 }
 
 impl<D, F> Behaviour<D, F>
@@ -436,12 +451,17 @@ where
         // were received locally.
         validate_config(&privacy, config.validation_mode())?;
 
+        let (validation_sender, validation_receiver) = mpsc::channel(0);
+
         Ok(Behaviour {
             metrics: metrics.map(|(registry, cfg)| Metrics::new(registry, cfg)),
             events: VecDeque::new(),
             control_pool: HashMap::new(),
             publish_config: privacy.into(),
-            duplicate_cache: DuplicateCache::new(config.duplicate_cache_time()),
+            duplicate_cache: DuplicateCache::with_capacity(
+                config.duplicate_cache_time(),
+                config.duplicate_cache_capacity().get(),
+            ),
             topic_peers: HashMap::new(),
             peer_topics: HashMap::new(),
             explicit_peers: HashSet::new(),
@@ -465,12 +485,16 @@ where
             peer_score: None,
             count_received_ihave: HashMap::new(),
             count_sent_iwant: HashMap::new(),
+            count_received_iwant: HashMap::new(),
             pending_iwant_msgs: HashSet::new(),
             connected_peers: HashMap::new(),
             published_message_ids: DuplicateCache::new(config.published_message_ids_cache_time()),
+            publish_rate_limiter: config.publish_rate_limit().map(PublishRateLimiter::new),
             config,
             subscription_filter,
             data_transform,
+            validation_sender,
+            validation_receiver,
         })
     }
 }
@@ -490,6 +514,7 @@ where
         self.mesh.get(topic_hash).into_iter().flat_map(|x| x.iter())
     }
 
+    /// Lists all mesh peers across all topics, without duplicates.
     pub fn all_mesh_peers(&self) -> impl Iterator<Item = &PeerId> {
         let mut res = BTreeSet::new();
         for peers in self.mesh.values() {
@@ -498,6 +523,20 @@ where
         res.into_iter()
     }
 
+    /// Lists the hashes of the topics we published on without being subscribed to them, i.e.
+    /// the topics we maintain fanout peers for.
+    pub fn fanout_topics(&self) -> impl Iterator<Item = &TopicHash> {
+        self.fanout.keys()
+    }
+
+    /// Lists all fanout peers for a certain topic hash.
+    pub fn fanout_peers(&self, topic_hash: &TopicHash) -> impl Iterator<Item = &PeerId> {
+        self.fanout
+            .get(topic_hash)
+            .into_iter()
+            .flat_map(|x| x.iter())
+    }
+
     /// Lists all known peers and their associated subscribed topics.
     pub fn all_peers(&self) -> impl Iterator<Item = (&PeerId, Vec<&TopicHash>)> {
         self.peer_topics
@@ -636,6 +675,16 @@ where
             return Err(PublishError::MessageTooLarge);
         }
 
+        // check that the size doesn't exceed the max message size of the topic
+        if raw_message.raw_protobuf_len()
+            > self.config.max_transmit_size_for_topic(&raw_message.topic)
+        {
+            if let Some(metrics) = self.metrics.as_mut() {
+                metrics.register_oversized_message(&raw_message.topic);
+            }
+            return Err(PublishError::MessageTooLarge);
+        }
+
         // Check the if the message has been published before
         if self.duplicate_cache.contains(&msg_id) {
             // This message has already been seen. We don't re-publish messages that have already
@@ -781,6 +830,8 @@ where
     /// in the cache anymore.
     ///
     /// This should only be called once per message.
+    ///
+    /// See [`Behaviour::validation_handle`] for reporting the result from another task.
     pub fn report_message_validation_result(
         &mut self,
         msg_id: &MessageId,
@@ -846,6 +897,24 @@ where
         }
     }
 
+    /// Returns a [`ValidationHandle`] for a message received via [`Event::Message`].
+    ///
+    /// This is meant for validation that cannot be performed synchronously when
+    /// [`Config::validate_messages()`] is `true`. The handle can be moved to another task and
+    /// resolved there once validation completes; the result is then applied as if passed to
+    /// [`Behaviour::report_message_validation_result`] the next time the behaviour is polled.
+    ///
+    /// Validation through a handle happens in addition to the checks configured via
+    /// [`ValidationMode`], which are performed before a message is emitted as an
+    /// [`Event::Message`]. Messages failing those are never handed to the application.
+    pub fn validation_handle(
+        &self,
+        msg_id: MessageId,
+        propagation_source: PeerId,
+    ) -> ValidationHandle {
+        ValidationHandle::new(msg_id, propagation_source, self.validation_sender.clone())
+    }
+
     /// Adds a new peer to the list of explicitly connected peers.
     pub fn add_explicit_peer(&mut self, peer_id: &PeerId) {
         tracing::debug!(peer=%peer_id, "Adding explicit peer");
@@ -1330,11 +1399,34 @@ where
             return;
         }
 
+        // IWANT flood protection
+        let peer_want = self.count_received_iwant.entry(*peer_id).or_insert(0);
+        *peer_want += 1;
+        if *peer_want > self.config.max_iwant_messages() {
+            tracing::debug!(
+                peer=%peer_id,
+                "IWANT: peer has requested too many times ({}) within this heartbeat \
+            interval; ignoring",
+                *peer_want
+            );
+            self.penalize_iwant_flood(peer_id);
+            return;
+        }
+
+        if iwant_msgs.len() > self.config.max_iwant_length() {
+            tracing::debug!(
+                peer=%peer_id,
+                "IWANT: peer has requested too many messages ({}) at once; truncating",
+                iwant_msgs.len()
+            );
+            self.penalize_iwant_flood(peer_id);
+        }
+
         tracing::debug!(peer=%peer_id, "Handling IWANT for peer");
         // build a hashmap of available messages
         let mut cached_messages = HashMap::new();
 
-        for id in iwant_msgs {
+        for id in iwant_msgs.into_iter().take(self.config.max_iwant_length()) {
             // If we have it and the IHAVE count is not above the threshold, add it do the
             // cached_messages mapping
             if let Some((msg, count)) = self.mcache.get_with_iwant_counts(&id, peer_id) {
@@ -1787,7 +1879,7 @@ where
             return;
         }
 
-        if !self.duplicate_cache.insert(msg_id.clone()) {
+        if self.duplicate_cache.contains(&msg_id) {
             tracing::debug!(message=%msg_id, "Message already received, ignoring");
             if let Some((peer_score, ..)) = &mut self.peer_score {
                 peer_score.duplicated_message(propagation_source, &msg_id, &message.topic);
@@ -1795,6 +1887,56 @@ where
             self.mcache.observe_duplicate(&msg_id, propagation_source);
             return;
         }
+
+        // Reject the message if it exceeds the max message size of its topic. Unlike invalid
+        // messages, it is added to the duplicate cache, such that it is not processed again while
+        // every peer forwarding it is still penalized.
+        if raw_message.raw_protobuf_len() > self.config.max_transmit_size_for_topic(&message.topic)
+        {
+            tracing::debug!(
+                peer=%propagation_source,
+                topic=%message.topic,
+                "Message exceeds the max message size of the topic, rejecting message"
+            );
+            if let Some(metrics) = self.metrics.as_mut() {
+                metrics.register_oversized_message(&message.topic);
+            }
+            self.duplicate_cache.insert(msg_id.clone());
+            if let Some((peer_score, .., gossip_promises)) = &mut self.peer_score {
+                let reason = RejectReason::ValidationFailed;
+                peer_score.reject_message(propagation_source, &msg_id, &message.topic, reason);
+                gossip_promises.reject_message(&msg_id, &reason);
+            }
+            return;
+        }
+
+        // Drop the message if the peer exceeds the publish rate limit for this topic.
+        if let Some(rate_limiter) = self.publish_rate_limiter.as_mut() {
+            if !rate_limiter.allow(
+                propagation_source,
+                &raw_message.topic,
+                raw_message.raw_protobuf_len(),
+            ) {
+                tracing::debug!(
+                    peer=%propagation_source,
+                    topic=%raw_message.topic,
+                    "Peer exceeded the publish rate limit, dropping message"
+                );
+                if let Some(metrics) = self.metrics.as_mut() {
+                    metrics.register_rate_limited_message(&raw_message.topic);
+                }
+                if self.config.penalize_rate_limited_publishers() {
+                    if let Some((peer_score, ..)) = &mut self.peer_score {
+                        peer_score.reject_invalid_message(propagation_source, &raw_message.topic);
+                    }
+                }
+                // Not added to the duplicate cache, such that the message is accepted from
+                // another peer.
+                return;
+            }
+        }
+
+        self.duplicate_cache.insert(msg_id.clone());
         tracing::debug!(
             message=%msg_id,
             "Put message in duplicate_cache and resolve promises"
@@ -2066,6 +2208,16 @@ where
         );
     }
 
+    /// Applies a behavioural penalty to a peer that flooded us with IWANT requests.
+    fn penalize_iwant_flood(&mut self, peer_id: &PeerId) {
+        if let Some((peer_score, ..)) = &mut self.peer_score {
+            if let Some(metrics) = self.metrics.as_mut() {
+                metrics.register_score_penalty(Penalty::IWantFlood);
+            }
+            peer_score.add_penalty(peer_id, 1);
+        }
+    }
+
     /// Applies penalties to peers that did not respond to our IWANT requests.
     fn apply_iwant_penalties(&mut self) {
         if let Some((peer_score, .., gossip_promises)) = &mut self.peer_score {
@@ -2092,9 +2244,15 @@ where
         // clean up expired backoffs
         self.backoffs.heartbeat();
 
+        // clean up ended rate limit windows
+        if let Some(rate_limiter) = self.publish_rate_limiter.as_mut() {
+            rate_limiter.prune();
+        }
+
         // clean up ihave counters
         self.count_sent_iwant.clear();
         self.count_received_ihave.clear();
+        self.count_received_iwant.clear();
 
         // apply iwant penalties
         self.apply_iwant_penalties();
@@ -3221,6 +3379,10 @@ where
             if let Some((peer_score, ..)) = &mut self.peer_score {
                 peer_score.remove_peer(&peer_id);
             }
+
+            if let Some(rate_limiter) = self.publish_rate_limiter.as_mut() {
+                rate_limiter.remove_peer(&peer_id);
+            }
         }
     }
 
@@ -3425,6 +3587,16 @@ where
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        while let Poll::Ready(Some(result)) = self.validation_receiver.poll_next_unpin(cx) {
+            if let Err(e) = self.report_message_validation_result(
+                &result.message_id,
+                &result.propagation_source,
+                result.acceptance,
+            ) {
+                tracing::debug!(message=%result.message_id, "Failed to forward validated message: {e}");
+            }
+        }
+
         if let Some(event) = self.events.pop_front() {
             return Poll::Ready(event);
         }
@@ -29,6 +29,7 @@ use async_std::net::Ipv4Addr;
 use byteorder::{BigEndian, ByteOrder};
 use libp2p_core::{ConnectedPoint, Endpoint};
 use rand::Rng;
+use std::num::NonZeroUsize;
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -743,6 +744,17 @@ fn test_fanout() {
         gs.config.mesh_n(),
         "Fanout should contain `mesh_n` peers for fanout topic"
     );
+    assert_eq!(
+        gs.fanout_topics().collect::<Vec<_>>(),
+        vec![&topic_hashes[0]],
+        "Fanout topics should list the fanout topic"
+    );
+    assert_eq!(
+        gs.fanout_peers(&topic_hashes[0]).count(),
+        gs.config.mesh_n(),
+        "Fanout peers should list the fanout peers of the topic"
+    );
+    assert_eq!(gs.mesh_peers(&topic_hashes[0]).count(), 0);
 
     // Collect all publish messages
     let publishes = gs
@@ -1361,6 +1373,20 @@ fn count_control_msgs<D: DataTransform, F: TopicSubscriptionFilter>(
             .sum::<usize>()
 }
 
+/// Counts the messages sent to peers in the given events.
+fn count_sent_messages(events: &VecDeque<ToSwarm<Event, HandlerIn>>) -> usize {
+    events
+        .iter()
+        .map(|e| match e {
+            ToSwarm::NotifyHandler {
+                event: HandlerIn::Message(ref m),
+                ..
+            } => proto_to_message(m).messages.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
 fn flush_events<D: DataTransform, F: TopicSubscriptionFilter>(gs: &mut Behaviour<D, F>) {
     gs.control_pool.clear();
     gs.events.clear();
@@ -3637,6 +3663,265 @@ fn test_scoring_p4_invalid_signature() {
     );
 }
 
+#[test]
+fn test_publish_rate_limit_drops_and_penalizes_excess_messages() {
+    let config = ConfigBuilder::default()
+        .publish_rate_limit(crate::PublishRateLimit {
+            max_messages_per_second: 2,
+            max_bytes_per_second: usize::MAX,
+        })
+        .penalize_rate_limited_publishers(true)
+        .build()
+        .unwrap();
+    let mut peer_score_params = PeerScoreParams::default();
+    let topic = Topic::new("test");
+    let topic_hash = topic.hash();
+    let topic_params = TopicScoreParams {
+        time_in_mesh_weight: 0.0,             //deactivate time in mesh
+        first_message_deliveries_weight: 0.0, //deactivate first time deliveries
+        mesh_message_deliveries_weight: 0.0,  //deactivate message deliveries
+        mesh_failure_penalty_weight: 0.0,     //deactivate mesh failure penalties
+        invalid_message_deliveries_weight: -2.0,
+        invalid_message_deliveries_decay: 0.9,
+        topic_weight: 0.7,
+        ..Default::default()
+    };
+    peer_score_params.topics.insert(topic_hash, topic_params);
+    let peer_score_thresholds = PeerScoreThresholds::default();
+
+    //build mesh with one peer
+    let (mut gs, peers, topics) = inject_nodes1()
+        .peer_no(1)
+        .topics(vec!["test".into()])
+        .to_subscribe(true)
+        .gs_config(config)
+        .explicit(0)
+        .outbound(0)
+        .scoring(Some((peer_score_params, peer_score_thresholds)))
+        .create_network();
+
+    let mut seq = 0;
+    for _ in 0..3 {
+        gs.handle_received_message(random_message(&mut seq, &topics), &peers[0]);
+    }
+
+    let received = gs
+        .events
+        .iter()
+        .filter(|e| matches!(e, ToSwarm::GenerateEvent(Event::Message { .. })))
+        .count();
+    assert_eq!(received, 2, "the third message should have been dropped");
+
+    assert_eq!(
+        gs.peer_score.as_ref().unwrap().0.score(&peers[0]),
+        -2.0 * 0.7
+    );
+}
+
+#[test]
+fn test_publish_rate_limit_ignores_duplicates() {
+    let config = ConfigBuilder::default()
+        .publish_rate_limit(crate::PublishRateLimit {
+            max_messages_per_second: 1,
+            max_bytes_per_second: usize::MAX,
+        })
+        .build()
+        .unwrap();
+
+    let (mut gs, peers, topics) = inject_nodes1()
+        .peer_no(3)
+        .topics(vec!["test".into()])
+        .to_subscribe(true)
+        .gs_config(config)
+        .create_network();
+
+    let mut seq = 0;
+    let first = random_message(&mut seq, &topics);
+    let second = random_message(&mut seq, &topics);
+
+    // Relaying a message received before doesn't count towards the limit of the relay.
+    gs.handle_received_message(first.clone(), &peers[0]);
+    gs.handle_received_message(first, &peers[1]);
+    gs.handle_received_message(second.clone(), &peers[1]);
+
+    // A message dropped due to the limit of one peer is still accepted from another one.
+    let third = random_message(&mut seq, &topics);
+    gs.handle_received_message(third.clone(), &peers[0]);
+    gs.handle_received_message(third.clone(), &peers[2]);
+
+    let received = gs
+        .events
+        .iter()
+        .filter_map(|e| match e {
+            ToSwarm::GenerateEvent(Event::Message {
+                propagation_source,
+                message,
+                ..
+            }) => Some((*propagation_source, message.data.clone())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(received.len(), 3);
+    assert_eq!(received[1], (peers[1], second.data));
+    assert_eq!(received[2], (peers[2], third.data));
+}
+
+#[test]
+fn test_duplicate_cache_capacity_evicts_oldest_messages() {
+    let config = ConfigBuilder::default()
+        .duplicate_cache(NonZeroUsize::new(1).unwrap(), Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    let (mut gs, peers, topics) = inject_nodes1()
+        .peer_no(1)
+        .topics(vec!["test".into()])
+        .to_subscribe(true)
+        .gs_config(config)
+        .create_network();
+
+    let mut seq = 0;
+    let first = random_message(&mut seq, &topics);
+    let second = random_message(&mut seq, &topics);
+
+    let received = |gs: &Behaviour| {
+        gs.events
+            .iter()
+            .filter(|e| matches!(e, ToSwarm::GenerateEvent(Event::Message { .. })))
+            .count()
+    };
+
+    gs.handle_received_message(first.clone(), &peers[0]);
+    gs.handle_received_message(first.clone(), &peers[0]);
+    assert_eq!(
+        received(&gs),
+        1,
+        "the duplicate should have been suppressed"
+    );
+
+    // Evicts the first message from the duplicate cache.
+    gs.handle_received_message(second, &peers[0]);
+    gs.handle_received_message(first, &peers[0]);
+    assert_eq!(
+        received(&gs),
+        3,
+        "the evicted message should have been processed again"
+    );
+}
+
+#[test]
+fn test_max_transmit_size_for_topic_rejects_oversized_messages() {
+    let small_topic = Topic::new("small");
+    let config = ConfigBuilder::default()
+        .max_transmit_size_for_topic(small_topic.hash(), 250)
+        .build()
+        .unwrap();
+
+    let (mut gs, peers, topics) = inject_nodes1()
+        .peer_no(1)
+        .topics(vec!["small".into(), "large".into()])
+        .to_subscribe(true)
+        .gs_config(config)
+        .create_network();
+
+    assert!(matches!(
+        gs.publish(topics[0].clone(), vec![0; 500]),
+        Err(PublishError::MessageTooLarge)
+    ));
+    assert!(gs.publish(topics[0].clone(), vec![0; 10]).is_ok());
+    assert!(gs.publish(topics[1].clone(), vec![0; 500]).is_ok());
+
+    let mut seq = 0;
+    for topic in &topics {
+        let message = RawMessage {
+            data: vec![0; 500],
+            topic: topic.clone(),
+            ..random_message(&mut seq, &topics)
+        };
+        gs.handle_received_message(message, &peers[0]);
+    }
+
+    let received = gs
+        .events
+        .iter()
+        .filter_map(|e| match e {
+            ToSwarm::GenerateEvent(Event::Message { message, .. }) => Some(&message.topic),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        received,
+        vec![&topics[1]],
+        "only the message on the large topic should be accepted"
+    );
+}
+
+#[test]
+fn test_oversized_message_is_cached_and_penalized() {
+    let small_topic = Topic::new("small");
+    let config = ConfigBuilder::default()
+        .max_transmit_size_for_topic(small_topic.hash(), 250)
+        .build()
+        .unwrap();
+    let mut peer_score_params = PeerScoreParams::default();
+    let topic_params = TopicScoreParams {
+        time_in_mesh_weight: 0.0,             //deactivate time in mesh
+        first_message_deliveries_weight: 0.0, //deactivate first time deliveries
+        mesh_message_deliveries_weight: 0.0,  //deactivate message deliveries
+        mesh_failure_penalty_weight: 0.0,     //deactivate mesh failure penalties
+        invalid_message_deliveries_weight: -2.0,
+        invalid_message_deliveries_decay: 0.9,
+        topic_weight: 0.7,
+        ..Default::default()
+    };
+    peer_score_params
+        .topics
+        .insert(small_topic.hash(), topic_params);
+    peer_score_params.app_specific_weight = 1.0;
+
+    let (mut gs, peers, topics) = inject_nodes1()
+        .peer_no(2)
+        .topics(vec!["small".into()])
+        .to_subscribe(true)
+        .gs_config(config)
+        .explicit(0)
+        .outbound(0)
+        .scoring(Some((peer_score_params, PeerScoreThresholds::default())))
+        .create_network();
+
+    let mut seq = 0;
+    let message = RawMessage {
+        data: vec![0; 500],
+        ..random_message(&mut seq, &topics)
+    };
+    let message_id = gs.config.message_id(
+        &gs.data_transform
+            .inbound_transform(message.clone())
+            .unwrap(),
+    );
+
+    gs.handle_received_message(message.clone(), &peers[0]);
+    assert!(gs.duplicate_cache.contains(&message_id));
+    assert_eq!(
+        gs.peer_score.as_ref().unwrap().0.score(&peers[0]),
+        -2.0 * 0.7
+    );
+
+    // Peers forwarding the message later are penalized as well.
+    gs.handle_received_message(message, &peers[1]);
+    assert_eq!(
+        gs.peer_score.as_ref().unwrap().0.score(&peers[1]),
+        -2.0 * 0.7
+    );
+
+    assert!(
+        !gs.events
+            .iter()
+            .any(|e| matches!(e, ToSwarm::GenerateEvent(Event::Message { .. }))),
+        "the oversized message should not be delivered"
+    );
+}
+
 #[test]
 fn test_scoring_p4_message_from_self() {
     let config = ConfigBuilder::default()
@@ -4419,6 +4704,98 @@ fn test_ignore_too_many_iwants_from_same_peer_for_same_message() {
     );
 }
 
+#[test]
+fn test_ignore_too_many_iwants_within_heartbeat() {
+    let config = ConfigBuilder::default()
+        .max_iwant_messages(2)
+        .build()
+        .unwrap();
+    let peer_score_params = PeerScoreParams {
+        behaviour_penalty_weight: -1.0,
+        ..Default::default()
+    };
+    let (mut gs, _, topics) = inject_nodes1()
+        .peer_no(config.mesh_n_high())
+        .topics(vec!["test".into()])
+        .to_subscribe(false)
+        .gs_config(config.clone())
+        .scoring(Some((peer_score_params, PeerScoreThresholds::default())))
+        .create_network();
+
+    //add another peer not in the mesh
+    let peer = add_peer(&mut gs, &topics, false, false);
+
+    //receive some messages
+    let mut seq = 0;
+    let ids: Vec<_> = (0..4)
+        .map(|_| {
+            let m = random_message(&mut seq, &topics);
+            let id = config.message_id(&gs.data_transform.inbound_transform(m.clone()).unwrap());
+            gs.handle_received_message(m, &PeerId::random());
+            id
+        })
+        .collect();
+
+    gs.events.clear();
+
+    //the peer requests each message in its own IWANT, only the first two are served
+    for id in &ids {
+        gs.handle_iwant(&peer, vec![id.clone()]);
+    }
+    assert_eq!(count_sent_messages(&gs.events), 2);
+    assert_eq!(
+        gs.peer_score.as_ref().unwrap().0.score(&peer),
+        2.0 * 2.0 * -1.0
+    );
+
+    //the limit is reset with the next heartbeat
+    gs.events.clear();
+    gs.heartbeat();
+    gs.events.clear();
+    gs.handle_iwant(&peer, vec![ids[3].clone()]);
+    assert_eq!(count_sent_messages(&gs.events), 1);
+}
+
+#[test]
+fn test_truncate_iwants_with_too_many_messages() {
+    let config = ConfigBuilder::default()
+        .max_iwant_length(2)
+        .build()
+        .unwrap();
+    let peer_score_params = PeerScoreParams {
+        behaviour_penalty_weight: -1.0,
+        ..Default::default()
+    };
+    let (mut gs, _, topics) = inject_nodes1()
+        .peer_no(config.mesh_n_high())
+        .topics(vec!["test".into()])
+        .to_subscribe(false)
+        .gs_config(config.clone())
+        .scoring(Some((peer_score_params, PeerScoreThresholds::default())))
+        .create_network();
+
+    //add another peer not in the mesh
+    let peer = add_peer(&mut gs, &topics, false, false);
+
+    //receive some messages
+    let mut seq = 0;
+    let ids: Vec<_> = (0..3)
+        .map(|_| {
+            let m = random_message(&mut seq, &topics);
+            let id = config.message_id(&gs.data_transform.inbound_transform(m.clone()).unwrap());
+            gs.handle_received_message(m, &PeerId::random());
+            id
+        })
+        .collect();
+
+    gs.events.clear();
+
+    //the peer requests all messages at once, only the first two are served
+    gs.handle_iwant(&peer, ids);
+    assert_eq!(count_sent_messages(&gs.events), 2);
+    assert_eq!(gs.peer_score.as_ref().unwrap().0.score(&peer), -1.0);
+}
+
 #[test]
 fn test_ignore_too_many_ihaves() {
     let config = ConfigBuilder::default()
@@ -5174,3 +5551,59 @@ fn test_graft_without_subscribe() {
     // We unsubscribe from the topic.
     let _ = gs.unsubscribe(&Topic::new(topic));
 }
+
+#[test]
+fn test_validation_handle_resolved_from_other_thread() {
+    let config = ConfigBuilder::default()
+        .validate_messages()
+        .build()
+        .unwrap();
+    let (mut gs, peers, topics) = inject_nodes1()
+        .peer_no(2)
+        .topics(vec!["test".into()])
+        .to_subscribe(true)
+        .gs_config(config.clone())
+        .create_network();
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+
+    let mut seq = 0;
+    let accepted = random_message(&mut seq, &topics);
+    let rejected = random_message(&mut seq, &topics);
+    gs.handle_received_message(accepted.clone(), &peers[0]);
+    gs.handle_received_message(rejected.clone(), &peers[0]);
+    let accepted_id = config.message_id(&gs.data_transform.inbound_transform(accepted).unwrap());
+    let rejected_id = config.message_id(&gs.data_transform.inbound_transform(rejected).unwrap());
+    gs.events.clear();
+
+    let accept = gs.validation_handle(accepted_id.clone(), peers[0]);
+    let reject = gs.validation_handle(rejected_id.clone(), peers[0]);
+    assert_eq!(accept.message_id(), &accepted_id);
+    assert_eq!(accept.propagation_source(), &peers[0]);
+
+    // Nothing is forwarded while validation is pending.
+    assert!(gs.poll(&mut cx).is_pending());
+
+    std::thread::spawn(move || {
+        accept.accept();
+        reject.reject();
+    })
+    .join()
+    .unwrap();
+
+    let mut forwarded = Vec::new();
+    while let Poll::Ready(event) = gs.poll(&mut cx) {
+        if let ToSwarm::NotifyHandler {
+            peer_id,
+            event: HandlerIn::Message(rpc),
+            ..
+        } = event
+        {
+            forwarded.extend(rpc.publish.iter().map(|_| peer_id));
+        }
+    }
+
+    assert!(!forwarded.is_empty());
+    assert!(forwarded.iter().all(|peer| *peer != peers[0]));
+    assert!(gs.mcache.get(&accepted_id).is_some());
+    assert!(gs.mcache.get(&rejected_id).is_none());
+}
@@ -19,11 +19,15 @@
 // DEALINGS IN THE SOFTWARE.
 
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::error::ConfigBuilderError;
 use crate::protocol::{ProtocolConfig, ProtocolId, FLOODSUB_PROTOCOL};
+use crate::rate_limiter::PublishRateLimit;
+use crate::topic::TopicHash;
 use crate::types::{Message, MessageId, PeerKind};
 
 use libp2p_identity::PeerId;
@@ -76,6 +80,7 @@ pub struct Config {
     fanout_ttl: Duration,
     check_explicit_peers_ticks: u64,
     duplicate_cache_time: Duration,
+    duplicate_cache_capacity: NonZeroUsize,
     validate_messages: bool,
     message_id_fn: Arc<dyn Fn(&Message) -> MessageId + Send + Sync + 'static>,
     allow_self_origin: bool,
@@ -93,8 +98,13 @@ pub struct Config {
     max_messages_per_rpc: Option<usize>,
     max_ihave_length: usize,
     max_ihave_messages: usize,
+    max_iwant_messages: usize,
+    max_iwant_length: usize,
+    max_transmit_size_per_topic: HashMap<TopicHash, usize>,
     iwant_followup_time: Duration,
     published_message_ids_cache_time: Duration,
+    publish_rate_limit: Option<PublishRateLimit>,
+    penalize_rate_limited_publishers: bool,
 }
 
 impl Config {
@@ -190,10 +200,18 @@ impl Config {
         self.duplicate_cache_time
     }
 
+    /// The maximum number of message id's stored in the duplicate cache. Once exceeded, the
+    /// oldest message id's are evicted before [`Config::duplicate_cache_time`] expired. The
+    /// default is unbounded.
+    pub fn duplicate_cache_capacity(&self) -> NonZeroUsize {
+        self.duplicate_cache_capacity
+    }
+
     /// When set to `true`, prevents automatic forwarding of all received messages. This setting
     /// allows a user to validate the messages before propagating them to their peers. If set to
     /// true, the user must manually call [`crate::Behaviour::report_message_validation_result()`]
-    /// on the behaviour to forward message once validated (default is `false`).
+    /// on the behaviour, or resolve a [`crate::ValidationHandle`], to forward message once
+    /// validated (default is `false`).
     /// The default is `false`.
     pub fn validate_messages(&self) -> bool {
         self.validate_messages
@@ -334,6 +352,32 @@ impl Config {
         self.max_ihave_messages
     }
 
+    /// The maximum number of IWANT messages to accept from a peer within a heartbeat, to protect
+    /// from IWANT floods. Further IWANT messages within the heartbeat are ignored and, if peer
+    /// scoring is enabled, each adds a behavioural penalty to the peer. The default is 10.
+    pub fn max_iwant_messages(&self) -> usize {
+        self.max_iwant_messages
+    }
+
+    /// The maximum number of messages served in response to a single IWANT message. Further
+    /// message ids in the IWANT are ignored and, if peer scoring is enabled, a behavioural penalty
+    /// is added to the peer. The default is 5000.
+    pub fn max_iwant_length(&self) -> usize {
+        self.max_iwant_length
+    }
+
+    /// The maximum size of an encoded message on the given topic. Larger messages are neither
+    /// published nor accepted from peers, and thus never forwarded on this topic. Defaults to
+    /// [`Config::max_transmit_size`], which also caps any limit configured for the topic via
+    /// [`ConfigBuilder::max_transmit_size_for_topic`].
+    pub fn max_transmit_size_for_topic(&self, topic: &TopicHash) -> usize {
+        let max_transmit_size = self.max_transmit_size();
+
+        self.max_transmit_size_per_topic
+            .get(topic)
+            .map_or(max_transmit_size, |size| (*size).min(max_transmit_size))
+    }
+
     /// Time to wait for a message requested through IWANT following an IHAVE advertisement.
     /// If the message is not received within this window, a broken promise is declared and
     /// the router may apply behavioural penalties. The default is 3 seconds.
@@ -350,6 +394,20 @@ impl Config {
     pub fn published_message_ids_cache_time(&self) -> Duration {
         self.published_message_ids_cache_time
     }
+
+    /// The maximum rate at which a single peer may publish messages on a single topic. Only
+    /// messages not received before count towards the rate, so that peers aren't limited for
+    /// relaying duplicates. Messages received above this rate are dropped before validation and
+    /// are still accepted from other peers. The default is None (no limit).
+    pub fn publish_rate_limit(&self) -> Option<PublishRateLimit> {
+        self.publish_rate_limit
+    }
+
+    /// Whether peers exceeding the [`Config::publish_rate_limit`] are penalized as if they had
+    /// sent an invalid message. Has no effect unless peer scoring is enabled. The default is false.
+    pub fn penalize_rate_limited_publishers(&self) -> bool {
+        self.penalize_rate_limited_publishers
+    }
 }
 
 impl Default for Config {
@@ -385,6 +443,7 @@ impl Default for ConfigBuilder {
                 fanout_ttl: Duration::from_secs(60),
                 check_explicit_peers_ticks: 300,
                 duplicate_cache_time: Duration::from_secs(60),
+                duplicate_cache_capacity: NonZeroUsize::MAX,
                 validate_messages: false,
                 message_id_fn: Arc::new(|message| {
                     // default message id is: source + sequence number
@@ -415,8 +474,13 @@ impl Default for ConfigBuilder {
                 max_messages_per_rpc: None,
                 max_ihave_length: 5000,
                 max_ihave_messages: 10,
+                max_iwant_messages: 10,
+                max_iwant_length: 5000,
+                max_transmit_size_per_topic: HashMap::new(),
                 iwant_followup_time: Duration::from_secs(3),
                 published_message_ids_cache_time: Duration::from_secs(10),
+                publish_rate_limit: None,
+                penalize_rate_limited_publishers: false,
             },
             invalid_protocol: false,
         }
@@ -586,10 +650,26 @@ impl ConfigBuilder {
         self
     }
 
+    /// Sets both the maximum number of message id's stored in the duplicate cache and the time
+    /// period they are stored for, see [`ConfigBuilder::duplicate_cache_time`].
+    ///
+    /// Once `capacity` is exceeded, the oldest message id's are evicted before `ttl` expired.
+    /// This bounds the memory used by the cache on high-throughput topics, at the cost of
+    /// accuracy: an evicted message that is received again is processed as a new message. Choose
+    /// a capacity of at least the expected number of messages received within `ttl` to avoid
+    /// reprocessing. The capacity is unbounded by default.
+    ///
+    /// The capacity is non-zero, as a cache without capacity would not detect any duplicates.
+    pub fn duplicate_cache(&mut self, capacity: NonZeroUsize, ttl: Duration) -> &mut Self {
+        self.config.duplicate_cache_capacity = capacity;
+        self.config.duplicate_cache_time = ttl;
+        self
+    }
+
     /// When set, prevents automatic forwarding of all received messages. This setting
     /// allows a user to validate the messages before propagating them to their peers. If set,
     /// the user must manually call [`crate::Behaviour::report_message_validation_result()`] on the
-    /// behaviour to forward a message once validated.
+    /// behaviour, or resolve a [`crate::ValidationHandle`], to forward a message once validated.
     pub fn validate_messages(&mut self) -> &mut Self {
         self.config.validate_messages = true;
         self
@@ -609,7 +689,8 @@ impl ConfigBuilder {
     /// of the same content from being duplicated.
     ///
     /// The function takes a [`Message`] as input and outputs a String to be
-    /// interpreted as the message id.
+    /// interpreted as the message id. Ids depending on the author of the message can be derived
+    /// from [`Message::source`].
     pub fn message_id_fn<F>(&mut self, id_fn: F) -> &mut Self
     where
         F: Fn(&Message) -> MessageId + Send + Sync + 'static,
@@ -742,6 +823,36 @@ impl ConfigBuilder {
         self
     }
 
+    /// The maximum number of IWANT messages to accept from a peer within a heartbeat, to protect
+    /// from IWANT floods. Further IWANT messages within the heartbeat are ignored and, if peer
+    /// scoring is enabled, each adds a behavioural penalty to the peer. The default is 10.
+    pub fn max_iwant_messages(&mut self, max_iwant_messages: usize) -> &mut Self {
+        self.config.max_iwant_messages = max_iwant_messages;
+        self
+    }
+
+    /// The maximum number of messages served in response to a single IWANT message. Further
+    /// message ids in the IWANT are ignored and, if peer scoring is enabled, a behavioural penalty
+    /// is added to the peer. The default is 5000.
+    pub fn max_iwant_length(&mut self, max_iwant_length: usize) -> &mut Self {
+        self.config.max_iwant_length = max_iwant_length;
+        self
+    }
+
+    /// The maximum size of an encoded message on the given topic, overriding
+    /// [`ConfigBuilder::max_transmit_size`] for messages on this topic. Larger messages are
+    /// neither published nor accepted from peers, and thus never forwarded on this topic. Peers
+    /// sending larger messages are penalized like for invalid messages if scoring is enabled.
+    ///
+    /// The [`ConfigBuilder::max_transmit_size`] still limits every RPC and hence has to be at
+    /// least as large as the largest limit of any topic.
+    pub fn max_transmit_size_for_topic(&mut self, topic: TopicHash, max_size: usize) -> &mut Self {
+        self.config
+            .max_transmit_size_per_topic
+            .insert(topic, max_size);
+        self
+    }
+
     /// By default, gossipsub will reject messages that are sent to us that has the same message
     /// source as we have specified locally. Enabling this, allows these messages and prevents
     /// penalizing the peer that sent us the message. Default is false.
@@ -782,6 +893,25 @@ impl ConfigBuilder {
         self
     }
 
+    /// The maximum rate at which a single peer may publish messages on a single topic. Only
+    /// messages not received before count towards the rate, so that peers aren't limited for
+    /// relaying duplicates. Messages received above this rate are dropped before validation and
+    /// are still accepted from other peers. The default is None (no limit).
+    pub fn publish_rate_limit(&mut self, publish_rate_limit: PublishRateLimit) -> &mut Self {
+        self.config.publish_rate_limit = Some(publish_rate_limit);
+        self
+    }
+
+    /// Whether peers exceeding the [`Config::publish_rate_limit`] are penalized as if they had
+    /// sent an invalid message. Has no effect unless peer scoring is enabled. The default is false.
+    pub fn penalize_rate_limited_publishers(
+        &mut self,
+        penalize_rate_limited_publishers: bool,
+    ) -> &mut Self {
+        self.config.penalize_rate_limited_publishers = penalize_rate_limited_publishers;
+        self
+    }
+
     /// Constructs a [`Config`] from the given configuration and validates the settings.
     pub fn build(&self) -> Result<Config, ConfigBuilderError> {
         // check all constraints on config
@@ -833,6 +963,7 @@ impl std::fmt::Debug for Config {
         let _ = builder.field("heartbeat_interval", &self.heartbeat_interval);
         let _ = builder.field("fanout_ttl", &self.fanout_ttl);
         let _ = builder.field("duplicate_cache_time", &self.duplicate_cache_time);
+        let _ = builder.field("duplicate_cache_capacity", &self.duplicate_cache_capacity);
         let _ = builder.field("validate_messages", &self.validate_messages);
         let _ = builder.field("allow_self_origin", &self.allow_self_origin);
         let _ = builder.field("do_px", &self.do_px);
@@ -847,11 +978,22 @@ impl std::fmt::Debug for Config {
         let _ = builder.field("max_messages_per_rpc", &self.max_messages_per_rpc);
         let _ = builder.field("max_ihave_length", &self.max_ihave_length);
         let _ = builder.field("max_ihave_messages", &self.max_ihave_messages);
+        let _ = builder.field("max_iwant_messages", &self.max_iwant_messages);
+        let _ = builder.field("max_iwant_length", &self.max_iwant_length);
+        let _ = builder.field(
+            "max_transmit_size_per_topic",
+            &self.max_transmit_size_per_topic,
+        );
         let _ = builder.field("iwant_followup_time", &self.iwant_followup_time);
         let _ = builder.field(
             "published_message_ids_cache_time",
             &self.published_message_ids_cache_time,
         );
+        let _ = builder.field("publish_rate_limit", &self.publish_rate_limit);
+        let _ = builder.field(
+            "penalize_rate_limited_publishers",
+            &self.penalize_rate_limited_publishers,
+        );
         builder.finish()
     }
 }
@@ -103,12 +103,14 @@ mod mcache;
 mod metrics;
 mod peer_score;
 mod protocol;
+mod rate_limiter;
 mod rpc_proto;
 mod subscription_filter;
 mod time_cache;
 mod topic;
 mod transform;
 mod types;
+mod validation;
 
 pub use self::behaviour::{Behaviour, Event, MessageAuthenticity};
 pub use self::config::{Config, ConfigBuilder, ValidationMode, Version};
@@ -118,6 +120,7 @@ pub use self::peer_score::{
     score_parameter_decay, score_parameter_decay_with_base, PeerScoreParams, PeerScoreThresholds,
     TopicScoreParams,
 };
+pub use self::rate_limiter::PublishRateLimit;
 pub use self::subscription_filter::{
     AllowAllSubscriptionFilter, CallbackSubscriptionFilter, CombinedSubscriptionFilters,
     MaxCountSubscriptionFilter, RegexSubscriptionFilter, TopicSubscriptionFilter,
@@ -126,6 +129,7 @@ pub use self::subscription_filter::{
 pub use self::topic::{Hasher, Topic, TopicHash};
 pub use self::transform::{DataTransform, IdentityTransform};
 pub use self::types::{Message, MessageAcceptance, MessageId, RawMessage};
+pub use self::validation::ValidationHandle;
 
 #[deprecated(note = "Will be removed from the public API.")]
 pub type Rpc = self::types::Rpc;
@@ -121,6 +121,10 @@ pub(crate) struct Metrics {
     topic_peers_count: Family<TopicHash, Gauge>,
     /// The number of invalid messages received for a given topic.
     invalid_messages: Family<TopicHash, Counter>,
+    /// The number of messages dropped because the publisher exceeded the publish rate limit.
+    rate_limited_messages: Family<TopicHash, Counter>,
+    /// The number of messages rejected for exceeding the maximum message size of the topic.
+    oversized_messages: Family<TopicHash, Counter>,
     /// The number of messages accepted by the application (validation result).
     accepted_messages: Family<TopicHash, Counter>,
     /// The number of messages ignored by the application (validation result).
@@ -207,6 +211,16 @@ impl Metrics {
             "Number of invalid messages received for each topic"
         );
 
+        let rate_limited_messages = register_family!(
+            "rate_limited_messages_per_topic",
+            "Number of messages dropped for exceeding the publish rate limit for each topic"
+        );
+
+        let oversized_messages = register_family!(
+            "oversized_messages_per_topic",
+            "Number of published or received messages rejected for exceeding the maximum message size for each topic"
+        );
+
         let accepted_messages = register_family!(
             "accepted_messages_per_topic",
             "Number of accepted messages received for each topic"
@@ -309,6 +323,8 @@ impl Metrics {
             topic_subscription_status,
             topic_peers_count,
             invalid_messages,
+            rate_limited_messages,
+            oversized_messages,
             accepted_messages,
             ignored_messages,
             rejected_messages,
@@ -428,6 +444,20 @@ impl Metrics {
         }
     }
 
+    /// Register that a message was dropped because its publisher exceeded the publish rate limit.
+    pub(crate) fn register_rate_limited_message(&mut self, topic: &TopicHash) {
+        if self.register_topic(topic).is_ok() {
+            self.rate_limited_messages.get_or_create(topic).inc();
+        }
+    }
+
+    /// Register that a message was rejected for exceeding the maximum message size of its topic.
+    pub(crate) fn register_oversized_message(&mut self, topic: &TopicHash) {
+        if self.register_topic(topic).is_ok() {
+            self.oversized_messages.get_or_create(topic).inc();
+        }
+    }
+
     /// Register a score penalty.
     pub(crate) fn register_score_penalty(&mut self, penalty: Penalty) {
         self.scoring_penalties
@@ -567,6 +597,8 @@ pub(crate) enum Penalty {
     MessageDeficit,
     /// Too many peers under one IP address.
     IPColocation,
+    /// A peer sent too many IWANT requests or requested too many messages in one IWANT.
+    IWantFlood,
 }
 
 /// Label for the mesh inclusion event metrics.
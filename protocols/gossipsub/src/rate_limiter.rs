@@ -0,0 +1,176 @@
+// Copyright 2023 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Data structure for limiting the rate at which peers publish messages on a topic.
+use crate::topic::TopicHash;
+use instant::Instant;
+use libp2p_identity::PeerId;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Limits the rate at which a single peer may publish messages on a single topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublishRateLimit {
+    /// The maximum number of messages a peer may publish on a topic per second.
+    pub max_messages_per_second: u32,
+    /// The maximum number of bytes a peer may publish on a topic per second.
+    pub max_bytes_per_second: usize,
+}
+
+/// Messages and bytes received from a peer on a topic within the current one second window.
+struct Window {
+    start: Instant,
+    messages: u32,
+    bytes: usize,
+}
+
+/// Tracks the publish rate of each peer on each topic.
+pub(crate) struct PublishRateLimiter {
+    limit: PublishRateLimit,
+    windows: HashMap<(PeerId, TopicHash), Window>,
+}
+
+impl PublishRateLimiter {
+    pub(crate) fn new(limit: PublishRateLimit) -> Self {
+        Self {
+            limit,
+            windows: HashMap::new(),
+        }
+    }
+
+    /// Records a message of `bytes` bytes received from `peer` on `topic`.
+    ///
+    /// Returns `false` if the message exceeds the configured limit and should be dropped.
+    /// Dropped messages don't count towards the limit.
+    pub(crate) fn allow(&mut self, peer: &PeerId, topic: &TopicHash, bytes: usize) -> bool {
+        let now = Instant::now();
+        let window = self
+            .windows
+            .entry((*peer, topic.clone()))
+            .or_insert_with(|| Window {
+                start: now,
+                messages: 0,
+                bytes: 0,
+            });
+
+        if now.duration_since(window.start) >= Duration::from_secs(1) {
+            window.start = now;
+            window.messages = 0;
+            window.bytes = 0;
+        }
+
+        if window.messages >= self.limit.max_messages_per_second
+            || window.bytes + bytes > self.limit.max_bytes_per_second
+        {
+            return false;
+        }
+
+        window.messages += 1;
+        window.bytes += bytes;
+
+        true
+    }
+
+    /// Forgets the windows that ended, such that peers that stopped publishing on a topic don't
+    /// take up memory.
+    pub(crate) fn prune(&mut self) {
+        let now = Instant::now();
+        self.windows
+            .retain(|_, window| now.duration_since(window.start) < Duration::from_secs(1));
+    }
+
+    /// Forgets all state associated with `peer`.
+    pub(crate) fn remove_peer(&mut self, peer: &PeerId) {
+        self.windows.retain(|(p, _), _| p != peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_messages_per_topic() {
+        let mut limiter = PublishRateLimiter::new(PublishRateLimit {
+            max_messages_per_second: 2,
+            max_bytes_per_second: usize::MAX,
+        });
+        let peer = PeerId::random();
+        let topic = TopicHash::from_raw("topic");
+        let other_topic = TopicHash::from_raw("other");
+
+        assert!(limiter.allow(&peer, &topic, 10));
+        assert!(limiter.allow(&peer, &topic, 10));
+        assert!(!limiter.allow(&peer, &topic, 10));
+
+        assert!(limiter.allow(&peer, &other_topic, 10));
+        assert!(limiter.allow(&PeerId::random(), &topic, 10));
+    }
+
+    #[test]
+    fn limits_bytes_per_topic() {
+        let mut limiter = PublishRateLimiter::new(PublishRateLimit {
+            max_messages_per_second: u32::MAX,
+            max_bytes_per_second: 100,
+        });
+        let peer = PeerId::random();
+        let topic = TopicHash::from_raw("topic");
+
+        assert!(limiter.allow(&peer, &topic, 60));
+        assert!(!limiter.allow(&peer, &topic, 60));
+        assert!(limiter.allow(&peer, &topic, 40));
+    }
+
+    #[test]
+    fn removed_peer_starts_over() {
+        let mut limiter = PublishRateLimiter::new(PublishRateLimit {
+            max_messages_per_second: 1,
+            max_bytes_per_second: usize::MAX,
+        });
+        let peer = PeerId::random();
+        let topic = TopicHash::from_raw("topic");
+
+        assert!(limiter.allow(&peer, &topic, 10));
+        assert!(!limiter.allow(&peer, &topic, 10));
+
+        limiter.remove_peer(&peer);
+
+        assert!(limiter.allow(&peer, &topic, 10));
+    }
+
+    #[test]
+    fn ended_windows_are_pruned() {
+        let mut limiter = PublishRateLimiter::new(PublishRateLimit {
+            max_messages_per_second: 1,
+            max_bytes_per_second: usize::MAX,
+        });
+        let topic = TopicHash::from_raw("topic");
+
+        assert!(limiter.allow(&PeerId::random(), &topic, 10));
+        limiter.prune();
+        assert_eq!(limiter.windows.len(), 1);
+
+        limiter.windows.values_mut().for_each(|window| {
+            window.start -= Duration::from_secs(1);
+        });
+        limiter.prune();
+        assert!(limiter.windows.is_empty());
+    }
+}
@@ -153,25 +153,48 @@ where
     pub(crate) fn contains_key(&self, key: &Key) -> bool {
         self.map.contains_key(key)
     }
+
+    /// Removes the oldest keys until at most `capacity` keys remain.
+    fn evict_oldest_keys(&mut self, capacity: usize) {
+        while self.map.len() > capacity {
+            let Some(element) = self.list.pop_front() else {
+                break;
+            };
+            self.map.remove(&element.element);
+        }
+    }
 }
 
-pub(crate) struct DuplicateCache<Key>(TimeCache<Key, ()>);
+pub(crate) struct DuplicateCache<Key> {
+    cache: TimeCache<Key, ()>,
+    /// The maximum number of keys, beyond which the oldest keys are evicted before they expire.
+    capacity: usize,
+}
 
 impl<Key> DuplicateCache<Key>
 where
     Key: Eq + std::hash::Hash + Clone,
 {
     pub(crate) fn new(ttl: Duration) -> Self {
-        Self(TimeCache::new(ttl))
+        Self::with_capacity(ttl, usize::MAX)
     }
 
-    // Inserts new elements and removes any expired elements.
+    pub(crate) fn with_capacity(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            cache: TimeCache::new(ttl),
+            capacity,
+        }
+    }
+
+    // Inserts new elements and removes any expired elements, as well as the oldest elements if
+    // the capacity is exceeded.
     //
     // If the key was not present this returns `true`. If the value was already present this
     // returns `false`.
     pub(crate) fn insert(&mut self, key: Key) -> bool {
-        if let Entry::Vacant(entry) = self.0.entry(key) {
+        if let Entry::Vacant(entry) = self.cache.entry(key) {
             entry.insert(());
+            self.cache.evict_oldest_keys(self.capacity);
             true
         } else {
             false
@@ -179,7 +202,7 @@ where
     }
 
     pub(crate) fn contains(&self, key: &Key) -> bool {
-        self.0.contains_key(key)
+        self.cache.contains_key(key)
     }
 }
 
@@ -216,4 +239,19 @@ mod test {
         // should be removed from the cache
         assert!(cache.insert("t"));
     }
+
+    #[test]
+    fn cache_evicts_oldest_entries_beyond_capacity() {
+        let mut cache = DuplicateCache::with_capacity(Duration::from_secs(10), 2);
+
+        cache.insert("t");
+        cache.insert("e");
+        cache.insert("s");
+
+        // 't' is the oldest entry and thus evicted
+        assert!(!cache.contains(&"t"));
+        assert!(cache.contains(&"e"));
+        assert!(cache.contains(&"s"));
+        assert!(cache.insert("t"));
+    }
 }
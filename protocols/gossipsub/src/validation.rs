@@ -0,0 +1,109 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::types::{MessageAcceptance, MessageId};
+use futures::channel::mpsc;
+use libp2p_identity::PeerId;
+
+/// A pending validation of a received message.
+///
+/// Obtained via [`Behaviour::validation_handle`](crate::Behaviour::validation_handle) when
+/// [`Config::validate_messages`](crate::Config::validate_messages) is enabled. The handle can be
+/// moved to another task, e.g. one performing an asynchronous signature check, and resolved
+/// from there with [`ValidationHandle::accept`], [`ValidationHandle::reject`] or
+/// [`ValidationHandle::ignore`]. The result is applied the next time the
+/// [`Behaviour`](crate::Behaviour) is polled, exactly as if it had been passed to
+/// [`Behaviour::report_message_validation_result`](crate::Behaviour::report_message_validation_result).
+///
+/// Until then the message is neither forwarded nor served to peers via gossip. Messages that
+/// are still pending when they are evicted from the message cache, i.e. after
+/// [`Config::history_length`](crate::Config::history_length) heartbeats, are dropped and late
+/// results for them have no effect. Dropping the handle without resolving it leaves the
+/// message pending until it is evicted.
+#[derive(Debug)]
+#[must_use = "The message is withheld until the handle is resolved."]
+pub struct ValidationHandle {
+    message_id: MessageId,
+    propagation_source: PeerId,
+    sender: mpsc::Sender<ValidationResult>,
+}
+
+/// The outcome of a validation reported through a [`ValidationHandle`].
+#[derive(Debug)]
+pub(crate) struct ValidationResult {
+    pub(crate) message_id: MessageId,
+    pub(crate) propagation_source: PeerId,
+    pub(crate) acceptance: MessageAcceptance,
+}
+
+impl ValidationHandle {
+    pub(crate) fn new(
+        message_id: MessageId,
+        propagation_source: PeerId,
+        sender: mpsc::Sender<ValidationResult>,
+    ) -> Self {
+        Self {
+            message_id,
+            propagation_source,
+            sender,
+        }
+    }
+
+    /// The ID of the message being validated.
+    pub fn message_id(&self) -> &MessageId {
+        &self.message_id
+    }
+
+    /// The peer the message was received from.
+    pub fn propagation_source(&self) -> &PeerId {
+        &self.propagation_source
+    }
+
+    /// Accepts the message, delivering it to the mesh.
+    ///
+    /// See [`MessageAcceptance::Accept`].
+    pub fn accept(self) {
+        self.report(MessageAcceptance::Accept)
+    }
+
+    /// Rejects the message, penalizing the peers that sent it.
+    ///
+    /// See [`MessageAcceptance::Reject`].
+    pub fn reject(self) {
+        self.report(MessageAcceptance::Reject)
+    }
+
+    /// Ignores the message without penalizing the peers that sent it.
+    ///
+    /// See [`MessageAcceptance::Ignore`].
+    pub fn ignore(self) {
+        self.report(MessageAcceptance::Ignore)
+    }
+
+    fn report(mut self, acceptance: MessageAcceptance) {
+        // Every handle owns a clone of the sender, each of which is guaranteed a slot in the
+        // channel. Sending can thus only fail if the behaviour has been dropped.
+        let _ = self.sender.try_send(ValidationResult {
+            message_id: self.message_id,
+            propagation_source: self.propagation_source,
+            acceptance,
+        });
+    }
+}
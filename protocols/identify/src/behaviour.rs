@@ -32,8 +32,10 @@ use libp2p_swarm::{ConnectionId, THandler, THandlerOutEvent};
 use lru::LruCache;
 use std::collections::hash_map::Entry;
 use std::num::NonZeroUsize;
+use std::sync::Arc;
 use std::{
     collections::{HashMap, HashSet, VecDeque},
+    fmt,
     iter::FromIterator,
     task::Context,
     task::Poll,
@@ -44,7 +46,7 @@ use std::{
 /// about them, and answers identify queries from other nodes.
 ///
 /// All external addresses of the local node supposedly observed by remotes
-/// are reported via [`ToSwarm::NewExternalAddrCandidate`].
+/// are reported via [`ToSwarm::ObservedExternalAddr`].
 pub struct Behaviour {
     config: Config,
     /// For each peer we're connected to, the observed address to send back to it.
@@ -98,6 +100,19 @@ pub struct Config {
     ///
     /// Disabled by default.
     pub cache_size: usize,
+
+    /// Filter applied to the local addresses before they are sent to peers.
+    address_filter: Option<AddressFilter>,
+}
+
+/// Predicate deciding which local addresses are advertised to peers.
+#[derive(Clone)]
+struct AddressFilter(Arc<dyn Fn(&Multiaddr) -> bool + Send + Sync>);
+
+impl fmt::Debug for AddressFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AddressFilter").finish_non_exhaustive()
+    }
 }
 
 impl Config {
@@ -111,6 +126,7 @@ impl Config {
             interval: Duration::from_secs(5 * 60),
             push_listen_addr_updates: false,
             cache_size: 100,
+            address_filter: None,
         }
     }
 
@@ -140,6 +156,19 @@ impl Config {
         self.cache_size = cache_size;
         self
     }
+
+    /// Configures a filter for the listen and external addresses of the local node that are sent
+    /// to peers, e.g. to not leak addresses of private networks.
+    ///
+    /// Only addresses for which the filter returns `true` are advertised. The address observed
+    /// for the remote peer is sent regardless of the filter.
+    pub fn with_address_filter(
+        mut self,
+        filter: impl Fn(&Multiaddr) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.address_filter = Some(AddressFilter(Arc::new(filter)));
+        self
+    }
 }
 
 impl Behaviour {
@@ -162,6 +191,10 @@ impl Behaviour {
     }
 
     /// Initiates an active push of the local peer information to the given peers.
+    ///
+    /// The push is sent immediately on one of the established connections to each peer.
+    /// Peers we are not connected to are skipped, i.e. the push is neither sent nor queued
+    /// until a connection is established.
     pub fn push<I>(&mut self, peers: I)
     where
         I: IntoIterator<Item = PeerId>,
@@ -211,6 +244,10 @@ impl Behaviour {
         self.listen_addresses
             .iter()
             .chain(self.external_addresses.iter())
+            .filter(|addr| match &self.config.address_filter {
+                Some(AddressFilter(filter)) => filter(addr),
+                None => true,
+            })
             .cloned()
             .collect()
     }
@@ -279,8 +316,10 @@ impl NetworkBehaviour for Behaviour {
                 match self.our_observed_addresses.entry(id) {
                     Entry::Vacant(not_yet_observed) => {
                         not_yet_observed.insert(observed.clone());
-                        self.events
-                            .push_back(ToSwarm::NewExternalAddrCandidate(observed));
+                        self.events.push_back(ToSwarm::ObservedExternalAddr {
+                            address: observed,
+                            observer: peer_id,
+                        });
                     }
                     Entry::Occupied(already_observed) if already_observed.get() == &observed => {
                         // No-op, we already observed this address.
@@ -293,8 +332,10 @@ impl NetworkBehaviour for Behaviour {
                         );
 
                         *already_observed.get_mut() = observed.clone();
-                        self.events
-                            .push_back(ToSwarm::NewExternalAddrCandidate(observed));
+                        self.events.push_back(ToSwarm::ObservedExternalAddr {
+                            address: observed,
+                            observer: peer_id,
+                        });
                     }
                 }
             }
@@ -509,4 +550,16 @@ mod tests {
         ));
         assert!(multiaddr_matches_peer_id(&addr_without_peer_id, &peer_id));
     }
+
+    #[test]
+    fn push_skips_disconnected_peers() {
+        let mut behaviour = Behaviour::new(Config::new(
+            "a".to_string(),
+            libp2p_identity::Keypair::generate_ed25519().public(),
+        ));
+
+        behaviour.push([PeerId::random(), PeerId::random()]);
+
+        assert!(behaviour.events.is_empty());
+    }
 }
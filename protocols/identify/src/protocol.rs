@@ -296,4 +296,24 @@ mod tests {
 
         assert_eq!(info.listen_addrs, vec![valid_multiaddr])
     }
+
+    #[test]
+    fn skip_invalid_protocol() {
+        let payload = proto::Identify {
+            agentVersion: None,
+            listenAddrs: vec![],
+            observedAddr: None,
+            protocolVersion: None,
+            protocols: vec!["/ipfs/kad/1.0.0".to_owned(), "ipfs/ping/1.0.0".to_owned()],
+            publicKey: Some(
+                identity::Keypair::generate_ed25519()
+                    .public()
+                    .encode_protobuf(),
+            ),
+        };
+
+        let info = Info::try_from(payload).expect("not to fail");
+
+        assert_eq!(info.protocols, vec![StreamProtocol::new("/ipfs/kad/1.0.0")])
+    }
 }
@@ -0,0 +1,186 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::FutureExt;
+use futures_timer::Delay;
+use libp2p_core::{upgrade::ReadyUpgrade, Endpoint, Multiaddr};
+use libp2p_identify as identify;
+use libp2p_identity::PeerId;
+use libp2p_swarm::{
+    handler::ConnectionEvent, ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent,
+    ConnectionId, FromSwarm, NetworkBehaviour, StreamProtocol, SubstreamProtocol, Swarm,
+    SwarmEvent, THandler, THandlerInEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use void::Void;
+
+const PROTOCOL: StreamProtocol = StreamProtocol::new("/defer/1.0.0");
+
+#[async_std::test]
+async fn deferring_inbound_streams_does_not_push_protocol_changes() {
+    let mut swarm1 = Swarm::new_ephemeral(|identity| Behaviour {
+        identify: identify::Behaviour::new(identify::Config::new(
+            "a".to_string(),
+            identity.public(),
+        )),
+        defer: Defer,
+    });
+    let mut swarm2 = Swarm::new_ephemeral(|identity| {
+        identify::Behaviour::new(identify::Config::new("b".to_string(), identity.public()))
+    });
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    let info = loop {
+        futures::select! {
+            event = swarm2.next_swarm_event().fuse() => {
+                if let SwarmEvent::Behaviour(identify::Event::Received { info, .. }) = event {
+                    break info;
+                }
+            }
+            _ = swarm1.next_swarm_event().fuse() => {}
+        }
+    };
+    assert!(info.protocols.contains(&PROTOCOL));
+
+    // The handler of `swarm1` flips between deferring and accepting inbound streams meanwhile.
+    let no_push = async {
+        loop {
+            futures::select! {
+                event = swarm1.next_swarm_event().fuse() => {
+                    if let SwarmEvent::Behaviour(BehaviourEvent::Identify(
+                        identify::Event::Pushed { .. },
+                    )) = event
+                    {
+                        panic!("Unexpected push of the local protocols")
+                    }
+                }
+                event = swarm2.next_swarm_event().fuse() => {
+                    if let SwarmEvent::Behaviour(identify::Event::Received { .. }) = event {
+                        panic!("Unexpected identify info received")
+                    }
+                }
+            }
+        }
+    };
+    let _ = async_std::future::timeout(Duration::from_millis(500), no_push).await;
+}
+
+#[derive(libp2p_swarm::NetworkBehaviour)]
+#[behaviour(prelude = "libp2p_swarm::derive_prelude")]
+struct Behaviour {
+    identify: identify::Behaviour,
+    defer: Defer,
+}
+
+/// Supports [`PROTOCOL`] with a handler that keeps flipping between deferring and accepting
+/// inbound streams.
+struct Defer;
+
+impl NetworkBehaviour for Defer {
+    type ConnectionHandler = DeferHandler;
+    type ToSwarm = Void;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(DeferHandler::default())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(DeferHandler::default())
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(&mut self, _: PeerId, _: ConnectionId, event: Void) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Void, THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
+
+struct DeferHandler {
+    accepts_inbound_streams: bool,
+    flip: Delay,
+}
+
+impl Default for DeferHandler {
+    fn default() -> Self {
+        Self {
+            accepts_inbound_streams: true,
+            flip: Delay::new(Duration::from_millis(20)),
+        }
+    }
+}
+
+impl ConnectionHandler for DeferHandler {
+    type FromBehaviour = Void;
+    type ToBehaviour = Void;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = Void;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, ()> {
+        SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL), ())
+    }
+
+    fn accepts_inbound_streams(&self) -> bool {
+        self.accepts_inbound_streams
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, Void, Void>> {
+        while self.flip.poll_unpin(cx).is_ready() {
+            self.accepts_inbound_streams = !self.accepts_inbound_streams;
+            self.flip.reset(Duration::from_millis(20));
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, event: Void) {
+        void::unreachable(event)
+    }
+
+    fn on_connection_event(
+        &mut self,
+        _: ConnectionEvent<Self::InboundProtocol, Self::OutboundProtocol, (), Void>,
+    ) {
+    }
+}
@@ -84,6 +84,48 @@ async fn periodic_identify() {
         other => panic!("Unexpected events: {other:?}"),
     }
 }
+
+#[async_std::test]
+async fn address_filter_omits_addresses_from_sent_info() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    let is_memory =
+        |addr: &libp2p_core::Multiaddr| addr.iter().any(|p| matches!(p, Protocol::Memory(_)));
+
+    let mut swarm1 = Swarm::new_ephemeral(|identity| {
+        identify::Behaviour::new(
+            identify::Config::new("a".to_string(), identity.public())
+                .with_address_filter(move |addr| !is_memory(addr)),
+        )
+    });
+    let mut swarm2 = Swarm::new_ephemeral(|identity| {
+        identify::Behaviour::new(identify::Config::new("a".to_string(), identity.public()))
+    });
+
+    let (swarm1_memory_listen, swarm1_tcp_listen_addr) =
+        swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    let swarm2_received_info = swarm2
+        .wait(|event| match event {
+            SwarmEvent::Behaviour(identify::Event::Received { info, .. }) => Some(info),
+            _ => None,
+        })
+        .await;
+
+    assert!(swarm2_received_info
+        .listen_addrs
+        .contains(&swarm1_tcp_listen_addr));
+    assert!(!swarm2_received_info
+        .listen_addrs
+        .contains(&swarm1_memory_listen));
+    assert!(!swarm2_received_info.listen_addrs.iter().any(is_memory));
+    // The observed address is not subject to the filter.
+    assert!(is_memory(&swarm2_received_info.observed_addr));
+}
+
 #[async_std::test]
 async fn only_emits_address_candidate_once_per_connection() {
     let _ = tracing_subscriber::fmt()
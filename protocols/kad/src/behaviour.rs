@@ -23,6 +23,7 @@
 mod test;
 
 use crate::addresses::Addresses;
+use crate::bootstrap;
 use crate::handler::{Handler, HandlerEvent, HandlerIn, RequestId};
 use crate::jobs::*;
 use crate::kbucket::{self, Distance, KBucketsTable, NodeStatus};
@@ -114,6 +115,12 @@ pub struct Behaviour<TStore> {
     auto_mode: bool,
     no_events_waker: Option<Waker>,
 
+    /// Decides when to bootstrap automatically, if enabled.
+    bootstrap_status: Option<bootstrap::Status>,
+
+    /// The progress of the running bootstraps.
+    bootstraps: HashMap<QueryId, bootstrap::Progress>,
+
     /// The record storage.
     store: TStore,
 }
@@ -181,6 +188,9 @@ pub struct Config {
     provider_publication_interval: Option<Duration>,
     kbucket_inserts: BucketInserts,
     caching: Caching,
+    periodic_bootstrap_interval: Option<Duration>,
+    bootstrap_min_peers: usize,
+    bootstrap_idle_timeout: Option<Duration>,
 }
 
 impl Default for Config {
@@ -197,6 +207,9 @@ impl Default for Config {
             provider_record_ttl: Some(Duration::from_secs(24 * 60 * 60)),
             kbucket_inserts: BucketInserts::OnConnected,
             caching: Caching::Enabled { max_peers: 1 },
+            periodic_bootstrap_interval: None,
+            bootstrap_min_peers: K_VALUE.get(),
+            bootstrap_idle_timeout: None,
         }
     }
 }
@@ -272,7 +285,15 @@ impl Config {
     /// in the presence of potentially adversarial nodes.
     ///
     /// When enabled the number of disjoint paths used equals the configured
-    /// parallelism.
+    /// parallelism, see [`Config::set_parallelism`]. Each peer is queried by
+    /// at most one path, and the query only finishes once all paths finished,
+    /// returning the closest peers found by any of them. As long as one path
+    /// avoids the adversarial nodes, the real closest peers are found.
+    ///
+    /// The increased robustness comes at the cost of latency and traffic:
+    /// the query waits for the slowest path, and the paths cannot share the
+    /// peers they discovered to shortcut one another, so more peers are
+    /// contacted overall.
     ///
     /// See the S/Kademlia paper for more information on the high level design
     /// as well as its security improvements.
@@ -391,6 +412,45 @@ impl Config {
         self.caching = c;
         self
     }
+
+    /// Sets the interval at which [`Behaviour::bootstrap`] is called automatically.
+    ///
+    /// The first automatic bootstrap happens one interval after the creation of the
+    /// [`Behaviour`]. Every bootstrap, including ones started manually, postpones the next
+    /// periodic bootstrap by a full interval. With periodic bootstrapping enabled, the node
+    /// additionally bootstraps when the routing table shrinks below
+    /// [`Config::set_bootstrap_min_peers`] peers and, if configured, when no query succeeded
+    /// for [`Config::set_bootstrap_idle_timeout`]. The outcome of each bootstrap is reported
+    /// as [`Event::BootstrapFinished`].
+    ///
+    /// `None` disables automatic bootstrapping. The default is `None`.
+    pub fn set_periodic_bootstrap_interval(&mut self, interval: Option<Duration>) -> &mut Self {
+        self.periodic_bootstrap_interval = interval;
+        self
+    }
+
+    /// Sets the number of peers below which the removal or eviction of a peer from the routing
+    /// table triggers an automatic bootstrap.
+    ///
+    /// Only applies if periodic bootstrapping is enabled via
+    /// [`Config::set_periodic_bootstrap_interval`]. The default is [`K_VALUE`].
+    pub fn set_bootstrap_min_peers(&mut self, min_peers: usize) -> &mut Self {
+        self.bootstrap_min_peers = min_peers;
+        self
+    }
+
+    /// Sets the duration without any successfully finished query after which the node
+    /// bootstraps automatically.
+    ///
+    /// A query counts as successful if it produced an `Ok` result and reached at least one
+    /// peer. Periodic bootstraps don't postpone the idle bootstrap, unless they succeed.
+    ///
+    /// Only applies if periodic bootstrapping is enabled via
+    /// [`Config::set_periodic_bootstrap_interval`]. The default is `None`.
+    pub fn set_bootstrap_idle_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.bootstrap_idle_timeout = timeout;
+        self
+    }
 }
 
 impl<TStore> Behaviour<TStore>
@@ -448,6 +508,14 @@ where
             mode: Mode::Client,
             auto_mode: true,
             no_events_waker: None,
+            bootstrap_status: config.periodic_bootstrap_interval.map(|interval| {
+                bootstrap::Status::new(
+                    interval,
+                    config.bootstrap_min_peers,
+                    config.bootstrap_idle_timeout,
+                )
+            }),
+            bootstraps: Default::default(),
         }
     }
 
@@ -602,6 +670,9 @@ where
         match self.kbuckets.entry(&key) {
             kbucket::Entry::Present(mut entry, _) => {
                 if entry.value().remove(address).is_err() {
+                    if let Some(status) = self.bootstrap_status.as_mut() {
+                        status.on_routing_table_shrank();
+                    }
                     Some(entry.remove()) // it is the last address, thus remove the peer.
                 } else {
                     None
@@ -628,7 +699,12 @@ where
     ) -> Option<kbucket::EntryView<kbucket::Key<PeerId>, Addresses>> {
         let key = kbucket::Key::from(*peer);
         match self.kbuckets.entry(&key) {
-            kbucket::Entry::Present(entry, _) => Some(entry.remove()),
+            kbucket::Entry::Present(entry, _) => {
+                if let Some(status) = self.bootstrap_status.as_mut() {
+                    status.on_routing_table_shrank();
+                }
+                Some(entry.remove())
+            }
             kbucket::Entry::Pending(entry, _) => Some(entry.remove()),
             kbucket::Entry::Absent(..) | kbucket::Entry::SelfEntry => None,
         }
@@ -673,6 +749,43 @@ where
         self.queries.add_iter_closest(target, peer_keys, inner)
     }
 
+    /// Initiates an iterative query for the closest peers to the given key that finishes as soon
+    /// as [`GetClosestPeersConfig::num_results`] peers responded, instead of running until the
+    /// closest peers are known.
+    ///
+    /// Use this over [`Behaviour::get_closest_peers`] for latency-sensitive lookups where any
+    /// close and responsive peers suffice. The returned peers are thus not necessarily the
+    /// closest ones in the DHT.
+    ///
+    /// The result of the query is delivered in a
+    /// [`Event::OutboundQueryProgressed{QueryResult::GetClosestPeers}`]. If the query times out
+    /// after [`GetClosestPeersConfig::timeout`], the peers found so far are reported in
+    /// [`GetClosestPeersError::Timeout`].
+    pub fn get_closest_peers_with_config<K>(
+        &mut self,
+        key: K,
+        config: GetClosestPeersConfig,
+    ) -> QueryId
+    where
+        K: Into<kbucket::Key<K>> + Into<Vec<u8>> + Clone,
+    {
+        let target: kbucket::Key<K> = key.clone().into();
+        let key: Vec<u8> = key.into();
+        let info = QueryInfo::GetClosestPeers {
+            key,
+            step: ProgressStep::first(),
+        };
+        let peer_keys: Vec<kbucket::Key<PeerId>> = self.kbuckets.closest_keys(&target).collect();
+        let inner = QueryInner::new(info);
+        self.queries.add_iter_closest_bounded(
+            target,
+            peer_keys,
+            inner,
+            config.num_results,
+            config.timeout,
+        )
+    }
+
     /// Returns closest peers to the given key; takes peers from local routing table only.
     pub fn get_closest_local_peers<'a, K: Clone>(
         &'a mut self,
@@ -686,6 +799,19 @@ where
     /// The result of this operation is delivered in a
     /// [`Event::OutboundQueryProgressed{QueryResult::GetRecord}`].
     pub fn get_record(&mut self, key: record::Key) -> QueryId {
+        self.get_record_with_config(key, GetRecordConfig::default())
+    }
+
+    /// Performs a lookup for a record in the DHT, overriding the configured replication factor
+    /// and finishing as soon as [`GetRecordConfig::quorum`] records were found.
+    ///
+    /// A record stored locally counts towards the quorum. If the query finishes having found
+    /// some, but fewer records than required, the records are reported in
+    /// [`GetRecordError::QuorumFailed`].
+    ///
+    /// The result of this operation is delivered in a
+    /// [`Event::OutboundQueryProgressed{QueryResult::GetRecord}`].
+    pub fn get_record_with_config(&mut self, key: record::Key, config: GetRecordConfig) -> QueryId {
         let record = if let Some(record) = self.store.get(&key) {
             if record.is_expired(Instant::now()) {
                 self.store.remove(&key);
@@ -701,31 +827,47 @@ where
         };
 
         let step = ProgressStep::first();
+        let replication_factor = config
+            .replication_factor
+            .unwrap_or(self.queries.config().replication_factor);
+        let quorum = config.quorum.map(|quorum| quorum.eval(replication_factor));
 
         let target = kbucket::Key::new(key.clone());
-        let info = if record.is_some() {
-            QueryInfo::GetRecord {
-                key,
-                step: step.next(),
-                found_a_record: true,
-                cache_candidates: BTreeMap::new(),
-            }
-        } else {
-            QueryInfo::GetRecord {
-                key,
-                step: step.clone(),
-                found_a_record: false,
-                cache_candidates: BTreeMap::new(),
-            }
+        let info = QueryInfo::GetRecord {
+            key,
+            step: if record.is_some() {
+                step.next()
+            } else {
+                step.clone()
+            },
+            found_a_record: record.is_some(),
+            cache_candidates: BTreeMap::new(),
+            quorum,
+            records: match (quorum, &record) {
+                (Some(_), Some(record)) => vec![record.clone()],
+                _ => Vec::new(),
+            },
         };
         let peers = self.kbuckets.closest_keys(&target);
         let inner = QueryInner::new(info);
-        let id = self.queries.add_iter_closest(target.clone(), peers, inner);
+        let id = self.queries.add_iter_closest_with_num_results(
+            target.clone(),
+            peers,
+            inner,
+            replication_factor,
+        );
 
         // No queries were actually done for the results yet.
         let stats = QueryStats::empty();
 
         if let Some(record) = record {
+            if quorum.map_or(false, |quorum| quorum.get() == 1) {
+                self.queries
+                    .get_mut(&id)
+                    .expect("query was just added")
+                    .finish();
+            }
+
             self.queued_events
                 .push_back(ToSwarm::GenerateEvent(Event::OutboundQueryProgressed {
                     id,
@@ -756,17 +898,37 @@ where
     /// does not update the record's expiration in local storage, thus a given record
     /// with an explicit expiration will always expire at that instant and until then
     /// is subject to regular (re-)replication and (re-)publication.
-    pub fn put_record(
+    pub fn put_record(&mut self, record: Record, quorum: Quorum) -> Result<QueryId, store::Error> {
+        self.put_record_with_config(
+            record,
+            PutRecordConfig {
+                quorum,
+                replication_factor: None,
+            },
+        )
+    }
+
+    /// Stores a record in the DHT like [`Behaviour::put_record`], overriding the configured
+    /// replication factor.
+    ///
+    /// The record is replicated to the [`PutRecordConfig::replication_factor`] closest peers to
+    /// its key and the [`PutRecordConfig::quorum`] is understood in the context of that number.
+    /// The peers the record was stored on are reported in [`PutRecordOk::success`], respectively
+    /// [`PutRecordError::QuorumFailed`].
+    pub fn put_record_with_config(
         &mut self,
         mut record: Record,
-        quorum: Quorum,
+        config: PutRecordConfig,
     ) -> Result<QueryId, store::Error> {
         record.publisher = Some(*self.kbuckets.local_key().preimage());
         self.store.put(record.clone())?;
         record.expires = record
             .expires
             .or_else(|| self.record_ttl.map(|ttl| Instant::now() + ttl));
-        let quorum = quorum.eval(self.queries.config().replication_factor);
+        let replication_factor = config
+            .replication_factor
+            .unwrap_or(self.queries.config().replication_factor);
+        let quorum = config.quorum.eval(replication_factor);
         let target = kbucket::Key::new(record.key.clone());
         let peers = self.kbuckets.closest_keys(&target);
         let context = PutRecordContext::Publish;
@@ -777,7 +939,12 @@ where
             phase: PutRecordPhase::GetClosestPeers,
         };
         let inner = QueryInner::new(info);
-        Ok(self.queries.add_iter_closest(target.clone(), peers, inner))
+        Ok(self.queries.add_iter_closest_with_num_results(
+            target.clone(),
+            peers,
+            inner,
+            replication_factor,
+        ))
     }
 
     /// Stores a record at specific peers, without storing it locally.
@@ -868,6 +1035,13 @@ where
     /// > **Note**: Bootstrapping requires at least one node of the DHT to be known.
     /// > See [`Behaviour::add_address`].
     pub fn bootstrap(&mut self) -> Result<QueryId, NoKnownPeers> {
+        self.bootstrap_with_trigger(BootstrapTrigger::Manual)
+    }
+
+    fn bootstrap_with_trigger(
+        &mut self,
+        trigger: BootstrapTrigger,
+    ) -> Result<QueryId, NoKnownPeers> {
         let local_key = self.kbuckets.local_key().clone();
         let info = QueryInfo::Bootstrap {
             peer: *local_key.preimage(),
@@ -878,9 +1052,62 @@ where
         if peers.is_empty() {
             Err(NoKnownPeers())
         } else {
+            if let Some(status) = self.bootstrap_status.as_mut() {
+                status.on_bootstrap_started();
+            }
+            let known_peers = self.routing_table_peers().collect();
+
             let inner = QueryInner::new(info);
-            Ok(self.queries.add_iter_closest(local_key, peers, inner))
+            let id = self.queries.add_iter_closest(local_key, peers, inner);
+            self.bootstraps
+                .insert(id, bootstrap::Progress::new(trigger, known_peers));
+            Ok(id)
+        }
+    }
+
+    /// Returns the peers currently in the routing table.
+    fn routing_table_peers(&mut self) -> impl Iterator<Item = PeerId> {
+        let mut peers = Vec::new();
+        for bucket in self.kbuckets.iter() {
+            peers.extend(bucket.iter().map(|entry| *entry.node.key.preimage()));
         }
+        peers.into_iter()
+    }
+
+    /// Records the outcome of a step of a bootstrap, reporting [`Event::BootstrapFinished`]
+    /// after the last one.
+    fn on_bootstrap_step(
+        &mut self,
+        id: QueryId,
+        contacted: impl Iterator<Item = PeerId>,
+        timed_out: bool,
+        last: bool,
+    ) {
+        let Some(progress) = self.bootstraps.get_mut(&id) else {
+            return;
+        };
+        progress.on_step(contacted);
+        if timed_out {
+            progress.num_timeouts += 1;
+        }
+        if !last {
+            return;
+        }
+
+        let progress = self
+            .bootstraps
+            .remove(&id)
+            .expect("progress of the bootstrap to be tracked");
+        let (peers_added, peers_refreshed) = progress.outcome(self.routing_table_peers());
+
+        self.queued_events
+            .push_back(ToSwarm::GenerateEvent(Event::BootstrapFinished {
+                id,
+                trigger: progress.trigger,
+                peers_added,
+                peers_refreshed,
+                num_timeouts: progress.num_timeouts,
+            }));
     }
 
     /// Establishes the local node as a provider of a value for the given key.
@@ -1335,6 +1562,12 @@ where
                 remaining,
                 mut step,
             } => {
+                // A cancelled bootstrap does not continue with the remaining bucket refreshes.
+                let remaining = if result.inner.cancelled {
+                    Some(Vec::new().into_iter())
+                } else {
+                    remaining
+                };
                 let local_key = self.kbuckets.local_key().clone();
                 let mut remaining = remaining.unwrap_or_else(|| {
                     debug_assert_eq!(&peer, local_key.preimage());
@@ -1390,6 +1623,8 @@ where
                     step.last = true;
                 };
 
+                self.on_bootstrap_step(query_id, result.peers, false, step.last);
+
                 Some(Event::OutboundQueryProgressed {
                     id: query_id,
                     stats: result.stats,
@@ -1415,17 +1650,20 @@ where
                 })
             }
 
-            QueryInfo::GetProviders { mut step, .. } => {
+            QueryInfo::GetProviders { key, mut step, .. } => {
                 step.last = true;
 
+                let closest_peers = result.peers.collect();
+                let ok = if result.inner.cancelled {
+                    GetProvidersOk::Cancelled { key, closest_peers }
+                } else {
+                    GetProvidersOk::FinishedWithNoAdditionalRecord { closest_peers }
+                };
+
                 Some(Event::OutboundQueryProgressed {
                     id: query_id,
                     stats: result.stats,
-                    result: QueryResult::GetProviders(Ok(
-                        GetProvidersOk::FinishedWithNoAdditionalRecord {
-                            closest_peers: result.peers.collect(),
-                        },
-                    )),
+                    result: QueryResult::GetProviders(Ok(ok)),
                     step,
                 })
             }
@@ -1478,16 +1716,26 @@ where
                 mut step,
                 found_a_record,
                 cache_candidates,
+                quorum,
+                records,
             } => {
                 step.last = true;
 
-                let results = if found_a_record {
-                    Ok(GetRecordOk::FinishedWithNoAdditionalRecord { cache_candidates })
-                } else {
-                    Err(GetRecordError::NotFound {
+                let results = match quorum {
+                    Some(quorum) if found_a_record && records.len() < quorum.get() => {
+                        Err(GetRecordError::QuorumFailed {
+                            key,
+                            records,
+                            quorum,
+                        })
+                    }
+                    _ if found_a_record => {
+                        Ok(GetRecordOk::FinishedWithNoAdditionalRecord { cache_candidates })
+                    }
+                    _ => Err(GetRecordError::NotFound {
                         key,
                         closest_peers: result.peers.collect(),
-                    })
+                    }),
                 };
                 Some(Event::OutboundQueryProgressed {
                     id: query_id,
@@ -1529,7 +1777,7 @@ where
             } => {
                 let mk_result = |key: record::Key| {
                     if success.len() >= quorum.get() {
-                        Ok(PutRecordOk { key })
+                        Ok(PutRecordOk { key, success })
                     } else {
                         Err(PutRecordError::QuorumFailed {
                             key,
@@ -1592,6 +1840,8 @@ where
                     step.last = true;
                 }
 
+                self.on_bootstrap_step(query_id, result.peers, true, step.last);
+
                 Some(Event::OutboundQueryProgressed {
                     id: query_id,
                     stats: result.stats,
@@ -1989,10 +2239,12 @@ where
             DialError::LocalPeerId { .. }
             | DialError::WrongPeerId { .. }
             | DialError::Aborted
+            | DialError::Cancelled
             | DialError::Denied { .. }
             | DialError::Transport(_)
+            | DialError::Dns(_)
             | DialError::NoAddresses => {
-                if let DialError::Transport(addresses) = error {
+                if let DialError::Transport(addresses) | DialError::Dns(addresses) = error {
                     for (addr, _) in addresses {
                         self.address_failed(peer_id, addr)
                     }
@@ -2236,7 +2488,11 @@ where
             } => {
                 let peers = closer_peers.iter().chain(provider_peers.iter());
                 self.discovered(&query_id, &source, peers);
-                if let Some(query) = self.queries.get_mut(&query_id) {
+                if let Some(query) = self
+                    .queries
+                    .get_mut(&query_id)
+                    .filter(|query| !query.inner.cancelled)
+                {
                     let stats = query.stats().clone();
                     if let QueryInfo::GetProviders {
                         ref key,
@@ -2330,11 +2586,14 @@ where
             } => {
                 if let Some(query) = self.queries.get_mut(&query_id) {
                     let stats = query.stats().clone();
+                    let mut quorum_reached = false;
                     if let QueryInfo::GetRecord {
                         key,
                         ref mut step,
                         ref mut found_a_record,
                         cache_candidates,
+                        quorum,
+                        records,
                     } = &mut query.inner.info
                     {
                         if let Some(record) = record {
@@ -2344,6 +2603,11 @@ where
                                 record,
                             };
 
+                            if let Some(quorum) = quorum {
+                                records.push(record.clone());
+                                quorum_reached = records.len() >= quorum.get();
+                            }
+
                             self.queued_events.push_back(ToSwarm::GenerateEvent(
                                 Event::OutboundQueryProgressed {
                                     id: query_id,
@@ -2373,6 +2637,10 @@ where
                             }
                         }
                     }
+
+                    if quorum_reached {
+                        query.finish();
+                    }
                 }
 
                 self.discovered(&query_id, &source, closer_peers.iter());
@@ -2421,6 +2689,23 @@ where
     ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
         let now = Instant::now();
 
+        // Bootstrap automatically, unless already bootstrapping.
+        if let Some(status) = self.bootstrap_status.as_mut() {
+            let kbuckets = &mut self.kbuckets;
+            if let Poll::Ready(trigger) =
+                status.poll_trigger(cx, || kbuckets.iter().map(|b| b.num_entries()).sum())
+            {
+                if self.bootstraps.is_empty() {
+                    if let Err(NoKnownPeers()) = self.bootstrap_with_trigger(trigger) {
+                        tracing::debug!(
+                            ?trigger,
+                            "Skipping automatic bootstrap without known peers"
+                        );
+                    }
+                }
+            }
+        }
+
         // Calculate the available capacity for queries triggered by background jobs.
         let mut jobs_query_capacity = JOBS_MAX_QUERIES.saturating_sub(self.queries.size());
 
@@ -2465,6 +2750,9 @@ where
 
             // Drain applied pending entries from the routing table.
             if let Some(entry) = self.kbuckets.take_applied_pending() {
+                if let (Some(status), Some(_)) = (self.bootstrap_status.as_mut(), &entry.evicted) {
+                    status.on_routing_table_shrank();
+                }
                 let kbucket::Node { key, value } = entry.inserted;
                 let event = Event::RoutingUpdated {
                     bucket_range: self
@@ -2485,6 +2773,17 @@ where
                 match self.queries.poll(now) {
                     QueryPoolState::Finished(q) => {
                         if let Some(event) = self.query_finished(q) {
+                            if let (
+                                Some(status),
+                                Event::OutboundQueryProgressed { result, stats, .. },
+                            ) = (self.bootstrap_status.as_mut(), &event)
+                            {
+                                // Finished queries may still have failed, e.g. by not finding
+                                // a record or reaching no peer at all.
+                                if result.is_ok() && stats.num_successes() > 0 {
+                                    status.on_query_succeeded();
+                                }
+                            }
                             return Poll::Ready(ToSwarm::GenerateEvent(event));
                         }
                     }
@@ -2676,6 +2975,26 @@ pub enum Event {
     /// This happens in response to an external
     /// address being added or removed.
     ModeChanged { new_mode: Mode },
+
+    /// A bootstrap finished.
+    ///
+    /// Reported after the last [`Event::OutboundQueryProgressed`] of a bootstrap
+    /// started via [`Behaviour::bootstrap`] or automatically, see
+    /// [`Config::set_periodic_bootstrap_interval`].
+    BootstrapFinished {
+        /// The ID of the bootstrap query.
+        id: QueryId,
+        /// Why the bootstrap was started.
+        trigger: BootstrapTrigger,
+        /// The number of peers added to the routing table while bootstrapping.
+        peers_added: usize,
+        /// The number of peers that were already in the routing table and
+        /// responded to the bootstrap.
+        peers_refreshed: usize,
+        /// The number of bootstrap steps, i.e. the self-lookup and the bucket refreshes,
+        /// that timed out.
+        num_timeouts: u32,
+    },
 }
 
 /// Information about progress events.
@@ -2770,6 +3089,19 @@ pub enum QueryResult {
     RepublishRecord(PutRecordResult),
 }
 
+impl QueryResult {
+    fn is_ok(&self) -> bool {
+        match self {
+            QueryResult::Bootstrap(r) => r.is_ok(),
+            QueryResult::GetClosestPeers(r) => r.is_ok(),
+            QueryResult::GetProviders(r) => r.is_ok(),
+            QueryResult::StartProviding(r) | QueryResult::RepublishProvider(r) => r.is_ok(),
+            QueryResult::GetRecord(r) => r.is_ok(),
+            QueryResult::PutRecord(r) | QueryResult::RepublishRecord(r) => r.is_ok(),
+        }
+    }
+}
+
 /// The result of [`Behaviour::get_record`].
 pub type GetRecordResult = Result<GetRecordOk, GetRecordError>;
 
@@ -2837,6 +3169,8 @@ pub type PutRecordResult = Result<PutRecordOk, PutRecordError>;
 #[derive(Debug, Clone)]
 pub struct PutRecordOk {
     pub key: record::Key,
+    /// [`PeerId`]s of the peers the record was successfully stored on.
+    pub success: Vec<PeerId>,
 }
 
 /// The error result of [`Behaviour::put_record`].
@@ -2887,6 +3221,19 @@ pub struct BootstrapOk {
     pub num_remaining: u32,
 }
 
+/// The reason a bootstrap was started, see [`Event::BootstrapFinished`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootstrapTrigger {
+    /// The bootstrap was started via [`Behaviour::bootstrap`].
+    Manual,
+    /// The periodic bootstrap interval elapsed.
+    Periodic,
+    /// The routing table shrank below [`Config::set_bootstrap_min_peers`] peers.
+    RoutingTableShrank,
+    /// No query succeeded for [`Config::set_bootstrap_idle_timeout`].
+    Idle,
+}
+
 /// The error result of [`Behaviour::bootstrap`].
 #[derive(Debug, Clone, Error)]
 pub enum BootstrapError {
@@ -2897,6 +3244,39 @@ pub enum BootstrapError {
     },
 }
 
+/// The configuration of a query started with [`Behaviour::get_closest_peers_with_config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GetClosestPeersConfig {
+    /// The number of successfully contacted peers after which the query finishes.
+    pub num_results: NonZeroUsize,
+    /// The duration after which the query times out, reporting the peers found so far.
+    pub timeout: Duration,
+}
+
+/// The configuration of a query started with [`Behaviour::put_record_with_config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PutRecordConfig {
+    /// The number of peers the record must be stored on for the query to succeed.
+    pub quorum: Quorum,
+    /// The number of closest peers to store the record on.
+    ///
+    /// Defaults to [`Config::set_replication_factor`] if `None`.
+    pub replication_factor: Option<NonZeroUsize>,
+}
+
+/// The configuration of a query started with [`Behaviour::get_record_with_config`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GetRecordConfig {
+    /// The number of records after which the query finishes.
+    ///
+    /// If `None`, the query runs until the closest peers to the key were queried.
+    pub quorum: Option<Quorum>,
+    /// The number of closest peers to the key to query.
+    ///
+    /// Defaults to [`Config::set_replication_factor`] if `None`.
+    pub replication_factor: Option<NonZeroUsize>,
+}
+
 /// The result of [`Behaviour::get_closest_peers`].
 pub type GetClosestPeersResult = Result<GetClosestPeersOk, GetClosestPeersError>;
 
@@ -2945,6 +3325,11 @@ pub enum GetProvidersOk {
     FinishedWithNoAdditionalRecord {
         closest_peers: Vec<PeerId>,
     },
+    /// The query was cancelled via [`QueryMut::cancel`] before it finished.
+    Cancelled {
+        key: record::Key,
+        closest_peers: Vec<PeerId>,
+    },
 }
 
 /// The error result of [`Behaviour::get_providers`].
@@ -3032,6 +3417,8 @@ struct QueryInner {
     /// A request is pending if the targeted peer is not currently connected
     /// and these requests are sent as soon as a connection to the peer is established.
     pending_rpcs: SmallVec<[(PeerId, HandlerIn); K_VALUE.get()]>,
+    /// Whether the query was cancelled via [`QueryMut::cancel`].
+    cancelled: bool,
 }
 
 impl QueryInner {
@@ -3040,6 +3427,7 @@ impl QueryInner {
             info,
             addresses: Default::default(),
             pending_rpcs: SmallVec::default(),
+            cancelled: false,
         }
     }
 }
@@ -3137,6 +3525,10 @@ pub enum QueryInfo {
         /// The peers closest to the `key` that were queried but did not return a record,
         /// i.e. the peers that are candidates for caching the record.
         cache_candidates: BTreeMap<kbucket::Distance, PeerId>,
+        /// The number of records after which the query finishes, if any.
+        quorum: Option<NonZeroUsize>,
+        /// The records found so far, tracked only if a quorum is set.
+        records: Vec<PeerRecord>,
     },
 }
 
@@ -3254,6 +3646,21 @@ impl<'a> QueryMut<'a> {
     pub fn finish(&mut self) {
         self.query.finish()
     }
+
+    /// Cancels the query.
+    ///
+    /// Like [`QueryMut::finish`], no further requests are sent. Requests to peers that are
+    /// not yet connected are dropped and responses to requests in flight are ignored. The
+    /// final [`Event::OutboundQueryProgressed`] of a [`Behaviour::get_providers`] query reports
+    /// [`GetProvidersOk::Cancelled`] instead of
+    /// [`GetProvidersOk::FinishedWithNoAdditionalRecord`]. A [`Behaviour::bootstrap`] query
+    /// does not continue with its remaining bucket refreshes and reports this step as its last.
+    /// Other queries report their regular result.
+    pub fn cancel(&mut self) {
+        self.query.inner.cancelled = true;
+        self.query.inner.pending_rpcs.clear();
+        self.query.finish()
+    }
 }
 
 /// An immutable reference to a running query.
@@ -240,6 +240,70 @@ fn bootstrap() {
     QuickCheck::new().tests(10).quickcheck(prop as fn(_) -> _)
 }
 
+/// Polls the swarms until the first one reports [`Event::BootstrapFinished`].
+fn wait_bootstrap_finished(mut swarms: Vec<TestSwarm>) -> (BootstrapTrigger, usize, usize) {
+    block_on(poll_fn(move |ctx| {
+        for (i, swarm) in swarms.iter_mut().enumerate() {
+            loop {
+                match swarm.poll_next_unpin(ctx) {
+                    Poll::Ready(Some(SwarmEvent::Behaviour(Event::BootstrapFinished {
+                        trigger,
+                        peers_added,
+                        peers_refreshed,
+                        ..
+                    }))) if i == 0 => {
+                        return Poll::Ready((trigger, peers_added, peers_refreshed));
+                    }
+                    // Ignore any other event.
+                    Poll::Ready(Some(_)) => (),
+                    e @ Poll::Ready(_) => panic!("Unexpected return value: {e:?}"),
+                    Poll::Pending => break,
+                }
+            }
+        }
+        Poll::Pending
+    }))
+}
+
+#[test]
+fn periodic_bootstrap() {
+    let mut cfg = Config::default();
+    cfg.set_periodic_bootstrap_interval(Some(Duration::from_millis(100)));
+
+    // The first node only knows the second node, which knows the third node.
+    let swarms = build_connected_nodes_with_config(3, 1, cfg)
+        .into_iter()
+        .map(|(_a, s)| s)
+        .collect::<Vec<_>>();
+
+    let (trigger, peers_added, peers_refreshed) = wait_bootstrap_finished(swarms);
+
+    assert_eq!(trigger, BootstrapTrigger::Periodic);
+    assert_eq!(peers_added, 1);
+    assert_eq!(peers_refreshed, 1);
+}
+
+#[test]
+fn bootstrap_when_routing_table_shrinks() {
+    let mut cfg = Config::default();
+    cfg.set_periodic_bootstrap_interval(Some(Duration::from_secs(60 * 60)));
+    cfg.set_bootstrap_min_peers(2);
+
+    // The first node knows the other two nodes.
+    let mut swarms = build_connected_nodes_with_config(3, 2, cfg)
+        .into_iter()
+        .map(|(_a, s)| s)
+        .collect::<Vec<_>>();
+
+    let removed = *swarms[2].local_peer_id();
+    assert!(swarms[0].behaviour_mut().remove_peer(&removed).is_some());
+
+    let (trigger, _, peers_refreshed) = wait_bootstrap_finished(swarms);
+
+    assert_eq!(trigger, BootstrapTrigger::RoutingTableShrank);
+    assert_eq!(peers_refreshed, 1);
+}
+
 #[test]
 fn query_iter() {
     fn distances<K>(key: &kbucket::Key<K>, peers: Vec<PeerId>) -> Vec<Distance> {
@@ -319,6 +383,56 @@ fn query_iter() {
     }
 }
 
+#[test]
+fn get_closest_peers_with_config_terminates_early() {
+    let num_total = 10;
+    let num_results = NonZeroUsize::new(2).unwrap();
+
+    let mut swarms = build_fully_connected_nodes_with_config(num_total, Default::default())
+        .into_iter()
+        .map(|(_a, s)| s)
+        .collect::<Vec<_>>();
+
+    let search_target = PeerId::random();
+    let qid = swarms[0].behaviour_mut().get_closest_peers_with_config(
+        search_target,
+        GetClosestPeersConfig {
+            num_results,
+            timeout: Duration::from_secs(60),
+        },
+    );
+
+    block_on(poll_fn(move |ctx| {
+        for swarm in swarms.iter_mut() {
+            loop {
+                match swarm.poll_next_unpin(ctx) {
+                    Poll::Ready(Some(SwarmEvent::Behaviour(Event::OutboundQueryProgressed {
+                        id,
+                        result: QueryResult::GetClosestPeers(Ok(ok)),
+                        step,
+                        ..
+                    }))) => {
+                        assert_eq!(id, qid);
+                        assert!(step.last);
+                        assert_eq!(ok.peers.len(), num_results.get());
+                        assert!(ok.peers.len() < num_total - 1);
+                        return Poll::Ready(());
+                    }
+                    Poll::Ready(Some(SwarmEvent::Behaviour(Event::OutboundQueryProgressed {
+                        result: QueryResult::GetClosestPeers(Err(e)),
+                        ..
+                    }))) => panic!("Unexpected error: {e:?}"),
+                    // Ignore any other event.
+                    Poll::Ready(Some(_)) => (),
+                    e @ Poll::Ready(_) => panic!("Unexpected return value: {e:?}"),
+                    Poll::Pending => break,
+                }
+            }
+        }
+        Poll::Pending
+    }))
+}
+
 #[test]
 fn unresponsive_not_returned_direct() {
     let _ = tracing_subscriber::fmt()
@@ -855,6 +969,166 @@ fn get_record_many() {
     }))
 }
 
+#[test]
+fn put_record_with_config_fails_if_quorum_is_not_reached() {
+    let mut swarms = build_fully_connected_nodes_with_config(3, Default::default())
+        .into_iter()
+        .map(|(_addr, swarm)| swarm)
+        .collect::<Vec<_>>();
+
+    let record = Record::new(random_multihash(), vec![4, 5, 6]);
+    let quorum = NonZeroUsize::new(3).unwrap();
+    let qid = swarms[0]
+        .behaviour_mut()
+        .put_record_with_config(
+            record.clone(),
+            PutRecordConfig {
+                quorum: Quorum::N(quorum),
+                replication_factor: None,
+            },
+        )
+        .unwrap();
+
+    block_on(poll_fn(move |ctx| {
+        for swarm in swarms.iter_mut() {
+            loop {
+                match swarm.poll_next_unpin(ctx) {
+                    Poll::Ready(Some(SwarmEvent::Behaviour(Event::OutboundQueryProgressed {
+                        id,
+                        result: QueryResult::PutRecord(res),
+                        ..
+                    }))) => {
+                        assert_eq!(id, qid);
+                        match res {
+                            Err(PutRecordError::QuorumFailed {
+                                key,
+                                success,
+                                quorum: q,
+                            }) => {
+                                assert_eq!(key, record.key);
+                                assert_eq!(success.len(), 2);
+                                assert_eq!(q, quorum);
+                            }
+                            other => panic!("Unexpected result: {other:?}"),
+                        }
+                        return Poll::Ready(());
+                    }
+                    // Ignore any other event.
+                    Poll::Ready(Some(_)) => (),
+                    e @ Poll::Ready(_) => panic!("Unexpected return value: {e:?}"),
+                    Poll::Pending => break,
+                }
+            }
+        }
+        Poll::Pending
+    }))
+}
+
+#[test]
+fn put_record_with_config_overrides_replication_factor() {
+    let mut swarms = build_fully_connected_nodes_with_config(5, Default::default())
+        .into_iter()
+        .map(|(_addr, swarm)| swarm)
+        .collect::<Vec<_>>();
+
+    let record = Record::new(random_multihash(), vec![4, 5, 6]);
+    let qid = swarms[0]
+        .behaviour_mut()
+        .put_record_with_config(
+            record,
+            PutRecordConfig {
+                quorum: Quorum::All,
+                replication_factor: NonZeroUsize::new(2),
+            },
+        )
+        .unwrap();
+
+    block_on(poll_fn(move |ctx| {
+        for swarm in swarms.iter_mut() {
+            loop {
+                match swarm.poll_next_unpin(ctx) {
+                    Poll::Ready(Some(SwarmEvent::Behaviour(Event::OutboundQueryProgressed {
+                        id,
+                        result: QueryResult::PutRecord(res),
+                        ..
+                    }))) => {
+                        assert_eq!(id, qid);
+                        assert_eq!(res.unwrap().success.len(), 2);
+                        return Poll::Ready(());
+                    }
+                    // Ignore any other event.
+                    Poll::Ready(Some(_)) => (),
+                    e @ Poll::Ready(_) => panic!("Unexpected return value: {e:?}"),
+                    Poll::Pending => break,
+                }
+            }
+        }
+        Poll::Pending
+    }))
+}
+
+#[test]
+fn get_record_with_config_finishes_once_quorum_is_reached() {
+    let num_nodes = 12;
+    let mut swarms = build_connected_nodes(num_nodes, 3)
+        .into_iter()
+        .map(|(_addr, swarm)| swarm)
+        .collect::<Vec<_>>();
+
+    let record = Record::new(random_multihash(), vec![4, 5, 6]);
+
+    for swarm in swarms.iter_mut().skip(1) {
+        swarm.behaviour_mut().store.put(record.clone()).unwrap();
+    }
+
+    let quorum = NonZeroUsize::new(3).unwrap();
+    let qid = swarms[0].behaviour_mut().get_record_with_config(
+        record.key.clone(),
+        GetRecordConfig {
+            quorum: Some(Quorum::N(quorum)),
+            replication_factor: None,
+        },
+    );
+
+    let mut records = Vec::new();
+    block_on(poll_fn(move |ctx| {
+        for swarm in swarms.iter_mut() {
+            loop {
+                match swarm.poll_next_unpin(ctx) {
+                    Poll::Ready(Some(SwarmEvent::Behaviour(Event::OutboundQueryProgressed {
+                        id,
+                        result: QueryResult::GetRecord(Ok(r)),
+                        step,
+                        ..
+                    }))) => {
+                        assert_eq!(id, qid);
+                        match r {
+                            GetRecordOk::FoundRecord(r) => {
+                                assert_eq!(r.record, record);
+                                records.push(r);
+                            }
+                            GetRecordOk::FinishedWithNoAdditionalRecord { .. } => {
+                                assert!(step.last);
+                                assert_eq!(records.len(), quorum.get());
+                                return Poll::Ready(());
+                            }
+                        }
+                    }
+                    Poll::Ready(Some(SwarmEvent::Behaviour(Event::OutboundQueryProgressed {
+                        result: QueryResult::GetRecord(Err(e)),
+                        ..
+                    }))) => panic!("Unexpected error: {e:?}"),
+                    // Ignore any other event.
+                    Poll::Ready(Some(_)) => (),
+                    e @ Poll::Ready(_) => panic!("Unexpected return value: {e:?}"),
+                    Poll::Pending => break,
+                }
+            }
+        }
+        Poll::Pending
+    }))
+}
+
 /// A node joining a fully connected network via three (ALPHA_VALUE) bootnodes
 /// should be able to add itself as a provider to the X closest nodes of the
 /// network where X is equal to the configured replication factor.
@@ -1288,6 +1562,50 @@ fn manual_bucket_inserts() {
     }));
 }
 
+#[test]
+fn inspect_and_manage_routing_table() {
+    let (_, mut swarm) = build_node();
+    let local_key = kbucket::Key::from(*swarm.local_peer_id());
+
+    let peers = (0..3).map(|_| PeerId::random()).collect::<Vec<_>>();
+    let address: Multiaddr = Protocol::Memory(1).into();
+    for peer in &peers {
+        swarm.behaviour_mut().add_address(peer, address.clone());
+    }
+
+    let mut seen = Vec::new();
+    for bucket in swarm.behaviour_mut().kbuckets() {
+        for entry in bucket.iter() {
+            let peer = *entry.node.key.preimage();
+            let distance = local_key.distance(entry.node.key);
+            assert_eq!(distance.ilog2(), Some(bucket.index() as u32));
+            assert_eq!(
+                entry.node.value.iter().collect::<Vec<_>>(),
+                vec![&address.clone().with_p2p(peer).unwrap()]
+            );
+            assert_eq!(entry.status, NodeStatus::Disconnected);
+            seen.push(peer);
+        }
+    }
+    seen.sort();
+    let mut expected = peers.clone();
+    expected.sort();
+    assert_eq!(seen, expected);
+
+    // Removing the last address of a peer removes the peer from the routing table.
+    assert!(swarm
+        .behaviour_mut()
+        .remove_address(&peers[0], &address)
+        .is_some());
+    assert!(swarm.behaviour_mut().remove_peer(&peers[1]).is_some());
+    let remaining = swarm
+        .behaviour_mut()
+        .kbuckets()
+        .flat_map(|b| b.iter().map(|e| *e.node.key.preimage()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(remaining, vec![peers[2]]);
+}
+
 #[test]
 fn network_behaviour_on_address_change() {
     let local_peer_id = PeerId::random();
@@ -1527,3 +1845,111 @@ fn get_providers_limit_n_2() {
 fn get_providers_limit_n_5() {
     get_providers_limit::<5>();
 }
+
+#[test]
+fn get_providers_cancel() {
+    let mut swarms = build_nodes(3);
+
+    // Let first peer know of second peer and second peer know of third peer.
+    for i in 0..2 {
+        let (peer_id, address) = (
+            *Swarm::local_peer_id(&swarms[i + 1].1),
+            swarms[i + 1].0.clone(),
+        );
+        swarms[i].1.behaviour_mut().add_address(&peer_id, address);
+    }
+
+    let mut swarms = swarms
+        .into_iter()
+        .map(|(_addr, swarm)| swarm)
+        .collect::<Vec<_>>();
+
+    // Provide the content on peer 2 and 3.
+    let key = record::Key::new(&"cancel");
+    for swarm in swarms.iter_mut().skip(1) {
+        swarm
+            .behaviour_mut()
+            .start_providing(key.clone())
+            .expect("could not provide");
+    }
+
+    let query_id = swarms[0].behaviour_mut().get_providers(key.clone());
+    let mut cancelled = false;
+
+    block_on(poll_fn(move |ctx| {
+        for (i, swarm) in swarms.iter_mut().enumerate() {
+            loop {
+                match swarm.poll_next_unpin(ctx) {
+                    Poll::Ready(Some(SwarmEvent::Behaviour(Event::OutboundQueryProgressed {
+                        id,
+                        result: QueryResult::GetProviders(Ok(ok)),
+                        step,
+                        ..
+                    }))) if i == 0 && id == query_id => match ok {
+                        GetProvidersOk::FoundProviders { .. } => {
+                            assert!(!cancelled, "No providers are reported after cancelling");
+                            assert!(!step.last);
+                            swarm.behaviour_mut().query_mut(&id).unwrap().cancel();
+                            cancelled = true;
+                        }
+                        GetProvidersOk::Cancelled {
+                            key: cancelled_key, ..
+                        } => {
+                            assert!(cancelled);
+                            assert!(step.last);
+                            assert_eq!(cancelled_key, key);
+                            assert!(swarm.behaviour_mut().query(&id).is_none());
+                            return Poll::Ready(());
+                        }
+                        GetProvidersOk::FinishedWithNoAdditionalRecord { .. } => {
+                            panic!("Expected the query to be cancelled")
+                        }
+                    },
+                    Poll::Ready(..) => {}
+                    Poll::Pending => break,
+                }
+            }
+        }
+        Poll::Pending
+    }));
+}
+
+#[test]
+fn bootstrap_cancel() {
+    let mut swarms = build_connected_nodes(10, 9)
+        .into_iter()
+        .map(|(_a, s)| s)
+        .collect::<Vec<_>>();
+    let local_peer_id = *swarms[0].local_peer_id();
+
+    let qid = swarms[0].behaviour_mut().bootstrap().unwrap();
+    swarms[0].behaviour_mut().query_mut(&qid).unwrap().cancel();
+
+    block_on(poll_fn(move |ctx| {
+        for (i, swarm) in swarms.iter_mut().enumerate() {
+            loop {
+                match swarm.poll_next_unpin(ctx) {
+                    Poll::Ready(Some(SwarmEvent::Behaviour(Event::OutboundQueryProgressed {
+                        id,
+                        result: QueryResult::Bootstrap(Ok(ok)),
+                        step,
+                        ..
+                    }))) => {
+                        assert_eq!(id, qid);
+                        assert_eq!(i, 0);
+                        // No bucket refreshes follow the cancelled self-lookup.
+                        assert_eq!(ok.peer, local_peer_id);
+                        assert_eq!(ok.num_remaining, 0);
+                        assert!(step.last);
+                        assert_eq!(swarm.behaviour_mut().queries.size(), 0);
+                        return Poll::Ready(());
+                    }
+                    Poll::Ready(Some(_)) => (),
+                    e @ Poll::Ready(_) => panic!("Unexpected return value: {e:?}"),
+                    Poll::Pending => break,
+                }
+            }
+        }
+        Poll::Pending
+    }));
+}
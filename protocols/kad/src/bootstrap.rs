@@ -0,0 +1,195 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Automatic bootstrapping and the tracking of a bootstrap's outcome.
+
+use crate::BootstrapTrigger;
+use futures::FutureExt;
+use futures_timer::Delay;
+use libp2p_identity::PeerId;
+use std::collections::HashSet;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Decides when to bootstrap automatically.
+pub(crate) struct Status {
+    interval: Duration,
+    min_peers: usize,
+    idle_timeout: Option<Duration>,
+    /// Fires when the next periodic bootstrap is due.
+    next_periodic: Delay,
+    /// Fires when no query succeeded for `idle_timeout`.
+    next_idle: Option<Delay>,
+    /// Whether peers were removed from or evicted out of the routing table since the last poll.
+    shrank: bool,
+}
+
+impl Status {
+    pub(crate) fn new(
+        interval: Duration,
+        min_peers: usize,
+        idle_timeout: Option<Duration>,
+    ) -> Self {
+        Self {
+            interval,
+            min_peers,
+            idle_timeout,
+            next_periodic: Delay::new(interval),
+            next_idle: idle_timeout.map(Delay::new),
+            shrank: false,
+        }
+    }
+
+    /// Postpones the next periodic bootstrap, as a bootstrap just started.
+    ///
+    /// The idle timer keeps running until a query, e.g. of the bootstrap, succeeds.
+    pub(crate) fn on_bootstrap_started(&mut self) {
+        self.next_periodic.reset(self.interval);
+    }
+
+    pub(crate) fn on_query_succeeded(&mut self) {
+        if let (Some(delay), Some(timeout)) = (self.next_idle.as_mut(), self.idle_timeout) {
+            delay.reset(timeout);
+        }
+    }
+
+    pub(crate) fn on_routing_table_shrank(&mut self) {
+        self.shrank = true;
+    }
+
+    /// Returns the reason for bootstrapping, once a bootstrap is due.
+    ///
+    /// The periodic timer is restarted whenever a bootstrap is due, the idle timer only when it
+    /// fired itself or a query succeeded. [`Status::on_bootstrap_started`] is expected to be
+    /// called for bootstraps started otherwise.
+    pub(crate) fn poll_trigger(
+        &mut self,
+        cx: &mut Context<'_>,
+        num_peers: impl FnOnce() -> usize,
+    ) -> Poll<BootstrapTrigger> {
+        let trigger = if std::mem::take(&mut self.shrank) && num_peers() < self.min_peers {
+            BootstrapTrigger::RoutingTableShrank
+        } else if self.next_periodic.poll_unpin(cx).is_ready() {
+            BootstrapTrigger::Periodic
+        } else if self
+            .next_idle
+            .as_mut()
+            .map_or(false, |delay| delay.poll_unpin(cx).is_ready())
+        {
+            if let (Some(delay), Some(timeout)) = (self.next_idle.as_mut(), self.idle_timeout) {
+                delay.reset(timeout);
+            }
+            BootstrapTrigger::Idle
+        } else {
+            return Poll::Pending;
+        };
+
+        self.on_bootstrap_started();
+
+        Poll::Ready(trigger)
+    }
+}
+
+/// The progress of a single bootstrap, from which its outcome is summarized.
+pub(crate) struct Progress {
+    pub(crate) trigger: BootstrapTrigger,
+    /// The peers in the routing table when the bootstrap started.
+    known_peers: HashSet<PeerId>,
+    /// The peers of `known_peers` that were successfully contacted.
+    refreshed: HashSet<PeerId>,
+    pub(crate) num_timeouts: u32,
+}
+
+impl Progress {
+    pub(crate) fn new(trigger: BootstrapTrigger, known_peers: HashSet<PeerId>) -> Self {
+        Self {
+            trigger,
+            known_peers,
+            refreshed: HashSet::new(),
+            num_timeouts: 0,
+        }
+    }
+
+    /// Records the peers successfully contacted by a step of the bootstrap.
+    pub(crate) fn on_step(&mut self, contacted: impl Iterator<Item = PeerId>) {
+        let known_peers = &self.known_peers;
+        self.refreshed
+            .extend(contacted.filter(|peer| known_peers.contains(peer)));
+    }
+
+    /// Returns the number of peers that were added to and refreshed in the routing table.
+    pub(crate) fn outcome(&self, peers: impl Iterator<Item = PeerId>) -> (usize, usize) {
+        let added = peers
+            .filter(|peer| !self.known_peers.contains(peer))
+            .count();
+
+        (added, self.refreshed.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::future::poll_fn;
+
+    #[test]
+    fn idle_bootstrap_is_not_postponed_by_periodic_ones() {
+        let mut status = Status::new(
+            Duration::from_millis(10),
+            0,
+            Some(Duration::from_millis(50)),
+        );
+
+        let triggers = block_on(async {
+            let mut triggers = Vec::new();
+            while !triggers.contains(&BootstrapTrigger::Idle) && triggers.len() < 50 {
+                triggers.push(poll_fn(|cx| status.poll_trigger(cx, || 0)).await);
+            }
+            triggers
+        });
+
+        assert_eq!(triggers.last(), Some(&BootstrapTrigger::Idle));
+        assert!(triggers.contains(&BootstrapTrigger::Periodic));
+    }
+
+    #[test]
+    fn successful_query_postpones_idle_bootstrap() {
+        let mut status = Status::new(
+            Duration::from_secs(60 * 60),
+            0,
+            Some(Duration::from_millis(50)),
+        );
+
+        block_on(async {
+            futures_timer::Delay::new(Duration::from_millis(30)).await;
+            status.on_query_succeeded();
+            futures_timer::Delay::new(Duration::from_millis(30)).await;
+            assert!(poll_fn(|cx| Poll::Ready(status.poll_trigger(cx, || 0)))
+                .await
+                .is_pending());
+
+            assert_eq!(
+                poll_fn(|cx| status.poll_trigger(cx, || 0)).await,
+                BootstrapTrigger::Idle
+            );
+        });
+    }
+}
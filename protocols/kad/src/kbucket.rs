@@ -475,6 +475,12 @@ where
     TKey: Clone + AsRef<KeyBytes>,
     TVal: Clone,
 {
+    /// Returns the index of this bucket in the routing table, i.e. the integer part of the
+    /// base 2 logarithm of the distances of the keys it contains to the local key.
+    pub fn index(&self) -> usize {
+        self.index.get()
+    }
+
     /// Returns the minimum inclusive and maximum inclusive distance for
     /// this bucket.
     pub fn range(&self) -> (Distance, Distance) {
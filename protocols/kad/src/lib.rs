@@ -37,6 +37,7 @@
 
 mod addresses;
 mod behaviour;
+mod bootstrap;
 mod handler;
 mod jobs;
 mod kbucket;
@@ -56,11 +57,12 @@ mod proto {
 pub use addresses::Addresses;
 pub use behaviour::{
     AddProviderContext, AddProviderError, AddProviderOk, AddProviderPhase, AddProviderResult,
-    BootstrapError, BootstrapOk, BootstrapResult, GetClosestPeersError, GetClosestPeersOk,
-    GetClosestPeersResult, GetProvidersError, GetProvidersOk, GetProvidersResult, GetRecordError,
-    GetRecordOk, GetRecordResult, InboundRequest, Mode, NoKnownPeers, PeerRecord, PutRecordContext,
-    PutRecordError, PutRecordOk, PutRecordPhase, PutRecordResult, QueryInfo, QueryMut, QueryRef,
-    QueryResult, QueryStats, RoutingUpdate,
+    BootstrapError, BootstrapOk, BootstrapResult, BootstrapTrigger, GetClosestPeersConfig,
+    GetClosestPeersError, GetClosestPeersOk, GetClosestPeersResult, GetProvidersError,
+    GetProvidersOk, GetProvidersResult, GetRecordConfig, GetRecordError, GetRecordOk,
+    GetRecordResult, InboundRequest, Mode, NoKnownPeers, PeerRecord, PutRecordConfig,
+    PutRecordContext, PutRecordError, PutRecordOk, PutRecordPhase, PutRecordResult, QueryInfo,
+    QueryMut, QueryRef, QueryResult, QueryStats, RoutingUpdate,
 };
 pub use behaviour::{
     Behaviour, BucketInserts, Caching, Config, Event, ProgressStep, Quorum, StoreInserts,
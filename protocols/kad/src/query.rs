@@ -133,23 +133,79 @@ impl<TInner> QueryPool<TInner> {
     ) where
         T: Into<KeyBytes> + Clone,
         I: IntoIterator<Item = Key<PeerId>>,
+    {
+        let peer_iter = self.closest_peers_iter(self.config.replication_factor, target, peers);
+        let query = Query::new(id, peer_iter, inner);
+        self.queries.insert(id, query);
+    }
+
+    /// Adds a query to the pool that iterates towards the `num_results` closest peers to the
+    /// target, instead of as many as the configured replication factor.
+    pub(crate) fn add_iter_closest_with_num_results<T, I>(
+        &mut self,
+        target: T,
+        peers: I,
+        inner: TInner,
+        num_results: NonZeroUsize,
+    ) -> QueryId
+    where
+        T: Into<KeyBytes> + Clone,
+        I: IntoIterator<Item = Key<PeerId>>,
+    {
+        let id = self.next_query_id();
+        let peer_iter = self.closest_peers_iter(num_results, target, peers);
+        let query = Query::new(id, peer_iter, inner);
+        self.queries.insert(id, query);
+        id
+    }
+
+    /// Adds a query to the pool that iterates towards the closest peers to the target, finishing
+    /// as soon as `num_results` peers have been contacted successfully or times out after
+    /// `timeout`, whichever comes first.
+    pub(crate) fn add_iter_closest_bounded<T, I>(
+        &mut self,
+        target: T,
+        peers: I,
+        inner: TInner,
+        num_results: NonZeroUsize,
+        timeout: Duration,
+    ) -> QueryId
+    where
+        T: Into<KeyBytes> + Clone,
+        I: IntoIterator<Item = Key<PeerId>>,
+    {
+        let id = self.next_query_id();
+        let peer_iter = self.closest_peers_iter(num_results, target, peers);
+        let mut query = Query::new(id, peer_iter, inner);
+        query.num_results = Some(num_results);
+        query.timeout = Some(timeout);
+        self.queries.insert(id, query);
+        id
+    }
+
+    fn closest_peers_iter<T, I>(
+        &self,
+        num_results: NonZeroUsize,
+        target: T,
+        peers: I,
+    ) -> QueryPeerIter
+    where
+        T: Into<KeyBytes> + Clone,
+        I: IntoIterator<Item = Key<PeerId>>,
     {
         let cfg = ClosestPeersIterConfig {
-            num_results: self.config.replication_factor,
+            num_results,
             parallelism: self.config.parallelism,
             ..ClosestPeersIterConfig::default()
         };
 
-        let peer_iter = if self.config.disjoint_query_paths {
+        if self.config.disjoint_query_paths {
             QueryPeerIter::ClosestDisjoint(ClosestDisjointPeersIter::with_config(
                 cfg, target, peers,
             ))
         } else {
             QueryPeerIter::Closest(ClosestPeersIter::with_config(cfg, target, peers))
-        };
-
-        let query = Query::new(id, peer_iter, inner);
-        self.queries.insert(id, query);
+        }
     }
 
     fn next_query_id(&mut self) -> QueryId {
@@ -188,7 +244,7 @@ impl<TInner> QueryPool<TInner> {
                 }
                 PeersIterState::Waiting(None) | PeersIterState::WaitingAtCapacity => {
                     let elapsed = now - query.stats.start.unwrap_or(now);
-                    if elapsed >= self.config.timeout {
+                    if elapsed >= query.timeout.unwrap_or(self.config.timeout) {
                         timeout = Some(query_id);
                         break;
                     }
@@ -271,6 +327,10 @@ pub(crate) struct Query<TInner> {
     peer_iter: QueryPeerIter,
     /// Execution statistics of the query.
     stats: QueryStats,
+    /// The number of successfully contacted peers after which the query finishes early, if any.
+    num_results: Option<NonZeroUsize>,
+    /// Timeout of the query, overriding [`QueryConfig::timeout`] if set.
+    timeout: Option<Duration>,
     /// The opaque inner query state.
     pub(crate) inner: TInner,
 }
@@ -290,6 +350,8 @@ impl<TInner> Query<TInner> {
             inner,
             peer_iter,
             stats: QueryStats::empty(),
+            num_results: None,
+            timeout: None,
         }
     }
 
@@ -330,6 +392,11 @@ impl<TInner> Query<TInner> {
         if updated {
             self.stats.success += 1;
         }
+        if let Some(num_results) = self.num_results {
+            if self.stats.success as usize >= num_results.get() {
+                self.finish();
+            }
+        }
     }
 
     /// Advances the state of the underlying peer iterator.
@@ -18,8 +18,12 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
+#[cfg(not(target_arch = "wasm32"))]
+mod file;
 mod memory;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use file::FileStore;
 pub use memory::{MemoryStore, MemoryStoreConfig};
 use thiserror::Error;
 
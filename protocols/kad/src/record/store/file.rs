@@ -0,0 +1,416 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use super::*;
+
+use crate::record::store::{MemoryStore, MemoryStoreConfig};
+use libp2p_identity::PeerId;
+use std::borrow::Cow;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Version of the on-disk format written by [`FileStore::persist`].
+const FORMAT_VERSION: u8 = 1;
+
+const TAG_RECORD: u8 = 0;
+const TAG_PROVIDER: u8 = 1;
+
+/// A `RecordStore` that keeps its records in memory and persists them to a file.
+///
+/// Both value records and provider records are written to disk, together with
+/// their expiration times. Since the [`Instant`]s used by the DHT are only
+/// meaningful within the current process, expiration times are stored as wall-clock
+/// timestamps and converted back when the store is reopened. Records that expired
+/// in the meantime are dropped on [`FileStore::open`].
+///
+/// Changes are written to disk when [`FileStore::persist`] is called and when the
+/// store is dropped. Call [`FileStore::persist`] periodically (e.g. via
+/// [`Behaviour::store_mut`](crate::Behaviour::store_mut)) to bound the amount of
+/// data lost on an unclean shutdown.
+///
+/// # Example
+///
+/// ```no_run
+/// # use libp2p_identity::PeerId;
+/// # use libp2p_kad::{store::FileStore, Behaviour};
+/// # fn main() -> std::io::Result<()> {
+/// let local_id = PeerId::random();
+/// let store = FileStore::open(local_id, "kad-records.bin")?;
+/// let behaviour = Behaviour::new(local_id, store);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FileStore {
+    /// The in-memory representation of the stored records.
+    inner: MemoryStore,
+    /// The file that the records are persisted to.
+    path: PathBuf,
+    /// Whether `inner` changed since the last call to `persist`.
+    dirty: bool,
+}
+
+impl FileStore {
+    /// Opens the store persisted at `path` with a default configuration.
+    ///
+    /// If the file does not exist, an empty store is created.
+    pub fn open(local_id: PeerId, path: impl Into<PathBuf>) -> io::Result<Self> {
+        Self::open_with_config(local_id, path, Default::default())
+    }
+
+    /// Opens the store persisted at `path` with the given configuration.
+    ///
+    /// If the file does not exist, an empty store is created.
+    pub fn open_with_config(
+        local_id: PeerId,
+        path: impl Into<PathBuf>,
+        config: MemoryStoreConfig,
+    ) -> io::Result<Self> {
+        let path = path.into();
+        let mut inner = MemoryStore::with_config(local_id, config);
+
+        match fs::read(&path) {
+            Ok(bytes) => decode(&bytes, &mut inner)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        Ok(FileStore {
+            inner,
+            path,
+            dirty: false,
+        })
+    }
+
+    /// Writes all records currently held by the store to disk.
+    ///
+    /// The file is replaced atomically, i.e. a failed call leaves the previously
+    /// persisted state intact.
+    pub fn persist(&mut self) -> io::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, encode(&self.inner))?;
+        fs::rename(&tmp, &self.path)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+}
+
+impl Drop for FileStore {
+    fn drop(&mut self) {
+        if !self.dirty {
+            return;
+        }
+
+        if let Err(e) = self.persist() {
+            tracing::warn!(path=%self.path.display(), "Failed to persist records: {e}");
+        }
+    }
+}
+
+impl RecordStore for FileStore {
+    type RecordsIter<'a> = <MemoryStore as RecordStore>::RecordsIter<'a>;
+    type ProvidedIter<'a> = <MemoryStore as RecordStore>::ProvidedIter<'a>;
+
+    fn get(&self, k: &Key) -> Option<Cow<'_, Record>> {
+        self.inner.get(k)
+    }
+
+    fn put(&mut self, r: Record) -> Result<()> {
+        self.inner.put(r)?;
+        self.dirty = true;
+
+        Ok(())
+    }
+
+    fn remove(&mut self, k: &Key) {
+        self.inner.remove(k);
+        self.dirty = true;
+    }
+
+    fn records(&self) -> Self::RecordsIter<'_> {
+        self.inner.records()
+    }
+
+    fn add_provider(&mut self, record: ProviderRecord) -> Result<()> {
+        self.inner.add_provider(record)?;
+        self.dirty = true;
+
+        Ok(())
+    }
+
+    fn providers(&self, key: &Key) -> Vec<ProviderRecord> {
+        self.inner.providers(key)
+    }
+
+    fn provided(&self) -> Self::ProvidedIter<'_> {
+        self.inner.provided()
+    }
+
+    fn remove_provider(&mut self, k: &Key, p: &PeerId) {
+        self.inner.remove_provider(k, p);
+        self.dirty = true;
+    }
+}
+
+fn encode(store: &MemoryStore) -> Vec<u8> {
+    let now = (Instant::now(), SystemTime::now());
+    let mut buf = vec![FORMAT_VERSION];
+
+    for record in store.records() {
+        buf.push(TAG_RECORD);
+        write_bytes(&mut buf, record.key.as_ref());
+        write_bytes(&mut buf, &record.value);
+        write_bytes(
+            &mut buf,
+            &record.publisher.map(|p| p.to_bytes()).unwrap_or_default(),
+        );
+        write_expiry(&mut buf, record.expires, now);
+    }
+
+    for record in store.all_providers() {
+        buf.push(TAG_PROVIDER);
+        write_bytes(&mut buf, record.key.as_ref());
+        write_bytes(&mut buf, &record.provider.to_bytes());
+        write_expiry(&mut buf, record.expires, now);
+        buf.extend_from_slice(&(record.addresses.len() as u32).to_be_bytes());
+        for address in &record.addresses {
+            write_bytes(&mut buf, &address.to_vec());
+        }
+    }
+
+    buf
+}
+
+fn decode(bytes: &[u8], store: &mut MemoryStore) -> io::Result<()> {
+    let now = (Instant::now(), SystemTime::now());
+    let mut reader = Reader(bytes);
+
+    if reader.u8()? != FORMAT_VERSION {
+        return Err(invalid_data("unsupported format version"));
+    }
+
+    while !reader.0.is_empty() {
+        match reader.u8()? {
+            TAG_RECORD => {
+                let key = Key::from(reader.bytes()?.to_vec());
+                let value = reader.bytes()?.to_vec();
+                let publisher = match reader.bytes()? {
+                    [] => None,
+                    bytes => Some(PeerId::from_bytes(bytes).map_err(invalid_data)?),
+                };
+                let Some(expires) = reader.expiry(now)? else {
+                    continue;
+                };
+
+                let record = Record {
+                    key,
+                    value,
+                    publisher,
+                    expires,
+                };
+                if let Err(e) = store.put(record) {
+                    tracing::debug!("Dropping persisted record: {e}");
+                }
+            }
+            TAG_PROVIDER => {
+                let key = Key::from(reader.bytes()?.to_vec());
+                let provider = PeerId::from_bytes(reader.bytes()?).map_err(invalid_data)?;
+                let expires = reader.expiry(now)?;
+                let addresses = (0..reader.u32()?)
+                    .map(|_| Multiaddr::try_from(reader.bytes()?.to_vec()).map_err(invalid_data))
+                    .collect::<io::Result<Vec<_>>>()?;
+                let Some(expires) = expires else {
+                    continue;
+                };
+
+                let record = ProviderRecord {
+                    key,
+                    provider,
+                    expires,
+                    addresses,
+                };
+                if let Err(e) = store.add_provider(record) {
+                    tracing::debug!("Dropping persisted provider record: {e}");
+                }
+            }
+            _ => return Err(invalid_data("unknown entry")),
+        }
+    }
+
+    Ok(())
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// Writes the expiration time as milliseconds since the unix epoch, `0` meaning "never".
+fn write_expiry(buf: &mut Vec<u8>, expires: Option<Instant>, now: (Instant, SystemTime)) {
+    let millis = expires.map_or(0, |t| {
+        let deadline = now.1 + t.saturating_duration_since(now.0);
+        deadline
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |d| (d.as_millis() as u64).max(1))
+    });
+    buf.extend_from_slice(&millis.to_be_bytes());
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes(bytes.try_into().expect("4 bytes")))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_be_bytes(bytes.try_into().expect("8 bytes")))
+    }
+
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    /// Reads an expiration time written by `write_expiry`.
+    ///
+    /// Returns `None` if the deadline has already passed.
+    fn expiry(&mut self, now: (Instant, SystemTime)) -> io::Result<Option<Option<Instant>>> {
+        let millis = self.u64()?;
+        if millis == 0 {
+            return Ok(Some(None));
+        }
+
+        let deadline = UNIX_EPOCH + Duration::from_millis(millis);
+        match deadline.duration_since(now.1) {
+            Ok(remaining) if !remaining.is_zero() => Ok(Some(Some(now.0 + remaining))),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p_core::multihash::Multihash;
+
+    const SHA_256_MH: u64 = 0x12;
+
+    fn random_key() -> Key {
+        Key::from(Multihash::<64>::wrap(SHA_256_MH, &rand::random::<[u8; 32]>()).unwrap())
+    }
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("libp2p-kad-store-{}", rand::random::<u64>()))
+    }
+
+    #[test]
+    fn records_survive_reopen() {
+        let path = temp_path();
+        let local_id = PeerId::random();
+        let provider = PeerId::random();
+        let in_an_hour = Some(Instant::now() + Duration::from_secs(3600));
+
+        let mut live = Record::new(random_key(), b"live".to_vec());
+        live.publisher = Some(PeerId::random());
+        live.expires = in_an_hour;
+        let forever = Record::new(random_key(), b"forever".to_vec());
+        let mut expired = Record::new(random_key(), b"expired".to_vec());
+        expired.expires = Some(Instant::now());
+        let live_provider = ProviderRecord {
+            key: random_key(),
+            provider,
+            expires: in_an_hour,
+            addresses: vec!["/ip4/127.0.0.1/tcp/4001".parse().unwrap()],
+        };
+        let expired_provider = ProviderRecord::new(random_key(), provider, Vec::new());
+
+        {
+            let mut store = FileStore::open(local_id, &path).unwrap();
+            store.put(live.clone()).unwrap();
+            store.put(forever.clone()).unwrap();
+            store.put(expired.clone()).unwrap();
+            store.add_provider(live_provider.clone()).unwrap();
+            store
+                .add_provider(ProviderRecord {
+                    expires: Some(Instant::now()),
+                    ..expired_provider.clone()
+                })
+                .unwrap();
+        }
+
+        let store = FileStore::open(local_id, &path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let restored = store.get(&live.key).unwrap();
+        assert_eq!(restored.value, live.value);
+        assert_eq!(restored.publisher, live.publisher);
+        let restored_expiry = restored.expires.unwrap();
+        assert!(restored_expiry > Instant::now() + Duration::from_secs(3500));
+        assert_eq!(store.get(&forever.key).unwrap().into_owned(), forever);
+        assert!(store.get(&expired.key).is_none());
+
+        let providers = store.providers(&live_provider.key);
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].provider, provider);
+        assert_eq!(providers[0].addresses, live_provider.addresses);
+        assert!(store.providers(&expired_provider.key).is_empty());
+    }
+
+    #[test]
+    fn missing_file_opens_empty_store() {
+        let store = FileStore::open(PeerId::random(), temp_path()).unwrap();
+
+        assert_eq!(store.records().count(), 0);
+    }
+
+    #[test]
+    fn rejects_corrupt_file() {
+        let path = temp_path();
+        fs::write(&path, [FORMAT_VERSION, TAG_RECORD, 0, 0, 0, 42]).unwrap();
+
+        let result = FileStore::open(PeerId::random(), &path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
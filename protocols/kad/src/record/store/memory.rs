@@ -94,6 +94,12 @@ impl MemoryStore {
     {
         self.records.retain(f);
     }
+
+    /// Returns an iterator over all stored provider records.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn all_providers(&self) -> impl Iterator<Item = &ProviderRecord> {
+        self.providers.values().flatten()
+    }
 }
 
 impl RecordStore for MemoryStore {
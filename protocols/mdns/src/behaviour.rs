@@ -300,9 +300,16 @@ where
                     {
                         continue;
                     }
+                    if !self.config.is_interface_enabled(&addr) {
+                        continue;
+                    }
                     if let Entry::Vacant(e) = self.if_tasks.entry(addr) {
+                        // Only accept packets from the interface's own network if the interfaces
+                        // are restricted, as the socket also receives those of other interfaces.
+                        let network = self.config.interfaces.is_some().then_some(inet);
                         match InterfaceState::<P::Socket, P::Timer>::new(
                             addr,
+                            network,
                             self.config.clone(),
                             self.local_peer_id,
                             self.listen_addresses.clone(),
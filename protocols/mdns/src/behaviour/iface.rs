@@ -27,6 +27,7 @@ use crate::behaviour::{socket::AsyncSocket, timer::Builder};
 use crate::Config;
 use futures::channel::mpsc;
 use futures::{SinkExt, StreamExt};
+use if_watch::IpNet;
 use libp2p_core::Multiaddr;
 use libp2p_identity::PeerId;
 use libp2p_swarm::ListenAddresses;
@@ -64,6 +65,18 @@ impl ProbeState {
             ProbeState::Finished(query_interval) => query_interval,
         }
     }
+
+    /// Doubles the probing interval until it reaches `query_interval`.
+    fn advance(&mut self, query_interval: Duration) {
+        if let ProbeState::Probing(interval) = *self {
+            let interval = interval * 2;
+            *self = if interval >= query_interval {
+                ProbeState::Finished(query_interval)
+            } else {
+                ProbeState::Probing(interval)
+            };
+        }
+    }
 }
 
 /// An mDNS instance for a networking interface. To discover all peers when having multiple
@@ -72,6 +85,8 @@ impl ProbeState {
 pub(crate) struct InterfaceState<U, T> {
     /// Address this instance is bound to.
     addr: IpAddr,
+    /// Network packets must originate from, if restricted.
+    network: Option<IpNet>,
     /// Receive socket.
     recv_socket: U,
     /// Send socket.
@@ -112,6 +127,7 @@ where
     /// Builds a new [`InterfaceState`].
     pub(crate) fn new(
         addr: IpAddr,
+        network: Option<IpNet>,
         config: Config,
         local_peer_id: PeerId,
         listen_addresses: Arc<RwLock<ListenAddresses>>,
@@ -167,6 +183,7 @@ where
         };
         Ok(Self {
             addr,
+            network,
             recv_socket,
             send_socket,
             listen_addresses,
@@ -212,14 +229,7 @@ where
                 tracing::trace!(address=%this.addr, probe_state=?this.probe_state, "tick");
 
                 // Stop to probe when the initial interval reach the query interval
-                if let ProbeState::Probing(interval) = this.probe_state {
-                    let interval = interval * 2;
-                    this.probe_state = if interval >= this.query_interval {
-                        ProbeState::Finished(this.query_interval)
-                    } else {
-                        ProbeState::Probing(interval)
-                    };
-                }
+                this.probe_state.advance(this.query_interval);
 
                 this.reset_timer();
             }
@@ -264,6 +274,18 @@ where
                 .poll_read(cx, &mut this.recv_buffer)
                 .map_ok(|(len, from)| MdnsPacket::new_from_bytes(&this.recv_buffer[..len], from))
             {
+                Poll::Ready(Ok(Ok(Some(packet))))
+                    if this
+                        .network
+                        .is_some_and(|network| !network.contains(&packet.remote_addr().ip())) =>
+                {
+                    tracing::trace!(
+                        address=%this.addr,
+                        remote_address=%packet.remote_addr(),
+                        "ignoring packet from outside the interface's network"
+                    );
+                    continue;
+                }
                 Poll::Ready(Ok(Ok(Some(MdnsPacket::Query(query))))) => {
                     tracing::trace!(
                         address=%this.addr,
@@ -326,3 +348,35 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probing_stops_at_query_interval() {
+        let config = Config::default().with_query_interval(Duration::from_secs(1));
+
+        let mut probe_state = ProbeState::default();
+        probe_state.advance(config.query_interval);
+
+        assert!(matches!(probe_state, ProbeState::Finished(_)));
+        assert_eq!(*probe_state.interval(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn lower_query_interval_shortens_timer() {
+        let default_config = Config::default();
+        let config = Config::default().with_query_interval(Duration::from_secs(2));
+
+        let mut default_probe_state = ProbeState::default();
+        let mut probe_state = ProbeState::default();
+        for _ in 0..10 {
+            default_probe_state.advance(default_config.query_interval);
+            probe_state.advance(config.query_interval);
+        }
+
+        assert_eq!(*probe_state.interval(), Duration::from_secs(2));
+        assert!(probe_state.interval() < default_probe_state.interval());
+    }
+}
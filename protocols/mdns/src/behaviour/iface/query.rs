@@ -79,6 +79,15 @@ impl MdnsPacket {
 
         Ok(None)
     }
+
+    /// Source address of the packet.
+    pub(crate) fn remote_addr(&self) -> &SocketAddr {
+        match self {
+            MdnsPacket::Query(query) => query.remote_addr(),
+            MdnsPacket::Response(response) => response.remote_addr(),
+            MdnsPacket::ServiceDiscovery(disc) => disc.remote_addr(),
+        }
+    }
 }
 
 /// A received mDNS query.
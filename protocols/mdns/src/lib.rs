@@ -35,7 +35,7 @@
 
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::time::Duration;
 
 mod behaviour;
@@ -72,6 +72,8 @@ pub struct Config {
     pub query_interval: Duration,
     /// Use IPv6 instead of IPv4.
     pub enable_ipv6: bool,
+    /// Addresses of the network interfaces to run mDNS on. `None` runs it on all interfaces.
+    pub interfaces: Option<Vec<IpAddr>>,
 }
 
 impl Default for Config {
@@ -80,6 +82,64 @@ impl Default for Config {
             ttl: Duration::from_secs(6 * 60),
             query_interval: Duration::from_secs(5 * 60),
             enable_ipv6: false,
+            interfaces: None,
         }
     }
 }
+
+impl Config {
+    /// Sets the TTL to use for mdns records. Remote peers that discovered us expire our addresses
+    /// after this duration unless they receive a new response.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the interval at which to poll the network for new peers.
+    ///
+    /// Increasing the interval reduces the traffic caused by mDNS, e.g. on battery-powered
+    /// devices.
+    pub fn with_query_interval(mut self, query_interval: Duration) -> Self {
+        self.query_interval = query_interval;
+        self
+    }
+
+    /// Restricts mDNS to the network interfaces with the given addresses.
+    ///
+    /// Queries and responses are only sent on these interfaces and peers discovered on other
+    /// interfaces are not reported. Loopback interfaces are never used.
+    pub fn with_interfaces(mut self, interfaces: Vec<IpAddr>) -> Self {
+        self.interfaces = Some(interfaces);
+        self
+    }
+
+    /// Whether mDNS should run on the interface with the given address.
+    pub(crate) fn is_interface_enabled(&self, addr: &IpAddr) -> bool {
+        self.interfaces
+            .as_ref()
+            .map_or(true, |interfaces| interfaces.contains(addr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_interfaces_enabled_by_default() {
+        let config = Config::default();
+
+        assert!(config.is_interface_enabled(&Ipv4Addr::new(192, 168, 1, 2).into()));
+        assert!(config.is_interface_enabled(&Ipv4Addr::new(10, 8, 0, 1).into()));
+    }
+
+    #[test]
+    fn only_configured_interfaces_enabled() {
+        let lan = IpAddr::from(Ipv4Addr::new(192, 168, 1, 2));
+        let vpn = IpAddr::from(Ipv4Addr::new(10, 8, 0, 1));
+        let config = Config::default().with_interfaces(vec![lan]);
+
+        assert!(config.is_interface_enabled(&lan));
+        assert!(!config.is_interface_enabled(&vpn));
+    }
+}
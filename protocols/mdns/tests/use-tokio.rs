@@ -16,11 +16,14 @@
 // AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
 // LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
-// DEALINGS IN THE SOFTWARE.use futures::StreamExt;
+// DEALINGS IN THE SOFTWARE.
+
 use futures::future::Either;
+use futures::StreamExt;
 use libp2p_mdns::{tokio::Behaviour, Config, Event};
 use libp2p_swarm::{Swarm, SwarmEvent};
 use libp2p_swarm_test::SwarmExt as _;
+use std::net::{IpAddr, Ipv4Addr};
 use std::time::Duration;
 use tracing_subscriber::EnvFilter;
 
@@ -81,6 +84,64 @@ async fn test_expired_tokio() {
     }
 }
 
+#[tokio::test]
+async fn test_no_discovery_on_excluded_interfaces_tokio() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    let interface = non_loopback_ipv4_address().await;
+
+    let mut a = create_swarm(Config::default().with_interfaces(vec![interface])).await;
+    let mut b = create_swarm(Config::default()).await;
+    let b_peer_id = *b.local_peer_id();
+    // Restricted to loopback, on which mDNS never runs, excluding the interface `b` is on.
+    let mut c =
+        create_swarm(Config::default().with_interfaces(vec![Ipv4Addr::LOCALHOST.into()])).await;
+    let c_peer_id = *c.local_peer_id();
+
+    let mut discovered_b = false;
+    let quiet_period = tokio::time::sleep(Duration::from_secs(1));
+    tokio::pin!(quiet_period);
+
+    let wait = async {
+        loop {
+            tokio::select! {
+                event = a.next_behaviour_event() => {
+                    if let Event::Discovered(peers) = event {
+                        assert!(peers.iter().all(|(p, _)| *p != c_peer_id));
+                        discovered_b |= peers.iter().any(|(p, _)| *p == b_peer_id);
+                    }
+                }
+                event = b.next_behaviour_event() => {
+                    if let Event::Discovered(peers) = event {
+                        assert!(peers.iter().all(|(p, _)| *p != c_peer_id));
+                    }
+                }
+                event = c.next_behaviour_event() => panic!("Unexpected event of excluded swarm: {event:?}"),
+                // Give `c` some time to discover peers, once discovery is known to work on this host.
+                _ = &mut quiet_period, if discovered_b => return,
+            }
+        }
+    };
+    async_std::future::timeout(Duration::from_secs(30), wait)
+        .await
+        .expect("`a` to discover `b` on the enabled interface");
+}
+
+/// Address of a non-loopback interface of the host, on which mDNS can run.
+async fn non_loopback_ipv4_address() -> IpAddr {
+    let mut interfaces = if_watch::tokio::IfWatcher::new().unwrap();
+
+    loop {
+        if let if_watch::IfEvent::Up(net) = interfaces.select_next_some().await.unwrap() {
+            if net.addr().is_ipv4() && !net.addr().is_loopback() {
+                return net.addr();
+            }
+        }
+    }
+}
+
 async fn run_discovery_test(config: Config) {
     let mut a = create_swarm(config.clone()).await;
     let a_peer_id = *a.local_peer_id();
@@ -22,7 +22,9 @@ use crate::{protocol, PROTOCOL_NAME};
 use futures::future::{BoxFuture, Either};
 use futures::prelude::*;
 use futures_timer::Delay;
+use instant::Instant;
 use libp2p_core::upgrade::ReadyUpgrade;
+use libp2p_identity::PeerId;
 use libp2p_swarm::handler::{
     ConnectionEvent, DialUpgradeError, FullyNegotiatedInbound, FullyNegotiatedOutbound,
 };
@@ -31,6 +33,8 @@ use libp2p_swarm::{
     SubstreamProtocol,
 };
 use std::collections::VecDeque;
+use std::num::NonZeroU32;
+use std::sync::Arc;
 use std::{
     error::Error,
     fmt, io,
@@ -40,14 +44,25 @@ use std::{
 use void::Void;
 
 /// The configuration for outbound pings.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Config {
     /// The timeout of an outbound ping.
     timeout: Duration,
     /// The duration between outbound pings.
     interval: Duration,
+    /// The size of the payload of an outbound ping in bytes.
+    payload_size: usize,
+    /// Callback invoked with the round-trip time of every successful outbound ping.
+    pub(crate) rtt_observer: Option<RttObserver>,
+    /// The number of consecutive failures after which the connection is closed.
+    max_failures: Option<NonZeroU32>,
 }
 
+/// The maximum size of the payload of an outbound ping.
+const MAX_PAYLOAD_SIZE: usize = 1024;
+
+type RttObserver = Arc<dyn Fn(PeerId, Duration) + Send + Sync>;
+
 impl Config {
     /// Creates a new [`Config`] with the following default settings:
     ///
@@ -63,6 +78,9 @@ impl Config {
         Self {
             timeout: Duration::from_secs(20),
             interval: Duration::from_secs(15),
+            payload_size: protocol::PING_SIZE,
+            rtt_observer: None,
+            max_failures: None,
         }
     }
 
@@ -77,6 +95,66 @@ impl Config {
         self.interval = d;
         self
     }
+
+    /// Sets the size of the payload of outbound pings in bytes.
+    ///
+    /// Defaults to 32 bytes, the payload size mandated by the ping specification.
+    /// Larger payloads can be used to probe the characteristics of a path, e.g. its MTU.
+    /// Remote peers answer pings in chunks of 32 bytes, thus the size must be a multiple of 32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is not a multiple of 32 or not within `32..=1024`.
+    pub fn with_payload_size(mut self, size: usize) -> Self {
+        assert!(
+            (protocol::PING_SIZE..=MAX_PAYLOAD_SIZE).contains(&size),
+            "ping payload size must be within {}..={MAX_PAYLOAD_SIZE}",
+            protocol::PING_SIZE
+        );
+        assert_eq!(
+            size % protocol::PING_SIZE,
+            0,
+            "ping payload size must be a multiple of {}",
+            protocol::PING_SIZE
+        );
+
+        self.payload_size = size;
+        self
+    }
+
+    /// Sets a callback that is invoked with the [`PeerId`] of the remote and the measured
+    /// round-trip time of every successful ping.
+    ///
+    /// Failed pings don't invoke the callback. They are still reported via [`Event`](crate::Event).
+    pub fn with_rtt_observer(
+        mut self,
+        f: impl Fn(PeerId, Duration) + Send + Sync + 'static,
+    ) -> Self {
+        self.rtt_observer = Some(Arc::new(f));
+        self
+    }
+
+    /// Sets the number of consecutive ping failures after which the connection is closed.
+    ///
+    /// A successful ping resets the count. The connection is treated as dead and closed once the
+    /// count reaches `n`, after reporting the last failure via [`Event`](crate::Event). By default,
+    /// failures are only reported and the connection is never closed because of them.
+    pub fn with_max_failures(mut self, n: NonZeroU32) -> Self {
+        self.max_failures = Some(n);
+        self
+    }
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("timeout", &self.timeout)
+            .field("interval", &self.interval)
+            .field("payload_size", &self.payload_size)
+            .field("rtt_observer", &self.rtt_observer.is_some())
+            .field("max_failures", &self.max_failures)
+            .finish()
+    }
 }
 
 impl Default for Config {
@@ -93,7 +171,8 @@ pub enum Failure {
     Timeout,
     /// The peer does not support the ping protocol.
     Unsupported,
-    /// The ping failed for reasons other than a timeout.
+    /// The ping failed for reasons other than a timeout, e.g. because the remote closed or reset
+    /// the stream.
     Other {
         error: Box<dyn std::error::Error + Send + 'static>,
     },
@@ -125,6 +204,18 @@ impl Error for Failure {
     }
 }
 
+/// The outcome of an outbound ping, reported by the [`Handler`] to the behaviour.
+#[derive(Debug)]
+pub struct OutboundPing {
+    /// The round-trip time of the ping or the reason it failed.
+    pub(crate) result: Result<Duration, Failure>,
+    /// The time elapsed since the ping attempt started, see [`Event::elapsed`](crate::Event::elapsed).
+    pub(crate) elapsed: Duration,
+    /// Whether the maximum number of consecutive failures was reached, see
+    /// [`Config::with_max_failures`].
+    pub(crate) max_failures_reached: bool,
+}
+
 /// Protocol handler that handles pinging the remote at a regular period
 /// and answering ping queries.
 pub struct Handler {
@@ -132,14 +223,18 @@ pub struct Handler {
     config: Config,
     /// The timer used for the delay to the next ping.
     interval: Delay,
-    /// Outbound ping failures that are pending to be processed by `poll()`.
-    pending_errors: VecDeque<Failure>,
+    /// Outbound ping failures that are pending to be processed by `poll()`, together with the
+    /// time elapsed since their ping attempt started.
+    pending_errors: VecDeque<(Failure, Duration)>,
     /// The number of consecutive ping failures that occurred.
     ///
     /// Each successful ping resets this counter to 0.
     failures: u32,
     /// The outbound ping state.
     outbound: Option<OutboundState>,
+    /// When the current outbound ping attempt started, i.e. when its stream was requested or, for
+    /// a reused stream, when the ping was sent.
+    ping_started: Instant,
     /// The inbound pong handler, i.e. if there is an inbound
     /// substream, this is always a future that waits for the
     /// next inbound ping to be answered.
@@ -170,6 +265,7 @@ impl Handler {
             pending_errors: VecDeque::with_capacity(2),
             failures: 0,
             outbound: None,
+            ping_started: Instant::now(),
             inbound: None,
             state: State::Active,
         }
@@ -202,13 +298,14 @@ impl Handler {
             StreamUpgradeError::Io(e) => Failure::Other { error: Box::new(e) },
         };
 
-        self.pending_errors.push_front(error);
+        self.pending_errors
+            .push_front((error, self.ping_started.elapsed()));
     }
 }
 
 impl ConnectionHandler for Handler {
     type FromBehaviour = Void;
-    type ToBehaviour = Result<Duration, Failure>;
+    type ToBehaviour = OutboundPing;
     type InboundProtocol = ReadyUpgrade<StreamProtocol>;
     type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
     type OutboundOpenInfo = ();
@@ -224,17 +321,18 @@ impl ConnectionHandler for Handler {
     fn poll(
         &mut self,
         cx: &mut Context<'_>,
-    ) -> Poll<ConnectionHandlerEvent<ReadyUpgrade<StreamProtocol>, (), Result<Duration, Failure>>>
-    {
+    ) -> Poll<ConnectionHandlerEvent<ReadyUpgrade<StreamProtocol>, (), OutboundPing>> {
         match self.state {
             State::Inactive { reported: true } => {
                 return Poll::Pending; // nothing to do on this connection
             }
             State::Inactive { reported: false } => {
                 self.state = State::Inactive { reported: true };
-                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Err(
-                    Failure::Unsupported,
-                )));
+                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(OutboundPing {
+                    result: Err(Failure::Unsupported),
+                    elapsed: self.ping_started.elapsed(),
+                    max_failures_reached: false,
+                }));
             }
             State::Active => {}
         }
@@ -258,18 +356,27 @@ impl ConnectionHandler for Handler {
 
         loop {
             // Check for outbound ping failures.
-            if let Some(error) = self.pending_errors.pop_back() {
+            if let Some((error, elapsed)) = self.pending_errors.pop_back() {
                 tracing::debug!("Ping failure: {:?}", error);
 
                 self.failures += 1;
 
+                let max_failures_reached = self
+                    .config
+                    .max_failures
+                    .map_or(false, |max| self.failures >= max.get());
+
                 // Note: For backward-compatibility the first failure is always "free"
-                // and silent. This allows peers who use a new substream
-                // for each ping to have successful ping exchanges with peers
-                // that use a single substream, since every successful ping
-                // resets `failures` to `0`.
-                if self.failures > 1 {
-                    return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Err(error)));
+                // and silent, unless it already closes the connection. This allows peers who
+                // use a new substream for each ping to have successful ping exchanges with peers
+                // that use a single substream, since every successful ping resets `failures`
+                // to `0`.
+                if self.failures > 1 || max_failures_reached {
+                    return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(OutboundPing {
+                        result: Err(error),
+                        elapsed,
+                        max_failures_reached,
+                    }));
                 }
             }
 
@@ -285,11 +392,18 @@ impl ConnectionHandler for Handler {
                         self.failures = 0;
                         self.interval.reset(self.config.interval);
                         self.outbound = Some(OutboundState::Idle(stream));
-                        return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Ok(rtt)));
+                        return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
+                            OutboundPing {
+                                result: Ok(rtt),
+                                elapsed: self.ping_started.elapsed(),
+                                max_failures_reached: false,
+                            },
+                        ));
                     }
                     Poll::Ready(Err(e)) => {
                         self.interval.reset(self.config.interval);
-                        self.pending_errors.push_front(e);
+                        self.pending_errors
+                            .push_front((e, self.ping_started.elapsed()));
                     }
                 },
                 Some(OutboundState::Idle(stream)) => match self.interval.poll_unpin(cx) {
@@ -298,8 +412,10 @@ impl ConnectionHandler for Handler {
                         break;
                     }
                     Poll::Ready(()) => {
+                        self.ping_started = Instant::now();
                         self.outbound = Some(OutboundState::Ping(
-                            send_ping(stream, self.config.timeout).boxed(),
+                            send_ping(stream, self.config.timeout, self.config.payload_size)
+                                .boxed(),
                         ));
                     }
                 },
@@ -310,6 +426,7 @@ impl ConnectionHandler for Handler {
                 None => match self.interval.poll_unpin(cx) {
                     Poll::Pending => break,
                     Poll::Ready(()) => {
+                        self.ping_started = Instant::now();
                         self.outbound = Some(OutboundState::OpenStream);
                         let protocol = SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL_NAME), ());
                         return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
@@ -346,7 +463,7 @@ impl ConnectionHandler for Handler {
             }) => {
                 stream.ignore_for_keep_alive();
                 self.outbound = Some(OutboundState::Ping(
-                    send_ping(stream, self.config.timeout).boxed(),
+                    send_ping(stream, self.config.timeout, self.config.payload_size).boxed(),
                 ));
             }
             ConnectionEvent::DialUpgradeError(dial_upgrade_error) => {
@@ -371,8 +488,12 @@ enum OutboundState {
 }
 
 /// A wrapper around [`protocol::send_ping`] that enforces a time out.
-async fn send_ping(stream: Stream, timeout: Duration) -> Result<(Stream, Duration), Failure> {
-    let ping = protocol::send_ping(stream);
+async fn send_ping(
+    stream: Stream,
+    timeout: Duration,
+    payload_size: usize,
+) -> Result<(Stream, Duration), Failure> {
+    let ping = protocol::send_ping(stream, payload_size);
     futures::pin_mut!(ping);
 
     match future::select(ping, Delay::new(timeout)).await {
@@ -54,14 +54,15 @@ use handler::Handler;
 use libp2p_core::{Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use libp2p_swarm::{
-    behaviour::FromSwarm, ConnectionDenied, ConnectionId, NetworkBehaviour, THandler,
-    THandlerInEvent, THandlerOutEvent, ToSwarm,
+    behaviour::FromSwarm, CloseConnection, ConnectionDenied, ConnectionId, NetworkBehaviour,
+    THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
 };
 use std::time::Duration;
 use std::{
     collections::VecDeque,
     task::{Context, Poll},
 };
+use void::Void;
 
 pub use self::protocol::PROTOCOL_NAME;
 pub use handler::{Config, Failure};
@@ -74,7 +75,7 @@ pub struct Behaviour {
     /// Configuration for outbound pings.
     config: Config,
     /// Queue of events to yield to the swarm.
-    events: VecDeque<Event>,
+    events: VecDeque<ToSwarm<Event, Void>>,
 }
 
 /// Event generated by the `Ping` network behaviour.
@@ -84,8 +85,17 @@ pub struct Event {
     pub peer: PeerId,
     /// The connection the ping was executed on.
     pub connection: ConnectionId,
-    /// The result of an inbound or outbound ping.
+    /// The result of an outbound ping, i.e. the round-trip time or the reason it failed.
+    ///
+    /// Events are only emitted for pings sent by the local node. Failures to answer pings of the
+    /// remote are not reported.
     pub result: Result<Duration, Failure>,
+    /// How long the local node waited for the outbound ping.
+    ///
+    /// This is the time from requesting the ping stream, or sending the ping on an already open
+    /// stream, until the pong was received or the ping failed. For a [`Failure::Timeout`] this is
+    /// at least the configured timeout.
+    pub elapsed: Duration,
 }
 
 impl Behaviour {
@@ -132,19 +142,37 @@ impl NetworkBehaviour for Behaviour {
         &mut self,
         peer: PeerId,
         connection: ConnectionId,
-        result: THandlerOutEvent<Self>,
+        handler::OutboundPing {
+            result,
+            elapsed,
+            max_failures_reached,
+        }: THandlerOutEvent<Self>,
     ) {
-        self.events.push_front(Event {
+        if let (Ok(rtt), Some(observer)) = (&result, &self.config.rtt_observer) {
+            observer(peer, *rtt);
+        }
+
+        self.events.push_front(ToSwarm::GenerateEvent(Event {
             peer,
             connection,
             result,
-        })
+            elapsed,
+        }));
+
+        if max_failures_reached {
+            tracing::debug!(%peer, ?connection, "Closing connection after consecutive ping failures");
+
+            self.events.push_front(ToSwarm::CloseConnection {
+                peer_id: peer,
+                connection: CloseConnection::One(connection),
+            });
+        }
     }
 
     #[tracing::instrument(level = "trace", name = "NetworkBehaviour::poll", skip(self))]
     fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
         if let Some(e) = self.events.pop_back() {
-            Poll::Ready(ToSwarm::GenerateEvent(e))
+            Poll::Ready(e)
         } else {
             Poll::Pending
         }
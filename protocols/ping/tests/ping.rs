@@ -20,12 +20,27 @@
 
 //! Integration tests for the `Ping` network behaviour.
 
+use futures::{AsyncReadExt, AsyncWriteExt};
+use libp2p_core::upgrade::{DeniedUpgrade, ReadyUpgrade};
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
 use libp2p_ping as ping;
-use libp2p_swarm::dummy;
+use libp2p_swarm::handler::{ConnectionEvent, FullyNegotiatedInbound};
+use libp2p_swarm::{
+    dummy, ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, StreamProtocol, SubstreamProtocol, THandler, THandlerInEvent,
+    THandlerOutEvent, ToSwarm,
+};
 use libp2p_swarm::{Swarm, SwarmEvent};
 use libp2p_swarm_test::SwarmExt;
 use quickcheck::*;
-use std::{num::NonZeroU8, time::Duration};
+use std::{
+    num::{NonZeroU32, NonZeroU8},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+use void::Void;
 
 #[test]
 fn ping_pong() {
@@ -55,6 +70,92 @@ fn ping_pong() {
     QuickCheck::new().tests(10).quickcheck(prop as fn(_))
 }
 
+#[test]
+fn ping_pong_with_large_payload() {
+    let cfg = ping::Config::new()
+        .with_interval(Duration::from_millis(10))
+        .with_payload_size(512);
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+    let mut swarm2 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+
+    async_std::task::block_on(async {
+        swarm1.listen().with_memory_addr_external().await;
+        swarm2.connect(&mut swarm1).await;
+
+        let ([e1], [e2]): ([ping::Event; 1], [ping::Event; 1]) =
+            libp2p_swarm_test::drive(&mut swarm1, &mut swarm2).await;
+
+        assert!(e1.result.is_ok());
+        assert!(e2.result.is_ok());
+    });
+}
+
+#[test]
+fn tampered_pong_is_reported_as_other_failure() {
+    let cfg = ping::Config::new()
+        .with_interval(Duration::from_millis(10))
+        .with_payload_size(512);
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| TamperInboundPings);
+    let mut swarm2 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg));
+
+    let event = async_std::task::block_on(async {
+        swarm1.listen().with_memory_addr_external().await;
+        swarm2.connect(&mut swarm1).await;
+        async_std::task::spawn(swarm1.loop_on_next());
+
+        swarm2
+            .wait(|e| match e {
+                SwarmEvent::Behaviour(e @ ping::Event { result: Err(_), .. }) => Some(e),
+                _ => None,
+            })
+            .await
+    });
+
+    match event.result {
+        Err(ping::Failure::Other { error }) => {
+            let error = error
+                .downcast_ref::<std::io::Error>()
+                .expect("an I/O error");
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        }
+        other => panic!("Unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+#[should_panic]
+fn payload_size_must_be_multiple_of_32() {
+    let _ = ping::Config::new().with_payload_size(100);
+}
+
+#[test]
+fn rtt_observer_receives_successful_pings() {
+    let samples = Arc::new(Mutex::new(Vec::new()));
+    let cfg = ping::Config::new()
+        .with_interval(Duration::from_millis(10))
+        .with_rtt_observer({
+            let samples = samples.clone();
+            move |peer, rtt| samples.lock().unwrap().push((peer, rtt))
+        });
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+    let mut swarm2 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+
+    let ([e1], [e2]): ([ping::Event; 1], [ping::Event; 1]) = async_std::task::block_on(async {
+        swarm1.listen().with_memory_addr_external().await;
+        swarm2.connect(&mut swarm1).await;
+
+        libp2p_swarm_test::drive(&mut swarm1, &mut swarm2).await
+    });
+
+    let samples = samples.lock().unwrap();
+    assert_eq!(samples.len(), 2);
+    assert!(samples.contains(&(e1.peer, e1.result.unwrap())));
+    assert!(samples.contains(&(e2.peer, e2.result.unwrap())));
+}
+
 fn assert_ping_rtt_less_than_50ms(e: ping::Event) {
     let rtt = e.result.expect("a ping success");
 
@@ -93,3 +194,264 @@ fn unsupported_doesnt_fail() {
 
     result.expect("node with ping should not fail connection due to unsupported protocol");
 }
+
+#[test]
+fn dropped_ping_stream_is_not_a_timeout() {
+    let timeout = Duration::from_secs(10);
+    let cfg = ping::Config::new()
+        .with_interval(Duration::from_millis(10))
+        .with_timeout(timeout);
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| DropInboundPings);
+    let mut swarm2 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg));
+
+    let event = async_std::task::block_on(async {
+        swarm1.listen().with_memory_addr_external().await;
+        swarm2.connect(&mut swarm1).await;
+        async_std::task::spawn(swarm1.loop_on_next());
+
+        swarm2
+            .wait(|e| match e {
+                SwarmEvent::Behaviour(e @ ping::Event { result: Err(_), .. }) => Some(e),
+                _ => None,
+            })
+            .await
+    });
+
+    assert!(matches!(event.result, Err(ping::Failure::Other { .. })));
+    assert!(event.elapsed < timeout);
+}
+
+#[test]
+fn connection_is_closed_after_max_failures() {
+    // The first failure is not reported.
+    assert_eq!(reported_failures_until_closed(3), 2);
+}
+
+#[test]
+fn connection_is_closed_after_single_max_failure() {
+    // The first failure is reported if it already closes the connection.
+    assert_eq!(reported_failures_until_closed(1), 1);
+}
+
+/// Pings a peer that never answers until the connection is closed because of `max_failures`
+/// consecutive ping failures, returning the number of reported failures.
+fn reported_failures_until_closed(max_failures: u32) -> usize {
+    let cfg = ping::Config::new()
+        .with_interval(Duration::from_millis(10))
+        .with_max_failures(NonZeroU32::new(max_failures).unwrap());
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| DropInboundPings);
+    let mut swarm2 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg));
+
+    let (failures, cause) = async_std::task::block_on(async {
+        swarm1.listen().with_memory_addr_external().await;
+        swarm2.connect(&mut swarm1).await;
+        async_std::task::spawn(swarm1.loop_on_next());
+
+        let mut failures = 0;
+        loop {
+            match swarm2.next_swarm_event().await {
+                SwarmEvent::Behaviour(ping::Event { result: Err(_), .. }) => failures += 1,
+                SwarmEvent::ConnectionClosed { cause, .. } => break (failures, cause),
+                _ => {}
+            }
+        }
+    });
+
+    assert!(cause.is_none(), "connection should be closed actively");
+
+    failures
+}
+
+/// Accepts inbound ping streams and drops them without answering.
+struct DropInboundPings;
+
+impl NetworkBehaviour for DropInboundPings {
+    type ConnectionHandler = DropInboundPingsHandler;
+    type ToSwarm = Void;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(DropInboundPingsHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(DropInboundPingsHandler)
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
+
+struct DropInboundPingsHandler;
+
+impl ConnectionHandler for DropInboundPingsHandler {
+    type FromBehaviour = Void;
+    type ToBehaviour = Void;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = DeniedUpgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = Void;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(ReadyUpgrade::new(ping::PROTOCOL_NAME), ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        void::unreachable(event)
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        Poll::Pending
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        if let ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+            protocol: stream,
+            ..
+        }) = event
+        {
+            drop(stream);
+        }
+    }
+}
+
+/// Answers inbound pings of 512 bytes with a pong whose first byte is flipped.
+struct TamperInboundPings;
+
+impl NetworkBehaviour for TamperInboundPings {
+    type ConnectionHandler = TamperInboundPingsHandler;
+    type ToSwarm = Void;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(TamperInboundPingsHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(TamperInboundPingsHandler)
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
+
+struct TamperInboundPingsHandler;
+
+impl ConnectionHandler for TamperInboundPingsHandler {
+    type FromBehaviour = Void;
+    type ToBehaviour = Void;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = DeniedUpgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = Void;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(ReadyUpgrade::new(ping::PROTOCOL_NAME), ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        void::unreachable(event)
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        Poll::Pending
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        if let ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+            protocol: mut stream,
+            ..
+        }) = event
+        {
+            async_std::task::spawn(async move {
+                let mut payload = [0u8; 512];
+                stream.read_exact(&mut payload).await?;
+                payload[0] ^= 0xff;
+                stream.write_all(&payload).await?;
+                stream.flush().await
+            });
+        }
+    }
+}
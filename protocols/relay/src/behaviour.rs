@@ -49,11 +49,21 @@ use std::time::Duration;
 /// [`Config::max_circuit_duration`] may not exceed [`u32::MAX`].
 pub struct Config {
     pub max_reservations: usize,
+    /// Maximum number of reservations a single peer may hold at once.
+    ///
+    /// New reservations beyond this limit are denied with
+    /// [`RESOURCE_LIMIT_EXCEEDED`](proto::Status::RESOURCE_LIMIT_EXCEEDED) and reported as
+    /// [`Event::ReservationLimitPerPeerReached`]. Renewals of existing reservations are not affected.
     pub max_reservations_per_peer: usize,
     pub reservation_duration: Duration,
     pub reservation_rate_limiters: Vec<Box<dyn rate_limiter::RateLimiter>>,
 
     pub max_circuits: usize,
+    /// Maximum number of circuits a single source peer may have open at once.
+    ///
+    /// Circuit requests beyond this limit are denied with
+    /// [`RESOURCE_LIMIT_EXCEEDED`](proto::Status::RESOURCE_LIMIT_EXCEEDED) and reported as
+    /// [`Event::CircuitLimitPerPeerReached`].
     pub max_circuits_per_peer: usize,
     pub max_circuit_duration: Duration,
     pub max_circuit_bytes: u64,
@@ -188,6 +198,15 @@ pub enum Event {
     },
     /// An inbound reservation has timed out.
     ReservationTimedOut { src_peer_id: PeerId },
+    /// An inbound reservation request is denied because the peer already holds
+    /// [`Config::max_reservations_per_peer`] reservations.
+    ReservationLimitPerPeerReached { src_peer_id: PeerId },
+    /// An inbound circuit request is denied because the source peer already has
+    /// [`Config::max_circuits_per_peer`] circuits.
+    CircuitLimitPerPeerReached {
+        src_peer_id: PeerId,
+        dst_peer_id: PeerId,
+    },
     /// An inbound circuit request has been denied.
     CircuitReqDenied {
         src_peer_id: PeerId,
@@ -380,15 +399,24 @@ impl NetworkBehaviour for Behaviour {
                      denies all inbound substreams."
                 );
 
-                let action = if
                 // Deny if it is a new reservation and exceeds `max_reservations_per_peer`.
-                (!renewed
+                let peer_limit_reached = !renewed
                     && self
                         .reservations
                         .get(&event_source)
                         .map(|cs| cs.len())
                         .unwrap_or(0)
-                        > self.config.max_reservations_per_peer)
+                        >= self.config.max_reservations_per_peer;
+
+                if peer_limit_reached {
+                    self.queued_actions.push_back(ToSwarm::GenerateEvent(
+                        Event::ReservationLimitPerPeerReached {
+                            src_peer_id: event_source,
+                        },
+                    ));
+                }
+
+                let action = if peer_limit_reached
                     // Deny if it exceeds `max_reservations`.
                     || self
                         .reservations
@@ -514,8 +542,19 @@ impl NetworkBehaviour for Behaviour {
                      denies all inbound substreams."
                 );
 
-                let action = if self.circuits.num_circuits_of_peer(event_source)
-                    > self.config.max_circuits_per_peer
+                let peer_limit_reached = self.circuits.num_circuits_of_peer(event_source)
+                    >= self.config.max_circuits_per_peer;
+
+                if peer_limit_reached {
+                    self.queued_actions.push_back(ToSwarm::GenerateEvent(
+                        Event::CircuitLimitPerPeerReached {
+                            src_peer_id: event_source,
+                            dst_peer_id: inbound_circuit_req.dst(),
+                        },
+                    ));
+                }
+
+                let action = if peer_limit_reached
                     || self.circuits.len() >= self.config.max_circuits
                     || !self
                         .config
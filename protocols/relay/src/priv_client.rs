@@ -20,10 +20,12 @@
 
 //! [`NetworkBehaviour`] to act as a circuit relay v2 **client**.
 
+mod auto_reserve;
 pub(crate) mod handler;
 pub(crate) mod transport;
 
 use crate::multiaddr_ext::MultiaddrExt;
+use crate::priv_client::auto_reserve::AutoReserve;
 use crate::priv_client::handler::Handler;
 use crate::protocol::{self, inbound_stop};
 use bytes::Bytes;
@@ -34,9 +36,12 @@ use futures::io::{AsyncRead, AsyncWrite};
 use futures::ready;
 use futures::stream::StreamExt;
 use libp2p_core::multiaddr::Protocol;
+use libp2p_core::transport::ListenerId;
 use libp2p_core::{Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
-use libp2p_swarm::behaviour::{ConnectionClosed, ConnectionEstablished, FromSwarm};
+use libp2p_swarm::behaviour::{
+    ConnectionClosed, ConnectionEstablished, FromSwarm, ListenerClosed, ListenerError,
+};
 use libp2p_swarm::dial_opts::DialOpts;
 use libp2p_swarm::{
     dummy, ConnectionDenied, ConnectionHandler, ConnectionId, DialFailure, NetworkBehaviour,
@@ -46,6 +51,7 @@ use std::collections::{hash_map, HashMap, VecDeque};
 use std::io::{Error, ErrorKind, IoSlice};
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use transport::Transport;
 use void::Void;
 
@@ -59,6 +65,17 @@ pub enum Event {
         renewal: bool,
         limit: Option<protocol::Limit>,
     },
+    /// The reservation on a relay expires in `expires_in` unless it is renewed in the meantime.
+    ///
+    /// Only emitted if [`Config::reservation_expiry_lead_time`] is set.
+    ReservationExpiring {
+        relay_peer_id: PeerId,
+        expires_in: Duration,
+    },
+    /// The reservation on a relay expired without being renewed.
+    ///
+    /// Only emitted if [`Config::reservation_expiry_lead_time`] is set.
+    ReservationExpired { relay_peer_id: PeerId },
     OutboundCircuitEstablished {
         relay_peer_id: PeerId,
         limit: Option<protocol::Limit>,
@@ -68,6 +85,20 @@ pub enum Event {
         src_peer_id: PeerId,
         limit: Option<protocol::Limit>,
     },
+    /// The set of relays on which [`Behaviour::auto_reserve`] holds an active reservation
+    /// changed.
+    ActiveRelaysChanged { relays: Vec<PeerId> },
+}
+
+/// Configuration for the client [`Behaviour`].
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// How long before a reservation expires to emit [`Event::ReservationExpiring`], e.g. to
+    /// renew it proactively or to pick a different relay.
+    ///
+    /// If `None`, the default, neither [`Event::ReservationExpiring`] nor
+    /// [`Event::ReservationExpired`] are emitted.
+    pub reservation_expiry_lead_time: Option<Duration>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -80,6 +111,7 @@ enum ReservationStatus {
 /// functionality of the circuit relay v2 protocol.
 pub struct Behaviour {
     local_peer_id: PeerId,
+    config: Config,
 
     from_transport: Receiver<transport::TransportToBehaviourMsg>,
     /// Set of directly connected peers, i.e. not connected via a relayed
@@ -95,23 +127,82 @@ pub struct Behaviour {
     queued_actions: VecDeque<ToSwarm<Event, Either<handler::In, Void>>>,
 
     pending_handler_commands: HashMap<ConnectionId, handler::In>,
+
+    /// Reservations maintained through [`Behaviour::auto_reserve`].
+    auto_reserve: AutoReserve,
 }
 
 /// Create a new client relay [`Behaviour`] with it's corresponding [`Transport`].
 pub fn new(local_peer_id: PeerId) -> (Transport, Behaviour) {
+    new_with_config(local_peer_id, Config::default())
+}
+
+/// Create a new client relay [`Behaviour`] with the given [`Config`] and it's corresponding
+/// [`Transport`].
+pub fn new_with_config(local_peer_id: PeerId, config: Config) -> (Transport, Behaviour) {
     let (transport, from_transport) = Transport::new();
     let behaviour = Behaviour {
         local_peer_id,
+        config,
         from_transport,
         directly_connected_peers: Default::default(),
         reservation_addresses: Default::default(),
         queued_actions: Default::default(),
         pending_handler_commands: Default::default(),
+        auto_reserve: Default::default(),
     };
     (transport, behaviour)
 }
 
 impl Behaviour {
+    /// Maintain `desired` reservations across the given pool of candidate relays.
+    ///
+    /// Relays are selected automatically and re-selected whenever a reservation fails, expires or
+    /// its relay connection closes, preferring relays with a lower RTT. The RTT of a relay is the
+    /// time it took for its last reservation to be accepted. Relays on which a reservation failed
+    /// are retried after a backoff.
+    ///
+    /// Calling this again replaces the pool, keeping the existing reservations on relays that are
+    /// still part of it. Changes to the set of active relays are reported via
+    /// [`Event::ActiveRelaysChanged`].
+    pub fn auto_reserve(&mut self, candidates: Vec<(PeerId, Multiaddr)>, desired: usize) {
+        let active_relays = self.auto_reserve.active_relays();
+
+        for id in self.auto_reserve.replace(candidates, desired) {
+            self.queued_actions
+                .push_back(ToSwarm::RemoveListener { id });
+        }
+
+        self.drive_auto_reserve(active_relays);
+    }
+
+    /// Makes new reservations as needed and reports whether the set of active relays changed
+    /// compared to `previous_active_relays`.
+    fn drive_auto_reserve(&mut self, previous_active_relays: Vec<PeerId>) {
+        for opts in self.auto_reserve.select() {
+            self.queued_actions.push_back(ToSwarm::ListenOn { opts });
+        }
+
+        let relays = self.auto_reserve.active_relays();
+        if relays != previous_active_relays {
+            self.queued_actions
+                .push_back(ToSwarm::GenerateEvent(Event::ActiveRelaysChanged {
+                    relays,
+                }));
+        }
+    }
+
+    /// Returns `false` if the listener does not belong to an automatic reservation.
+    fn on_listener_closed(&mut self, listener_id: ListenerId) -> bool {
+        let active_relays = self.auto_reserve.active_relays();
+        if !self.auto_reserve.on_listener_closed(listener_id) {
+            return false;
+        }
+        self.drive_auto_reserve(active_relays);
+
+        true
+    }
+
     fn on_connection_closed(
         &mut self,
         ConnectionClosed {
@@ -163,7 +254,12 @@ impl NetworkBehaviour for Behaviour {
         if local_addr.is_relayed() {
             return Ok(Either::Right(dummy::ConnectionHandler));
         }
-        let mut handler = Handler::new(self.local_peer_id, peer, remote_addr.clone());
+        let mut handler = Handler::new(
+            self.local_peer_id,
+            peer,
+            remote_addr.clone(),
+            self.config.reservation_expiry_lead_time,
+        );
 
         if let Some(event) = self.pending_handler_commands.remove(&connection_id) {
             handler.on_behaviour_event(event)
@@ -183,7 +279,12 @@ impl NetworkBehaviour for Behaviour {
             return Ok(Either::Right(dummy::ConnectionHandler));
         }
 
-        let mut handler = Handler::new(self.local_peer_id, peer, addr.clone());
+        let mut handler = Handler::new(
+            self.local_peer_id,
+            peer,
+            addr.clone(),
+            self.config.reservation_expiry_lead_time,
+        );
 
         if let Some(event) = self.pending_handler_commands.remove(&connection_id) {
             handler.on_behaviour_event(event)
@@ -222,6 +323,15 @@ impl NetworkBehaviour for Behaviour {
                 self.reservation_addresses.remove(&connection_id);
                 self.pending_handler_commands.remove(&connection_id);
             }
+            FromSwarm::ListenerClosed(ListenerClosed { listener_id, .. }) => {
+                self.on_listener_closed(listener_id);
+            }
+            FromSwarm::ListenerError(ListenerError { listener_id, .. }) => {
+                if self.on_listener_closed(listener_id) {
+                    self.queued_actions
+                        .push_back(ToSwarm::RemoveListener { id: listener_id });
+                }
+            }
             _ => {}
         }
     }
@@ -250,12 +360,34 @@ impl NetworkBehaviour for Behaviour {
                         .push_back(ToSwarm::ExternalAddrConfirmed(addr.clone()));
                 }
 
+                if !renewal {
+                    let active_relays = self.auto_reserve.active_relays();
+                    self.auto_reserve.on_reservation_accepted(event_source);
+                    self.drive_auto_reserve(active_relays);
+                }
+
                 Event::ReservationReqAccepted {
                     relay_peer_id: event_source,
                     renewal,
                     limit,
                 }
             }
+            handler::Event::ReservationExpiring { expires_in } => Event::ReservationExpiring {
+                relay_peer_id: event_source,
+                expires_in,
+            },
+            handler::Event::ReservationExpired => {
+                let active_relays = self.auto_reserve.active_relays();
+                if let Some(id) = self.auto_reserve.on_reservation_expired(event_source) {
+                    self.queued_actions
+                        .push_back(ToSwarm::RemoveListener { id });
+                }
+                self.drive_auto_reserve(active_relays);
+
+                Event::ReservationExpired {
+                    relay_peer_id: event_source,
+                }
+            }
             handler::Event::OutboundCircuitEstablished { limit } => {
                 Event::OutboundCircuitEstablished {
                     relay_peer_id: event_source,
@@ -279,6 +411,15 @@ impl NetworkBehaviour for Behaviour {
             return Poll::Ready(action);
         }
 
+        if self.auto_reserve.poll_retry(cx).is_ready() {
+            let active_relays = self.auto_reserve.active_relays();
+            self.drive_auto_reserve(active_relays);
+
+            if let Some(action) = self.queued_actions.pop_front() {
+                return Poll::Ready(action);
+            }
+        }
+
         let action = match ready!(self.from_transport.poll_next_unpin(cx)) {
             Some(transport::TransportToBehaviourMsg::ListenReq {
                 relay_peer_id,
@@ -0,0 +1,257 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Automatic selection of relays from a pool of candidates, see
+//! [`Behaviour::auto_reserve`](super::Behaviour::auto_reserve).
+
+use futures::FutureExt;
+use futures_timer::Delay;
+use instant::Instant;
+use libp2p_core::multiaddr::Protocol;
+use libp2p_core::transport::ListenerId;
+use libp2p_core::Multiaddr;
+use libp2p_identity::PeerId;
+use libp2p_swarm::ListenOpts;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// How long to wait before retrying a relay on which a reservation failed.
+const RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Maintains a desired number of reservations across a pool of candidate relays.
+#[derive(Default)]
+pub(crate) struct AutoReserve {
+    desired: usize,
+    candidates: Vec<Candidate>,
+    /// Fires once the earliest candidate in backoff may be retried.
+    retry: Option<Delay>,
+}
+
+struct Candidate {
+    peer_id: PeerId,
+    address: Multiaddr,
+    state: State,
+    /// Time it took for the last reservation on this relay to be accepted.
+    rtt: Option<Duration>,
+}
+
+enum State {
+    Idle {
+        retry_at: Option<Instant>,
+    },
+    Pending {
+        listener_id: ListenerId,
+        since: Instant,
+    },
+    Active {
+        listener_id: ListenerId,
+    },
+}
+
+impl Candidate {
+    fn listener_id(&self) -> Option<ListenerId> {
+        match self.state {
+            State::Idle { .. } => None,
+            State::Pending { listener_id, .. } | State::Active { listener_id } => Some(listener_id),
+        }
+    }
+
+    fn is_eligible(&self, now: Instant) -> bool {
+        match self.state {
+            State::Idle { retry_at } => retry_at.map_or(true, |at| at <= now),
+            State::Pending { .. } | State::Active { .. } => false,
+        }
+    }
+
+    fn fail(&mut self) {
+        self.state = State::Idle {
+            retry_at: Some(Instant::now() + RETRY_BACKOFF),
+        };
+    }
+}
+
+impl AutoReserve {
+    /// Replaces the pool of candidates and the desired number of reservations.
+    ///
+    /// Reservations on relays that remain candidates are kept. Returns the listeners of the
+    /// reservations on relays that are no longer candidates.
+    pub(crate) fn replace(
+        &mut self,
+        candidates: Vec<(PeerId, Multiaddr)>,
+        desired: usize,
+    ) -> Vec<ListenerId> {
+        let mut previous = std::mem::take(&mut self.candidates);
+
+        self.desired = desired;
+        self.retry = None;
+        self.candidates = candidates
+            .into_iter()
+            .map(
+                |(peer_id, address)| match previous.iter().position(|c| c.peer_id == peer_id) {
+                    Some(i) => Candidate {
+                        address,
+                        ..previous.swap_remove(i)
+                    },
+                    None => Candidate {
+                        peer_id,
+                        address,
+                        state: State::Idle { retry_at: None },
+                        rtt: None,
+                    },
+                },
+            )
+            .collect();
+
+        let mut to_remove: Vec<_> = previous.iter().filter_map(Candidate::listener_id).collect();
+
+        // Drop surplus reservations, keeping the ones on the fastest relays.
+        let mut reserved: Vec<_> = self
+            .candidates
+            .iter_mut()
+            .filter(|c| c.listener_id().is_some())
+            .collect();
+        reserved.sort_by_key(|c| (c.rtt.is_none(), c.rtt));
+        for candidate in reserved.into_iter().skip(desired) {
+            to_remove.extend(candidate.listener_id());
+            candidate.state = State::Idle { retry_at: None };
+        }
+
+        to_remove
+    }
+
+    /// Selects relays to make new reservations on until the desired number of reservations is
+    /// pending or active.
+    ///
+    /// Relays with a lower RTT are preferred, followed by relays we did not make a reservation on
+    /// yet, in the order they were given.
+    pub(crate) fn select(&mut self) -> Vec<ListenOpts> {
+        let now = Instant::now();
+        let reserved = self
+            .candidates
+            .iter()
+            .filter(|c| c.listener_id().is_some())
+            .count();
+        let missing = self.desired.saturating_sub(reserved);
+
+        let mut eligible: Vec<_> = self
+            .candidates
+            .iter_mut()
+            .filter(|c| c.is_eligible(now))
+            .collect();
+        eligible.sort_by_key(|c| (c.rtt.is_none(), c.rtt));
+
+        let selected: Vec<_> = eligible
+            .into_iter()
+            .take(missing)
+            .map(|candidate| {
+                let opts = ListenOpts::new(
+                    candidate
+                        .address
+                        .clone()
+                        .with(Protocol::P2p(candidate.peer_id))
+                        .with(Protocol::P2pCircuit),
+                );
+                tracing::debug!(relay=%candidate.peer_id, "Automatically reserving on relay");
+                candidate.state = State::Pending {
+                    listener_id: opts.listener_id(),
+                    since: now,
+                };
+                opts
+            })
+            .collect();
+
+        if selected.len() < missing {
+            self.retry = self
+                .candidates
+                .iter()
+                .filter_map(|c| match c.state {
+                    State::Idle { retry_at } => retry_at,
+                    _ => None,
+                })
+                .min()
+                .map(|at| Delay::new(at.saturating_duration_since(now)));
+        }
+
+        selected
+    }
+
+    /// Marks a pending reservation on the given relay as active.
+    pub(crate) fn on_reservation_accepted(&mut self, relay_peer_id: PeerId) {
+        for candidate in self
+            .candidates
+            .iter_mut()
+            .filter(|c| c.peer_id == relay_peer_id)
+        {
+            if let State::Pending { listener_id, since } = candidate.state {
+                candidate.rtt = Some(since.elapsed());
+                candidate.state = State::Active { listener_id };
+            }
+        }
+    }
+
+    /// Marks the reservation on the given relay as failed, returning its listener.
+    pub(crate) fn on_reservation_expired(&mut self, relay_peer_id: PeerId) -> Option<ListenerId> {
+        let candidate = self
+            .candidates
+            .iter_mut()
+            .find(|c| c.peer_id == relay_peer_id && c.listener_id().is_some())?;
+        let listener_id = candidate.listener_id();
+        candidate.fail();
+
+        listener_id
+    }
+
+    /// Marks the reservation belonging to the given listener as failed.
+    ///
+    /// Returns `false` if the listener does not belong to an automatic reservation.
+    pub(crate) fn on_listener_closed(&mut self, listener_id: ListenerId) -> bool {
+        match self
+            .candidates
+            .iter_mut()
+            .find(|c| c.listener_id() == Some(listener_id))
+        {
+            Some(candidate) => {
+                candidate.fail();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the relays we currently have an active reservation on.
+    pub(crate) fn active_relays(&self) -> Vec<PeerId> {
+        self.candidates
+            .iter()
+            .filter(|c| matches!(c.state, State::Active { .. }))
+            .map(|c| c.peer_id)
+            .collect()
+    }
+
+    /// Resolves once a relay in backoff may be retried.
+    pub(crate) fn poll_retry(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        match self.retry.as_mut().map(|delay| delay.poll_unpin(cx)) {
+            Some(Poll::Ready(())) => {
+                self.retry = None;
+                Poll::Ready(())
+            }
+            _ => Poll::Pending,
+        }
+    }
+}
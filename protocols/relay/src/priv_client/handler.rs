@@ -24,6 +24,7 @@ use crate::{priv_client, proto, HOP_PROTOCOL_NAME, STOP_PROTOCOL_NAME};
 use futures::channel::{mpsc, oneshot};
 use futures::future::FutureExt;
 use futures_timer::Delay;
+use instant::Instant;
 use libp2p_core::multiaddr::Protocol;
 use libp2p_core::upgrade::ReadyUpgrade;
 use libp2p_core::Multiaddr;
@@ -81,6 +82,10 @@ pub enum Event {
         renewal: bool,
         limit: Option<protocol::Limit>,
     },
+    /// The reservation expires in `expires_in` unless it is renewed.
+    ReservationExpiring { expires_in: Duration },
+    /// The reservation expired without being renewed.
+    ReservationExpired,
     /// An outbound circuit has been established.
     OutboundCircuitEstablished { limit: Option<protocol::Limit> },
     /// An inbound circuit has been established.
@@ -124,10 +129,20 @@ pub struct Handler {
         futures_bounded::FuturesSet<Result<(), inbound_stop::Error>>,
 
     reservation: Reservation,
+
+    /// How long before the expiry of a reservation to report it as expiring, if at all.
+    reservation_expiry_lead_time: Option<Duration>,
+    /// Tracks the expiry of the last accepted reservation.
+    reservation_expiry: Option<ReservationExpiry>,
 }
 
 impl Handler {
-    pub fn new(local_peer_id: PeerId, remote_peer_id: PeerId, remote_addr: Multiaddr) -> Self {
+    pub fn new(
+        local_peer_id: PeerId,
+        remote_peer_id: PeerId,
+        remote_addr: Multiaddr,
+        reservation_expiry_lead_time: Option<Duration>,
+    ) -> Self {
         Self {
             local_peer_id,
             remote_peer_id,
@@ -151,6 +166,8 @@ impl Handler {
                 MAX_NUMBER_DENYING_CIRCUIT,
             ),
             reservation: Reservation::None,
+            reservation_expiry_lead_time,
+            reservation_expiry: None,
         }
     }
 
@@ -276,11 +293,16 @@ impl ConnectionHandler for Handler {
                 Poll::Ready((
                     Ok(Ok(outbound_hop::Reservation {
                         renewal_timeout,
+                        expires_in,
                         addrs,
                         limit,
                     })),
                     to_listener,
                 )) => {
+                    self.reservation_expiry = self
+                        .reservation_expiry_lead_time
+                        .map(|lead_time| ReservationExpiry::new(expires_in, lead_time));
+
                     return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
                         self.reservation.accepted(
                             renewal_timeout,
@@ -401,6 +423,15 @@ impl ConnectionHandler for Handler {
                 Poll::Pending => {}
             }
 
+            if let Some(expiry) = self.reservation_expiry.as_mut() {
+                if let Poll::Ready(event) = expiry.poll(cx) {
+                    if let Event::ReservationExpired = event {
+                        self.reservation_expiry = None;
+                    }
+                    return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event));
+                }
+            }
+
             if let Poll::Ready(Some(to_listener)) = self.reservation.poll(cx) {
                 self.pending_requests
                     .push_back(PendingRequest::Reserve { to_listener });
@@ -614,6 +645,41 @@ impl Reservation {
     }
 }
 
+/// Timers for reporting the upcoming and actual expiry of a reservation.
+struct ReservationExpiry {
+    expires_at: Instant,
+    /// Fires the configured lead time before `expires_at`. `None` once fired.
+    expiring: Option<Delay>,
+    expired: Delay,
+}
+
+impl ReservationExpiry {
+    fn new(expires_in: Duration, lead_time: Duration) -> Self {
+        Self {
+            expires_at: Instant::now() + expires_in,
+            expiring: Some(Delay::new(expires_in.saturating_sub(lead_time))),
+            expired: Delay::new(expires_in),
+        }
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Event> {
+        if let Some(expiring) = self.expiring.as_mut() {
+            if expiring.poll_unpin(cx).is_ready() {
+                self.expiring = None;
+                return Poll::Ready(Event::ReservationExpiring {
+                    expires_in: self.expires_at.saturating_duration_since(Instant::now()),
+                });
+            }
+        }
+
+        if self.expired.poll_unpin(cx).is_ready() {
+            return Poll::Ready(Event::ReservationExpired);
+        }
+
+        Poll::Pending
+    }
+}
+
 pub(crate) enum PendingRequest {
     Reserve {
         /// A channel into the [`Transport`](priv_client::Transport).
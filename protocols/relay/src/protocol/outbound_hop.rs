@@ -102,6 +102,8 @@ impl From<quick_protobuf_codec::Error> for ReserveError {
 
 pub(crate) struct Reservation {
     pub(crate) renewal_timeout: Delay,
+    /// The remaining duration of the reservation.
+    pub(crate) expires_in: Duration,
     pub(crate) addrs: Vec<Multiaddr>,
     pub(crate) limit: Option<Limit>,
 }
@@ -185,7 +187,7 @@ pub(crate) async fn make_reservation(stream: Stream) -> Result<Reservation, Rese
         .collect::<Result<Vec<Multiaddr>, _>>()
         .map_err(|_| ReserveError::Protocol(ProtocolViolation::InvalidReservationAddrs))?;
 
-    let renewal_timeout = reservation
+    let expires_in = reservation
         .expire
         .checked_sub(
             SystemTime::now()
@@ -193,16 +195,16 @@ pub(crate) async fn make_reservation(stream: Stream) -> Result<Reservation, Rese
                 .unwrap()
                 .as_secs(),
         )
-        // Renew the reservation after 3/4 of the reservation expiration timestamp.
-        .and_then(|duration| duration.checked_sub(duration / 4))
-        .map(Duration::from_secs)
-        .map(Delay::new)
         .ok_or(ReserveError::Protocol(
             ProtocolViolation::InvalidReservationExpiration,
         ))?;
 
+    // Renew the reservation after 3/4 of the reservation expiration timestamp.
+    let renewal_timeout = Delay::new(Duration::from_secs(expires_in - expires_in / 4));
+
     Ok(Reservation {
         renewal_timeout,
+        expires_in: Duration::from_secs(expires_in),
         addrs,
         limit,
     })
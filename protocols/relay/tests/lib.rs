@@ -37,6 +37,7 @@ use libp2p_swarm::dial_opts::DialOpts;
 use libp2p_swarm::{Config, DialError, NetworkBehaviour, Swarm, SwarmEvent};
 use libp2p_swarm_test::SwarmExt;
 use std::error::Error;
+use std::num::NonZeroU32;
 use std::time::Duration;
 use tracing_subscriber::EnvFilter;
 
@@ -182,6 +183,84 @@ fn new_reservation_to_same_relay_replaces_old() {
     });
 }
 
+#[test]
+fn reservation_expiring_before_expired() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+    let mut pool = LocalPool::new();
+
+    let relay_addr = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+    // Refuse the renewal so that the reservation expires.
+    let mut relay = build_relay_with_config(
+        relay::Config {
+            reservation_duration: Duration::from_secs(4),
+            ..Default::default()
+        }
+        .reservation_rate_per_peer(NonZeroU32::new(1).unwrap(), Duration::from_secs(60)),
+    );
+    let relay_peer_id = *relay.local_peer_id();
+
+    relay.listen_on(relay_addr.clone()).unwrap();
+    relay.add_external_address(relay_addr.clone());
+    spawn_swarm_on_pool(&pool, relay);
+
+    let client_addr = relay_addr
+        .with(Protocol::P2p(relay_peer_id))
+        .with(Protocol::P2pCircuit);
+    let mut client = build_client_with_relay_config(
+        Config::with_async_std_executor().with_idle_connection_timeout(Duration::from_secs(10)),
+        relay::client::Config {
+            reservation_expiry_lead_time: Some(Duration::from_secs(2)),
+        },
+    );
+    let client_peer_id = *client.local_peer_id();
+
+    client.listen_on(client_addr.clone()).unwrap();
+
+    // Wait for connection to relay.
+    assert!(pool.run_until(wait_for_dial(&mut client, relay_peer_id)));
+
+    // Wait for initial reservation.
+    pool.run_until(wait_for_reservation(
+        &mut client,
+        client_addr.with(Protocol::P2p(client_peer_id)),
+        relay_peer_id,
+        false, // No renewal.
+    ));
+
+    pool.run_until(async {
+        let mut expiring = false;
+        loop {
+            match client.select_next_some().await {
+                SwarmEvent::Behaviour(ClientEvent::Relay(
+                    relay::client::Event::ReservationExpiring {
+                        relay_peer_id: peer_id,
+                        expires_in,
+                    },
+                )) => {
+                    assert_eq!(peer_id, relay_peer_id);
+                    assert!(expires_in <= Duration::from_secs(2));
+                    expiring = true;
+                }
+                SwarmEvent::Behaviour(ClientEvent::Relay(
+                    relay::client::Event::ReservationExpired {
+                        relay_peer_id: peer_id,
+                    },
+                )) => {
+                    assert_eq!(peer_id, relay_peer_id);
+                    assert!(expiring, "expected `ReservationExpiring` first");
+                    break;
+                }
+                SwarmEvent::Behaviour(ClientEvent::Relay(
+                    relay::client::Event::ReservationReqAccepted { .. },
+                )) => panic!("renewal should have been refused"),
+                _ => {}
+            }
+        }
+    });
+}
+
 #[test]
 fn connect() {
     let _ = tracing_subscriber::fmt()
@@ -394,6 +473,158 @@ fn propagate_connect_error_to_unknown_peer_to_dialer() {
     ));
 }
 
+#[test]
+fn deny_reservation_exceeding_per_peer_limit() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+    let mut pool = LocalPool::new();
+
+    let relay_addr = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+    let mut relay = build_relay_with_config(relay::Config {
+        max_reservations_per_peer: 0,
+        ..Default::default()
+    });
+    let relay_peer_id = *relay.local_peer_id();
+
+    relay.listen_on(relay_addr.clone()).unwrap();
+    relay.add_external_address(relay_addr.clone());
+
+    let client_addr = relay_addr
+        .with(Protocol::P2p(relay_peer_id))
+        .with(Protocol::P2pCircuit);
+    let mut client = build_client();
+    let client_peer_id = *client.local_peer_id();
+
+    let reservation_listener = client.listen_on(client_addr).unwrap();
+
+    let (limit_reached_by, error) = pool.run_until(async {
+        let mut limit_reached_by = None;
+        let mut error = None;
+
+        while limit_reached_by.is_none() || error.is_none() {
+            futures::select! {
+                event = relay.select_next_some() => {
+                    if let SwarmEvent::Behaviour(RelayEvent::Relay(
+                        relay::Event::ReservationLimitPerPeerReached { src_peer_id },
+                    )) = event
+                    {
+                        limit_reached_by = Some(src_peer_id);
+                    }
+                }
+                event = client.select_next_some() => {
+                    if let SwarmEvent::ListenerClosed {
+                        listener_id,
+                        reason: Err(e),
+                        ..
+                    } = event
+                    {
+                        if listener_id == reservation_listener {
+                            error = Some(e);
+                        }
+                    }
+                }
+            }
+        }
+
+        (limit_reached_by.unwrap(), error.unwrap())
+    });
+
+    let error = error
+        .source()
+        .unwrap()
+        .downcast_ref::<relay::outbound::hop::ReserveError>()
+        .unwrap();
+
+    assert_eq!(limit_reached_by, client_peer_id);
+    assert!(matches!(
+        error,
+        relay::outbound::hop::ReserveError::ResourceLimitExceeded
+    ));
+}
+
+#[test]
+fn deny_circuit_exceeding_per_peer_limit() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+    let mut pool = LocalPool::new();
+
+    let relay_addr = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+    let mut relay = build_relay_with_config(relay::Config {
+        max_circuits_per_peer: 0,
+        ..Default::default()
+    });
+    let relay_peer_id = *relay.local_peer_id();
+
+    relay.listen_on(relay_addr.clone()).unwrap();
+    relay.add_external_address(relay_addr.clone());
+
+    let mut src = build_client();
+    let src_peer_id = *src.local_peer_id();
+
+    let dst_peer_id = PeerId::random();
+    let dst_addr = relay_addr
+        .with(Protocol::P2p(relay_peer_id))
+        .with(Protocol::P2pCircuit)
+        .with(Protocol::P2p(dst_peer_id));
+
+    let opts = DialOpts::from(dst_addr);
+    let circuit_connection_id = opts.connection_id();
+
+    src.dial(opts).unwrap();
+
+    let (limit_reached_by, error) = pool.run_until(async {
+        let mut limit_reached_by = None;
+        let mut error = None;
+
+        while limit_reached_by.is_none() || error.is_none() {
+            futures::select! {
+                event = relay.select_next_some() => {
+                    if let SwarmEvent::Behaviour(RelayEvent::Relay(
+                        relay::Event::CircuitLimitPerPeerReached {
+                            src_peer_id,
+                            dst_peer_id: dst,
+                        },
+                    )) = event
+                    {
+                        assert_eq!(dst, dst_peer_id);
+                        limit_reached_by = Some(src_peer_id);
+                    }
+                }
+                event = src.select_next_some() => {
+                    if let SwarmEvent::OutgoingConnectionError {
+                        connection_id,
+                        error: DialError::Transport(mut errors),
+                        ..
+                    } = event
+                    {
+                        if connection_id == circuit_connection_id {
+                            error = Some(errors.remove(0).1);
+                        }
+                    }
+                }
+            }
+        }
+
+        (limit_reached_by.unwrap(), error.unwrap())
+    });
+
+    let error = error
+        .source()
+        .unwrap()
+        .source()
+        .unwrap()
+        .downcast_ref::<relay::outbound::hop::ConnectError>()
+        .unwrap();
+
+    assert_eq!(limit_reached_by, src_peer_id);
+    assert!(matches!(
+        error,
+        relay::outbound::hop::ConnectError::ResourceLimitExceeded
+    ));
+}
+
 #[test]
 fn reuse_connection() {
     let _ = tracing_subscriber::fmt()
@@ -433,6 +664,45 @@ fn reuse_connection() {
     ));
 }
 
+#[test]
+fn auto_reserve_replaces_failed_relay() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+    let mut pool = LocalPool::new();
+
+    let mut candidates = Vec::new();
+    for config in [
+        relay::Config {
+            max_reservations: 0, // Will make us fail to make the reservation
+            ..relay::Config::default()
+        },
+        relay::Config::default(),
+        relay::Config::default(),
+    ] {
+        let relay_addr = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+        let mut relay = build_relay_with_config(config);
+        candidates.push((*relay.local_peer_id(), relay_addr.clone()));
+
+        relay.listen_on(relay_addr.clone()).unwrap();
+        relay.add_external_address(relay_addr);
+        spawn_swarm_on_pool(&pool, relay);
+    }
+    let expected_relays = vec![candidates[1].0, candidates[2].0];
+
+    let mut client = build_client();
+    client.behaviour_mut().relay.auto_reserve(candidates, 2);
+
+    let relays = pool.run_until(client.wait(|e| match e {
+        SwarmEvent::Behaviour(ClientEvent::Relay(relay::client::Event::ActiveRelaysChanged {
+            relays,
+        })) if relays.len() == 2 => Some(relays),
+        _ => None,
+    }));
+
+    assert_eq!(relays, expected_relays);
+}
+
 fn build_relay() -> Swarm<Relay> {
     build_relay_with_config(relay::Config {
         reservation_duration: Duration::from_secs(2),
@@ -462,10 +732,17 @@ fn build_client() -> Swarm<Client> {
 }
 
 fn build_client_with_config(config: Config) -> Swarm<Client> {
+    build_client_with_relay_config(config, relay::client::Config::default())
+}
+
+fn build_client_with_relay_config(
+    config: Config,
+    relay_config: relay::client::Config,
+) -> Swarm<Client> {
     let local_key = identity::Keypair::generate_ed25519();
     let local_peer_id = local_key.public().to_peer_id();
 
-    let (relay_transport, behaviour) = relay::client::new(local_peer_id);
+    let (relay_transport, behaviour) = relay::client::new_with_config(local_peer_id, relay_config);
     let transport = upgrade_transport(
         OrTransport::new(relay_transport, MemoryTransport::default()).boxed(),
         &local_key,
@@ -24,14 +24,15 @@ use futures::future::BoxFuture;
 use futures::future::FutureExt;
 use futures::stream::FuturesUnordered;
 use futures::stream::StreamExt;
+use futures_timer::Delay;
 use libp2p_core::{Endpoint, Multiaddr, PeerRecord};
 use libp2p_identity::{Keypair, PeerId, SigningError};
-use libp2p_request_response::{OutboundRequestId, ProtocolSupport};
+use libp2p_request_response::{OutboundFailure, OutboundRequestId, ProtocolSupport};
 use libp2p_swarm::{
     ConnectionDenied, ConnectionId, ExternalAddresses, FromSwarm, NetworkBehaviour, THandler,
     THandlerInEvent, THandlerOutEvent, ToSwarm,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::iter;
 use std::task::{Context, Poll};
 use std::time::Duration;
@@ -55,11 +56,94 @@ pub struct Behaviour {
     expiring_registrations: FuturesUnordered<BoxFuture<'static, (PeerId, Namespace)>>,
 
     external_addresses: ExternalAddresses,
+
+    config: Config,
+
+    /// Registrations that are automatically refreshed, see [`Config::with_auto_refresh`].
+    refreshes: HashMap<(PeerId, Namespace), Refresh>,
+    waiting_for_refresh: HashMap<OutboundRequestId, (PeerId, Namespace)>,
+    /// Refreshes that could not be sent, waiting to be reported.
+    failed_refreshes: VecDeque<Event>,
+}
+
+/// The configuration of a rendezvous client [`Behaviour`].
+#[derive(Debug, Clone)]
+pub struct Config {
+    auto_refresh: bool,
+    refresh_margin: Duration,
+    initial_retry_backoff: Duration,
+    max_retry_backoff: Duration,
+}
+
+impl Config {
+    /// Whether to automatically re-register with the same rendezvous node before a registration
+    /// expires. Disabled by default.
+    ///
+    /// Every refresh is reported via [`Event::RegistrationRefreshed`] or
+    /// [`Event::RegistrationRefreshFailed`]. Failed refreshes are retried with an exponential
+    /// backoff until they succeed or the namespace is unregistered via [`Behaviour::unregister`].
+    pub fn with_auto_refresh(mut self, auto_refresh: bool) -> Self {
+        self.auto_refresh = auto_refresh;
+        self
+    }
+
+    /// How long before the expiry of a registration it is refreshed. Defaults to 60 seconds.
+    ///
+    /// A registration is refreshed after half of its TTL at the earliest.
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// The delay before retrying a failed refresh for the first time. Defaults to 1 second.
+    ///
+    /// The delay is doubled with every further failure.
+    pub fn with_initial_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_retry_backoff = backoff;
+        self
+    }
+
+    /// The maximum delay between retries of a failed refresh. Defaults to 5 minutes.
+    pub fn with_max_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.max_retry_backoff = backoff;
+        self
+    }
+
+    fn refresh_delay(&self, ttl: Ttl) -> Duration {
+        let ttl = Duration::from_secs(ttl);
+
+        ttl.saturating_sub(self.refresh_margin).max(ttl / 2)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            auto_refresh: false,
+            refresh_margin: Duration::from_secs(60),
+            initial_retry_backoff: Duration::from_secs(1),
+            max_retry_backoff: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// The scheduled refresh of a registration.
+struct Refresh {
+    ttl: Ttl,
+    /// Fires when the refresh is due, `None` whilst waiting for the response to a refresh.
+    delay: Option<Delay>,
+    /// The delay before the next retry should this refresh fail.
+    backoff: Duration,
 }
 
 impl Behaviour {
     /// Create a new instance of the rendezvous [`NetworkBehaviour`].
     pub fn new(keypair: Keypair) -> Self {
+        Self::with_config(keypair, Config::default())
+    }
+
+    /// Create a new instance of the rendezvous [`NetworkBehaviour`] with the given [`Config`].
+    pub fn with_config(keypair: Keypair, config: Config) -> Self {
         Self {
             inner: libp2p_request_response::Behaviour::with_codec(
                 crate::codec::Codec::default(),
@@ -75,6 +159,10 @@ impl Behaviour {
                 futures::future::pending().boxed()
             ]),
             external_addresses: Default::default(),
+            config,
+            refreshes: Default::default(),
+            waiting_for_refresh: Default::default(),
+            failed_refreshes: Default::default(),
         }
     }
 
@@ -88,6 +176,19 @@ impl Behaviour {
         rendezvous_node: PeerId,
         ttl: Option<Ttl>,
     ) -> Result<(), RegisterError> {
+        let req_id = self.send_register(namespace.clone(), rendezvous_node, ttl)?;
+        self.waiting_for_register
+            .insert(req_id, (rendezvous_node, namespace));
+
+        Ok(())
+    }
+
+    fn send_register(
+        &mut self,
+        namespace: Namespace,
+        rendezvous_node: PeerId,
+        ttl: Option<Ttl>,
+    ) -> Result<OutboundRequestId, RegisterError> {
         let external_addresses = self.external_addresses.iter().cloned().collect::<Vec<_>>();
         if external_addresses.is_empty() {
             return Err(RegisterError::NoExternalAddresses);
@@ -96,18 +197,19 @@ impl Behaviour {
         let peer_record = PeerRecord::new(&self.keypair, external_addresses)?;
         let req_id = self.inner.send_request(
             &rendezvous_node,
-            Register(NewRegistration::new(namespace.clone(), peer_record, ttl)),
+            Register(NewRegistration::new(namespace, peer_record, ttl)),
         );
-        self.waiting_for_register
-            .insert(req_id, (rendezvous_node, namespace));
 
-        Ok(())
+        Ok(req_id)
     }
 
     /// Unregister ourselves from the given namespace with the given rendezvous peer.
     pub fn unregister(&mut self, namespace: Namespace, rendezvous_node: PeerId) {
         self.registered_namespaces
             .retain(|(rz_node, ns), _| rz_node.ne(&rendezvous_node) && ns.ne(&namespace));
+        self.refreshes.remove(&(rendezvous_node, namespace.clone()));
+        self.waiting_for_refresh
+            .retain(|_, (rz_node, ns)| *rz_node != rendezvous_node || *ns != namespace);
 
         self.inner
             .send_request(&rendezvous_node, Unregister(namespace));
@@ -149,6 +251,17 @@ pub enum RegisterError {
     FailedToMakeRecord(#[from] SigningError),
 }
 
+/// The reason of an [`Event::RegistrationRefreshFailed`].
+#[derive(Debug, thiserror::Error)]
+pub enum RefreshError {
+    #[error("The rendezvous node failed to register us: {0:?}")]
+    Remote(ErrorCode),
+    #[error("Failed to reach the rendezvous node")]
+    Network(#[source] OutboundFailure),
+    #[error("Failed to send the registration")]
+    Register(#[from] RegisterError),
+}
+
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum Event {
@@ -176,6 +289,21 @@ pub enum Event {
         namespace: Namespace,
         error: ErrorCode,
     },
+    /// We automatically refreshed our registration with the contained rendezvous node.
+    RegistrationRefreshed {
+        rendezvous_node: PeerId,
+        ttl: Ttl,
+        namespace: Namespace,
+    },
+    /// We failed to automatically refresh our registration with the contained rendezvous node.
+    ///
+    /// The refresh is retried after `retry_in`.
+    RegistrationRefreshFailed {
+        rendezvous_node: PeerId,
+        namespace: Namespace,
+        error: RefreshError,
+        retry_in: Duration,
+    },
     /// The connection details we learned from this node expired.
     Expired { peer: PeerId },
 }
@@ -252,6 +380,7 @@ impl NetworkBehaviour for Behaviour {
                         req_res::Message::Response {
                             request_id,
                             response,
+                            ..
                         },
                     ..
                 })) => {
@@ -263,9 +392,10 @@ impl NetworkBehaviour for Behaviour {
                 }
                 Poll::Ready(ToSwarm::GenerateEvent(req_res::Event::OutboundFailure {
                     request_id,
+                    error,
                     ..
                 })) => {
-                    if let Some(event) = self.event_for_outbound_failure(&request_id) {
+                    if let Some(event) = self.event_for_outbound_failure(&request_id, error) {
                         return Poll::Ready(ToSwarm::GenerateEvent(event));
                     }
 
@@ -299,6 +429,14 @@ impl NetworkBehaviour for Behaviour {
                 }));
             }
 
+            if let Some(event) = self.failed_refreshes.pop_front() {
+                return Poll::Ready(ToSwarm::GenerateEvent(event));
+            }
+
+            if self.poll_refreshes(cx) {
+                continue;
+            }
+
             return Poll::Pending;
         }
     }
@@ -328,7 +466,78 @@ impl NetworkBehaviour for Behaviour {
 }
 
 impl Behaviour {
-    fn event_for_outbound_failure(&mut self, req_id: &OutboundRequestId) -> Option<Event> {
+    /// Sends the refreshes that are due, returning whether any were due.
+    fn poll_refreshes(&mut self, cx: &mut Context<'_>) -> bool {
+        let due = self
+            .refreshes
+            .iter_mut()
+            .filter_map(|(key, refresh)| {
+                let delay = refresh.delay.as_mut()?;
+                delay.poll_unpin(cx).is_ready().then_some(key.clone())
+            })
+            .collect::<Vec<_>>();
+
+        for (rendezvous_node, namespace) in due.iter().cloned() {
+            let key = (rendezvous_node, namespace.clone());
+            let ttl = self.refreshes[&key].ttl;
+
+            match self.send_register(namespace, rendezvous_node, Some(ttl)) {
+                Ok(req_id) => {
+                    // The next refresh is scheduled once the response arrives.
+                    self.refreshes
+                        .get_mut(&key)
+                        .expect("due refresh to be scheduled")
+                        .delay = None;
+                    self.waiting_for_refresh.insert(req_id, key);
+                }
+                Err(e) => {
+                    tracing::warn!("refreshing registration failed: {e}");
+
+                    let retry_in = self
+                        .retry_refresh(&key)
+                        .expect("due refresh to be scheduled");
+                    self.failed_refreshes
+                        .push_back(Event::RegistrationRefreshFailed {
+                            rendezvous_node,
+                            namespace: key.1.clone(),
+                            error: RefreshError::Register(e),
+                            retry_in,
+                        });
+                }
+            }
+        }
+
+        !due.is_empty()
+    }
+
+    /// Schedules the refresh of a registration that was granted the given TTL.
+    fn schedule_refresh(&mut self, key: (PeerId, Namespace), ttl: Ttl) {
+        self.refreshes.insert(
+            key,
+            Refresh {
+                ttl,
+                delay: Some(Delay::new(self.config.refresh_delay(ttl))),
+                backoff: self.config.initial_retry_backoff,
+            },
+        );
+    }
+
+    /// Schedules the retry of a failed refresh, returning the delay until the retry.
+    fn retry_refresh(&mut self, key: &(PeerId, Namespace)) -> Option<Duration> {
+        let refresh = self.refreshes.get_mut(key)?;
+        let retry_in = refresh.backoff;
+
+        refresh.delay = Some(Delay::new(retry_in));
+        refresh.backoff = (retry_in * 2).min(self.config.max_retry_backoff);
+
+        Some(retry_in)
+    }
+
+    fn event_for_outbound_failure(
+        &mut self,
+        req_id: &OutboundRequestId,
+        error: OutboundFailure,
+    ) -> Option<Event> {
         if let Some((rendezvous_node, namespace)) = self.waiting_for_register.remove(req_id) {
             return Some(Event::RegisterFailed {
                 rendezvous_node,
@@ -345,7 +554,23 @@ impl Behaviour {
             });
         };
 
-        None
+        self.event_for_failed_refresh(req_id, RefreshError::Network(error))
+    }
+
+    fn event_for_failed_refresh(
+        &mut self,
+        req_id: &OutboundRequestId,
+        error: RefreshError,
+    ) -> Option<Event> {
+        let (rendezvous_node, namespace) = self.waiting_for_refresh.remove(req_id)?;
+        let retry_in = self.retry_refresh(&(rendezvous_node, namespace.clone()))?;
+
+        Some(Event::RegistrationRefreshFailed {
+            rendezvous_node,
+            namespace,
+            error,
+            retry_in,
+        })
     }
 
     fn handle_response(
@@ -360,6 +585,9 @@ impl Behaviour {
                 {
                     self.registered_namespaces
                         .insert((rendezvous_node, namespace.clone()), ttl);
+                    if self.config.auto_refresh {
+                        self.schedule_refresh((rendezvous_node, namespace.clone()), ttl);
+                    }
 
                     return Some(Event::Registered {
                         rendezvous_node,
@@ -368,6 +596,20 @@ impl Behaviour {
                     });
                 }
 
+                if let Some((rendezvous_node, namespace)) =
+                    self.waiting_for_refresh.remove(request_id)
+                {
+                    self.registered_namespaces
+                        .insert((rendezvous_node, namespace.clone()), ttl);
+                    self.schedule_refresh((rendezvous_node, namespace.clone()), ttl);
+
+                    return Some(Event::RegistrationRefreshed {
+                        rendezvous_node,
+                        ttl,
+                        namespace,
+                    });
+                }
+
                 None
             }
             RegisterResponse(Err(error_code)) => {
@@ -381,7 +623,7 @@ impl Behaviour {
                     });
                 }
 
-                None
+                self.event_for_failed_refresh(request_id, RefreshError::Remote(error_code))
             }
             DiscoverResponse(Ok((registrations, cookie))) => {
                 if let Some((rendezvous_node, _ns)) = self.waiting_for_discovery.remove(request_id)
@@ -220,6 +220,7 @@ impl NetworkBehaviour for Behaviour {
                     | ToSwarm::RemoveListener { .. }
                     | ToSwarm::NotifyHandler { .. }
                     | ToSwarm::NewExternalAddrCandidate(_)
+                    | ToSwarm::ObservedExternalAddr { .. }
                     | ToSwarm::ExternalAddrConfirmed(_)
                     | ToSwarm::ExternalAddrExpired(_)
                     | ToSwarm::CloseConnection { .. } => {
@@ -19,7 +19,7 @@
 // DEALINGS IN THE SOFTWARE.
 
 use futures::stream::FuturesUnordered;
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 use libp2p_core::multiaddr::Protocol;
 use libp2p_core::Multiaddr;
 use libp2p_identity as identity;
@@ -420,6 +420,154 @@ async fn registration_on_clients_expire() {
     assert!(matches!(error, DialError::NoAddresses));
 }
 
+#[tokio::test]
+async fn registration_is_refreshed_before_expiry() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+    let namespace = rendezvous::Namespace::from_static("some-namespace");
+    let mut robert = new_server(rendezvous::server::Config::default().with_min_ttl(1)).await;
+    let mut alice =
+        new_client_with_config(rendezvous::client::Config::default().with_auto_refresh(true)).await;
+    let mut bob = new_client().await;
+    alice.connect(&mut robert).await;
+    bob.connect(&mut robert).await;
+
+    let roberts_peer_id = *robert.local_peer_id();
+    tokio::spawn(robert.loop_on_next());
+
+    let registration_ttl = 2;
+
+    alice
+        .behaviour_mut()
+        .register(namespace.clone(), roberts_peer_id, Some(registration_ttl))
+        .unwrap();
+    match alice.next_behaviour_event().await {
+        rendezvous::client::Event::Registered { .. } => {}
+        event => panic!("Unexpected event: {event:?}"),
+    }
+    for _ in 0..2 {
+        match alice.next_behaviour_event().await {
+            rendezvous::client::Event::RegistrationRefreshed {
+                rendezvous_node,
+                ttl,
+                namespace: refreshed_namespace,
+            } => {
+                assert_eq!(rendezvous_node, roberts_peer_id);
+                assert_eq!(ttl, registration_ttl);
+                assert_eq!(refreshed_namespace, namespace);
+            }
+            event => panic!("Unexpected event: {event:?}"),
+        }
+    }
+    tokio::spawn(alice.loop_on_next());
+
+    bob.behaviour_mut()
+        .discover(Some(namespace), None, None, roberts_peer_id);
+    match bob.next_behaviour_event().await {
+        rendezvous::client::Event::Discovered { registrations, .. } => {
+            assert!(!registrations.is_empty());
+        }
+        event => panic!("Unexpected event: {event:?}"),
+    }
+}
+
+#[tokio::test]
+async fn failed_refresh_is_retried_with_backoff() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+    let namespace = rendezvous::Namespace::from_static("some-namespace");
+    let mut robert = new_server(rendezvous::server::Config::default().with_min_ttl(1)).await;
+    let mut alice = new_client_with_config(
+        rendezvous::client::Config::default()
+            .with_auto_refresh(true)
+            .with_initial_retry_backoff(Duration::from_millis(100)),
+    )
+    .await;
+    alice.connect(&mut robert).await;
+
+    let roberts_peer_id = *robert.local_peer_id();
+
+    alice
+        .behaviour_mut()
+        .register(namespace.clone(), roberts_peer_id, Some(1))
+        .unwrap();
+    loop {
+        futures::select! {
+            event = alice.next_behaviour_event().fuse() => match event {
+                rendezvous::client::Event::Registered { .. } => break,
+                event => panic!("Unexpected event: {event:?}"),
+            },
+            _ = robert.select_next_some() => {}
+        }
+    }
+    drop(robert);
+
+    for expected_retry_in in [Duration::from_millis(100), Duration::from_millis(200)] {
+        match alice.next_behaviour_event().await {
+            rendezvous::client::Event::RegistrationRefreshFailed {
+                rendezvous_node,
+                error: rendezvous::client::RefreshError::Network(_),
+                retry_in,
+                ..
+            } => {
+                assert_eq!(rendezvous_node, roberts_peer_id);
+                assert_eq!(retry_in, expected_retry_in);
+            }
+            event => panic!("Unexpected event: {event:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn refresh_that_cannot_be_sent_is_reported() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+    let namespace = rendezvous::Namespace::from_static("some-namespace");
+    let mut robert = new_server(rendezvous::server::Config::default().with_min_ttl(1)).await;
+    let mut alice = new_client_with_config(
+        rendezvous::client::Config::default()
+            .with_auto_refresh(true)
+            .with_initial_retry_backoff(Duration::from_millis(100)),
+    )
+    .await;
+    alice.connect(&mut robert).await;
+
+    let roberts_peer_id = *robert.local_peer_id();
+    tokio::spawn(robert.loop_on_next());
+
+    alice
+        .behaviour_mut()
+        .register(namespace.clone(), roberts_peer_id, Some(1))
+        .unwrap();
+    match alice.next_behaviour_event().await {
+        rendezvous::client::Event::Registered { .. } => {}
+        event => panic!("Unexpected event: {event:?}"),
+    }
+
+    // Without external addresses, the refresh cannot be sent.
+    let external_addresses = alice.external_addresses().cloned().collect::<Vec<_>>();
+    for address in external_addresses {
+        alice.remove_external_address(&address);
+    }
+
+    match alice.next_behaviour_event().await {
+        rendezvous::client::Event::RegistrationRefreshFailed {
+            rendezvous_node,
+            namespace: failed_namespace,
+            error: rendezvous::client::RefreshError::Register(RegisterError::NoExternalAddresses),
+            retry_in,
+        } => {
+            assert_eq!(rendezvous_node, roberts_peer_id);
+            assert_eq!(failed_namespace, namespace);
+            assert_eq!(retry_in, Duration::from_millis(100));
+        }
+        event => panic!("Unexpected event: {event:?}"),
+    }
+}
+
 async fn new_server_with_connected_clients<const N: usize>(
     config: rendezvous::server::Config,
 ) -> (
@@ -453,6 +601,17 @@ async fn new_client() -> Swarm<rendezvous::client::Behaviour> {
     client
 }
 
+async fn new_client_with_config(
+    config: rendezvous::client::Config,
+) -> Swarm<rendezvous::client::Behaviour> {
+    let mut client = Swarm::new_ephemeral(|identity| {
+        rendezvous::client::Behaviour::with_config(identity, config)
+    });
+    client.listen().with_memory_addr_external().await;
+
+    client
+}
+
 async fn new_server(config: rendezvous::server::Config) -> Swarm<rendezvous::server::Behaviour> {
     let mut server = Swarm::new_ephemeral(|_| rendezvous::server::Behaviour::new(config));
 
@@ -40,10 +40,18 @@
 ///     [(StreamProtocol::new("/my-cbor-protocol"), ProtocolSupport::Full)],
 ///     request_response::Config::default()
 /// );
+///
+/// // Or, with custom size limits:
+/// let behaviour = cbor::Behaviour::<GreetRequest, GreetResponse>::with_codec(
+///     cbor::codec::Codec::default().set_request_size_maximum(64 * 1024),
+///     [(StreamProtocol::new("/my-cbor-protocol"), ProtocolSupport::Full)],
+///     request_response::Config::default()
+/// );
 /// ```
 pub type Behaviour<Req, Resp> = crate::Behaviour<codec::Codec<Req, Resp>>;
 
-mod codec {
+pub mod codec {
+    use crate::codec::read_to_end_limited;
     use async_trait::async_trait;
     use cbor4ii::core::error::DecodeError;
     use futures::prelude::*;
@@ -58,12 +66,16 @@ mod codec {
     const RESPONSE_SIZE_MAXIMUM: u64 = 10 * 1024 * 1024;
 
     pub struct Codec<Req, Resp> {
+        request_size_maximum: u64,
+        response_size_maximum: u64,
         phantom: PhantomData<(Req, Resp)>,
     }
 
     impl<Req, Resp> Default for Codec<Req, Resp> {
         fn default() -> Self {
             Codec {
+                request_size_maximum: REQUEST_SIZE_MAXIMUM,
+                response_size_maximum: RESPONSE_SIZE_MAXIMUM,
                 phantom: PhantomData,
             }
         }
@@ -71,7 +83,25 @@ mod codec {
 
     impl<Req, Resp> Clone for Codec<Req, Resp> {
         fn clone(&self) -> Self {
-            Self::default()
+            Self {
+                request_size_maximum: self.request_size_maximum,
+                response_size_maximum: self.response_size_maximum,
+                phantom: PhantomData,
+            }
+        }
+    }
+
+    impl<Req, Resp> Codec<Req, Resp> {
+        /// Sets the limit for request size in bytes. Defaults to 1 MiB.
+        pub fn set_request_size_maximum(mut self, request_size_maximum: u64) -> Self {
+            self.request_size_maximum = request_size_maximum;
+            self
+        }
+
+        /// Sets the limit for response size in bytes. Defaults to 10 MiB.
+        pub fn set_response_size_maximum(mut self, response_size_maximum: u64) -> Self {
+            self.response_size_maximum = response_size_maximum;
+            self
         }
     }
 
@@ -89,9 +119,7 @@ mod codec {
         where
             T: AsyncRead + Unpin + Send,
         {
-            let mut vec = Vec::new();
-
-            io.take(REQUEST_SIZE_MAXIMUM).read_to_end(&mut vec).await?;
+            let vec = read_to_end_limited(io, self.request_size_maximum, "request").await?;
 
             cbor4ii::serde::from_slice(vec.as_slice()).map_err(decode_into_io_error)
         }
@@ -100,9 +128,7 @@ mod codec {
         where
             T: AsyncRead + Unpin + Send,
         {
-            let mut vec = Vec::new();
-
-            io.take(RESPONSE_SIZE_MAXIMUM).read_to_end(&mut vec).await?;
+            let vec = read_to_end_limited(io, self.response_size_maximum, "response").await?;
 
             cbor4ii::serde::from_slice(vec.as_slice()).map_err(decode_into_io_error)
         }
@@ -216,6 +242,33 @@ mod tests {
         assert_eq!(actual_response, expected_response);
     }
 
+    #[async_std::test]
+    async fn rejects_request_exceeding_size_maximum() {
+        let request = TestRequest {
+            payload: "a".repeat(64),
+        };
+        let protocol = StreamProtocol::new("/test_cbor/1");
+        let mut codec = Codec::<TestRequest, TestResponse>::default().set_request_size_maximum(32);
+
+        let (mut a, mut b) = Endpoint::pair(256, 256);
+        codec
+            .write_request(&protocol, &mut a, request)
+            .await
+            .expect("Should write request");
+        a.close().await.unwrap();
+
+        let error = codec
+            .read_request(&protocol, &mut b)
+            .await
+            .expect_err("Should reject oversized request");
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "request exceeds maximum size of 32 bytes"
+        );
+    }
+
     #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
     struct TestRequest {
         payload: String,
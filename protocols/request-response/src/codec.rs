@@ -28,7 +28,7 @@ use std::io;
 #[async_trait]
 pub trait Codec {
     /// The type of protocol(s) or protocol versions being negotiated.
-    type Protocol: AsRef<str> + Send + Clone;
+    type Protocol: AsRef<str> + Send + Sync + Clone;
     /// The type of inbound and outbound requests.
     type Request: Send;
     /// The type of inbound and outbound responses.
@@ -75,4 +75,88 @@ pub trait Codec {
     ) -> io::Result<()>
     where
         T: AsyncWrite + Unpin + Send;
+
+    /// Reads the next chunk of a streamed response from the given I/O stream according to the
+    /// negotiated protocol, returning `None` once the responder finished the stream.
+    ///
+    /// Only used with [`Config::with_streaming_responses`](crate::Config::with_streaming_responses).
+    /// The default implementation expects every chunk to be prefixed with its length as a
+    /// big-endian `u32`, see [`Codec::write_response_chunk`], and decodes it via
+    /// [`Codec::read_response`].
+    async fn read_response_chunk<T>(
+        &mut self,
+        protocol: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Option<Self::Response>>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let mut len = [0u8; 4];
+        // The responder closing the stream in between two chunks marks the end of the response.
+        if io.read(&mut len[..1]).await? == 0 {
+            return Ok(None);
+        }
+        io.read_exact(&mut len[1..]).await?;
+
+        let mut chunk = io.take(u32::from_be_bytes(len).into());
+        let response = self.read_response(protocol, &mut chunk).await?;
+        // Skip whatever the codec did not consume, to stay aligned with the next chunk.
+        futures::io::copy(&mut chunk, &mut futures::io::sink()).await?;
+
+        Ok(Some(response))
+    }
+
+    /// Writes a chunk of a streamed response to the given I/O stream according to the
+    /// negotiated protocol.
+    ///
+    /// Only used with [`Config::with_streaming_responses`](crate::Config::with_streaming_responses).
+    /// The default implementation encodes the chunk via [`Codec::write_response`] and prefixes it
+    /// with its length as a big-endian `u32`.
+    async fn write_response_chunk<T>(
+        &mut self,
+        protocol: &Self::Protocol,
+        io: &mut T,
+        res: Self::Response,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let mut chunk = Vec::new();
+        self.write_response(protocol, &mut chunk, res).await?;
+        let len = u32::try_from(chunk.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "response chunk too large"))?;
+
+        io.write_all(&len.to_be_bytes()).await?;
+        io.write_all(&chunk).await?;
+
+        Ok(())
+    }
+}
+
+/// Reads `io` to the end, failing if more than `maximum` bytes are received.
+///
+/// `kind` names what is read, e.g. `"request"`, in the error.
+#[cfg(any(feature = "cbor", feature = "json"))]
+pub(crate) async fn read_to_end_limited<T>(
+    io: &mut T,
+    maximum: u64,
+    kind: &str,
+) -> io::Result<Vec<u8>>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let mut vec = Vec::new();
+
+    io.take(maximum.saturating_add(1))
+        .read_to_end(&mut vec)
+        .await?;
+
+    if vec.len() as u64 > maximum {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{kind} exceeds maximum size of {maximum} bytes"),
+        ));
+    }
+
+    Ok(vec)
 }
@@ -23,22 +23,22 @@ pub(crate) mod protocol;
 pub use protocol::ProtocolSupport;
 
 use crate::codec::Codec;
-use crate::handler::protocol::Protocol;
+use crate::handler::protocol::{Protocol, ProtocolName};
 use crate::{InboundRequestId, OutboundRequestId, EMPTY_QUEUE_SHRINK_THRESHOLD};
 
 use futures::channel::mpsc;
 use futures::{channel::oneshot, prelude::*};
 use libp2p_swarm::handler::{
     ConnectionEvent, DialUpgradeError, FullyNegotiatedInbound, FullyNegotiatedOutbound,
-    ListenUpgradeError,
+    ListenUpgradeError, ProtocolsChange,
 };
 use libp2p_swarm::{
     handler::{ConnectionHandler, ConnectionHandlerEvent, StreamUpgradeError},
-    SubstreamProtocol,
+    StreamProtocol, SubstreamProtocol,
 };
 use smallvec::SmallVec;
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
     fmt, io,
     sync::{
         atomic::{AtomicU64, Ordering},
@@ -55,6 +55,10 @@ where
 {
     /// The supported inbound protocols.
     inbound_protocols: SmallVec<[TCodec::Protocol; 2]>,
+    /// The protocols requests are sent with.
+    outbound_protocols: SmallVec<[TCodec::Protocol; 2]>,
+    /// The outbound protocols the remote reported to support, e.g. via `identify`.
+    remote_outbound_protocols: HashSet<StreamProtocol>,
     /// The request/response message codec.
     codec: TCodec,
     /// Queue of events to emit in `poll()`.
@@ -67,20 +71,49 @@ where
     inbound_receiver: mpsc::Receiver<(
         InboundRequestId,
         TCodec::Request,
-        oneshot::Sender<TCodec::Response>,
+        ResponseSender<TCodec::Response>,
     )>,
     /// The [`mpsc::Sender`] for the above receiver. Cloned for each inbound request.
     inbound_sender: mpsc::Sender<(
         InboundRequestId,
         TCodec::Request,
-        oneshot::Sender<TCodec::Response>,
+        ResponseSender<TCodec::Response>,
     )>,
 
     inbound_request_id: Arc<AtomicU64>,
 
+    /// Whether responses are sent and received as a stream of chunks.
+    streaming_responses: bool,
+
     worker_streams: futures_bounded::FuturesMap<RequestId, Result<Event<TCodec>, io::Error>>,
 }
 
+/// The sending half for the response to an inbound request.
+pub enum ResponseSender<TResponse> {
+    /// Sends a single response.
+    Single(oneshot::Sender<TResponse>),
+    /// Sends the receiving half of a stream of response chunks.
+    Stream(oneshot::Sender<mpsc::Receiver<TResponse>>),
+}
+
+impl<TResponse> ResponseSender<TResponse> {
+    pub(crate) fn is_canceled(&self) -> bool {
+        match self {
+            ResponseSender::Single(sender) => sender.is_canceled(),
+            ResponseSender::Stream(sender) => sender.is_canceled(),
+        }
+    }
+}
+
+impl<TResponse> fmt::Debug for ResponseSender<TResponse> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResponseSender::Single(_) => f.write_str("ResponseSender::Single"),
+            ResponseSender::Stream(_) => f.write_str("ResponseSender::Stream"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum RequestId {
     Inbound(InboundRequestId),
@@ -93,14 +126,18 @@ where
 {
     pub(super) fn new(
         inbound_protocols: SmallVec<[TCodec::Protocol; 2]>,
+        outbound_protocols: SmallVec<[TCodec::Protocol; 2]>,
         codec: TCodec,
         substream_timeout: Duration,
         inbound_request_id: Arc<AtomicU64>,
         max_concurrent_streams: usize,
+        streaming_responses: bool,
     ) -> Self {
         let (inbound_sender, inbound_receiver) = mpsc::channel(0);
         Self {
             inbound_protocols,
+            outbound_protocols,
+            remote_outbound_protocols: HashSet::new(),
             codec,
             pending_outbound: VecDeque::new(),
             requested_outbound: Default::default(),
@@ -108,6 +145,7 @@ where
             inbound_sender,
             pending_events: VecDeque::new(),
             inbound_request_id,
+            streaming_responses,
             worker_streams: futures_bounded::FuturesMap::new(
                 substream_timeout,
                 max_concurrent_streams,
@@ -115,6 +153,31 @@ where
         }
     }
 
+    fn on_remote_protocols_change(&mut self, change: ProtocolsChange) {
+        let streaming = self.streaming_responses;
+        let is_outbound = |protocol: &StreamProtocol| {
+            self.outbound_protocols
+                .iter()
+                .any(|p| ProtocolName::new(p, streaming).as_ref() == protocol.as_ref())
+        };
+
+        match change {
+            ProtocolsChange::Added(added) => {
+                let added: Vec<_> = added.filter(|p| is_outbound(p)).cloned().collect();
+                self.remote_outbound_protocols.extend(added);
+            }
+            ProtocolsChange::Removed(removed) => {
+                for protocol in removed {
+                    self.remote_outbound_protocols.remove(protocol);
+                }
+            }
+        }
+
+        self.pending_events.push_back(Event::RemoteProtocolSupport(
+            !self.remote_outbound_protocols.is_empty(),
+        ));
+    }
+
     /// Returns the next inbound request ID.
     fn next_inbound_request_id(&mut self) -> InboundRequestId {
         InboundRequestId(self.inbound_request_id.fetch_add(1, Ordering::Relaxed))
@@ -133,16 +196,42 @@ where
         let mut codec = self.codec.clone();
         let request_id = self.next_inbound_request_id();
         let mut sender = self.inbound_sender.clone();
+        let streaming_responses = self.streaming_responses;
 
         let recv = async move {
+            let read = codec.read_request(&protocol, &mut stream);
+            let request = read.await?;
+
+            if streaming_responses {
+                // A channel for handing the stream of response chunks to the inbound upgrade.
+                let (rs_send, rs_recv) = oneshot::channel();
+
+                sender
+                    .send((request_id, request, ResponseSender::Stream(rs_send)))
+                    .await
+                    .expect("`ConnectionHandler` owns both ends of the channel");
+                drop(sender);
+
+                let Ok(mut chunks) = rs_recv.await else {
+                    stream.close().await?;
+                    return Ok(Event::ResponseOmission(request_id));
+                };
+
+                while let Some(chunk) = chunks.next().await {
+                    let write = codec.write_response_chunk(&protocol, &mut stream, chunk);
+                    write.await?;
+                }
+
+                stream.close().await?;
+                return Ok(Event::ResponseSent(request_id));
+            }
+
             // A channel for notifying the inbound upgrade when the
             // response is sent.
             let (rs_send, rs_recv) = oneshot::channel();
 
-            let read = codec.read_request(&protocol, &mut stream);
-            let request = read.await?;
             sender
-                .send((request_id, request, rs_send))
+                .send((request_id, request, ResponseSender::Single(rs_send)))
                 .await
                 .expect("`ConnectionHandler` owns both ends of the channel");
             drop(sender);
@@ -186,22 +275,49 @@ where
         let mut codec = self.codec.clone();
         let request_id = message.request_id;
 
-        let send = async move {
-            let write = codec.write_request(&protocol, &mut stream, message.request);
-            write.await?;
-            stream.close().await?;
-            let read = codec.read_response(&protocol, &mut stream);
-            let response = read.await?;
+        let send = if self.streaming_responses {
+            let (mut chunk_sender, chunks) = mpsc::channel(0);
+            self.pending_events
+                .push_back(Event::ResponseStream { request_id, chunks });
 
-            Ok(Event::Response {
-                request_id,
-                response,
-            })
+            async move {
+                let write = codec.write_request(&protocol, &mut stream, message.request);
+                write.await?;
+                stream.close().await?;
+
+                loop {
+                    let read = codec.read_response_chunk(&protocol, &mut stream);
+                    let Some(chunk) = read.await? else {
+                        break;
+                    };
+                    if chunk_sender.send(chunk).await.is_err() {
+                        // The receiving end was dropped, no one is interested in the remaining chunks.
+                        break;
+                    }
+                }
+
+                Ok(Event::ResponseStreamFinished(request_id))
+            }
+            .boxed()
+        } else {
+            async move {
+                let write = codec.write_request(&protocol, &mut stream, message.request);
+                write.await?;
+                stream.close().await?;
+                let read = codec.read_response(&protocol, &mut stream);
+                let response = read.await?;
+
+                Ok(Event::Response {
+                    request_id,
+                    response,
+                })
+            }
+            .boxed()
         };
 
         if self
             .worker_streams
-            .try_push(RequestId::Outbound(request_id), send.boxed())
+            .try_push(RequestId::Outbound(request_id), send)
             .is_err()
         {
             tracing::warn!("Dropping outbound stream because we are at capacity")
@@ -264,13 +380,20 @@ where
     Request {
         request_id: InboundRequestId,
         request: TCodec::Request,
-        sender: oneshot::Sender<TCodec::Response>,
+        sender: ResponseSender<TCodec::Response>,
     },
     /// A response has been received.
     Response {
         request_id: OutboundRequestId,
         response: TCodec::Response,
     },
+    /// The stream of response chunks to an outbound request has been opened.
+    ResponseStream {
+        request_id: OutboundRequestId,
+        chunks: mpsc::Receiver<TCodec::Response>,
+    },
+    /// All chunks of a streamed response have been received.
+    ResponseStreamFinished(OutboundRequestId),
     /// A response to an inbound request has been sent.
     ResponseSent(InboundRequestId),
     /// A response to an inbound request was omitted as a result
@@ -292,6 +415,9 @@ where
         request_id: InboundRequestId,
         error: io::Error,
     },
+    /// The remote reported whether it supports any of the protocols we send requests with,
+    /// e.g. via `identify`.
+    RemoteProtocolSupport(bool),
 }
 
 impl<TCodec: Codec> fmt::Debug for Event<TCodec> {
@@ -312,6 +438,17 @@ impl<TCodec: Codec> fmt::Debug for Event<TCodec> {
                 .debug_struct("Event::Response")
                 .field("request_id", request_id)
                 .finish(),
+            Event::ResponseStream {
+                request_id,
+                chunks: _,
+            } => f
+                .debug_struct("Event::ResponseStream")
+                .field("request_id", request_id)
+                .finish(),
+            Event::ResponseStreamFinished(request_id) => f
+                .debug_tuple("Event::ResponseStreamFinished")
+                .field(request_id)
+                .finish(),
             Event::ResponseSent(request_id) => f
                 .debug_tuple("Event::ResponseSent")
                 .field(request_id)
@@ -342,6 +479,10 @@ impl<TCodec: Codec> fmt::Debug for Event<TCodec> {
                 .field("request_id", &request_id)
                 .field("error", &error)
                 .finish(),
+            Event::RemoteProtocolSupport(supported) => f
+                .debug_tuple("Event::RemoteProtocolSupport")
+                .field(supported)
+                .finish(),
         }
     }
 }
@@ -376,6 +517,7 @@ where
         SubstreamProtocol::new(
             Protocol {
                 protocols: self.inbound_protocols.clone(),
+                streaming: self.streaming_responses,
             },
             (),
         )
@@ -447,7 +589,13 @@ where
             self.requested_outbound.push_back(request);
 
             return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
-                protocol: SubstreamProtocol::new(Protocol { protocols }, ()),
+                protocol: SubstreamProtocol::new(
+                    Protocol {
+                        protocols,
+                        streaming: self.streaming_responses,
+                    },
+                    (),
+                ),
             });
         }
 
@@ -482,6 +630,9 @@ where
             ConnectionEvent::ListenUpgradeError(listen_upgrade_error) => {
                 self.on_listen_upgrade_error(listen_upgrade_error)
             }
+            ConnectionEvent::RemoteProtocolsChange(change) => {
+                self.on_remote_protocols_change(change)
+            }
             _ => {}
         }
     }
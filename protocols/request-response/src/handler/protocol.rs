@@ -27,6 +27,14 @@ use futures::future::{ready, Ready};
 use libp2p_core::upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeInfo};
 use libp2p_swarm::Stream;
 use smallvec::SmallVec;
+use std::sync::Arc;
+
+/// The suffix of the protocol names negotiated with streaming responses, see
+/// [`Config::with_streaming_responses`](crate::Config::with_streaming_responses).
+///
+/// Streamed responses are framed differently than single ones, thus they must not be negotiated
+/// under the same name.
+pub(crate) const STREAMING_SUFFIX: &str = "/stream";
 
 /// The level of support for a particular protocol.
 #[derive(Debug, Clone)]
@@ -63,17 +71,52 @@ impl ProtocolSupport {
 #[derive(Debug)]
 pub struct Protocol<P> {
     pub(crate) protocols: SmallVec<[P; 2]>,
+    /// Whether responses are streamed, in which case the protocols are negotiated with the
+    /// [`STREAMING_SUFFIX`].
+    pub(crate) streaming: bool,
+}
+
+/// A protocol of the codec together with the name it is negotiated with.
+#[derive(Debug, Clone)]
+pub struct ProtocolName<P> {
+    protocol: P,
+    /// The name to negotiate, if different from the protocol itself.
+    name: Option<Arc<str>>,
+}
+
+impl<P> ProtocolName<P>
+where
+    P: AsRef<str>,
+{
+    pub(crate) fn new(protocol: P, streaming: bool) -> Self {
+        let name = streaming.then(|| format!("{}{STREAMING_SUFFIX}", protocol.as_ref()).into());
+
+        Self { protocol, name }
+    }
+}
+
+impl<P> AsRef<str> for ProtocolName<P>
+where
+    P: AsRef<str>,
+{
+    fn as_ref(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.protocol.as_ref())
+    }
 }
 
 impl<P> UpgradeInfo for Protocol<P>
 where
     P: AsRef<str> + Clone,
 {
-    type Info = P;
+    type Info = ProtocolName<P>;
     type InfoIter = smallvec::IntoIter<[Self::Info; 2]>;
 
     fn protocol_info(&self) -> Self::InfoIter {
-        self.protocols.clone().into_iter()
+        self.protocols
+            .iter()
+            .map(|p| ProtocolName::new(p.clone(), self.streaming))
+            .collect::<SmallVec<_>>()
+            .into_iter()
     }
 }
 
@@ -86,7 +129,7 @@ where
     type Future = Ready<Result<Self::Output, Self::Error>>;
 
     fn upgrade_inbound(self, io: Stream, protocol: Self::Info) -> Self::Future {
-        ready(Ok((io, protocol)))
+        ready(Ok((io, protocol.protocol)))
     }
 }
 
@@ -99,6 +142,6 @@ where
     type Future = Ready<Result<Self::Output, Self::Error>>;
 
     fn upgrade_outbound(self, io: Stream, protocol: Self::Info) -> Self::Future {
-        ready(Ok((io, protocol)))
+        ready(Ok((io, protocol.protocol)))
     }
 }
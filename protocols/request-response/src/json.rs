@@ -42,7 +42,8 @@
 /// ```
 pub type Behaviour<Req, Resp> = crate::Behaviour<codec::Codec<Req, Resp>>;
 
-mod codec {
+pub mod codec {
+    use crate::codec::read_to_end_limited;
     use async_trait::async_trait;
     use futures::prelude::*;
     use futures::{AsyncRead, AsyncWrite};
@@ -56,12 +57,16 @@ mod codec {
     const RESPONSE_SIZE_MAXIMUM: u64 = 10 * 1024 * 1024;
 
     pub struct Codec<Req, Resp> {
+        request_size_maximum: u64,
+        response_size_maximum: u64,
         phantom: PhantomData<(Req, Resp)>,
     }
 
     impl<Req, Resp> Default for Codec<Req, Resp> {
         fn default() -> Self {
             Codec {
+                request_size_maximum: REQUEST_SIZE_MAXIMUM,
+                response_size_maximum: RESPONSE_SIZE_MAXIMUM,
                 phantom: PhantomData,
             }
         }
@@ -69,7 +74,25 @@ mod codec {
 
     impl<Req, Resp> Clone for Codec<Req, Resp> {
         fn clone(&self) -> Self {
-            Self::default()
+            Self {
+                request_size_maximum: self.request_size_maximum,
+                response_size_maximum: self.response_size_maximum,
+                phantom: PhantomData,
+            }
+        }
+    }
+
+    impl<Req, Resp> Codec<Req, Resp> {
+        /// Sets the limit for request size in bytes. Defaults to 1 MiB.
+        pub fn set_request_size_maximum(mut self, request_size_maximum: u64) -> Self {
+            self.request_size_maximum = request_size_maximum;
+            self
+        }
+
+        /// Sets the limit for response size in bytes. Defaults to 10 MiB.
+        pub fn set_response_size_maximum(mut self, response_size_maximum: u64) -> Self {
+            self.response_size_maximum = response_size_maximum;
+            self
         }
     }
 
@@ -87,9 +110,7 @@ mod codec {
         where
             T: AsyncRead + Unpin + Send,
         {
-            let mut vec = Vec::new();
-
-            io.take(REQUEST_SIZE_MAXIMUM).read_to_end(&mut vec).await?;
+            let vec = read_to_end_limited(io, self.request_size_maximum, "request").await?;
 
             Ok(serde_json::from_slice(vec.as_slice())?)
         }
@@ -98,9 +119,7 @@ mod codec {
         where
             T: AsyncRead + Unpin + Send,
         {
-            let mut vec = Vec::new();
-
-            io.take(RESPONSE_SIZE_MAXIMUM).read_to_end(&mut vec).await?;
+            let vec = read_to_end_limited(io, self.response_size_maximum, "response").await?;
 
             Ok(serde_json::from_slice(vec.as_slice())?)
         }
@@ -190,6 +209,34 @@ mod tests {
         assert_eq!(actual_response, expected_response);
     }
 
+    #[async_std::test]
+    async fn rejects_response_exceeding_size_maximum() {
+        let response = TestResponse {
+            payload: "a".repeat(64),
+        };
+        let protocol = StreamProtocol::new("/test_json/1");
+        let mut codec = super::codec::Codec::<TestRequest, TestResponse>::default()
+            .set_response_size_maximum(32);
+
+        let (mut a, mut b) = Endpoint::pair(256, 256);
+        codec
+            .write_response(&protocol, &mut a, response)
+            .await
+            .expect("Should write response");
+        a.close().await.unwrap();
+
+        let error = codec
+            .read_response(&protocol, &mut b)
+            .await
+            .expect_err("Should reject oversized response");
+
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "response exceeds maximum size of 32 bytes"
+        );
+    }
+
     #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
     struct TestRequest {
         payload: String,
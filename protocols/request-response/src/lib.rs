@@ -39,6 +39,15 @@
 //! receiving a [`Message::Request`] via
 //! [`Event::Message`].
 //!
+//! ## Streaming Responses
+//!
+//! With [`Config::with_streaming_responses`], responses are sent as a stream of chunks instead
+//! of a single message. The responder turns the [`ResponseChannel`] into a [`ResponseSink`] via
+//! [`Behaviour::send_response_stream`] and writes chunks to it until it is closed. The requester
+//! receives the chunks through the [`ResponseStream`] of a [`Message::ResponseStream`].
+//! Streamed responses are negotiated under the protocols with `/stream` appended, thus both peers
+//! need to enable streaming responses to communicate.
+//!
 //! ## Predefined codecs
 //!
 //! In case your message types implement [`serde::Serialize`] and [`serde::Deserialize`],
@@ -76,9 +85,11 @@ pub mod json;
 pub use codec::Codec;
 pub use handler::ProtocolSupport;
 
-use crate::handler::OutboundMessage;
-use futures::channel::oneshot;
+use crate::handler::{OutboundMessage, ResponseSender};
+use futures::channel::mpsc;
+use futures::{Sink, Stream};
 use handler::Handler;
+use instant::Instant;
 use libp2p_core::{ConnectedPoint, Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use libp2p_swarm::{
@@ -91,6 +102,7 @@ use smallvec::SmallVec;
 use std::{
     collections::{HashMap, HashSet, VecDeque},
     fmt, io,
+    pin::Pin,
     sync::{atomic::AtomicU64, Arc},
     task::{Context, Poll},
     time::Duration,
@@ -120,6 +132,22 @@ pub enum Message<TRequest, TResponse, TChannelResponse = TResponse> {
         request_id: OutboundRequestId,
         /// The response message.
         response: TResponse,
+        /// The time elapsed between [`Behaviour::send_request`] and the receipt of the response.
+        rtt: Duration,
+    },
+    /// A stream of response chunks.
+    ///
+    /// Only emitted with [`Config::with_streaming_responses`], in place of [`Message::Response`].
+    ResponseStream {
+        /// The ID of the request that produced this response.
+        ///
+        /// See [`Behaviour::send_request`].
+        request_id: OutboundRequestId,
+        /// The chunks of the response.
+        ///
+        /// The stream ends once the responder finished the response or the request failed, in
+        /// which case an [`Event::OutboundFailure`] is emitted.
+        chunks: ResponseStream<TResponse>,
     },
 }
 
@@ -141,6 +169,10 @@ pub enum Event<TRequest, TResponse, TChannelResponse = TResponse> {
         request_id: OutboundRequestId,
         /// The error that occurred.
         error: OutboundFailure,
+        /// The time elapsed between [`Behaviour::send_request`] and the failure.
+        ///
+        /// For [`OutboundFailure::Timeout`] this is how long we waited for a response.
+        elapsed: Duration,
     },
     /// An inbound request failed.
     InboundFailure {
@@ -181,6 +213,11 @@ pub enum OutboundFailure {
     ConnectionClosed,
     /// The remote supports none of the requested protocols.
     UnsupportedProtocols,
+    /// The request was not sent because the configured maximum number of
+    /// concurrent outbound requests to the peer was reached.
+    ///
+    /// See [`Config::with_max_concurrent_outbound_per_peer`].
+    ResourceLimit,
     /// An IO failure happened on an outbound stream.
     Io(io::Error),
 }
@@ -196,6 +233,9 @@ impl fmt::Display for OutboundFailure {
             OutboundFailure::UnsupportedProtocols => {
                 write!(f, "The remote supports none of the requested protocols")
             }
+            OutboundFailure::ResourceLimit => {
+                write!(f, "Too many concurrent outbound requests to the peer")
+            }
             OutboundFailure::Io(e) => write!(f, "IO error on outbound stream: {e}"),
         }
     }
@@ -221,6 +261,11 @@ pub enum InboundFailure {
     /// due to the [`ResponseChannel`] being dropped instead of
     /// being passed to [`Behaviour::send_response`].
     ResponseOmission,
+    /// The request was refused because the configured maximum number of
+    /// concurrent inbound requests of the remote was reached.
+    ///
+    /// See [`Config::with_max_concurrent_inbound_per_peer`].
+    ResourceLimit,
     /// An IO failure happened on an inbound stream.
     Io(io::Error),
 }
@@ -242,6 +287,9 @@ impl fmt::Display for InboundFailure {
                 f,
                 "The response channel was dropped without sending a response to the remote"
             ),
+            InboundFailure::ResourceLimit => {
+                write!(f, "Too many concurrent inbound requests of the remote")
+            }
             InboundFailure::Io(e) => write!(f, "IO error on inbound stream: {e}"),
         }
     }
@@ -254,7 +302,7 @@ impl std::error::Error for InboundFailure {}
 /// See [`Behaviour::send_response`].
 #[derive(Debug)]
 pub struct ResponseChannel<TResponse> {
-    sender: oneshot::Sender<TResponse>,
+    sender: ResponseSender<TResponse>,
 }
 
 impl<TResponse> ResponseChannel<TResponse> {
@@ -270,6 +318,60 @@ impl<TResponse> ResponseChannel<TResponse> {
     }
 }
 
+/// A sink for sending the chunks of a streamed response to an inbound request.
+///
+/// The response is finished once the sink is closed or dropped.
+///
+/// See [`Behaviour::send_response_stream`].
+#[derive(Debug)]
+pub struct ResponseSink<TResponse> {
+    sender: mpsc::Sender<TResponse>,
+}
+
+impl<TResponse> Sink<TResponse> for ResponseSink<TResponse> {
+    type Error = io::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.sender.poll_ready(cx).map_err(response_stream_closed)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: TResponse) -> Result<(), Self::Error> {
+        self.sender.start_send(item).map_err(response_stream_closed)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.sender)
+            .poll_flush(cx)
+            .map_err(response_stream_closed)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.sender)
+            .poll_close(cx)
+            .map_err(response_stream_closed)
+    }
+}
+
+fn response_stream_closed(_: mpsc::SendError) -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "response stream closed")
+}
+
+/// The chunks of a streamed response to an outbound request.
+///
+/// See [`Message::ResponseStream`].
+#[derive(Debug)]
+pub struct ResponseStream<TResponse> {
+    receiver: mpsc::Receiver<TResponse>,
+}
+
+impl<TResponse> Stream for ResponseStream<TResponse> {
+    type Item = TResponse;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
 /// The ID of an inbound request.
 ///
 /// Note: [`InboundRequestId`]'s uniqueness is only guaranteed between
@@ -301,6 +403,9 @@ impl fmt::Display for OutboundRequestId {
 pub struct Config {
     request_timeout: Duration,
     max_concurrent_streams: usize,
+    max_concurrent_inbound_per_peer: usize,
+    max_concurrent_outbound_per_peer: usize,
+    streaming_responses: bool,
 }
 
 impl Default for Config {
@@ -308,6 +413,9 @@ impl Default for Config {
         Self {
             request_timeout: Duration::from_secs(10),
             max_concurrent_streams: 100,
+            max_concurrent_inbound_per_peer: usize::MAX,
+            max_concurrent_outbound_per_peer: usize::MAX,
+            streaming_responses: false,
         }
     }
 }
@@ -331,6 +439,49 @@ impl Config {
         self.max_concurrent_streams = num_streams;
         self
     }
+
+    /// Sets the upper bound for the number of inbound requests of a single peer that
+    /// may await a response at the same time.
+    ///
+    /// Requests beyond this limit are refused and reported as
+    /// [`InboundFailure::ResourceLimit`]. The remote observes its request failing
+    /// without a response.
+    ///
+    /// Defaults to no limit.
+    pub fn with_max_concurrent_inbound_per_peer(mut self, num_requests: usize) -> Self {
+        self.max_concurrent_inbound_per_peer = num_requests;
+        self
+    }
+
+    /// Sets the upper bound for the number of outbound requests to a single peer that
+    /// may await a response at the same time.
+    ///
+    /// Requests beyond this limit are not sent and are reported as
+    /// [`OutboundFailure::ResourceLimit`].
+    ///
+    /// Defaults to no limit.
+    pub fn with_max_concurrent_outbound_per_peer(mut self, num_requests: usize) -> Self {
+        self.max_concurrent_outbound_per_peer = num_requests;
+        self
+    }
+
+    /// Sets whether responses are sent and received as a stream of chunks.
+    ///
+    /// When enabled, responses to outbound requests are reported as [`Message::ResponseStream`]
+    /// and inbound requests are answered via [`Behaviour::send_response_stream`]. Chunks are
+    /// framed by [`Codec::write_response_chunk`] and [`Codec::read_response_chunk`].
+    ///
+    /// As the framing differs, the protocols are negotiated with `/stream` appended, e.g.
+    /// `/my-protocol/1.0.0/stream` for `/my-protocol/1.0.0`. Peers without streaming responses
+    /// thus report the protocols as unsupported.
+    ///
+    /// The request timeout applies to the entire exchange, including all chunks.
+    ///
+    /// Defaults to `false`.
+    pub fn with_streaming_responses(mut self, enabled: bool) -> Self {
+        self.streaming_responses = enabled;
+        self
+    }
 }
 
 /// A request/response protocol for some message codec.
@@ -361,6 +512,8 @@ where
     /// Requests that have not yet been sent and are waiting for a connection
     /// to be established.
     pending_outbound_requests: HashMap<PeerId, SmallVec<[OutboundMessage<TCodec>; 10]>>,
+    /// The point in time at which each unanswered outbound request was issued.
+    outbound_request_started: HashMap<OutboundRequestId, Instant>,
 }
 
 impl<TCodec> Behaviour<TCodec>
@@ -406,6 +559,7 @@ where
             pending_events: VecDeque::new(),
             connected: HashMap::new(),
             pending_outbound_requests: HashMap::new(),
+            outbound_request_started: HashMap::new(),
             addresses: HashMap::new(),
         }
     }
@@ -429,6 +583,20 @@ where
             request,
             protocols: self.outbound_protocols.clone(),
         };
+        self.outbound_request_started
+            .insert(request_id, Instant::now());
+
+        if self.num_outbound_requests(peer) >= self.config.max_concurrent_outbound_per_peer {
+            let elapsed = self.outbound_request_finished(request_id);
+            self.pending_events
+                .push_back(ToSwarm::GenerateEvent(Event::OutboundFailure {
+                    peer: *peer,
+                    request_id,
+                    error: OutboundFailure::ResourceLimit,
+                    elapsed,
+                }));
+            return request_id;
+        }
 
         if let Some(request) = self.try_send_request(peer, request) {
             self.pending_events.push_back(ToSwarm::Dial {
@@ -459,7 +627,55 @@ where
         ch: ResponseChannel<TCodec::Response>,
         rs: TCodec::Response,
     ) -> Result<(), TCodec::Response> {
-        ch.sender.send(rs)
+        match ch.sender {
+            ResponseSender::Single(sender) => sender.send(rs),
+            ResponseSender::Stream(sender) => {
+                // Send the response as the one and only chunk.
+                let (mut chunk_sender, chunks) = mpsc::channel(0);
+                chunk_sender
+                    .try_send(rs)
+                    .expect("a new channel to have capacity for one message");
+
+                sender.send(chunks).map_err(|mut chunks| {
+                    chunks
+                        .try_next()
+                        .ok()
+                        .flatten()
+                        .expect("the chunk to not have been received")
+                })
+            }
+        }
+    }
+
+    /// Initiates sending a streamed response to an inbound request, see
+    /// [`Config::with_streaming_responses`].
+    ///
+    /// Chunks written to the returned [`ResponseSink`] are sent to the remote in order. The
+    /// response is finished once the sink is closed or dropped, upon which
+    /// [`Event::ResponseSent`] is emitted.
+    ///
+    /// If streaming responses are not enabled or the [`ResponseChannel`] is already closed due to
+    /// a timeout or the connection being closed, the channel is returned as an `Err`.
+    pub fn send_response_stream(
+        &mut self,
+        ch: ResponseChannel<TCodec::Response>,
+    ) -> Result<ResponseSink<TCodec::Response>, ResponseChannel<TCodec::Response>> {
+        if !ch.is_open() {
+            return Err(ch);
+        }
+
+        match ch.sender {
+            sender @ ResponseSender::Single(_) => Err(ResponseChannel { sender }),
+            ResponseSender::Stream(sender) => {
+                let (chunk_sender, chunks) = mpsc::channel(0);
+                // If the remote went away in the meantime, sending on the sink fails.
+                let _ = sender.send(chunks);
+
+                Ok(ResponseSink {
+                    sender: chunk_sender,
+                })
+            }
+        }
     }
 
     /// Adds a known address for a peer that can be used for
@@ -495,6 +711,26 @@ where
         }
     }
 
+    /// Checks whether a connected peer supports any of the protocols requests are sent with.
+    ///
+    /// Support is learned from the protocols the peer reports, e.g. via `identify`, and from the
+    /// outcome of previous requests, the latter taking precedence. Returns `None` if the peer is
+    /// not connected or its support is not known yet. If the peer supports the protocols on any of
+    /// its connections, this returns `Some(true)`.
+    pub fn protocol_supported(&self, peer: &PeerId) -> Option<bool> {
+        let mut supported = None;
+
+        for connection in self.connected.get(peer)? {
+            match connection.protocol_supported {
+                Some(true) => return Some(true),
+                Some(false) => supported = Some(false),
+                None => {}
+            }
+        }
+
+        supported
+    }
+
     /// Checks whether an outbound request to the peer with the provided
     /// [`PeerId`] initiated by [`Behaviour::send_request`] is still
     /// pending, i.e. waiting for a response.
@@ -538,6 +774,41 @@ where
         request_id
     }
 
+    /// Returns the number of outbound requests to the given peer that await a response,
+    /// including those waiting for a connection to be established.
+    fn num_outbound_requests(&self, peer: &PeerId) -> usize {
+        let sent: usize = self
+            .connected
+            .get(peer)
+            .map(|connections| {
+                connections
+                    .iter()
+                    .map(|c| c.pending_outbound_responses.len())
+                    .sum()
+            })
+            .unwrap_or(0);
+        let queued = self
+            .pending_outbound_requests
+            .get(peer)
+            .map(|requests| requests.len())
+            .unwrap_or(0);
+
+        sent + queued
+    }
+
+    /// Returns the number of inbound requests of the given peer that await a response.
+    fn num_inbound_requests(&self, peer: &PeerId) -> usize {
+        self.connected
+            .get(peer)
+            .map(|connections| {
+                connections
+                    .iter()
+                    .map(|c| c.pending_inbound_responses.len())
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
     /// Tries to send a request by queueing an appropriate event to be
     /// emitted to the `Swarm`. If the peer is not currently connected,
     /// the given request is return unchanged.
@@ -580,6 +851,14 @@ where
             .unwrap_or(false)
     }
 
+    /// Returns the time elapsed since the given outbound request was issued and stops tracking it.
+    fn outbound_request_finished(&mut self, request: OutboundRequestId) -> Duration {
+        self.outbound_request_started
+            .remove(&request)
+            .map(|started| started.elapsed())
+            .unwrap_or_default()
+    }
+
     /// Remove pending inbound response for the given peer and connection.
     ///
     /// Returns `true` if the provided connection to the given peer is still
@@ -596,6 +875,28 @@ where
             .unwrap_or(false)
     }
 
+    /// Removes an inbound request refused because of
+    /// [`Config::with_max_concurrent_inbound_per_peer`] once its stream terminated.
+    ///
+    /// Returns `true` if the request was refused on the provided connection.
+    fn remove_refused_inbound_request(
+        &mut self,
+        peer: &PeerId,
+        connection: ConnectionId,
+        request: InboundRequestId,
+    ) -> bool {
+        self.get_connection_mut(peer, connection)
+            .map(|c| c.refused_inbound_requests.remove(&request))
+            .unwrap_or(false)
+    }
+
+    /// Records whether the remote supports our outbound protocols on the given connection.
+    fn set_protocol_supported(&mut self, peer: &PeerId, connection: ConnectionId, supported: bool) {
+        if let Some(connection) = self.get_connection_mut(peer, connection) {
+            connection.protocol_supported = Some(supported);
+        }
+    }
+
     /// Returns a mutable reference to the connection in `self.connected`
     /// corresponding to the given [`PeerId`] and [`ConnectionId`].
     fn get_connection_mut(
@@ -668,11 +969,13 @@ where
         }
 
         for request_id in connection.pending_outbound_responses {
+            let elapsed = self.outbound_request_finished(request_id);
             self.pending_events
                 .push_back(ToSwarm::GenerateEvent(Event::OutboundFailure {
                     peer: peer_id,
                     request_id,
                     error: OutboundFailure::ConnectionClosed,
+                    elapsed,
                 }));
         }
     }
@@ -687,11 +990,13 @@ where
             // another, concurrent dialing attempt ongoing.
             if let Some(pending) = self.pending_outbound_requests.remove(&peer) {
                 for request in pending {
+                    let elapsed = self.outbound_request_finished(request.request_id);
                     self.pending_events
                         .push_back(ToSwarm::GenerateEvent(Event::OutboundFailure {
                             peer,
                             request_id: request.request_id,
                             error: OutboundFailure::DialFailure,
+                            elapsed,
                         }));
                 }
             }
@@ -737,10 +1042,12 @@ where
     ) -> Result<THandler<Self>, ConnectionDenied> {
         let mut handler = Handler::new(
             self.inbound_protocols.clone(),
+            self.outbound_protocols.clone(),
             self.codec.clone(),
             self.config.request_timeout,
             self.next_inbound_request_id.clone(),
             self.config.max_concurrent_streams,
+            self.config.streaming_responses,
         );
 
         self.preload_new_handler(&mut handler, peer, connection_id, None);
@@ -780,10 +1087,12 @@ where
     ) -> Result<THandler<Self>, ConnectionDenied> {
         let mut handler = Handler::new(
             self.inbound_protocols.clone(),
+            self.outbound_protocols.clone(),
             self.codec.clone(),
             self.config.request_timeout,
             self.next_inbound_request_id.clone(),
             self.config.max_concurrent_streams,
+            self.config.streaming_responses,
         );
 
         self.preload_new_handler(
@@ -819,6 +1128,8 @@ where
                 request_id,
                 response,
             } => {
+                self.set_protocol_supported(&peer, connection, true);
+
                 let removed = self.remove_pending_outbound_response(&peer, connection, request_id);
                 debug_assert!(
                     removed,
@@ -828,10 +1139,50 @@ where
                 let message = Message::Response {
                     request_id,
                     response,
+                    rtt: self.outbound_request_finished(request_id),
                 };
                 self.pending_events
                     .push_back(ToSwarm::GenerateEvent(Event::Message { peer, message }));
             }
+            handler::Event::ResponseStream { request_id, chunks } => {
+                self.set_protocol_supported(&peer, connection, true);
+
+                let message = Message::ResponseStream {
+                    request_id,
+                    chunks: ResponseStream { receiver: chunks },
+                };
+                self.pending_events
+                    .push_back(ToSwarm::GenerateEvent(Event::Message { peer, message }));
+            }
+            handler::Event::ResponseStreamFinished(request_id) => {
+                let removed = self.remove_pending_outbound_response(&peer, connection, request_id);
+                debug_assert!(
+                    removed,
+                    "Expect request_id to be pending before finishing the response stream.",
+                );
+
+                self.outbound_request_finished(request_id);
+            }
+            handler::Event::Request { request_id, .. }
+                if self.num_inbound_requests(&peer)
+                    >= self.config.max_concurrent_inbound_per_peer =>
+            {
+                // Dropping the response sender closes the stream without a response.
+                tracing::debug!(
+                    %peer,
+                    "Refusing inbound request ({request_id}) because of too many concurrent requests"
+                );
+                if let Some(connection) = self.get_connection_mut(&peer, connection) {
+                    connection.refused_inbound_requests.insert(request_id);
+                }
+
+                self.pending_events
+                    .push_back(ToSwarm::GenerateEvent(Event::InboundFailure {
+                        peer,
+                        request_id,
+                        error: InboundFailure::ResourceLimit,
+                    }));
+            }
             handler::Event::Request {
                 request_id,
                 request,
@@ -868,6 +1219,11 @@ where
                     }));
             }
             handler::Event::ResponseOmission(request_id) => {
+                // The refusal has already been reported as `InboundFailure::ResourceLimit`.
+                if self.remove_refused_inbound_request(&peer, connection, request_id) {
+                    return;
+                }
+
                 let removed = self.remove_pending_inbound_response(&peer, connection, request_id);
                 debug_assert!(
                     removed,
@@ -888,39 +1244,54 @@ where
                     "Expect request_id to be pending before request times out."
                 );
 
+                let elapsed = self.outbound_request_finished(request_id);
+
                 self.pending_events
                     .push_back(ToSwarm::GenerateEvent(Event::OutboundFailure {
                         peer,
                         request_id,
                         error: OutboundFailure::Timeout,
+                        elapsed,
                     }));
             }
             handler::Event::OutboundUnsupportedProtocols(request_id) => {
+                self.set_protocol_supported(&peer, connection, false);
+
                 let removed = self.remove_pending_outbound_response(&peer, connection, request_id);
                 debug_assert!(
                     removed,
                     "Expect request_id to be pending before failing to connect.",
                 );
 
+                let elapsed = self.outbound_request_finished(request_id);
+
                 self.pending_events
                     .push_back(ToSwarm::GenerateEvent(Event::OutboundFailure {
                         peer,
                         request_id,
                         error: OutboundFailure::UnsupportedProtocols,
+                        elapsed,
                     }));
             }
             handler::Event::OutboundStreamFailed { request_id, error } => {
                 let removed = self.remove_pending_outbound_response(&peer, connection, request_id);
                 debug_assert!(removed, "Expect request_id to be pending upon failure");
 
+                let elapsed = self.outbound_request_finished(request_id);
+
                 self.pending_events
                     .push_back(ToSwarm::GenerateEvent(Event::OutboundFailure {
                         peer,
                         request_id,
                         error: OutboundFailure::Io(error),
+                        elapsed,
                     }))
             }
             handler::Event::InboundTimeout(request_id) => {
+                if self.remove_refused_inbound_request(&peer, connection, request_id) {
+                    return;
+                }
+
                 let removed = self.remove_pending_inbound_response(&peer, connection, request_id);
 
                 if removed {
@@ -938,6 +1309,10 @@ where
                 }
             }
             handler::Event::InboundStreamFailed { request_id, error } => {
+                if self.remove_refused_inbound_request(&peer, connection, request_id) {
+                    return;
+                }
+
                 let removed = self.remove_pending_inbound_response(&peer, connection, request_id);
 
                 if removed {
@@ -952,6 +1327,12 @@ where
                     tracing::debug!("Inbound failure is reported for an unknown request_id ({request_id}): {error}");
                 }
             }
+            handler::Event::RemoteProtocolSupport(supported) => {
+                // The outcome of a request is more reliable than the protocols the remote reports.
+                if let Some(connection) = self.get_connection_mut(&peer, connection) {
+                    connection.protocol_supported.get_or_insert(supported);
+                }
+            }
         }
     }
 
@@ -984,6 +1365,11 @@ struct Connection {
     /// Pending inbound responses for previously sent requests on this
     /// connection.
     pending_inbound_responses: HashSet<InboundRequestId>,
+    /// Inbound requests refused because of [`Config::with_max_concurrent_inbound_per_peer`] whose
+    /// stream has not terminated yet.
+    refused_inbound_requests: HashSet<InboundRequestId>,
+    /// Whether the remote supports any of our outbound protocols, if known.
+    protocol_supported: Option<bool>,
 }
 
 impl Connection {
@@ -993,6 +1379,8 @@ impl Connection {
             remote_address,
             pending_outbound_responses: Default::default(),
             pending_inbound_responses: Default::default(),
+            refused_inbound_requests: Default::default(),
+            protocol_supported: None,
         }
     }
 }
@@ -50,7 +50,7 @@ async fn report_outbound_failure_on_read_response() {
             .behaviour_mut()
             .send_request(&peer1_id, Action::FailOnReadResponse);
 
-        let (peer, req_id_done, error) = wait_outbound_failure(&mut swarm2).await.unwrap();
+        let (peer, req_id_done, error, _) = wait_outbound_failure(&mut swarm2).await.unwrap();
         assert_eq!(peer, peer1_id);
         assert_eq!(req_id_done, req_id);
 
@@ -93,7 +93,7 @@ async fn report_outbound_failure_on_write_request() {
             .behaviour_mut()
             .send_request(&peer1_id, Action::FailOnWriteRequest);
 
-        let (peer, req_id_done, error) = wait_outbound_failure(&mut swarm2).await.unwrap();
+        let (peer, req_id_done, error, _) = wait_outbound_failure(&mut swarm2).await.unwrap();
         assert_eq!(peer, peer1_id);
         assert_eq!(req_id_done, req_id);
 
@@ -150,10 +150,11 @@ async fn report_outbound_timeout_on_read_response() {
             .behaviour_mut()
             .send_request(&peer1_id, Action::TimeoutOnReadResponse);
 
-        let (peer, req_id_done, error) = wait_outbound_failure(&mut swarm2).await.unwrap();
+        let (peer, req_id_done, error, elapsed) = wait_outbound_failure(&mut swarm2).await.unwrap();
         assert_eq!(peer, peer1_id);
         assert_eq!(req_id_done, req_id);
         assert!(matches!(error, OutboundFailure::Timeout));
+        assert!(elapsed >= Duration::from_millis(100));
     };
 
     let server_task = pin!(server_task);
@@ -183,7 +184,7 @@ async fn report_inbound_failure_on_read_request() {
             .behaviour_mut()
             .send_request(&peer1_id, Action::FailOnReadRequest);
 
-        let (peer, req_id_done, error) = wait_outbound_failure(&mut swarm2).await.unwrap();
+        let (peer, req_id_done, error, _) = wait_outbound_failure(&mut swarm2).await.unwrap();
         assert_eq!(peer, peer1_id);
         assert_eq!(req_id_done, req_id);
 
@@ -242,7 +243,7 @@ async fn report_inbound_failure_on_write_response() {
             .behaviour_mut()
             .send_request(&peer1_id, Action::FailOnWriteResponse);
 
-        let (peer, req_id_done, error) = wait_outbound_failure(&mut swarm2).await.unwrap();
+        let (peer, req_id_done, error, _) = wait_outbound_failure(&mut swarm2).await.unwrap();
         assert_eq!(peer, peer1_id);
         assert_eq!(req_id_done, req_id);
 
@@ -299,7 +300,7 @@ async fn report_inbound_timeout_on_write_response() {
             .behaviour_mut()
             .send_request(&peer1_id, Action::TimeoutOnWriteResponse);
 
-        let (peer, req_id_done, error) = wait_outbound_failure(&mut swarm2).await.unwrap();
+        let (peer, req_id_done, error, _) = wait_outbound_failure(&mut swarm2).await.unwrap();
         assert_eq!(peer, peer1_id);
         assert_eq!(req_id_done, req_id);
 
@@ -321,6 +322,100 @@ async fn report_inbound_timeout_on_write_response() {
     futures::future::select(server_task, client_task).await;
 }
 
+#[async_std::test]
+async fn report_outbound_failure_on_resource_limit() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    let cfg = request_response::Config::default().with_request_timeout(Duration::from_secs(10));
+    let (peer1_id, mut swarm1) = new_swarm_with_config(cfg.clone());
+    let (peer2_id, mut swarm2) =
+        new_swarm_with_config(cfg.with_max_concurrent_outbound_per_peer(1));
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    // Receives the first request but never responds to it.
+    let server_task = async move {
+        let (peer, _, _, _resp_channel) = wait_request(&mut swarm1).await.unwrap();
+        assert_eq!(peer, peer2_id);
+
+        wait_no_events(&mut swarm1).await;
+    };
+
+    // Expects OutboundFailure::ResourceLimit for the second request
+    let client_task = async move {
+        swarm2
+            .behaviour_mut()
+            .send_request(&peer1_id, Action::FailOnReadResponse);
+        let req_id = swarm2
+            .behaviour_mut()
+            .send_request(&peer1_id, Action::FailOnReadResponse);
+
+        let (peer, req_id_done, error, _) = wait_outbound_failure(&mut swarm2).await.unwrap();
+        assert_eq!(peer, peer1_id);
+        assert_eq!(req_id_done, req_id);
+        assert!(matches!(error, OutboundFailure::ResourceLimit));
+    };
+
+    let server_task = pin!(server_task);
+    let client_task = pin!(client_task);
+    futures::future::select(server_task, client_task).await;
+}
+
+#[async_std::test]
+async fn report_inbound_failure_on_resource_limit() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    let cfg = request_response::Config::default().with_request_timeout(Duration::from_secs(10));
+    let (peer1_id, mut swarm1) =
+        new_swarm_with_config(cfg.clone().with_max_concurrent_inbound_per_peer(1));
+    let (peer2_id, mut swarm2) = new_swarm_with_config(cfg);
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    // Expects InboundFailure::ResourceLimit while the first request is not answered
+    let server_task = async move {
+        let (peer, req_id, _, _resp_channel) = wait_request(&mut swarm1).await.unwrap();
+        assert_eq!(peer, peer2_id);
+
+        let (peer, req_id_done, error) = wait_inbound_failure(&mut swarm1).await.unwrap();
+        assert_eq!(peer, peer2_id);
+        assert_ne!(req_id_done, req_id);
+        assert!(matches!(error, InboundFailure::ResourceLimit));
+    };
+
+    // Expects the refused request to fail with io::ErrorKind::UnexpectedEof
+    let client_task = async move {
+        swarm2
+            .behaviour_mut()
+            .send_request(&peer1_id, Action::FailOnReadResponse);
+        let req_id = swarm2
+            .behaviour_mut()
+            .send_request(&peer1_id, Action::FailOnReadResponse);
+
+        let (peer, req_id_done, error, _) = wait_outbound_failure(&mut swarm2).await.unwrap();
+        assert_eq!(peer, peer1_id);
+        assert_eq!(req_id_done, req_id);
+
+        match error {
+            OutboundFailure::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {}
+            e => panic!("Unexpected error: {e:?}"),
+        }
+
+        // Keep alive the task, so only `server_task` can finish
+        wait_no_events(&mut swarm2).await;
+    };
+
+    let server_task = pin!(server_task);
+    let client_task = pin!(client_task);
+    futures::future::select(server_task, client_task).await;
+}
+
 #[derive(Clone, Default)]
 struct TestCodec;
 
@@ -470,9 +565,14 @@ impl Codec for TestCodec {
 
 fn new_swarm_with_timeout(
     timeout: Duration,
+) -> (PeerId, Swarm<request_response::Behaviour<TestCodec>>) {
+    new_swarm_with_config(request_response::Config::default().with_request_timeout(timeout))
+}
+
+fn new_swarm_with_config(
+    cfg: request_response::Config,
 ) -> (PeerId, Swarm<request_response::Behaviour<TestCodec>>) {
     let protocols = iter::once((StreamProtocol::new("/test/1"), ProtocolSupport::Full));
-    let cfg = request_response::Config::default().with_request_timeout(timeout);
 
     let swarm =
         Swarm::new_ephemeral(|_| request_response::Behaviour::<TestCodec>::new(protocols, cfg));
@@ -551,15 +651,16 @@ async fn wait_inbound_failure(
 
 async fn wait_outbound_failure(
     swarm: &mut Swarm<request_response::Behaviour<TestCodec>>,
-) -> Result<(PeerId, OutboundRequestId, OutboundFailure)> {
+) -> Result<(PeerId, OutboundRequestId, OutboundFailure, Duration)> {
     loop {
         match swarm.select_next_some().await.try_into_behaviour_event() {
             Ok(request_response::Event::OutboundFailure {
                 peer,
                 request_id,
                 error,
+                elapsed,
             }) => {
-                return Ok((peer, request_id, error));
+                return Ok((peer, request_id, error, elapsed));
             }
             Ok(ev) => bail!("Unexpected event: {ev:?}"),
             Err(..) => {}
@@ -28,7 +28,7 @@ use libp2p_swarm::{StreamProtocol, Swarm, SwarmEvent};
 use libp2p_swarm_test::SwarmExt;
 use rand::{self, Rng};
 use serde::{Deserialize, Serialize};
-use std::{io, iter};
+use std::{io, iter, time::Duration};
 use tracing_subscriber::EnvFilter;
 
 #[async_std::test]
@@ -64,6 +64,7 @@ async fn is_response_outbound() {
             peer,
             request_id: req_id,
             error: _error,
+            ..
         } => {
             assert_eq!(&offline_peer, &peer);
             assert_eq!(req_id, request_id1);
@@ -155,9 +156,11 @@ async fn ping_protocol() {
                         request_response::Message::Response {
                             request_id,
                             response,
+                            rtt,
                         },
                 } => {
                     count += 1;
+                    assert!(rtt > Duration::ZERO);
                     assert_eq!(&response, &expected_pong);
                     assert_eq!(&peer, &peer1_id);
                     assert_eq!(req_id, request_id);
@@ -297,6 +300,97 @@ async fn emits_inbound_connection_closed_if_channel_is_dropped() {
     ));
 }
 
+#[async_std::test]
+#[cfg(feature = "cbor")]
+async fn protocol_support_is_learned_from_requests() {
+    let ping = Ping("ping".to_string().into_bytes());
+    let cfg = request_response::Config::default();
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| {
+        request_response::cbor::Behaviour::<Ping, Pong>::new(
+            [(StreamProtocol::new("/ping/1"), ProtocolSupport::Full)],
+            cfg.clone(),
+        )
+    });
+    let peer1_id = *swarm1.local_peer_id();
+    let mut swarm2 = Swarm::new_ephemeral(|_| {
+        request_response::cbor::Behaviour::<Ping, Pong>::new(
+            [
+                (StreamProtocol::new("/ping/2"), ProtocolSupport::Outbound),
+                (StreamProtocol::new("/ping/1"), ProtocolSupport::Inbound),
+            ],
+            cfg.clone(),
+        )
+    });
+    let peer2_id = *swarm2.local_peer_id();
+
+    assert!(!swarm2.behaviour().is_connected(&peer1_id));
+    assert_eq!(swarm2.behaviour().protocol_supported(&peer1_id), None);
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    assert!(swarm2.behaviour().is_connected(&peer1_id));
+    assert_eq!(swarm2.behaviour().protocol_supported(&peer1_id), None);
+
+    // Peer 1 does not support the only protocol peer 2 sends requests with.
+    swarm2.behaviour_mut().send_request(&peer1_id, ping.clone());
+    let error = loop {
+        futures::select!(
+            event = swarm1.select_next_some() => {
+                if let SwarmEvent::Behaviour(ev) = event {
+                    panic!("Peer1: Unexpected event: {ev:?}");
+                }
+            },
+            event = swarm2.select_next_some() => match event {
+                SwarmEvent::Behaviour(request_response::Event::OutboundFailure { error, .. }) => {
+                    break error;
+                }
+                SwarmEvent::Behaviour(ev) => panic!("Peer2: Unexpected event: {ev:?}"),
+                _ => {}
+            }
+        )
+    };
+    assert!(matches!(
+        error,
+        request_response::OutboundFailure::UnsupportedProtocols
+    ));
+    assert_eq!(
+        swarm2.behaviour().protocol_supported(&peer1_id),
+        Some(false)
+    );
+
+    // Peer 2 does support the protocol peer 1 sends requests with.
+    swarm1.behaviour_mut().send_request(&peer2_id, ping.clone());
+    loop {
+        futures::select!(
+            event = swarm1.select_next_some() => match event {
+                SwarmEvent::Behaviour(request_response::Event::Message {
+                    message: request_response::Message::Response { .. },
+                    ..
+                }) => break,
+                SwarmEvent::Behaviour(ev) => panic!("Peer1: Unexpected event: {ev:?}"),
+                _ => {}
+            },
+            event = swarm2.select_next_some() => match event {
+                SwarmEvent::Behaviour(request_response::Event::Message {
+                    message: request_response::Message::Request { channel, .. },
+                    ..
+                }) => {
+                    swarm2
+                        .behaviour_mut()
+                        .send_response(channel, Pong(ping.0.clone()))
+                        .unwrap();
+                }
+                SwarmEvent::Behaviour(request_response::Event::ResponseSent { .. }) => {}
+                SwarmEvent::Behaviour(ev) => panic!("Peer2: Unexpected event: {ev:?}"),
+                _ => {}
+            }
+        )
+    }
+    assert_eq!(swarm1.behaviour().protocol_supported(&peer2_id), Some(true));
+}
+
 // Simple Ping-Pong Protocol
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct Ping(Vec<u8>);
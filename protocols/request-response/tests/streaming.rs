@@ -0,0 +1,192 @@
+// Copyright 2023 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+#![cfg(feature = "cbor")]
+
+use futures::prelude::*;
+use libp2p_request_response as request_response;
+use libp2p_request_response::ProtocolSupport;
+use libp2p_swarm::{StreamProtocol, Swarm};
+use libp2p_swarm_test::SwarmExt;
+use serde::{Deserialize, Serialize};
+use std::iter;
+use tracing_subscriber::EnvFilter;
+
+#[async_std::test]
+async fn streams_response_chunks() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+
+    let (mut swarm1, mut swarm2) = connected_streaming_swarms().await;
+    let peer1_id = *swarm1.local_peer_id();
+
+    let request_id = swarm2.behaviour_mut().send_request(&peer1_id, Query(3));
+
+    let responder = async move {
+        let channel = match swarm1.next_behaviour_event().await {
+            request_response::Event::Message {
+                message:
+                    request_response::Message::Request {
+                        request: Query(n),
+                        channel,
+                        ..
+                    },
+                ..
+            } => {
+                assert_eq!(n, 3);
+                channel
+            }
+            e => panic!("Peer1: Unexpected event: {e:?}"),
+        };
+        let mut sink = swarm1
+            .behaviour_mut()
+            .send_response_stream(channel)
+            .unwrap();
+        async_std::task::spawn(async move {
+            for i in 0..3 {
+                sink.send(Chunk(i)).await.unwrap();
+            }
+            sink.close().await.unwrap();
+        });
+
+        match swarm1.next_behaviour_event().await {
+            request_response::Event::ResponseSent { .. } => {}
+            e => panic!("Peer1: Unexpected event: {e:?}"),
+        }
+        swarm1.loop_on_next().await;
+    };
+    async_std::task::spawn(responder);
+
+    let chunks = match swarm2.next_behaviour_event().await {
+        request_response::Event::Message {
+            message:
+                request_response::Message::ResponseStream {
+                    request_id: id,
+                    chunks,
+                },
+            ..
+        } => {
+            assert_eq!(id, request_id);
+            chunks
+        }
+        e => panic!("Peer2: Unexpected event: {e:?}"),
+    };
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    assert_eq!(
+        chunks.collect::<Vec<_>>().await,
+        vec![Chunk(0), Chunk(1), Chunk(2)]
+    );
+}
+
+#[async_std::test]
+async fn single_response_is_streamed_as_one_chunk() {
+    let (mut swarm1, mut swarm2) = connected_streaming_swarms().await;
+    let peer1_id = *swarm1.local_peer_id();
+
+    swarm2.behaviour_mut().send_request(&peer1_id, Query(1));
+
+    let responder = async move {
+        match swarm1.next_behaviour_event().await {
+            request_response::Event::Message {
+                message: request_response::Message::Request { channel, .. },
+                ..
+            } => {
+                swarm1
+                    .behaviour_mut()
+                    .send_response(channel, Chunk(42))
+                    .unwrap();
+            }
+            e => panic!("Peer1: Unexpected event: {e:?}"),
+        }
+        swarm1.loop_on_next().await;
+    };
+    async_std::task::spawn(responder);
+
+    let chunks = match swarm2.next_behaviour_event().await {
+        request_response::Event::Message {
+            message: request_response::Message::ResponseStream { chunks, .. },
+            ..
+        } => chunks,
+        e => panic!("Peer2: Unexpected event: {e:?}"),
+    };
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    assert_eq!(chunks.collect::<Vec<_>>().await, vec![Chunk(42)]);
+}
+
+#[async_std::test]
+async fn streaming_is_negotiated_under_a_distinct_protocol() {
+    let protocols = iter::once((StreamProtocol::new("/query/1"), ProtocolSupport::Full));
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| {
+        request_response::cbor::Behaviour::<Query, Chunk>::new(
+            protocols.clone(),
+            request_response::Config::default(),
+        )
+    });
+    let mut swarm2 = Swarm::new_ephemeral(|_| {
+        request_response::cbor::Behaviour::<Query, Chunk>::new(
+            protocols,
+            request_response::Config::default().with_streaming_responses(true),
+        )
+    });
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+    let peer1_id = *swarm1.local_peer_id();
+    async_std::task::spawn(swarm1.loop_on_next());
+
+    let request_id = swarm2.behaviour_mut().send_request(&peer1_id, Query(1));
+
+    match swarm2.next_behaviour_event().await {
+        request_response::Event::OutboundFailure {
+            request_id: id,
+            error: request_response::OutboundFailure::UnsupportedProtocols,
+            ..
+        } => assert_eq!(id, request_id),
+        e => panic!("Peer2: Unexpected event: {e:?}"),
+    }
+}
+
+async fn connected_streaming_swarms() -> (
+    Swarm<request_response::cbor::Behaviour<Query, Chunk>>,
+    Swarm<request_response::cbor::Behaviour<Query, Chunk>>,
+) {
+    let protocols = iter::once((StreamProtocol::new("/query/1"), ProtocolSupport::Full));
+    let cfg = request_response::Config::default().with_streaming_responses(true);
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| {
+        request_response::cbor::Behaviour::<Query, Chunk>::new(protocols.clone(), cfg.clone())
+    });
+    let mut swarm2 = Swarm::new_ephemeral(|_| {
+        request_response::cbor::Behaviour::<Query, Chunk>::new(protocols, cfg)
+    });
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    (swarm1, swarm2)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Query(u8);
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Chunk(u8);
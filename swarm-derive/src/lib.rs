@@ -60,6 +60,7 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
     let BehaviourAttributes {
         prelude_path,
         user_specified_out_event,
+        generated_event_name,
     } = parse_attributes(ast)?;
 
     let multiaddr = quote! { #prelude_path::Multiaddr };
@@ -84,6 +85,7 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
     let external_addr_confirmed = quote! { #prelude_path::ExternalAddrConfirmed };
     let listener_error = quote! { #prelude_path::ListenerError };
     let listener_closed = quote! { #prelude_path::ListenerClosed };
+    let datagram_received = quote! { #prelude_path::DatagramReceived };
     let t_handler = quote! { #prelude_path::THandler };
     let t_handler_in_event = quote! { #prelude_path::THandlerInEvent };
     let t_handler_out_event = quote! { #prelude_path::THandlerOutEvent };
@@ -101,7 +103,7 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
     let (out_event_name, out_event_definition, out_event_from_clauses) = {
         // If we find a `#[behaviour(to_swarm = "Foo")]` attribute on the
         // struct, we set `Foo` as the out event. If not, the `ToSwarm` is
-        // generated.
+        // generated, named after `#[behaviour(event_name = "Foo")]` if present.
         match user_specified_out_event {
             // User provided `ToSwarm`.
             Some(name) => {
@@ -118,7 +120,9 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
             }
             // User did not provide `ToSwarm`. Generate it.
             None => {
-                let enum_name_str = ast.ident.to_string() + "Event";
+                let enum_name_str = generated_event_name
+                    .map(|ident| ident.to_string())
+                    .unwrap_or_else(|| ast.ident.to_string() + "Event");
                 let enum_name: syn::Type =
                     syn::parse_str(&enum_name_str).expect("ident + `Event` is a valid type");
                 let definition = {
@@ -535,6 +539,31 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
             })
     };
 
+    // Build the list of statements to put in the body of `on_swarm_event()`
+    // for the `FromSwarm::DatagramReceived` variant.
+    let on_datagram_received_stmts = {
+        data_struct
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(field_n, field)| match field.ident {
+                Some(ref i) => quote! {
+                    self.#i.on_swarm_event(#from_swarm::DatagramReceived(#datagram_received {
+                        peer_id,
+                        connection_id,
+                        data,
+                    }));
+                },
+                None => quote! {
+                    self.#field_n.on_swarm_event(#from_swarm::DatagramReceived(#datagram_received {
+                        peer_id,
+                        connection_id,
+                        data,
+                    }));
+                },
+            })
+    };
+
     // Build the list of variants to put in the body of `on_connection_handler_event()`.
     //
     // The event type is a construction of nested `#either_ident`s of the events of the children.
@@ -832,6 +861,9 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
                     #from_swarm::ListenerClosed(
                         #listener_closed { listener_id, reason })
                     => { #(#on_listener_closed_stmts)* }
+                    #from_swarm::DatagramReceived(
+                        #datagram_received { peer_id, connection_id, data })
+                    => { #(#on_datagram_received_stmts)* }
                     _ => {}
                 }
             }
@@ -844,6 +876,7 @@ fn build_struct(ast: &DeriveInput, data_struct: &DataStruct) -> syn::Result<Toke
 struct BehaviourAttributes {
     prelude_path: syn::Path,
     user_specified_out_event: Option<syn::Type>,
+    generated_event_name: Option<syn::Ident>,
 }
 
 /// Parses the `value` of a key=value pair in the `#[behaviour]` attribute into the requested type.
@@ -851,7 +884,9 @@ fn parse_attributes(ast: &DeriveInput) -> syn::Result<BehaviourAttributes> {
     let mut attributes = BehaviourAttributes {
         prelude_path: syn::parse_quote! { ::libp2p::swarm::derive_prelude },
         user_specified_out_event: None,
+        generated_event_name: None,
     };
+    let mut event_name_meta = None;
 
     for attr in ast
         .attrs
@@ -876,8 +911,24 @@ fn parse_attributes(ast: &DeriveInput) -> syn::Result<BehaviourAttributes> {
 
                 continue;
             }
+
+            if meta.path().is_ident("event_name") {
+                let value = meta.require_name_value()?.value.require_str_lit()?;
+
+                attributes.generated_event_name = Some(syn::parse_str(&value)?);
+                event_name_meta = Some(meta);
+
+                continue;
+            }
         }
     }
 
+    if let (Some(_), Some(meta)) = (&attributes.user_specified_out_event, event_name_meta) {
+        return Err(syn::Error::new_spanned(
+            meta,
+            "`event_name` names the generated event and cannot be combined with `to_swarm`",
+        ));
+    }
+
     Ok(attributes)
 }
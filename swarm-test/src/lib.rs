@@ -47,11 +47,22 @@ pub trait SwarmExt {
     where
         Self: Sized;
 
+    /// Like [`SwarmExt::new_ephemeral`], but the [`swarm::Config`] is passed through `config_fn`
+    /// first, e.g. to enable optional features of the [`Swarm`].
+    fn new_ephemeral_with_config(
+        behaviour_fn: impl FnOnce(Keypair) -> Self::NB,
+        config_fn: impl FnOnce(swarm::Config) -> swarm::Config,
+    ) -> Self
+    where
+        Self: Sized;
+
     /// Establishes a connection to the given [`Swarm`], polling both of them until the connection is established.
     ///
     /// This will take addresses from the `other` [`Swarm`] via [`Swarm::external_addresses`].
     /// By default, this iterator will not yield any addresses.
     /// To add listen addresses as external addresses, use [`ListenFuture::with_memory_addr_external`] or [`ListenFuture::with_tcp_addr_external`].
+    ///
+    /// Panics if the connection is not established on both sides within 10 seconds.
     async fn connect<T>(&mut self, other: &mut Swarm<T>)
     where
         T: NetworkBehaviour + Send,
@@ -201,6 +212,16 @@ where
     type NB = B;
 
     fn new_ephemeral(behaviour_fn: impl FnOnce(Keypair) -> Self::NB) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new_ephemeral_with_config(behaviour_fn, |config| config)
+    }
+
+    fn new_ephemeral_with_config(
+        behaviour_fn: impl FnOnce(Keypair) -> Self::NB,
+        config_fn: impl FnOnce(swarm::Config) -> swarm::Config,
+    ) -> Self
     where
         Self: Sized,
     {
@@ -219,8 +240,10 @@ where
             transport,
             behaviour_fn(identity),
             peer_id,
-            swarm::Config::with_async_std_executor()
-                .with_idle_connection_timeout(Duration::from_secs(5)), // Some tests need connections to be kept alive beyond what the individual behaviour configures.,
+            config_fn(
+                swarm::Config::with_async_std_executor()
+                    .with_idle_connection_timeout(Duration::from_secs(5)), // Some tests need connections to be kept alive beyond what the individual behaviour configures.,
+            ),
         )
     }
 
@@ -229,20 +252,38 @@ where
         T: NetworkBehaviour + Send,
         <T as NetworkBehaviour>::ToSwarm: Debug,
     {
-        let external_addresses = other.external_addresses().cloned().collect();
+        let external_addresses = other.external_addresses().cloned().collect::<Vec<_>>();
+        let hint = if external_addresses.is_empty() {
+            " The other swarm has no external addresses, see `ListenFuture::with_memory_addr_external`."
+        } else {
+            ""
+        };
 
         let dial_opts = DialOpts::peer_id(*other.local_peer_id())
             .addresses(external_addresses)
             .condition(PeerCondition::Always)
             .build();
 
-        self.dial(dial_opts).unwrap();
+        if let Err(e) = self.dial(dial_opts) {
+            panic!("Failed to dial {}: {e}.{hint}", other.local_peer_id());
+        }
 
         let mut dialer_done = false;
         let mut listener_done = false;
+        let mut timeout = futures_timer::Delay::new(Duration::from_secs(10));
 
         loop {
-            match futures::future::select(self.next_swarm_event(), other.next_swarm_event()).await {
+            let next_event =
+                futures::future::select(self.next_swarm_event(), other.next_swarm_event());
+
+            let event = match futures::future::select(&mut timeout, next_event).await {
+                Either::Left(((), _)) => panic!(
+                    "Swarms did not connect within 10s (established on dialer: {dialer_done}, on listener: {listener_done})"
+                ),
+                Either::Right((event, _)) => event,
+            };
+
+            match event {
                 Either::Left((SwarmEvent::ConnectionEstablished { .. }, _)) => {
                     dialer_done = true;
                 }
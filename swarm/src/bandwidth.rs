@@ -0,0 +1,284 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use bytes::Bytes;
+use futures::{
+    io::{IoSlice, IoSliceMut},
+    ready, AsyncRead, AsyncWrite,
+};
+use libp2p_core::muxing::{
+    SendDatagramError, StreamMuxer, StreamMuxerBox, StreamMuxerEvent, SubstreamBox,
+};
+use libp2p_identity::PeerId;
+use std::{
+    collections::HashMap,
+    fmt, io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+
+/// Number of peers whose counters are kept, unless more are connected.
+const MAX_PEERS: usize = 1024;
+
+/// Counts the bytes of all connections, in total and per remote peer.
+///
+/// See [`Config::with_bandwidth_accounting`](crate::Config::with_bandwidth_accounting) for which
+/// bytes are counted. Obtained via [`Swarm::bandwidth`](crate::Swarm::bandwidth), the counters
+/// can be read from any task while the [`Swarm`](crate::Swarm) keeps updating them.
+///
+/// The per-peer map is only locked when a connection is established or the counters of a peer
+/// are read. Counting itself only touches atomics.
+///
+/// The counters of peers without a connection are kept until counters of 1024 peers are known, at
+/// which point they are forgotten.
+#[derive(Default)]
+pub struct Bandwidth {
+    total: Arc<Counters>,
+    peers: Mutex<HashMap<PeerId, Arc<Counters>>>,
+}
+
+#[derive(Default)]
+struct Counters {
+    inbound: AtomicU64,
+    outbound: AtomicU64,
+}
+
+impl Counters {
+    fn get(&self) -> (u64, u64) {
+        (
+            self.inbound.load(Ordering::Relaxed),
+            self.outbound.load(Ordering::Relaxed),
+        )
+    }
+}
+
+impl Bandwidth {
+    /// Wraps the muxer of a new connection to the given peer, counting the bytes of its streams.
+    pub(crate) fn wrap(&self, peer: PeerId, muxer: StreamMuxerBox) -> StreamMuxerBox {
+        StreamMuxerBox::new(CountingMuxer {
+            inner: muxer,
+            total: self.total.clone(),
+            peer: self.peer_counters(peer),
+        })
+    }
+
+    /// Returns the counters of the given peer, forgetting those of peers without a connection
+    /// if the map is full.
+    fn peer_counters(&self, peer: PeerId) -> Arc<Counters> {
+        let mut peers = self.peers.lock().expect("poisoned lock");
+
+        if !peers.contains_key(&peer) && peers.len() >= MAX_PEERS {
+            // Counters are shared with the muxers and streams of the peer's connections.
+            peers.retain(|_, counters| Arc::strong_count(counters) > 1);
+        }
+
+        peers.entry(peer).or_default().clone()
+    }
+
+    /// Returns the number of bytes received and sent across all connections.
+    ///
+    /// > **Note**: The counters are updated concurrently by the connection tasks. The returned
+    /// >           values should only ever be used for statistics purposes.
+    pub fn total(&self) -> (u64, u64) {
+        self.total.get()
+    }
+
+    /// Returns the number of bytes received from and sent to the given peer, across all
+    /// connections.
+    ///
+    /// Returns `(0, 0)` if the counters of the peer have been forgotten, see [`Bandwidth`].
+    pub fn peer(&self, peer: &PeerId) -> (u64, u64) {
+        self.peers
+            .lock()
+            .expect("poisoned lock")
+            .get(peer)
+            .map_or((0, 0), |counters| counters.get())
+    }
+}
+
+impl fmt::Debug for Bandwidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bandwidth")
+            .field("total", &self.total())
+            .field("peers", &self.peers.lock().expect("poisoned lock").len())
+            .finish()
+    }
+}
+
+struct CountingMuxer {
+    inner: StreamMuxerBox,
+    total: Arc<Counters>,
+    peer: Arc<Counters>,
+}
+
+impl CountingMuxer {
+    fn wrap_stream(&self, inner: SubstreamBox) -> CountingStream {
+        CountingStream {
+            inner,
+            total: self.total.clone(),
+            peer: self.peer.clone(),
+        }
+    }
+}
+
+impl StreamMuxer for CountingMuxer {
+    type Substream = CountingStream;
+    type Error = io::Error;
+
+    fn poll_inbound(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let stream = ready!(Pin::new(&mut self.inner).poll_inbound(cx))?;
+        Poll::Ready(Ok(self.wrap_stream(stream)))
+    }
+
+    fn poll_outbound(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let stream = ready!(Pin::new(&mut self.inner).poll_outbound(cx))?;
+        Poll::Ready(Ok(self.wrap_stream(stream)))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<StreamMuxerEvent, Self::Error>> {
+        Pin::new(&mut self.inner).poll(cx)
+    }
+
+    fn send_datagram(mut self: Pin<&mut Self>, data: Bytes) -> Result<(), SendDatagramError> {
+        Pin::new(&mut self.inner).send_datagram(data)
+    }
+
+    fn max_datagram_size(&self) -> Option<usize> {
+        self.inner.max_datagram_size()
+    }
+}
+
+struct CountingStream {
+    inner: SubstreamBox,
+    total: Arc<Counters>,
+    peer: Arc<Counters>,
+}
+
+impl CountingStream {
+    fn count_inbound(&self, num_bytes: usize) {
+        let num_bytes = u64::try_from(num_bytes).unwrap_or(u64::MAX);
+        self.total.inbound.fetch_add(num_bytes, Ordering::Relaxed);
+        self.peer.inbound.fetch_add(num_bytes, Ordering::Relaxed);
+    }
+
+    fn count_outbound(&self, num_bytes: usize) {
+        let num_bytes = u64::try_from(num_bytes).unwrap_or(u64::MAX);
+        self.total.outbound.fetch_add(num_bytes, Ordering::Relaxed);
+        self.peer.outbound.fetch_add(num_bytes, Ordering::Relaxed);
+    }
+}
+
+impl AsyncRead for CountingStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let num_bytes = ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        self.count_inbound(num_bytes);
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_read_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let num_bytes = ready!(Pin::new(&mut self.inner).poll_read_vectored(cx, bufs))?;
+        self.count_inbound(num_bytes);
+        Poll::Ready(Ok(num_bytes))
+    }
+}
+
+impl AsyncWrite for CountingStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let num_bytes = ready!(Pin::new(&mut self.inner).poll_write(cx, buf))?;
+        self.count_outbound(num_bytes);
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let num_bytes = ready!(Pin::new(&mut self.inner).poll_write_vectored(cx, bufs))?;
+        self.count_outbound(num_bytes);
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_of_disconnected_peers_are_forgotten_when_full() {
+        let bandwidth = Bandwidth::default();
+        let connected = PeerId::random();
+        let disconnected = PeerId::random();
+
+        let connection = bandwidth.peer_counters(connected);
+        connection.inbound.fetch_add(1, Ordering::Relaxed);
+        bandwidth
+            .peer_counters(disconnected)
+            .inbound
+            .fetch_add(1, Ordering::Relaxed);
+
+        for _ in 2..MAX_PEERS {
+            bandwidth.peer_counters(PeerId::random());
+        }
+        assert_eq!(bandwidth.peer(&disconnected), (1, 0));
+
+        bandwidth.peer_counters(PeerId::random());
+        assert_eq!(bandwidth.peers.lock().unwrap().len(), 2);
+        assert_eq!(bandwidth.peer(&connected), (1, 0));
+        assert_eq!(bandwidth.peer(&disconnected), (0, 0));
+    }
+}
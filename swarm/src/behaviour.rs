@@ -20,10 +20,12 @@
 
 mod either;
 mod external_addresses;
+mod group;
 mod listen_addresses;
 pub mod toggle;
 
 pub use external_addresses::ExternalAddresses;
+pub use group::Group;
 pub use listen_addresses::ListenAddresses;
 
 use crate::connection::ConnectionId;
@@ -33,6 +35,7 @@ use crate::{
     ConnectionDenied, ConnectionHandler, DialError, ListenError, THandler, THandlerInEvent,
     THandlerOutEvent,
 };
+use bytes::Bytes;
 use libp2p_core::{transport::ListenerId, ConnectedPoint, Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
 use std::{task::Context, task::Poll};
@@ -82,7 +85,8 @@ use std::{task::Context, task::Poll};
 /// `enum` event, with an `enum` variant for each `struct` member. Users can define this event
 /// `enum` themselves and provide the name to the derive macro via `#[behaviour(to_swarm =
 /// "MyCustomOutEvent")]`. If the user does not specify an `to_swarm`, the derive macro generates
-/// the event definition itself, naming it `<STRUCT_NAME>Event`.
+/// the event definition itself, naming it `<STRUCT_NAME>Event`, or the name provided via
+/// `#[behaviour(event_name = "MyCustomEventName")]`.
 ///
 /// The aforementioned conversion of each of the event types generated by the struct members to the
 /// custom `to_swarm` is handled by [`From`] implementations which the user needs to define in
@@ -278,6 +282,16 @@ pub enum ToSwarm<TOutEvent, TInEvent> {
     /// - We made an educated guess based on one of our listen addresses.
     NewExternalAddrCandidate(Multiaddr),
 
+    /// Reports that the given peer observed the provided address of ours, e.g. via identify.
+    ///
+    /// Handled like [`ToSwarm::NewExternalAddrCandidate`]. In addition, the score of a candidate
+    /// added via [`Swarm::add_external_address_candidate`](crate::Swarm::add_external_address_candidate)
+    /// increases once for every distinct peer that observed it.
+    ObservedExternalAddr {
+        address: Multiaddr,
+        observer: PeerId,
+    },
+
     /// Indicates to the [`Swarm`](crate::Swarm) that the provided address is confirmed to be externally reachable.
     ///
     /// This is intended to be issued in response to a [`FromSwarm::NewExternalAddrCandidate`] if we are indeed externally reachable on this address.
@@ -333,6 +347,9 @@ impl<TOutEvent, TInEventOld> ToSwarm<TOutEvent, TInEventOld> {
                 connection,
             },
             ToSwarm::NewExternalAddrCandidate(addr) => ToSwarm::NewExternalAddrCandidate(addr),
+            ToSwarm::ObservedExternalAddr { address, observer } => {
+                ToSwarm::ObservedExternalAddr { address, observer }
+            }
             ToSwarm::ExternalAddrConfirmed(addr) => ToSwarm::ExternalAddrConfirmed(addr),
             ToSwarm::ExternalAddrExpired(addr) => ToSwarm::ExternalAddrExpired(addr),
         }
@@ -357,6 +374,9 @@ impl<TOutEvent, THandlerIn> ToSwarm<TOutEvent, THandlerIn> {
                 event,
             },
             ToSwarm::NewExternalAddrCandidate(addr) => ToSwarm::NewExternalAddrCandidate(addr),
+            ToSwarm::ObservedExternalAddr { address, observer } => {
+                ToSwarm::ObservedExternalAddr { address, observer }
+            }
             ToSwarm::ExternalAddrConfirmed(addr) => ToSwarm::ExternalAddrConfirmed(addr),
             ToSwarm::ExternalAddrExpired(addr) => ToSwarm::ExternalAddrExpired(addr),
             ToSwarm::CloseConnection {
@@ -432,6 +452,10 @@ pub enum FromSwarm<'a> {
     ExternalAddrConfirmed(ExternalAddrConfirmed<'a>),
     /// Informs the behaviour that an external address of the local node expired, i.e. is no-longer confirmed.
     ExternalAddrExpired(ExternalAddrExpired<'a>),
+    /// Informs the behaviour that a datagram has been received on a connection.
+    ///
+    /// Datagrams are sent via [`Swarm::send_datagram`](crate::Swarm::send_datagram).
+    DatagramReceived(DatagramReceived<'a>),
 }
 
 /// [`FromSwarm`] variant that informs the behaviour about a newly established connection to a peer.
@@ -543,3 +567,12 @@ pub struct ExternalAddrConfirmed<'a> {
 pub struct ExternalAddrExpired<'a> {
     pub addr: &'a Multiaddr,
 }
+
+/// [`FromSwarm`] variant that informs the behaviour that a datagram has been received on a
+/// connection.
+#[derive(Debug, Clone, Copy)]
+pub struct DatagramReceived<'a> {
+    pub peer_id: PeerId,
+    pub connection_id: ConnectionId,
+    pub data: &'a Bytes,
+}
@@ -0,0 +1,358 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use crate::behaviour::toggle::ToggleConnectionHandler;
+use crate::behaviour::{AddressChange, ConnectionClosed, ConnectionEstablished, FromSwarm};
+use crate::connection::ConnectionId;
+use crate::handler::multi::MultiHandler;
+use crate::{
+    ConnectionDenied, NetworkBehaviour, NotifyHandler, THandler, THandlerInEvent, THandlerOutEvent,
+    ToSwarm,
+};
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::ops::Bound;
+use std::{task::Context, task::Poll};
+
+/// Implementation of [`NetworkBehaviour`] that combines a dynamic number of behaviours of the
+/// same type.
+///
+/// Every behaviour is identified by the index returned from [`Group::push`]. Indices are not
+/// reused after a behaviour was removed, and the events of a behaviour are reported together with
+/// its index.
+///
+/// Behaviours can be added and removed at runtime. Like with [`Toggle`](super::toggle::Toggle),
+/// an added behaviour only takes part in connections established from now on, and the connection
+/// handlers of a removed behaviour are closed gracefully.
+///
+/// Behaviours of the same type usually support the same inbound protocols. As the remote cannot
+/// address a specific behaviour, an inbound stream is passed to one of the behaviours supporting
+/// its protocol. Outbound streams are opened by each behaviour independently.
+pub struct Group<TBehaviour> {
+    behaviours: BTreeMap<usize, TBehaviour>,
+    next_index: usize,
+    /// The indices of the behaviours with a handler on each connection.
+    connections: HashMap<ConnectionId, (PeerId, HashSet<usize>)>,
+    /// Handlers of removed behaviours that still need to be closed.
+    pending_remove: VecDeque<(PeerId, ConnectionId, usize)>,
+    /// The index of the behaviour to poll first, such that all of them make progress.
+    next_poll: usize,
+}
+
+impl<TBehaviour> Group<TBehaviour> {
+    /// Creates an empty [`Group`].
+    pub fn new() -> Self {
+        Group {
+            behaviours: BTreeMap::new(),
+            next_index: 0,
+            connections: HashMap::new(),
+            pending_remove: VecDeque::new(),
+            next_poll: 0,
+        }
+    }
+
+    /// Adds a behaviour to the group, returning its index.
+    ///
+    /// The behaviour only takes part in connections established from now on.
+    pub fn push(&mut self, behaviour: TBehaviour) -> usize {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.behaviours.insert(index, behaviour);
+
+        index
+    }
+
+    /// Removes the behaviour with the given index from the group, if any.
+    ///
+    /// The connection handlers of the behaviour are closed gracefully via
+    /// [`ConnectionHandler::poll_close`](crate::ConnectionHandler::poll_close). Events they still
+    /// produce are discarded. The connections themselves are not closed.
+    pub fn remove(&mut self, index: usize) -> Option<TBehaviour> {
+        let behaviour = self.behaviours.remove(&index)?;
+
+        for (connection_id, (peer_id, indices)) in self.connections.iter_mut() {
+            if indices.remove(&index) {
+                self.pending_remove
+                    .push_back((*peer_id, *connection_id, index));
+            }
+        }
+
+        Some(behaviour)
+    }
+
+    /// Returns a reference to the behaviour with the given index.
+    pub fn get(&self, index: usize) -> Option<&TBehaviour> {
+        self.behaviours.get(&index)
+    }
+
+    /// Returns a mutable reference to the behaviour with the given index.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut TBehaviour> {
+        self.behaviours.get_mut(&index)
+    }
+
+    /// Returns the behaviours together with their index, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &TBehaviour)> {
+        self.behaviours.iter().map(|(index, b)| (*index, b))
+    }
+
+    /// Returns mutable references to the behaviours together with their index, in the order they
+    /// were added.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut TBehaviour)> {
+        self.behaviours.iter_mut().map(|(index, b)| (*index, b))
+    }
+
+    /// Returns the number of behaviours in the group.
+    pub fn len(&self) -> usize {
+        self.behaviours.len()
+    }
+
+    /// Returns `true` if the group contains no behaviours.
+    pub fn is_empty(&self) -> bool {
+        self.behaviours.is_empty()
+    }
+}
+
+impl<TBehaviour> Default for Group<TBehaviour> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TBehaviour> FromIterator<TBehaviour> for Group<TBehaviour> {
+    fn from_iter<I: IntoIterator<Item = TBehaviour>>(iter: I) -> Self {
+        let mut group = Group::new();
+        for behaviour in iter {
+            group.push(behaviour);
+        }
+
+        group
+    }
+}
+
+impl<TBehaviour> From<Vec<TBehaviour>> for Group<TBehaviour> {
+    fn from(behaviours: Vec<TBehaviour>) -> Self {
+        behaviours.into_iter().collect()
+    }
+}
+
+impl<TBehaviour> Group<TBehaviour>
+where
+    TBehaviour: NetworkBehaviour,
+{
+    fn new_handler(
+        &mut self,
+        mut new_handler: impl FnMut(&mut TBehaviour) -> Result<THandler<TBehaviour>, ConnectionDenied>,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        let handlers = self
+            .behaviours
+            .iter_mut()
+            .map(|(index, behaviour)| {
+                let handler = new_handler(behaviour)?;
+                Ok((*index, ToggleConnectionHandler::enabled(handler)))
+            })
+            .collect::<Result<Vec<_>, ConnectionDenied>>()?;
+
+        Ok(MultiHandler::from_iter_unchecked(handlers))
+    }
+}
+
+impl<TBehaviour> NetworkBehaviour for Group<TBehaviour>
+where
+    TBehaviour: NetworkBehaviour,
+{
+    type ConnectionHandler = MultiHandler<usize, ToggleConnectionHandler<THandler<TBehaviour>>>;
+    type ToSwarm = (usize, TBehaviour::ToSwarm);
+
+    fn handle_pending_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<(), ConnectionDenied> {
+        for behaviour in self.behaviours.values_mut() {
+            behaviour.handle_pending_inbound_connection(connection_id, local_addr, remote_addr)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.new_handler(|behaviour| {
+            behaviour.handle_established_inbound_connection(
+                connection_id,
+                peer,
+                local_addr,
+                remote_addr,
+            )
+        })
+    }
+
+    fn handle_pending_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        maybe_peer: Option<PeerId>,
+        addresses: &[Multiaddr],
+        effective_role: Endpoint,
+    ) -> Result<Vec<Multiaddr>, ConnectionDenied> {
+        let mut combined_addresses = Vec::new();
+        for behaviour in self.behaviours.values_mut() {
+            combined_addresses.extend(behaviour.handle_pending_outbound_connection(
+                connection_id,
+                maybe_peer,
+                addresses,
+                effective_role,
+            )?);
+        }
+
+        Ok(combined_addresses)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &Multiaddr,
+        role_override: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.new_handler(|behaviour| {
+            behaviour.handle_established_outbound_connection(
+                connection_id,
+                peer,
+                addr,
+                role_override,
+            )
+        })
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        // Events about a single connection only concern the behaviours with a handler on it.
+        let connection_id = match &event {
+            // The handler of a connection was created just before, i.e. for all behaviours.
+            FromSwarm::ConnectionEstablished(ConnectionEstablished {
+                peer_id,
+                connection_id,
+                ..
+            }) => {
+                self.connections.insert(
+                    *connection_id,
+                    (*peer_id, self.behaviours.keys().copied().collect()),
+                );
+                None
+            }
+            FromSwarm::ConnectionClosed(ConnectionClosed { connection_id, .. })
+            | FromSwarm::AddressChange(AddressChange { connection_id, .. }) => Some(*connection_id),
+            _ => None,
+        };
+
+        let indices = connection_id.and_then(|id| self.connections.get(&id));
+        for (index, behaviour) in self.behaviours.iter_mut() {
+            if indices.map_or(true, |(_, indices)| indices.contains(index)) {
+                behaviour.on_swarm_event(copy_event(&event));
+            }
+        }
+
+        if let FromSwarm::ConnectionClosed(ConnectionClosed { connection_id, .. }) = event {
+            self.connections.remove(&connection_id);
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        (index, event): THandlerOutEvent<Self>,
+    ) {
+        // Discard events of handlers that belong to a removed behaviour.
+        if let Some(behaviour) = self.behaviours.get_mut(&index) {
+            behaviour.on_connection_handler_event(peer_id, connection_id, event)
+        }
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some((peer_id, connection_id, index)) = self.pending_remove.pop_front() {
+            return Poll::Ready(ToSwarm::NotifyHandler {
+                peer_id,
+                handler: NotifyHandler::One(connection_id),
+                event: (index, None),
+            });
+        }
+
+        let next_poll = self.next_poll;
+        for range in [
+            (Bound::Included(next_poll), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Excluded(next_poll)),
+        ] {
+            for (index, behaviour) in self.behaviours.range_mut(range) {
+                if let Poll::Ready(action) = behaviour.poll(cx) {
+                    let index = *index;
+                    self.next_poll = index + 1;
+
+                    return Poll::Ready(
+                        action
+                            .map_out(|event| (index, event))
+                            .map_in(|event| (index, Some(event))),
+                    );
+                }
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Re-creates the event, such that it can be passed to several behaviours.
+fn copy_event<'a>(event: &FromSwarm<'a>) -> FromSwarm<'a> {
+    match event {
+        FromSwarm::ConnectionEstablished(e) => FromSwarm::ConnectionEstablished(*e),
+        FromSwarm::ConnectionClosed(ConnectionClosed {
+            peer_id,
+            connection_id,
+            endpoint,
+            remaining_established,
+        }) => FromSwarm::ConnectionClosed(ConnectionClosed {
+            peer_id: *peer_id,
+            connection_id: *connection_id,
+            endpoint: *endpoint,
+            remaining_established: *remaining_established,
+        }),
+        FromSwarm::AddressChange(e) => FromSwarm::AddressChange(*e),
+        FromSwarm::DialFailure(e) => FromSwarm::DialFailure(*e),
+        FromSwarm::ListenFailure(e) => FromSwarm::ListenFailure(*e),
+        FromSwarm::NewListener(e) => FromSwarm::NewListener(*e),
+        FromSwarm::NewListenAddr(e) => FromSwarm::NewListenAddr(*e),
+        FromSwarm::ExpiredListenAddr(e) => FromSwarm::ExpiredListenAddr(*e),
+        FromSwarm::ListenerError(e) => FromSwarm::ListenerError(*e),
+        FromSwarm::ListenerClosed(e) => FromSwarm::ListenerClosed(*e),
+        FromSwarm::NewExternalAddrCandidate(e) => FromSwarm::NewExternalAddrCandidate(*e),
+        FromSwarm::ExternalAddrConfirmed(e) => FromSwarm::ExternalAddrConfirmed(*e),
+        FromSwarm::ExternalAddrExpired(e) => FromSwarm::ExternalAddrExpired(*e),
+        FromSwarm::DatagramReceived(e) => FromSwarm::DatagramReceived(*e),
+    }
+}
@@ -18,7 +18,10 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use crate::behaviour::FromSwarm;
+use crate::behaviour::{
+    AddressChange as SwarmAddressChange, ConnectionClosed, ConnectionEstablished, DialFailure,
+    FromSwarm, ListenFailure,
+};
 use crate::connection::ConnectionId;
 use crate::handler::{
     AddressChange, ConnectionEvent, ConnectionHandler, ConnectionHandlerEvent, DialUpgradeError,
@@ -26,19 +29,31 @@ use crate::handler::{
 };
 use crate::upgrade::SendWrapper;
 use crate::{
-    ConnectionDenied, NetworkBehaviour, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+    ConnectionDenied, NetworkBehaviour, NotifyHandler, THandler, THandlerInEvent, THandlerOutEvent,
+    ToSwarm,
 };
 use either::Either;
 use futures::future;
 use libp2p_core::{upgrade::DeniedUpgrade, Endpoint, Multiaddr};
 use libp2p_identity::PeerId;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::{task::Context, task::Poll};
 
 /// Implementation of `NetworkBehaviour` that can be either in the disabled or enabled state.
 ///
-/// The state can only be chosen at initialization.
+/// The initial state is chosen at initialization. It can be changed at runtime via
+/// [`Toggle::disable`] and [`Toggle::enable`].
 pub struct Toggle<TBehaviour> {
     inner: Option<TBehaviour>,
+    /// The connections with a handler of the inner behaviour.
+    enabled_connections: HashMap<ConnectionId, PeerId>,
+    /// The pending connections the inner behaviour was asked about.
+    pending_connections: HashSet<ConnectionId>,
+    /// The pending connections the inner behaviour doesn't know about, as they started while the
+    /// `Toggle` was disabled or another behaviour was enabled.
+    unknown_pending_connections: HashSet<ConnectionId>,
+    /// Connections whose handler of the inner behaviour still needs to be disabled.
+    pending_disable: VecDeque<(PeerId, ConnectionId)>,
 }
 
 impl<TBehaviour> Toggle<TBehaviour> {
@@ -56,11 +71,47 @@ impl<TBehaviour> Toggle<TBehaviour> {
     pub fn as_mut(&mut self) -> Option<&mut TBehaviour> {
         self.inner.as_mut()
     }
+
+    /// Disables the `Toggle`, returning the inner `NetworkBehaviour` if it was enabled.
+    ///
+    /// The connection handlers of the inner behaviour are closed gracefully via
+    /// [`ConnectionHandler::poll_close`] and stop accepting new inbound streams. Events they still
+    /// produce are discarded. The connections themselves are not closed; they are kept alive or
+    /// closed by the remaining behaviours as usual.
+    pub fn disable(&mut self) -> Option<TBehaviour> {
+        self.unknown_pending_connections
+            .extend(self.pending_connections.drain());
+        self.pending_disable.extend(
+            self.enabled_connections
+                .drain()
+                .map(|(connection_id, peer_id)| (peer_id, connection_id)),
+        );
+
+        self.inner.take()
+    }
+
+    /// Enables the `Toggle` with the given `NetworkBehaviour`, returning the previous one if it
+    /// was enabled.
+    ///
+    /// The behaviour only takes part in connections established from now on. A previously enabled
+    /// behaviour is disabled as in [`Toggle::disable`].
+    pub fn enable(&mut self, behaviour: TBehaviour) -> Option<TBehaviour> {
+        let previous = self.disable();
+        self.inner = Some(behaviour);
+
+        previous
+    }
 }
 
 impl<TBehaviour> From<Option<TBehaviour>> for Toggle<TBehaviour> {
     fn from(inner: Option<TBehaviour>) -> Self {
-        Toggle { inner }
+        Toggle {
+            inner,
+            enabled_connections: HashMap::new(),
+            pending_connections: HashSet::new(),
+            unknown_pending_connections: HashSet::new(),
+            pending_disable: VecDeque::new(),
+        }
     }
 }
 
@@ -78,10 +129,14 @@ where
         remote_addr: &Multiaddr,
     ) -> Result<(), ConnectionDenied> {
         let inner = match self.inner.as_mut() {
-            None => return Ok(()),
+            None => {
+                self.unknown_pending_connections.insert(connection_id);
+                return Ok(());
+            }
             Some(inner) => inner,
         };
 
+        self.pending_connections.insert(connection_id);
         inner.handle_pending_inbound_connection(connection_id, local_addr, remote_addr)?;
 
         Ok(())
@@ -95,7 +150,7 @@ where
         remote_addr: &Multiaddr,
     ) -> Result<THandler<Self>, ConnectionDenied> {
         let inner = match self.inner.as_mut() {
-            None => return Ok(ToggleConnectionHandler { inner: None }),
+            None => return Ok(ToggleConnectionHandler::disabled()),
             Some(inner) => inner,
         };
 
@@ -106,9 +161,7 @@ where
             remote_addr,
         )?;
 
-        Ok(ToggleConnectionHandler {
-            inner: Some(handler),
-        })
+        Ok(ToggleConnectionHandler::enabled(handler))
     }
 
     fn handle_pending_outbound_connection(
@@ -119,10 +172,14 @@ where
         effective_role: Endpoint,
     ) -> Result<Vec<Multiaddr>, ConnectionDenied> {
         let inner = match self.inner.as_mut() {
-            None => return Ok(vec![]),
+            None => {
+                self.unknown_pending_connections.insert(connection_id);
+                return Ok(vec![]);
+            }
             Some(inner) => inner,
         };
 
+        self.pending_connections.insert(connection_id);
         let addresses = inner.handle_pending_outbound_connection(
             connection_id,
             maybe_peer,
@@ -141,7 +198,7 @@ where
         role_override: Endpoint,
     ) -> Result<THandler<Self>, ConnectionDenied> {
         let inner = match self.inner.as_mut() {
-            None => return Ok(ToggleConnectionHandler { inner: None }),
+            None => return Ok(ToggleConnectionHandler::disabled()),
             Some(inner) => inner,
         };
 
@@ -152,13 +209,44 @@ where
             role_override,
         )?;
 
-        Ok(ToggleConnectionHandler {
-            inner: Some(handler),
-        })
+        Ok(ToggleConnectionHandler::enabled(handler))
     }
 
     fn on_swarm_event(&mut self, event: FromSwarm) {
-        if let Some(behaviour) = &mut self.inner {
+        // Events about a single connection are only passed on if the inner behaviour knows the
+        // connection, i.e. if it was enabled when the connection was pending or established.
+        // Dial failures that occur before a connection is pending, e.g. as the peer is banned,
+        // are passed on.
+        let known = match event {
+            // The handler of a connection established while enabled belongs to the inner
+            // behaviour, as it was created just before.
+            FromSwarm::ConnectionEstablished(ConnectionEstablished {
+                peer_id,
+                connection_id,
+                ..
+            }) => {
+                self.pending_connections.remove(&connection_id);
+                self.unknown_pending_connections.remove(&connection_id);
+                if self.inner.is_some() {
+                    self.enabled_connections.insert(connection_id, peer_id);
+                }
+                true
+            }
+            FromSwarm::ConnectionClosed(ConnectionClosed { connection_id, .. }) => {
+                self.enabled_connections.remove(&connection_id).is_some()
+            }
+            FromSwarm::AddressChange(SwarmAddressChange { connection_id, .. }) => {
+                self.enabled_connections.contains_key(&connection_id)
+            }
+            FromSwarm::DialFailure(DialFailure { connection_id, .. })
+            | FromSwarm::ListenFailure(ListenFailure { connection_id, .. }) => {
+                self.pending_connections.remove(&connection_id);
+                !self.unknown_pending_connections.remove(&connection_id)
+            }
+            _ => true,
+        };
+
+        if let Some(behaviour) = self.inner.as_mut().filter(|_| known) {
             behaviour.on_swarm_event(event);
         }
     }
@@ -169,6 +257,11 @@ where
         connection_id: ConnectionId,
         event: THandlerOutEvent<Self>,
     ) {
+        // Discard events of handlers that belong to a previously disabled behaviour.
+        if !self.enabled_connections.contains_key(&connection_id) {
+            return;
+        }
+
         if let Some(behaviour) = &mut self.inner {
             behaviour.on_connection_handler_event(peer_id, connection_id, event)
         }
@@ -178,8 +271,16 @@ where
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some((peer_id, connection_id)) = self.pending_disable.pop_front() {
+            return Poll::Ready(ToSwarm::NotifyHandler {
+                peer_id,
+                handler: NotifyHandler::One(connection_id),
+                event: None,
+            });
+        }
+
         if let Some(inner) = self.inner.as_mut() {
-            inner.poll(cx)
+            inner.poll(cx).map(|action| action.map_in(Some))
         } else {
             Poll::Pending
         }
@@ -187,14 +288,50 @@ where
 }
 
 /// Implementation of [`ConnectionHandler`] that can be in the disabled state.
+///
+/// Receiving `None` from the behaviour disables an enabled handler. The inner handler is then
+/// closed gracefully via [`ConnectionHandler::poll_close`].
 pub struct ToggleConnectionHandler<TInner> {
     inner: Option<TInner>,
+    /// The inner handler after it was disabled, until it finished closing.
+    closing: Option<TInner>,
+    /// Whether the handler was disabled after being enabled.
+    ///
+    /// Streams and events destined for the inner handler may still arrive afterwards.
+    disabled: bool,
 }
 
 impl<TInner> ToggleConnectionHandler<TInner>
 where
     TInner: ConnectionHandler,
 {
+    pub(crate) fn enabled(inner: TInner) -> Self {
+        Self {
+            inner: Some(inner),
+            closing: None,
+            disabled: false,
+        }
+    }
+
+    fn disabled() -> Self {
+        Self {
+            inner: None,
+            closing: None,
+            disabled: false,
+        }
+    }
+
+    /// Returns the inner handler, including one that is closing.
+    ///
+    /// Panics with `msg` if there never was an inner handler.
+    fn inner_mut(&mut self, msg: &str) -> Option<&mut TInner> {
+        match self.inner.as_mut().or(self.closing.as_mut()) {
+            Some(inner) => Some(inner),
+            None if self.disabled => None,
+            None => panic!("{msg}"),
+        }
+    }
+
     fn on_fully_negotiated_inbound(
         &mut self,
         FullyNegotiatedInbound {
@@ -211,15 +348,16 @@ where
         };
 
         if let Either::Left(info) = info {
-            self.inner
-                .as_mut()
-                .expect("Can't receive an inbound substream if disabled; QED")
-                .on_connection_event(ConnectionEvent::FullyNegotiatedInbound(
+            if let Some(inner) =
+                self.inner_mut("Can't receive an inbound substream if disabled; QED")
+            {
+                inner.on_connection_event(ConnectionEvent::FullyNegotiatedInbound(
                     FullyNegotiatedInbound {
                         protocol: out,
                         info,
                     },
                 ));
+            }
         } else {
             panic!("Unexpected Either::Right in enabled `on_fully_negotiated_inbound`.")
         }
@@ -232,10 +370,14 @@ where
             <Self as ConnectionHandler>::InboundProtocol,
         >,
     ) {
-        let (inner, info) = match (self.inner.as_mut(), info) {
+        let disabled = self.disabled;
+        let (inner, info) = match (self.inner.as_mut().or(self.closing.as_mut()), info) {
             (Some(inner), Either::Left(info)) => (inner, info),
             // Ignore listen upgrade errors in disabled state.
             (None, Either::Right(())) => return,
+            // Upgrades of the inner handler may still fail after it was disabled.
+            (None, Either::Left(_)) if disabled => return,
+            (Some(_), Either::Right(())) if disabled => return,
             (Some(_), Either::Right(())) => panic!(
                 "Unexpected `Either::Right` inbound info through \
                  `on_listen_upgrade_error` in enabled state.",
@@ -262,7 +404,7 @@ impl<TInner> ConnectionHandler for ToggleConnectionHandler<TInner>
 where
     TInner: ConnectionHandler,
 {
-    type FromBehaviour = TInner::FromBehaviour;
+    type FromBehaviour = Option<TInner::FromBehaviour>;
     type ToBehaviour = TInner::ToBehaviour;
     type InboundProtocol = Either<SendWrapper<TInner::InboundProtocol>, SendWrapper<DeniedUpgrade>>;
     type OutboundProtocol = TInner::OutboundProtocol;
@@ -281,10 +423,17 @@ where
     }
 
     fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
-        self.inner
-            .as_mut()
-            .expect("Can't receive events if disabled; QED")
-            .on_behaviour_event(event)
+        let Some(event) = event else {
+            if let Some(inner) = self.inner.take() {
+                self.closing = Some(inner);
+                self.disabled = true;
+            }
+            return;
+        };
+
+        if let Some(inner) = self.inner_mut("Can't receive events if disabled; QED") {
+            inner.on_behaviour_event(event)
+        }
     }
 
     fn connection_keep_alive(&self) -> bool {
@@ -294,6 +443,13 @@ where
             .unwrap_or(false)
     }
 
+    fn accepts_inbound_streams(&self) -> bool {
+        self.inner
+            .as_ref()
+            .map(|h| h.accepts_inbound_streams())
+            .unwrap_or(true)
+    }
+
     fn poll(
         &mut self,
         cx: &mut Context<'_>,
@@ -301,10 +457,20 @@ where
         ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
     > {
         if let Some(inner) = self.inner.as_mut() {
-            inner.poll(cx)
-        } else {
-            Poll::Pending
+            return inner.poll(cx);
+        }
+
+        if let Some(closing) = self.closing.as_mut() {
+            // Events of a disabled handler have no behaviour to go to.
+            while let Poll::Ready(event) = closing.poll_close(cx) {
+                if event.is_none() {
+                    self.closing = None;
+                    break;
+                }
+            }
         }
+
+        Poll::Pending
     }
 
     fn on_connection_event(
@@ -323,16 +489,18 @@ where
             ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
                 protocol: out,
                 info,
-            }) => self
-                .inner
-                .as_mut()
-                .expect("Can't receive an outbound substream if disabled; QED")
-                .on_connection_event(ConnectionEvent::FullyNegotiatedOutbound(
-                    FullyNegotiatedOutbound {
-                        protocol: out,
-                        info,
-                    },
-                )),
+            }) => {
+                if let Some(inner) =
+                    self.inner_mut("Can't receive an outbound substream if disabled; QED")
+                {
+                    inner.on_connection_event(ConnectionEvent::FullyNegotiatedOutbound(
+                        FullyNegotiatedOutbound {
+                            protocol: out,
+                            info,
+                        },
+                    ))
+                }
+            }
             ConnectionEvent::AddressChange(address_change) => {
                 if let Some(inner) = self.inner.as_mut() {
                     inner.on_connection_event(ConnectionEvent::AddressChange(AddressChange {
@@ -340,14 +508,16 @@ where
                     }));
                 }
             }
-            ConnectionEvent::DialUpgradeError(DialUpgradeError { info, error: err }) => self
-                .inner
-                .as_mut()
-                .expect("Can't receive an outbound substream if disabled; QED")
-                .on_connection_event(ConnectionEvent::DialUpgradeError(DialUpgradeError {
-                    info,
-                    error: err,
-                })),
+            ConnectionEvent::DialUpgradeError(DialUpgradeError { info, error: err }) => {
+                if let Some(inner) =
+                    self.inner_mut("Can't receive an outbound substream if disabled; QED")
+                {
+                    inner.on_connection_event(ConnectionEvent::DialUpgradeError(DialUpgradeError {
+                        info,
+                        error: err,
+                    }))
+                }
+            }
             ConnectionEvent::ListenUpgradeError(listen_upgrade_error) => {
                 self.on_listen_upgrade_error(listen_upgrade_error)
             }
@@ -365,10 +535,23 @@ where
     }
 
     fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Option<Self::ToBehaviour>> {
-        let Some(inner) = self.inner.as_mut() else {
-            return Poll::Ready(None);
-        };
+        if let Some(inner) = self.inner.as_mut() {
+            return inner.poll_close(cx);
+        }
+
+        if let Some(closing) = self.closing.as_mut() {
+            while let Poll::Ready(event) = closing.poll_close(cx) {
+                if event.is_none() {
+                    self.closing = None;
+                    break;
+                }
+            }
+
+            if self.closing.is_some() {
+                return Poll::Pending;
+            }
+        }
 
-        inner.poll_close(cx)
+        Poll::Ready(None)
     }
 }
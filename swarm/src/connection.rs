@@ -39,6 +39,7 @@ use crate::upgrade::{InboundUpgradeSend, OutboundUpgradeSend};
 use crate::{
     ConnectionHandlerEvent, Stream, StreamProtocol, StreamUpgradeError, SubstreamProtocol,
 };
+use bytes::Bytes;
 use futures::future::BoxFuture;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
@@ -52,10 +53,11 @@ use libp2p_core::upgrade;
 use libp2p_core::upgrade::{NegotiationError, ProtocolError};
 use libp2p_core::Endpoint;
 use libp2p_identity::PeerId;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::future::Future;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::task::Waker;
 use std::time::Duration;
 use std::{fmt, io, mem, pin::Pin, task::Context, task::Poll};
@@ -105,6 +107,13 @@ pub(crate) enum Event<T> {
     Handler(T),
     /// Address of the remote has changed.
     AddressChange(Multiaddr),
+    /// A stream has been opened and the given protocol has been negotiated on it.
+    StreamOpened {
+        protocol: StreamProtocol,
+        direction: Endpoint,
+    },
+    /// A datagram has been received from the remote.
+    DatagramReceived(Bytes),
 }
 
 /// A multiplexed connection to a peer with an associated [`ConnectionHandler`].
@@ -152,11 +161,18 @@ where
     requested_substreams: FuturesUnordered<
         SubstreamRequested<THandler::OutboundOpenInfo, THandler::OutboundProtocol>,
     >,
+    /// Priorities of outbound streams by protocol.
+    ///
+    /// Whenever the muxer hands out a new outbound stream, it is assigned to the waiting request
+    /// with the highest priority. Protocols not contained have a priority of 0.
+    stream_priorities: Arc<HashMap<StreamProtocol, u8>>,
 
     local_supported_protocols: HashSet<StreamProtocol>,
     remote_supported_protocols: HashSet<StreamProtocol>,
     idle_timeout: Duration,
     stream_counter: ActiveStreamCounter,
+    /// Whether to report [`Event::StreamOpened`] for every negotiated stream.
+    report_opened_streams: bool,
 }
 
 impl<THandler> fmt::Debug for Connection<THandler>
@@ -185,6 +201,8 @@ where
         substream_upgrade_protocol_override: Option<upgrade::Version>,
         max_negotiating_inbound_streams: usize,
         idle_timeout: Duration,
+        report_opened_streams: bool,
+        stream_priorities: Arc<HashMap<StreamProtocol, u8>>,
     ) -> Self {
         let initial_protocols = gather_supported_protocols(&handler);
         if !initial_protocols.is_empty() {
@@ -201,10 +219,12 @@ where
             substream_upgrade_protocol_override,
             max_negotiating_inbound_streams,
             requested_substreams: Default::default(),
+            stream_priorities,
             local_supported_protocols: initial_protocols,
             remote_supported_protocols: Default::default(),
             idle_timeout,
             stream_counter: ActiveStreamCounter::default(),
+            report_opened_streams,
         }
     }
 
@@ -213,6 +233,13 @@ where
         self.handler.on_behaviour_event(event);
     }
 
+    /// Sends an unreliable datagram to the remote, dropping it if the muxer fails to send it.
+    pub(crate) fn send_datagram(&mut self, data: Bytes) {
+        if let Err(error) = self.muxing.send_datagram_unpin(data) {
+            tracing::debug!("Failed to send datagram: {error}");
+        }
+    }
+
     /// Begins an orderly shutdown of the connection, returning a stream of final events and a `Future` that resolves when connection shutdown is complete.
     pub(crate) fn close(
         self,
@@ -252,6 +279,8 @@ where
             remote_supported_protocols,
             idle_timeout,
             stream_counter,
+            report_opened_streams,
+            stream_priorities,
             ..
         } = self.get_mut();
 
@@ -276,8 +305,11 @@ where
                 Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest { protocol }) => {
                     let timeout = *protocol.timeout();
                     let (upgrade, user_data) = protocol.into_upgrade();
+                    let priority = stream_priority(stream_priorities, &upgrade);
 
-                    requested_substreams.push(SubstreamRequested::new(user_data, timeout, upgrade));
+                    requested_substreams.push(SubstreamRequested::new(
+                        user_data, timeout, upgrade, priority,
+                    ));
                     continue; // Poll handler until exhausted.
                 }
                 Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event)) => {
@@ -313,10 +345,16 @@ where
             // In case the [`ConnectionHandler`] can not make any more progress, poll the negotiating outbound streams.
             match negotiating_out.poll_next_unpin(cx) {
                 Poll::Pending | Poll::Ready(None) => {}
-                Poll::Ready(Some((info, Ok(protocol)))) => {
+                Poll::Ready(Some((info, Ok((negotiated, protocol))))) => {
                     handler.on_connection_event(ConnectionEvent::FullyNegotiatedOutbound(
                         FullyNegotiatedOutbound { protocol, info },
                     ));
+                    if let Some(protocol) = negotiated {
+                        return Poll::Ready(Ok(Event::StreamOpened {
+                            protocol,
+                            direction: Endpoint::Dialer,
+                        }));
+                    }
                     continue;
                 }
                 Poll::Ready(Some((info, Err(error)))) => {
@@ -331,10 +369,16 @@ where
             // make any more progress, poll the negotiating inbound streams.
             match negotiating_in.poll_next_unpin(cx) {
                 Poll::Pending | Poll::Ready(None) => {}
-                Poll::Ready(Some((info, Ok(protocol)))) => {
+                Poll::Ready(Some((info, Ok((negotiated, protocol))))) => {
                     handler.on_connection_event(ConnectionEvent::FullyNegotiatedInbound(
                         FullyNegotiatedInbound { protocol, info },
                     ));
+                    if let Some(protocol) = negotiated {
+                        return Poll::Ready(Ok(Event::StreamOpened {
+                            protocol,
+                            direction: Endpoint::Listener,
+                        }));
+                    }
                     continue;
                 }
                 Poll::Ready(Some((info, Err(StreamUpgradeError::Apply(error))))) => {
@@ -392,9 +436,16 @@ where
                     }));
                     return Poll::Ready(Ok(Event::AddressChange(address)));
                 }
+                Poll::Ready(StreamMuxerEvent::DatagramReceived(data)) => {
+                    return Poll::Ready(Ok(Event::DatagramReceived(data)));
+                }
             }
 
-            if let Some(requested_substream) = requested_substreams.iter_mut().next() {
+            if let Some(requested_substream) = requested_substreams
+                .iter_mut()
+                .filter(|r| r.is_waiting())
+                .max_by_key(|r| r.priority())
+            {
                 match muxing.poll_outbound_unpin(cx)? {
                     Poll::Pending => {}
                     Poll::Ready(substream) => {
@@ -407,6 +458,7 @@ where
                             upgrade,
                             *substream_upgrade_protocol_override,
                             stream_counter.clone(),
+                            *report_opened_streams,
                         ));
 
                         continue; // Go back to the top, handler can potentially make progress again.
@@ -414,7 +466,9 @@ where
                 }
             }
 
-            if negotiating_in.len() < *max_negotiating_inbound_streams {
+            if negotiating_in.len() < *max_negotiating_inbound_streams
+                && handler.accepts_inbound_streams()
+            {
                 match muxing.poll_inbound_unpin(cx)? {
                     Poll::Pending => {}
                     Poll::Ready(substream) => {
@@ -424,6 +478,7 @@ where
                             substream,
                             protocol,
                             stream_counter.clone(),
+                            *report_opened_streams,
                         ));
 
                         continue; // Go back to the top, handler can potentially make progress again.
@@ -520,7 +575,8 @@ impl<'a> IncomingInfo<'a> {
 struct StreamUpgrade<UserData, TOk, TErr> {
     user_data: Option<UserData>,
     timeout: Delay,
-    upgrade: BoxFuture<'static, Result<TOk, StreamUpgradeError<TErr>>>,
+    /// Resolves to the output of the upgrade and, if requested, the negotiated protocol.
+    upgrade: BoxFuture<'static, Result<(Option<StreamProtocol>, TOk), StreamUpgradeError<TErr>>>,
 }
 
 impl<UserData, TOk, TErr> StreamUpgrade<UserData, TOk, TErr> {
@@ -531,6 +587,7 @@ impl<UserData, TOk, TErr> StreamUpgrade<UserData, TOk, TErr> {
         upgrade: Upgrade,
         version_override: Option<upgrade::Version>,
         counter: ActiveStreamCounter,
+        report_protocol: bool,
     ) -> Self
     where
         Upgrade: OutboundUpgradeSend<Output = TOk, Error = TErr>,
@@ -561,12 +618,15 @@ impl<UserData, TOk, TErr> StreamUpgrade<UserData, TOk, TErr> {
                 .await
                 .map_err(to_stream_upgrade_error)?;
 
+                let negotiated = report_protocol
+                    .then(|| negotiated_protocol(&info))
+                    .flatten();
                 let output = upgrade
                     .upgrade_outbound(Stream::new(stream, counter), info)
                     .await
                     .map_err(StreamUpgradeError::Apply)?;
 
-                Ok(output)
+                Ok((negotiated, output))
             }),
         }
     }
@@ -577,6 +637,7 @@ impl<UserData, TOk, TErr> StreamUpgrade<UserData, TOk, TErr> {
         substream: SubstreamBox,
         protocol: SubstreamProtocol<Upgrade, UserData>,
         counter: ActiveStreamCounter,
+        report_protocol: bool,
     ) -> Self
     where
         Upgrade: InboundUpgradeSend<Output = TOk, Error = TErr>,
@@ -594,17 +655,24 @@ impl<UserData, TOk, TErr> StreamUpgrade<UserData, TOk, TErr> {
                         .await
                         .map_err(to_stream_upgrade_error)?;
 
+                let negotiated = report_protocol
+                    .then(|| negotiated_protocol(&info))
+                    .flatten();
                 let output = upgrade
                     .upgrade_inbound(Stream::new(stream, counter), info)
                     .await
                     .map_err(StreamUpgradeError::Apply)?;
 
-                Ok(output)
+                Ok((negotiated, output))
             }),
         }
     }
 }
 
+fn negotiated_protocol(info: &impl AsRef<str>) -> Option<StreamProtocol> {
+    StreamProtocol::try_from_owned(info.as_ref().to_owned()).ok()
+}
+
 fn to_stream_upgrade_error<T>(e: NegotiationError) -> StreamUpgradeError<T> {
     match e {
         NegotiationError::Failed => StreamUpgradeError::NegotiationFailed,
@@ -618,7 +686,10 @@ fn to_stream_upgrade_error<T>(e: NegotiationError) -> StreamUpgradeError<T> {
 impl<UserData, TOk, TErr> Unpin for StreamUpgrade<UserData, TOk, TErr> {}
 
 impl<UserData, TOk, TErr> Future for StreamUpgrade<UserData, TOk, TErr> {
-    type Output = (UserData, Result<TOk, StreamUpgradeError<TErr>>);
+    type Output = (
+        UserData,
+        Result<(Option<StreamProtocol>, TOk), StreamUpgradeError<TErr>>,
+    );
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
         match self.timeout.poll_unpin(cx) {
@@ -644,11 +715,30 @@ impl<UserData, TOk, TErr> Future for StreamUpgrade<UserData, TOk, TErr> {
     }
 }
 
+/// Returns the highest priority configured for any of the protocols offered by `upgrade`.
+fn stream_priority(priorities: &HashMap<StreamProtocol, u8>, upgrade: &impl UpgradeInfoSend) -> u8 {
+    if priorities.is_empty() {
+        return 0;
+    }
+
+    upgrade
+        .protocol_info()
+        .filter_map(|info| {
+            priorities
+                .iter()
+                .find(|(protocol, _)| protocol.as_ref() == info.as_ref())
+                .map(|(_, priority)| *priority)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
 enum SubstreamRequested<UserData, Upgrade> {
     Waiting {
         user_data: UserData,
         timeout: Delay,
         upgrade: Upgrade,
+        priority: u8,
         /// A waker to notify our [`FuturesUnordered`] that we have extracted the data.
         ///
         /// This will ensure that we will get polled again in the next iteration which allows us to
@@ -659,15 +749,27 @@ enum SubstreamRequested<UserData, Upgrade> {
 }
 
 impl<UserData, Upgrade> SubstreamRequested<UserData, Upgrade> {
-    fn new(user_data: UserData, timeout: Duration, upgrade: Upgrade) -> Self {
+    fn new(user_data: UserData, timeout: Duration, upgrade: Upgrade, priority: u8) -> Self {
         Self::Waiting {
             user_data,
             timeout: Delay::new(timeout),
             upgrade,
+            priority,
             extracted_waker: None,
         }
     }
 
+    fn is_waiting(&self) -> bool {
+        matches!(self, SubstreamRequested::Waiting { .. })
+    }
+
+    fn priority(&self) -> u8 {
+        match self {
+            SubstreamRequested::Waiting { priority, .. } => *priority,
+            SubstreamRequested::Done => 0,
+        }
+    }
+
     fn extract(&mut self) -> (UserData, Delay, Upgrade) {
         match mem::replace(self, Self::Done) {
             SubstreamRequested::Waiting {
@@ -675,6 +777,7 @@ impl<UserData, Upgrade> SubstreamRequested<UserData, Upgrade> {
                 timeout,
                 upgrade,
                 extracted_waker: waker,
+                ..
             } => {
                 if let Some(waker) = waker {
                     waker.wake();
@@ -700,6 +803,7 @@ impl<UserData, Upgrade> Future for SubstreamRequested<UserData, Upgrade> {
                 user_data,
                 upgrade,
                 mut timeout,
+                priority,
                 ..
             } => match timeout.poll_unpin(cx) {
                 Poll::Ready(()) => Poll::Ready(Err(user_data)),
@@ -708,6 +812,7 @@ impl<UserData, Upgrade> Future for SubstreamRequested<UserData, Upgrade> {
                         user_data,
                         upgrade,
                         timeout,
+                        priority,
                         extracted_waker: Some(cx.waker().clone()),
                     };
                     Poll::Pending
@@ -744,10 +849,13 @@ mod tests {
     use futures::future;
     use futures::AsyncRead;
     use futures::AsyncWrite;
-    use libp2p_core::upgrade::{DeniedUpgrade, InboundUpgrade, OutboundUpgrade, UpgradeInfo};
+    use libp2p_core::upgrade::{
+        DeniedUpgrade, InboundUpgrade, OutboundUpgrade, ReadyUpgrade, UpgradeInfo,
+    };
     use libp2p_core::StreamMuxer;
     use quickcheck::*;
-    use std::sync::{Arc, Weak};
+    use std::collections::VecDeque;
+    use std::sync::Weak;
     use std::time::Instant;
     use tracing_subscriber::EnvFilter;
     use void::Void;
@@ -770,6 +878,8 @@ mod tests {
                 None,
                 max_negotiating_inbound_streams,
                 Duration::ZERO,
+                false,
+                Default::default(),
             );
 
             let result = connection.poll_noop_waker();
@@ -785,6 +895,36 @@ mod tests {
         QuickCheck::new().quickcheck(prop as fn(_));
     }
 
+    #[test]
+    fn defers_inbound_streams_without_handler_capacity() {
+        let alive_substream_counter = Arc::new(());
+        let mut handler = MockConnectionHandler::new(Duration::from_secs(10));
+        handler.accepts_inbound_streams = false;
+        let mut connection = Connection::new(
+            StreamMuxerBox::new(DummyStreamMuxer {
+                counter: alive_substream_counter.clone(),
+            }),
+            handler,
+            None,
+            2,
+            Duration::ZERO,
+            false,
+            Default::default(),
+        );
+
+        assert!(connection.poll_noop_waker().is_pending());
+        assert_eq!(
+            Arc::weak_count(&alive_substream_counter),
+            0,
+            "Expect no inbound streams to be accepted without capacity"
+        );
+
+        connection.handler.accepts_inbound_streams = true;
+
+        assert!(connection.poll_noop_waker().is_pending());
+        assert_eq!(Arc::weak_count(&alive_substream_counter), 2);
+    }
+
     #[test]
     fn outbound_stream_timeout_starts_on_request() {
         let upgrade_timeout = Duration::from_secs(1);
@@ -794,6 +934,8 @@ mod tests {
             None,
             2,
             Duration::ZERO,
+            false,
+            Default::default(),
         );
 
         connection.handler.open_new_outbound();
@@ -817,6 +959,8 @@ mod tests {
             None,
             0,
             Duration::ZERO,
+            false,
+            Default::default(),
         );
 
         // First, start listening on a single protocol.
@@ -856,6 +1000,8 @@ mod tests {
             None,
             0,
             Duration::ZERO,
+            false,
+            Default::default(),
         );
 
         // First, remote supports a single protocol.
@@ -899,6 +1045,38 @@ mod tests {
         assert_eq!(connection.handler.remote_removed, vec![vec!["/bar"]]);
     }
 
+    #[test]
+    fn outbound_streams_are_opened_in_priority_order() {
+        let mut connection = Connection::new(
+            StreamMuxerBox::new(SingleOutboundStreamMuxer {
+                counter: Arc::new(()),
+                opened: false,
+            }),
+            OutboundRequestsConnectionHandler::new(&["/low", "/high"]),
+            None,
+            0,
+            Duration::ZERO,
+            false,
+            Arc::new(HashMap::from([(StreamProtocol::new("/high"), 1)])),
+        );
+
+        let _ = connection.poll_noop_waker();
+
+        let waiting = connection
+            .requested_substreams
+            .iter()
+            .filter_map(|r| match r {
+                SubstreamRequested::Waiting { user_data, .. } => Some(user_data.clone()),
+                SubstreamRequested::Done => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            waiting,
+            vec![StreamProtocol::new("/low")],
+            "expect the high priority request to be served first"
+        );
+    }
+
     #[tokio::test]
     async fn idle_timeout_with_keep_alive_no() {
         let idle_timeout = Duration::from_millis(100);
@@ -909,6 +1087,8 @@ mod tests {
             None,
             0,
             idle_timeout,
+            false,
+            Default::default(),
         );
 
         assert!(connection.poll_noop_waker().is_pending());
@@ -1022,6 +1202,48 @@ mod tests {
         }
     }
 
+    /// A [`StreamMuxer`] which returns exactly one outbound stream.
+    struct SingleOutboundStreamMuxer {
+        counter: Arc<()>,
+        opened: bool,
+    }
+
+    impl StreamMuxer for SingleOutboundStreamMuxer {
+        type Substream = PendingSubstream;
+        type Error = Void;
+
+        fn poll_inbound(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<Self::Substream, Self::Error>> {
+            Poll::Pending
+        }
+
+        fn poll_outbound(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<Self::Substream, Self::Error>> {
+            let this = self.get_mut();
+            if this.opened {
+                return Poll::Pending;
+            }
+            this.opened = true;
+
+            Poll::Ready(Ok(PendingSubstream(Arc::downgrade(&this.counter))))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<StreamMuxerEvent, Self::Error>> {
+            Poll::Pending
+        }
+    }
+
     /// A [`StreamMuxer`] which never returns a stream.
     struct PendingStreamMuxer;
 
@@ -1089,6 +1311,7 @@ mod tests {
         outbound_requested: bool,
         error: Option<StreamUpgradeError<Void>>,
         upgrade_timeout: Duration,
+        accepts_inbound_streams: bool,
     }
 
     impl MockConnectionHandler {
@@ -1097,6 +1320,7 @@ mod tests {
                 outbound_requested: false,
                 error: None,
                 upgrade_timeout,
+                accepts_inbound_streams: true,
             }
         }
 
@@ -1105,6 +1329,72 @@ mod tests {
         }
     }
 
+    /// A [`ConnectionHandler`] requesting one outbound stream per protocol, in the given order.
+    struct OutboundRequestsConnectionHandler {
+        requests: VecDeque<StreamProtocol>,
+    }
+
+    impl OutboundRequestsConnectionHandler {
+        fn new(protocols: &[&'static str]) -> Self {
+            Self {
+                requests: protocols.iter().copied().map(StreamProtocol::new).collect(),
+            }
+        }
+    }
+
+    impl ConnectionHandler for OutboundRequestsConnectionHandler {
+        type FromBehaviour = Void;
+        type ToBehaviour = Void;
+        type InboundProtocol = DeniedUpgrade;
+        type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+        type InboundOpenInfo = ();
+        type OutboundOpenInfo = StreamProtocol;
+
+        fn listen_protocol(
+            &self,
+        ) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+            SubstreamProtocol::new(DeniedUpgrade, ())
+        }
+
+        fn on_connection_event(
+            &mut self,
+            _: ConnectionEvent<
+                Self::InboundProtocol,
+                Self::OutboundProtocol,
+                Self::InboundOpenInfo,
+                Self::OutboundOpenInfo,
+            >,
+        ) {
+        }
+
+        fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+            void::unreachable(event)
+        }
+
+        fn connection_keep_alive(&self) -> bool {
+            true
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<
+            ConnectionHandlerEvent<
+                Self::OutboundProtocol,
+                Self::OutboundOpenInfo,
+                Self::ToBehaviour,
+            >,
+        > {
+            if let Some(protocol) = self.requests.pop_front() {
+                return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                    protocol: SubstreamProtocol::new(ReadyUpgrade::new(protocol.clone()), protocol),
+                });
+            }
+
+            Poll::Pending
+        }
+    }
+
     #[derive(Default)]
     struct ConfigurableProtocolConnectionHandler {
         events: Vec<ConnectionHandlerEvent<DeniedUpgrade, (), Void>>,
@@ -1153,6 +1443,10 @@ mod tests {
             SubstreamProtocol::new(DeniedUpgrade, ()).with_timeout(self.upgrade_timeout)
         }
 
+        fn accepts_inbound_streams(&self) -> bool {
+            self.accepts_inbound_streams
+        }
+
         fn on_connection_event(
             &mut self,
             event: ConnectionEvent<
@@ -25,8 +25,10 @@ use crate::{
         PendingInboundConnectionError, PendingOutboundConnectionError,
     },
     transport::TransportError,
-    ConnectedPoint, ConnectionHandler, Executor, Multiaddr, PeerId,
+    ConnectedPoint, ConnectionHandler, Executor, Multiaddr, PeerId, SendDatagramError,
+    StreamProtocol,
 };
+use bytes::Bytes;
 use concurrent_dial::ConcurrentDial;
 use fnv::FnvHashMap;
 use futures::prelude::*;
@@ -39,13 +41,15 @@ use futures::{
 };
 use instant::{Duration, Instant};
 use libp2p_core::connection::Endpoint;
-use libp2p_core::muxing::{StreamMuxerBox, StreamMuxerExt};
+use libp2p_core::muxing::{StreamMuxer, StreamMuxerBox, StreamMuxerExt};
 use std::task::Waker;
 use std::{
     collections::{hash_map, HashMap},
     fmt,
     num::{NonZeroU8, NonZeroUsize},
     pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+    sync::Arc,
     task::Context,
     task::Poll,
 };
@@ -130,6 +134,10 @@ where
     /// Waker in case we haven't established any connections yet.
     no_established_connections_waker: Option<Waker>,
 
+    /// Number of events reported by established connections that have not been returned by
+    /// [`Pool::poll`] yet, including events waiting for space in the per-connection buffers.
+    queued_connection_events: Arc<AtomicUsize>,
+
     /// Receivers for events reported from established connections.
     established_connection_events:
         SelectAll<mpsc::Receiver<task::EstablishedConnectionEvent<THandler::ToBehaviour>>>,
@@ -139,6 +147,12 @@ where
 
     /// How long a connection should be kept alive once it starts idling.
     idle_connection_timeout: Duration,
+
+    /// Whether connections report the protocol negotiated on every new stream.
+    report_opened_streams: bool,
+
+    /// Priorities of outbound streams by protocol, shared with every connection.
+    stream_priorities: Arc<HashMap<StreamProtocol, u8>>,
 }
 
 #[derive(Debug)]
@@ -146,6 +160,9 @@ pub(crate) struct EstablishedConnection<TInEvent> {
     endpoint: ConnectedPoint,
     /// Channel endpoint to send commands to the task.
     sender: mpsc::Sender<task::Command<TInEvent>>,
+    /// The maximum size of datagrams at the time the connection was established, `None` if the
+    /// connection doesn't support datagrams.
+    max_datagram_size: Option<usize>,
 }
 
 impl<TInEvent> EstablishedConnection<TInEvent> {
@@ -180,6 +197,19 @@ impl<TInEvent> EstablishedConnection<TInEvent> {
         self.sender.poll_ready(cx).map_err(|_| ())
     }
 
+    /// Sends an unreliable datagram to the remote.
+    ///
+    /// The datagram is dropped if the task is busy or the connection is about to close.
+    pub(crate) fn send_datagram(&mut self, data: Bytes) {
+        if self
+            .sender
+            .try_send(task::Command::SendDatagram(data))
+            .is_err()
+        {
+            tracing::debug!("Dropping datagram, connection is busy or closing");
+        }
+    }
+
     /// Initiates a graceful close of the connection.
     ///
     /// Has no effect if the connection is already closing.
@@ -197,6 +227,8 @@ struct PendingConnection {
     /// [`PeerId`] of the remote peer.
     peer_id: Option<PeerId>,
     endpoint: PendingPoint,
+    /// The addresses tried by an outgoing connection, empty for incoming ones.
+    addresses: Vec<Multiaddr>,
     /// When dropped, notifies the task which then knows to terminate.
     abort_notifier: Option<oneshot::Sender<Void>>,
     /// The moment we became aware of this possible connection, useful for timing metrics.
@@ -302,6 +334,23 @@ pub(crate) enum PoolEvent<ToBehaviour> {
         /// The old endpoint.
         old_endpoint: ConnectedPoint,
     },
+
+    /// A stream has been opened on a connection.
+    StreamOpened {
+        id: ConnectionId,
+        peer_id: PeerId,
+        /// The protocol negotiated on the stream.
+        protocol: StreamProtocol,
+        /// Whether the stream was opened by us or by the remote.
+        direction: Endpoint,
+    },
+
+    /// A datagram has been received on a connection.
+    DatagramReceived {
+        id: ConnectionId,
+        peer_id: PeerId,
+        data: Bytes,
+    },
 }
 
 impl<THandler> Pool<THandler>
@@ -326,15 +375,24 @@ where
             max_negotiating_inbound_streams: config.max_negotiating_inbound_streams,
             per_connection_event_buffer_size: config.per_connection_event_buffer_size,
             idle_connection_timeout: config.idle_connection_timeout,
+            report_opened_streams: config.report_opened_streams,
+            stream_priorities: Arc::new(config.stream_priorities),
             executor,
             pending_connection_events_tx,
             pending_connection_events_rx,
             no_established_connections_waker: None,
+            queued_connection_events: Default::default(),
             established_connection_events: Default::default(),
             new_connection_dropped_listeners: Default::default(),
         }
     }
 
+    /// Returns the number of events reported by connection handlers that have not been delivered
+    /// to the behaviour yet.
+    pub(crate) fn queued_connection_events(&self) -> usize {
+        self.queued_connection_events.load(Ordering::Relaxed)
+    }
+
     /// Gets the dedicated connection counters.
     pub(crate) fn counters(&self) -> &ConnectionCounters {
         &self.counters
@@ -384,6 +442,44 @@ where
         }
     }
 
+    /// Closes all established connections and aborts all pending ones.
+    pub(crate) fn disconnect_all(&mut self) {
+        for conn in self
+            .established
+            .values_mut()
+            .flat_map(|conns| conns.values_mut())
+        {
+            conn.start_close();
+        }
+
+        for (_, connection) in self.pending.iter_mut() {
+            connection.abort()
+        }
+    }
+
+    /// Sends an unreliable datagram on the connection to `peer` allowing for the largest datagrams.
+    pub(crate) fn send_datagram(
+        &mut self,
+        peer: PeerId,
+        data: Bytes,
+    ) -> Result<(), SendDatagramError> {
+        let (max_size, connection) = self
+            .established
+            .get_mut(&peer)
+            .ok_or(SendDatagramError::NotConnected)?
+            .values_mut()
+            .filter_map(|conn| Some((conn.max_datagram_size?, conn)))
+            .max_by_key(|(max_size, _)| *max_size)
+            .ok_or(SendDatagramError::Unsupported)?;
+
+        if data.len() > max_size {
+            return Err(SendDatagramError::TooLarge { max_size });
+        }
+
+        connection.send_datagram(data);
+        Ok(())
+    }
+
     /// Returns an iterator over all established connections of `peer`.
     pub(crate) fn iter_established_connections_of_peer(
         &mut self,
@@ -402,6 +498,29 @@ where
         })
     }
 
+    /// Returns an iterator over the addresses tried by all pending outgoing connections.
+    pub(crate) fn iter_pending_dials(&self) -> impl Iterator<Item = (ConnectionId, &Multiaddr)> {
+        self.pending
+            .iter()
+            .flat_map(|(id, info)| info.addresses.iter().map(move |address| (*id, address)))
+    }
+
+    /// Aborts the pending outgoing connection with the given ID.
+    ///
+    /// Returns `false` if there is no such connection or it is already being aborted.
+    pub(crate) fn abort_dial(&mut self, id: ConnectionId) -> bool {
+        match self.pending.get_mut(&id) {
+            Some(info)
+                if matches!(info.endpoint, PendingPoint::Dialer { .. })
+                    && info.abort_notifier.is_some() =>
+            {
+                info.abort();
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Returns an iterator over all connected peers, i.e. those that have
     /// at least one established connection in the pool.
     pub(crate) fn iter_connected(&self) -> impl Iterator<Item = &PeerId> {
@@ -412,6 +531,7 @@ where
     /// that establishes and negotiates the connection.
     pub(crate) fn add_outgoing(
         &mut self,
+        addresses: Vec<Multiaddr>,
         dials: Vec<
             BoxFuture<
                 'static,
@@ -451,6 +571,7 @@ where
             PendingConnection {
                 peer_id: peer,
                 endpoint,
+                addresses,
                 abort_notifier: Some(abort_notifier),
                 accepted_at: Instant::now(),
             },
@@ -490,6 +611,7 @@ where
             PendingConnection {
                 peer_id: None,
                 endpoint: endpoint.into(),
+                addresses: Vec::new(),
                 abort_notifier: Some(abort_notifier),
                 accepted_at: Instant::now(),
             },
@@ -516,6 +638,7 @@ where
             EstablishedConnection {
                 endpoint: endpoint.clone(),
                 sender: command_sender,
+                max_datagram_size: connection.max_datagram_size(),
             },
         );
         self.established_connection_events.push(event_receiver);
@@ -529,6 +652,8 @@ where
             self.substream_upgrade_protocol_override,
             self.max_negotiating_inbound_streams,
             self.idle_connection_timeout,
+            self.report_opened_streams,
+            self.stream_priorities.clone(),
         );
 
         let span = tracing::debug_span!(parent: tracing::Span::none(), "new_established_connection", remote_addr = %endpoint.get_remote_address(), %id, peer = %obtained_peer_id);
@@ -541,6 +666,7 @@ where
                 connection,
                 command_receiver,
                 event_sender,
+                self.queued_connection_events.clone(),
             )
             .instrument(span),
         )
@@ -563,7 +689,13 @@ where
                 self.no_established_connections_waker = Some(cx.waker().clone());
             }
 
-            Poll::Ready(Some(task::EstablishedConnectionEvent::Notify { id, peer_id, event })) => {
+            Poll::Ready(Some(task::EstablishedConnectionEvent::Notify {
+                id,
+                peer_id,
+                event,
+                queued,
+            })) => {
+                drop(queued);
                 return Poll::Ready(PoolEvent::ConnectionEvent { peer_id, id, event });
             }
             Poll::Ready(Some(task::EstablishedConnectionEvent::AddressChange {
@@ -581,6 +713,8 @@ where
                 new_endpoint.set_remote_address(new_address);
                 let old_endpoint =
                     std::mem::replace(&mut connection.endpoint, new_endpoint.clone());
+                self.counters.dec_transport(&old_endpoint);
+                self.counters.inc_transport(&new_endpoint);
 
                 return Poll::Ready(PoolEvent::AddressChange {
                     peer_id,
@@ -589,6 +723,26 @@ where
                     old_endpoint,
                 });
             }
+            Poll::Ready(Some(task::EstablishedConnectionEvent::StreamOpened {
+                id,
+                peer_id,
+                protocol,
+                direction,
+            })) => {
+                return Poll::Ready(PoolEvent::StreamOpened {
+                    id,
+                    peer_id,
+                    protocol,
+                    direction,
+                });
+            }
+            Poll::Ready(Some(task::EstablishedConnectionEvent::DatagramReceived {
+                id,
+                peer_id,
+                data,
+            })) => {
+                return Poll::Ready(PoolEvent::DatagramReceived { id, peer_id, data });
+            }
             Poll::Ready(Some(task::EstablishedConnectionEvent::Closed { id, peer_id, error })) => {
                 let connections = self
                     .established
@@ -639,6 +793,7 @@ where
                     let PendingConnection {
                         peer_id: expected_peer_id,
                         endpoint,
+                        addresses: _,
                         abort_notifier: _,
                         accepted_at,
                     } = self
@@ -750,6 +905,7 @@ where
                     if let Some(PendingConnection {
                         peer_id,
                         endpoint,
+                        addresses: _,
                         abort_notifier: _,
                         accepted_at: _, // Ignoring the time it took for the connection to fail.
                     }) = self.pending.remove(&id)
@@ -849,6 +1005,10 @@ pub struct ConnectionCounters {
     established_incoming: u32,
     /// The current number of established outbound connections.
     established_outgoing: u32,
+    /// The current number of established connections through a relay.
+    established_relayed: u32,
+    /// The current number of established connections per transport protocol stack.
+    established_per_transport: FnvHashMap<String, u32>,
 }
 
 impl ConnectionCounters {
@@ -858,6 +1018,8 @@ impl ConnectionCounters {
             pending_outgoing: 0,
             established_incoming: 0,
             established_outgoing: 0,
+            established_relayed: 0,
+            established_per_transport: Default::default(),
         }
     }
 
@@ -896,6 +1058,27 @@ impl ConnectionCounters {
         self.established_outgoing + self.established_incoming
     }
 
+    /// The number of established connections that are relayed through another peer.
+    pub fn num_established_relayed(&self) -> u32 {
+        self.established_relayed
+    }
+
+    /// The number of established connections that are not relayed.
+    pub fn num_established_direct(&self) -> u32 {
+        self.num_established() - self.established_relayed
+    }
+
+    /// The number of established connections per transport.
+    ///
+    /// Transports are identified by the protocol stack of the dialed address, respectively the
+    /// listen address, without the `/p2p` components, e.g. `/ip4/tcp` or
+    /// `/ip4/udp/quic-v1/p2p-circuit`.
+    pub fn established_per_transport(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.established_per_transport
+            .iter()
+            .map(|(transport, count)| (transport.as_str(), *count))
+    }
+
     fn inc_pending(&mut self, endpoint: &PendingPoint) {
         match endpoint {
             PendingPoint::Dialer { .. } => {
@@ -931,6 +1114,7 @@ impl ConnectionCounters {
                 self.established_incoming += 1;
             }
         }
+        self.inc_transport(endpoint);
     }
 
     fn dec_established(&mut self, endpoint: &ConnectedPoint) {
@@ -942,7 +1126,50 @@ impl ConnectionCounters {
                 self.established_incoming -= 1;
             }
         }
+        self.dec_transport(endpoint);
     }
+
+    fn inc_transport(&mut self, endpoint: &ConnectedPoint) {
+        if endpoint.is_relayed() {
+            self.established_relayed += 1;
+        }
+        *self
+            .established_per_transport
+            .entry(transport_of(endpoint))
+            .or_default() += 1;
+    }
+
+    fn dec_transport(&mut self, endpoint: &ConnectedPoint) {
+        if endpoint.is_relayed() {
+            self.established_relayed -= 1;
+        }
+        if let hash_map::Entry::Occupied(mut entry) =
+            self.established_per_transport.entry(transport_of(endpoint))
+        {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
+/// The protocol stack of the dialed address, respectively the local listen address, of a
+/// connection, without `/p2p` components.
+fn transport_of(endpoint: &ConnectedPoint) -> String {
+    let address = match endpoint {
+        ConnectedPoint::Dialer { address, .. } => address,
+        ConnectedPoint::Listener { local_addr, .. } => local_addr,
+    };
+
+    address
+        .protocol_stack()
+        .filter(|tag| *tag != "p2p")
+        .fold(String::new(), |mut stack, tag| {
+            stack.push('/');
+            stack.push_str(tag);
+            stack
+        })
 }
 
 /// Configuration options when creating a [`Pool`].
@@ -961,6 +1188,10 @@ pub(crate) struct PoolConfig {
     pub(crate) dial_concurrency_factor: NonZeroU8,
     /// How long a connection should be kept alive once it is idling.
     pub(crate) idle_connection_timeout: Duration,
+    /// Whether connections report the protocol negotiated on every new stream.
+    pub(crate) report_opened_streams: bool,
+    /// Priorities of outbound streams by protocol.
+    pub(crate) stream_priorities: HashMap<StreamProtocol, u8>,
     /// The configured override for substream protocol upgrades, if any.
     substream_upgrade_protocol_override: Option<libp2p_core::upgrade::Version>,
 
@@ -978,6 +1209,8 @@ impl PoolConfig {
             per_connection_event_buffer_size: 7,
             dial_concurrency_factor: NonZeroU8::new(8).expect("8 > 0"),
             idle_connection_timeout: Duration::ZERO,
+            report_opened_streams: false,
+            stream_priorities: HashMap::new(),
             substream_upgrade_protocol_override: None,
             max_negotiating_inbound_streams: 128,
         }
@@ -1053,4 +1286,40 @@ mod tests {
     impl Executor for Dummy {
         fn exec(&self, _: Pin<Box<dyn Future<Output = ()> + Send>>) {}
     }
+
+    #[test]
+    fn counts_relayed_connections() {
+        let relayed = ConnectedPoint::Dialer {
+            address: "/ip4/127.0.0.1/tcp/4001/p2p/12D3KooWDpJ7As7BWAwRMfu1VU2WCqNjvq387JEYKDBj4kx6nXTN/p2p-circuit"
+                .parse()
+                .unwrap(),
+            role_override: Endpoint::Dialer,
+        };
+        let direct = ConnectedPoint::Listener {
+            local_addr: "/ip4/127.0.0.1/tcp/4001".parse().unwrap(),
+            send_back_addr: "/ip4/127.0.0.1/tcp/4002".parse().unwrap(),
+        };
+        let mut counters = ConnectionCounters::new();
+
+        counters.inc_established(&relayed);
+        counters.inc_established(&direct);
+
+        assert_eq!(counters.num_established_relayed(), 1);
+        assert_eq!(counters.num_established_direct(), 1);
+        let mut per_transport = counters.established_per_transport().collect::<Vec<_>>();
+        per_transport.sort();
+        assert_eq!(
+            per_transport,
+            [("/ip4/tcp", 1), ("/ip4/tcp/p2p-circuit", 1)]
+        );
+
+        counters.dec_established(&relayed);
+
+        assert_eq!(counters.num_established_relayed(), 0);
+        assert_eq!(counters.num_established_direct(), 1);
+        assert_eq!(
+            counters.established_per_transport().collect::<Vec<_>>(),
+            [("/ip4/tcp", 1)]
+        );
+    }
 }
@@ -28,15 +28,19 @@ use crate::{
         PendingOutboundConnectionError,
     },
     transport::TransportError,
-    ConnectionHandler, Multiaddr, PeerId,
+    ConnectionHandler, Multiaddr, PeerId, StreamProtocol,
 };
+use bytes::Bytes;
 use futures::{
     channel::{mpsc, oneshot},
     future::{poll_fn, Either, Future},
     SinkExt, StreamExt,
 };
 use libp2p_core::muxing::StreamMuxerBox;
+use libp2p_core::Endpoint;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use void::Void;
 
 /// Commands that can be sent to a task driving an established connection.
@@ -44,6 +48,8 @@ use void::Void;
 pub(crate) enum Command<T> {
     /// Notify the connection handler of an event.
     NotifyHandler(T),
+    /// Send an unreliable datagram to the remote.
+    SendDatagram(Bytes),
     /// Gracefully close the connection (active close) before
     /// terminating the task.
     Close,
@@ -65,6 +71,26 @@ pub(crate) enum PendingConnectionEvent {
     },
 }
 
+/// Counts an event of a connection handler as queued for as long as it is alive.
+///
+/// The count is thus decremented once the event is delivered as well as when it is dropped,
+/// e.g. because the connection is closed or the task aborted before the event could be sent.
+#[derive(Debug)]
+pub(crate) struct QueuedEvent(Arc<AtomicUsize>);
+
+impl QueuedEvent {
+    fn new(counter: &Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter.clone())
+    }
+}
+
+impl Drop for QueuedEvent {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum EstablishedConnectionEvent<ToBehaviour> {
     /// A node we are connected to has changed its address.
@@ -78,6 +104,20 @@ pub(crate) enum EstablishedConnectionEvent<ToBehaviour> {
         id: ConnectionId,
         peer_id: PeerId,
         event: ToBehaviour,
+        queued: QueuedEvent,
+    },
+    /// A stream has been opened on the connection.
+    StreamOpened {
+        id: ConnectionId,
+        peer_id: PeerId,
+        protocol: StreamProtocol,
+        direction: Endpoint,
+    },
+    /// A datagram has been received on the connection.
+    DatagramReceived {
+        id: ConnectionId,
+        peer_id: PeerId,
+        data: Bytes,
     },
     /// A connection closed, possibly due to an error.
     ///
@@ -172,6 +212,7 @@ pub(crate) async fn new_for_established_connection<THandler>(
     mut connection: crate::connection::Connection<THandler>,
     mut command_receiver: mpsc::Receiver<Command<THandler::FromBehaviour>>,
     mut events: mpsc::Sender<EstablishedConnectionEvent<THandler::ToBehaviour>>,
+    queued_events: Arc<AtomicUsize>,
 ) where
     THandler: ConnectionHandler,
 {
@@ -184,6 +225,7 @@ pub(crate) async fn new_for_established_connection<THandler>(
         {
             Either::Left((Some(command), _)) => match command {
                 Command::NotifyHandler(event) => connection.on_behaviour_event(event),
+                Command::SendDatagram(data) => connection.send_datagram(data),
                 Command::Close => {
                     command_receiver.close();
                     let (remaining_events, closing_muxer) = connection.close();
@@ -194,6 +236,7 @@ pub(crate) async fn new_for_established_connection<THandler>(
                                 id: connection_id,
                                 event,
                                 peer_id,
+                                queued: QueuedEvent::new(&queued_events),
                             })
                         }))
                         .await;
@@ -222,6 +265,7 @@ pub(crate) async fn new_for_established_connection<THandler>(
                                 id: connection_id,
                                 peer_id,
                                 event,
+                                queued: QueuedEvent::new(&queued_events),
                             })
                             .await;
                     }
@@ -234,6 +278,28 @@ pub(crate) async fn new_for_established_connection<THandler>(
                             })
                             .await;
                     }
+                    Ok(connection::Event::StreamOpened {
+                        protocol,
+                        direction,
+                    }) => {
+                        let _ = events
+                            .send(EstablishedConnectionEvent::StreamOpened {
+                                id: connection_id,
+                                peer_id,
+                                protocol,
+                                direction,
+                            })
+                            .await;
+                    }
+                    Ok(connection::Event::DatagramReceived(data)) => {
+                        let _ = events
+                            .send(EstablishedConnectionEvent::DatagramReceived {
+                                id: connection_id,
+                                peer_id,
+                                data,
+                            })
+                            .await;
+                    }
                     Err(error) => {
                         command_receiver.close();
                         let (remaining_events, _closing_muxer) = connection.close();
@@ -244,6 +310,7 @@ pub(crate) async fn new_for_established_connection<THandler>(
                                     id: connection_id,
                                     event,
                                     peer_id,
+                                    queued: QueuedEvent::new(&queued_events),
                                 })
                             }))
                             .await;
@@ -263,3 +330,28 @@ pub(crate) async fn new_for_established_connection<THandler>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queued_event_is_counted_until_dropped() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (mut sender, receiver) = mpsc::channel(1);
+
+        let delivered = QueuedEvent::new(&counter);
+        sender.try_send(QueuedEvent::new(&counter)).unwrap();
+        assert_eq!(counter.load(Ordering::Relaxed), 2);
+
+        drop(delivered);
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+        // Events still in the channel of a closed connection are dropped with its receiver, as
+        // are events that can no longer be sent.
+        drop(receiver);
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+        assert!(sender.try_send(QueuedEvent::new(&counter)).is_err());
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+    }
+}
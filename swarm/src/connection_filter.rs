@@ -0,0 +1,57 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_core::Multiaddr;
+use std::{error, fmt};
+
+/// Decides whether the [`Swarm`](crate::Swarm) accepts an inbound connection.
+///
+/// The filter is consulted as soon as the transport reports an incoming connection, i.e. before
+/// any security or multiplexer upgrade is performed on it. Connections that are not allowed are
+/// dropped and reported as [`SwarmEvent::IncomingConnectionError`](crate::SwarmEvent::IncomingConnectionError)
+/// with [`ListenError::Denied`](crate::ListenError::Denied), the cause of which downcasts to
+/// [`InboundConnectionFiltered`].
+///
+/// The trait is implemented for all closures of type `Fn(&Multiaddr) -> bool`.
+pub trait ConnectionFilter: Send + 'static {
+    /// Returns whether an inbound connection from `remote` should be accepted.
+    fn allow_inbound(&self, remote: &Multiaddr) -> bool;
+}
+
+impl<F> ConnectionFilter for F
+where
+    F: Fn(&Multiaddr) -> bool + Send + 'static,
+{
+    fn allow_inbound(&self, remote: &Multiaddr) -> bool {
+        self(remote)
+    }
+}
+
+/// An inbound connection was rejected by the [`ConnectionFilter`] of the [`Swarm`](crate::Swarm).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InboundConnectionFiltered;
+
+impl fmt::Display for InboundConnectionFiltered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "inbound connection rejected by connection filter")
+    }
+}
+
+impl error::Error for InboundConnectionFiltered {}
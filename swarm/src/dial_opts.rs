@@ -24,7 +24,9 @@ use libp2p_core::connection::Endpoint;
 use libp2p_core::multiaddr::Protocol;
 use libp2p_core::Multiaddr;
 use libp2p_identity::PeerId;
+use rand::Rng;
 use std::num::NonZeroU8;
+use std::time::Duration;
 
 /// Options to configure a dial to a known or unknown peer.
 ///
@@ -44,6 +46,8 @@ pub struct DialOpts {
     extend_addresses_through_behaviour: bool,
     role_override: Endpoint,
     dial_concurrency_factor_override: Option<NonZeroU8>,
+    allowed_transports: Option<Vec<&'static str>>,
+    retry: Option<RetryConfig>,
     connection_id: ConnectionId,
 }
 
@@ -65,6 +69,7 @@ impl DialOpts {
             condition: Default::default(),
             role_override: Endpoint::Dialer,
             dial_concurrency_factor_override: Default::default(),
+            allowed_transports: None,
         }
     }
 
@@ -97,6 +102,25 @@ impl DialOpts {
         None
     }
 
+    /// Retry the dial with exponential backoff if it fails.
+    ///
+    /// Every failed attempt but the last is reported as
+    /// [`SwarmEvent::DialRetryScheduled`](crate::SwarmEvent::DialRetryScheduled), followed by a
+    /// new [`SwarmEvent::Dialing`](crate::SwarmEvent::Dialing) once the delay has elapsed. Only
+    /// the last failure is reported as
+    /// [`SwarmEvent::OutgoingConnectionError`](crate::SwarmEvent::OutgoingConnectionError).
+    /// Pending retries are cancelled once a connection to the peer is established, which ends the
+    /// dial with [`DialError::DialPeerConditionFalse`](crate::DialError::DialPeerConditionFalse)
+    /// once the next attempt would have been due.
+    ///
+    /// All attempts use the [`ConnectionId`] of these options, and the
+    /// [`NetworkBehaviour`](crate::NetworkBehaviour) is informed about every failed attempt via
+    /// [`DialFailure`](crate::DialFailure).
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
     /// Get the [`ConnectionId`] of this dial attempt.
     ///
     /// All future events of this dial will be associated with this ID.
@@ -109,6 +133,10 @@ impl DialOpts {
         self.addresses.clone()
     }
 
+    pub(crate) fn addresses_ref(&self) -> &[Multiaddr] {
+        &self.addresses
+    }
+
     pub(crate) fn extend_addresses_through_behaviour(&self) -> bool {
         self.extend_addresses_through_behaviour
     }
@@ -124,6 +152,69 @@ impl DialOpts {
     pub(crate) fn role_override(&self) -> Endpoint {
         self.role_override
     }
+
+    pub(crate) fn allowed_transports(&self) -> Option<&[&'static str]> {
+        self.allowed_transports.as_deref()
+    }
+
+    pub(crate) fn retry(&self) -> Option<&RetryConfig> {
+        self.retry.as_ref()
+    }
+
+    /// Returns the options for the next attempt of this dial, keeping its [`ConnectionId`].
+    pub(crate) fn next_attempt(&self) -> DialOpts {
+        DialOpts {
+            peer_id: self.peer_id,
+            condition: self.condition,
+            addresses: self.addresses.clone(),
+            extend_addresses_through_behaviour: self.extend_addresses_through_behaviour,
+            role_override: self.role_override,
+            dial_concurrency_factor_override: self.dial_concurrency_factor_override,
+            allowed_transports: self.allowed_transports.clone(),
+            retry: self.retry,
+            connection_id: self.connection_id,
+        }
+    }
+}
+
+/// Configures how a failed dial is retried, see [`DialOpts::with_retry`].
+///
+/// The `n`-th retry is attempted after `min(base * 2^(n - 1), max)` plus a random duration of up to
+/// `jitter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// The total number of attempts, including the initial one.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub base: Duration,
+    /// The upper bound for the exponentially growing delay, excluding jitter.
+    pub max: Duration,
+    /// The upper bound for the random duration added to each delay.
+    pub jitter: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(30),
+            jitter: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Returns the delay before the attempt following the given (failed) attempt.
+    pub(crate) fn delay(&self, failed_attempt: u32) -> Duration {
+        let backoff = 2u32
+            .checked_pow(failed_attempt.saturating_sub(1))
+            .and_then(|factor| self.base.checked_mul(factor))
+            .map_or(self.max, |delay| delay.min(self.max));
+        let jitter = rand::thread_rng().gen_range(Duration::ZERO..=self.jitter);
+
+        backoff + jitter
+    }
 }
 
 impl From<Multiaddr> for DialOpts {
@@ -144,6 +235,7 @@ pub struct WithPeerId {
     condition: PeerCondition,
     role_override: Endpoint,
     dial_concurrency_factor_override: Option<NonZeroU8>,
+    allowed_transports: Option<Vec<&'static str>>,
 }
 
 impl WithPeerId {
@@ -169,6 +261,7 @@ impl WithPeerId {
             extend_addresses_through_behaviour: false,
             role_override: self.role_override,
             dial_concurrency_factor_override: self.dial_concurrency_factor_override,
+            allowed_transports: self.allowed_transports,
         }
     }
 
@@ -183,6 +276,17 @@ impl WithPeerId {
         self
     }
 
+    /// Only dial addresses that contain at least one of the given protocols, e.g. `"quic-v1"` or `"tcp"`.
+    ///
+    /// Protocols are identified by their [`Protocol::tag`].
+    /// If none of the addresses of the dial contains any of the given protocols, the dial fails
+    /// with [`DialError::Transport`](crate::DialError::Transport), reporting each address as
+    /// not supported.
+    pub fn allowed_transports(mut self, protocols: impl IntoIterator<Item = &'static str>) -> Self {
+        self.allowed_transports = Some(protocols.into_iter().collect());
+        self
+    }
+
     /// Build the final [`DialOpts`].
     pub fn build(self) -> DialOpts {
         DialOpts {
@@ -192,6 +296,8 @@ impl WithPeerId {
             extend_addresses_through_behaviour: true,
             role_override: self.role_override,
             dial_concurrency_factor_override: self.dial_concurrency_factor_override,
+            allowed_transports: self.allowed_transports,
+            retry: None,
             connection_id: ConnectionId::next(),
         }
     }
@@ -205,6 +311,7 @@ pub struct WithPeerIdWithAddresses {
     extend_addresses_through_behaviour: bool,
     role_override: Endpoint,
     dial_concurrency_factor_override: Option<NonZeroU8>,
+    allowed_transports: Option<Vec<&'static str>>,
 }
 
 impl WithPeerIdWithAddresses {
@@ -239,6 +346,17 @@ impl WithPeerIdWithAddresses {
         self
     }
 
+    /// Only dial addresses that contain at least one of the given protocols, e.g. `"quic-v1"` or `"tcp"`.
+    ///
+    /// Protocols are identified by their [`Protocol::tag`].
+    /// If none of the addresses of the dial contains any of the given protocols, the dial fails
+    /// with [`DialError::Transport`](crate::DialError::Transport), reporting each address as
+    /// not supported.
+    pub fn allowed_transports(mut self, protocols: impl IntoIterator<Item = &'static str>) -> Self {
+        self.allowed_transports = Some(protocols.into_iter().collect());
+        self
+    }
+
     /// Build the final [`DialOpts`].
     pub fn build(self) -> DialOpts {
         DialOpts {
@@ -248,6 +366,8 @@ impl WithPeerIdWithAddresses {
             extend_addresses_through_behaviour: self.extend_addresses_through_behaviour,
             role_override: self.role_override,
             dial_concurrency_factor_override: self.dial_concurrency_factor_override,
+            allowed_transports: self.allowed_transports,
+            retry: None,
             connection_id: ConnectionId::next(),
         }
     }
@@ -262,6 +382,7 @@ impl WithoutPeerId {
         WithoutPeerIdWithAddress {
             address,
             role_override: Endpoint::Dialer,
+            allowed_transports: None,
         }
     }
 }
@@ -270,6 +391,7 @@ impl WithoutPeerId {
 pub struct WithoutPeerIdWithAddress {
     address: Multiaddr,
     role_override: Endpoint,
+    allowed_transports: Option<Vec<&'static str>>,
 }
 
 impl WithoutPeerIdWithAddress {
@@ -283,6 +405,17 @@ impl WithoutPeerIdWithAddress {
         self.role_override = Endpoint::Listener;
         self
     }
+    /// Only dial addresses that contain at least one of the given protocols, e.g. `"quic-v1"` or `"tcp"`.
+    ///
+    /// Protocols are identified by their [`Protocol::tag`].
+    /// If none of the addresses of the dial contains any of the given protocols, the dial fails
+    /// with [`DialError::Transport`](crate::DialError::Transport), reporting each address as
+    /// not supported.
+    pub fn allowed_transports(mut self, protocols: impl IntoIterator<Item = &'static str>) -> Self {
+        self.allowed_transports = Some(protocols.into_iter().collect());
+        self
+    }
+
     /// Build the final [`DialOpts`].
     pub fn build(self) -> DialOpts {
         DialOpts {
@@ -292,6 +425,8 @@ impl WithoutPeerIdWithAddress {
             extend_addresses_through_behaviour: false,
             role_override: self.role_override,
             dial_concurrency_factor_override: None,
+            allowed_transports: self.allowed_transports,
+            retry: None,
             connection_id: ConnectionId::next(),
         }
     }
@@ -0,0 +1,186 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_core::{multiaddr::Protocol, Multiaddr};
+use libp2p_identity::PeerId;
+use std::{cmp::Reverse, collections::HashMap};
+
+/// Upper bound of the absolute value of a score.
+///
+/// Keeps a long history of successes from outweighing a few recent failures for too long.
+const MAX_SCORE: i32 = 10;
+
+/// Maximum number of peers with scored addresses.
+///
+/// Once reached, the peer whose scores were updated least recently is forgotten.
+const MAX_PEERS: usize = 1024;
+
+/// Maximum number of scored addresses per peer.
+///
+/// Once reached, the address whose score was updated least recently is forgotten.
+const MAX_ADDRESSES_PER_PEER: usize = 16;
+
+/// Scores of the addresses of remote peers, derived from the outcome of connection attempts.
+///
+/// Addresses without a recorded outcome, e.g. those just discovered via `identify`, have a
+/// neutral score of zero.
+///
+/// Only the most recently updated [`MAX_PEERS`] peers and [`MAX_ADDRESSES_PER_PEER`] addresses per
+/// peer are kept. Forgotten addresses are scored neutral again.
+#[derive(Debug, Default)]
+pub(crate) struct DialScores {
+    peers: HashMap<PeerId, Peer>,
+    /// Incremented with every update, ordering the updates.
+    clock: u64,
+}
+
+#[derive(Debug, Default)]
+struct Peer {
+    /// The score of each address together with the time of its last update.
+    addresses: HashMap<Multiaddr, (i32, u64)>,
+    /// The time of the last update of any of the addresses.
+    last_update: u64,
+}
+
+impl DialScores {
+    pub(crate) fn get(&self, peer: &PeerId, address: &Multiaddr) -> i32 {
+        self.peers
+            .get(peer)
+            .and_then(|peer| peer.addresses.get(&without_p2p(address)))
+            .map_or(0, |(score, _)| *score)
+    }
+
+    /// Records the outcome of a connection attempt.
+    ///
+    /// The most recent outcome decides the sign of the score, i.e. a success turns a negative
+    /// score positive right away and vice versa.
+    pub(crate) fn record(&mut self, peer_id: PeerId, address: &Multiaddr, success: bool) {
+        self.clock += 1;
+        let now = self.clock;
+
+        if !self.peers.contains_key(&peer_id) && self.peers.len() >= MAX_PEERS {
+            evict_least_recent(&mut self.peers, |peer| peer.last_update);
+        }
+        let peer = self.peers.entry(peer_id).or_default();
+        peer.last_update = now;
+
+        let address = without_p2p(address);
+        if !peer.addresses.contains_key(&address) && peer.addresses.len() >= MAX_ADDRESSES_PER_PEER
+        {
+            evict_least_recent(&mut peer.addresses, |(_, last_update)| *last_update);
+        }
+        let (score, last_update) = peer.addresses.entry(address).or_default();
+        *last_update = now;
+
+        *score = if success {
+            (*score).max(0) + 1
+        } else {
+            (*score).min(0) - 1
+        }
+        .clamp(-MAX_SCORE, MAX_SCORE);
+    }
+
+    /// Orders the addresses by descending score.
+    ///
+    /// Addresses with the same score keep their relative order.
+    pub(crate) fn sort(&self, peer: &PeerId, addresses: &mut [Multiaddr]) {
+        if !self.peers.contains_key(peer) {
+            return;
+        }
+
+        addresses.sort_by_cached_key(|address| Reverse(self.get(peer, address)));
+    }
+}
+
+/// Removes the entry with the lowest time of last update.
+fn evict_least_recent<K, V>(map: &mut HashMap<K, V>, last_update: impl Fn(&V) -> u64)
+where
+    K: Clone + Eq + std::hash::Hash,
+{
+    if let Some(key) = map
+        .iter()
+        .min_by_key(|(_, value)| last_update(value))
+        .map(|(key, _)| key.clone())
+    {
+        map.remove(&key);
+    }
+}
+
+/// Dialed addresses carry the `/p2p` suffix of the peer, addresses passed to the swarm may not.
+fn without_p2p(address: &Multiaddr) -> Multiaddr {
+    let mut address = address.clone();
+    if let Some(Protocol::P2p(_)) = address.iter().last() {
+        address.pop();
+    }
+    address
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_are_ordered_by_recent_outcomes() {
+        let peer = PeerId::random();
+        let good: Multiaddr = "/memory/1".parse().unwrap();
+        let bad: Multiaddr = "/memory/2".parse().unwrap();
+        let unknown: Multiaddr = "/memory/3".parse().unwrap();
+        let mut scores = DialScores::default();
+
+        scores.record(peer, &bad, false);
+        scores.record(peer, &good.clone().with(Protocol::P2p(peer)), true);
+
+        let mut addresses = vec![bad.clone(), unknown.clone(), good.clone()];
+        scores.sort(&peer, &mut addresses);
+        assert_eq!(addresses, [good.clone(), unknown.clone(), bad.clone()]);
+
+        for _ in 0..2 * MAX_SCORE {
+            scores.record(peer, &good, true);
+        }
+        assert_eq!(scores.get(&peer, &good), MAX_SCORE);
+
+        scores.record(peer, &good, false);
+        assert_eq!(scores.get(&peer, &good), -1);
+        assert_eq!(scores.get(&peer, &unknown), 0);
+    }
+
+    #[test]
+    fn least_recently_updated_entries_are_evicted() {
+        let mut scores = DialScores::default();
+        let address = |i: usize| Multiaddr::empty().with(Protocol::Memory(i as u64));
+        let first_peer = PeerId::random();
+
+        for i in 0..=MAX_ADDRESSES_PER_PEER {
+            scores.record(first_peer, &address(i), true);
+        }
+        assert_eq!(
+            scores.peers[&first_peer].addresses.len(),
+            MAX_ADDRESSES_PER_PEER
+        );
+        assert_eq!(scores.get(&first_peer, &address(0)), 0);
+        assert_eq!(scores.get(&first_peer, &address(1)), 1);
+
+        for _ in 0..MAX_PEERS {
+            scores.record(PeerId::random(), &address(0), true);
+        }
+        assert_eq!(scores.peers.len(), MAX_PEERS);
+        assert_eq!(scores.get(&first_peer, &address(1)), 0);
+    }
+}
@@ -39,6 +39,7 @@
 //! >           [`NetworkBehaviour`](crate::behaviour::NetworkBehaviour) trait.
 
 pub mod either;
+mod idle_grace;
 mod map_in;
 mod map_out;
 pub mod multi;
@@ -47,6 +48,7 @@ mod pending;
 mod select;
 
 pub use crate::upgrade::{InboundUpgradeSend, OutboundUpgradeSend, SendWrapper, UpgradeInfoSend};
+pub use idle_grace::IdleGracePeriod;
 pub use map_in::MapInEvent;
 pub use map_out::MapOutEvent;
 pub use one_shot::{OneShotHandler, OneShotHandlerConfig};
@@ -138,10 +140,48 @@ pub trait ConnectionHandler: Send + 'static {
     /// - Protocols like [circuit-relay v2](https://github.com/libp2p/specs/blob/master/relay/circuit-v2.md) need to keep a connection alive beyond these circumstances and can thus override this method.
     /// - Protocols like [ping](https://github.com/libp2p/specs/blob/master/ping/ping.md) **don't** want to keep a connection alive despite an active streams.
     /// In that case, protocol authors can use [`Stream::ignore_for_keep_alive`](crate::Stream::ignore_for_keep_alive) to opt-out a particular stream from the keep-alive algorithm.
+    ///
+    /// ## Grace period
+    ///
+    /// Once this returns `false`, the connection is closed after the idle connection timeout
+    /// configured via [`Config::with_idle_connection_timeout`](crate::Config::with_idle_connection_timeout).
+    /// Handlers serving bursty workloads can use [`IdleGracePeriod`] to keep the connection alive
+    /// for a little longer after their last stream closed.
     fn connection_keep_alive(&self) -> bool {
         false
     }
 
+    /// Returns whether the handler currently has capacity for new inbound streams.
+    ///
+    /// Whilst this returns `false`, the connection stops accepting inbound streams from the
+    /// muxer. Streams opened by the remote stay queued in the muxer, whose flow control (e.g.
+    /// yamux' stream and receive window limits) eventually makes the remote back off, instead of
+    /// the streams being negotiated and buffered by the handler. The method is checked again
+    /// every time the connection is polled, i.e. after each call to [`ConnectionHandler::poll`]
+    /// and [`ConnectionHandler::on_behaviour_event`]. Capacity freed up elsewhere must thus wake
+    /// the task via the [`Context`] passed to [`ConnectionHandler::poll`].
+    ///
+    /// ## Interaction with multistream-select
+    ///
+    /// The protocol of an inbound stream is only known once multistream-select negotiated it,
+    /// which requires the stream to be accepted first. Backpressure therefore applies to all
+    /// inbound streams of the connection and not only to those of a single protocol. Deferred
+    /// streams are not negotiated at all, so the remote does not get a `na` response but waits
+    /// for the negotiation to complete and eventually hits its upgrade timeout if capacity does
+    /// not free up in time. Rejecting a busy protocol instead, by leaving it out of
+    /// [`ConnectionHandler::listen_protocol`], makes the remote consider it unsupported.
+    ///
+    /// Handlers combined via [`ConnectionHandler::select`] or [`MultiHandler`](multi::MultiHandler)
+    /// are checked individually: streams are accepted as long as one of them has capacity. The
+    /// protocols of all handlers stay offered to the remote. A stream negotiated for a handler
+    /// without capacity is held until the handler accepts inbound streams again, during which no
+    /// further streams are accepted on the connection.
+    ///
+    /// Defaults to `true`.
+    fn accepts_inbound_streams(&self) -> bool {
+        true
+    }
+
     /// Should behave like `Stream::poll()`.
     fn poll(
         &mut self,
@@ -190,6 +230,7 @@ pub trait ConnectionHandler: Send + 'static {
     fn select<TProto2>(self, other: TProto2) -> ConnectionHandlerSelect<Self, TProto2>
     where
         Self: Sized,
+        TProto2: ConnectionHandler,
     {
         ConnectionHandlerSelect::new(self, other)
     }
@@ -463,6 +504,11 @@ pub struct ListenUpgradeError<IOI, IP: InboundUpgradeSend> {
 ///
 /// The inbound substream protocol(s) are defined by [`ConnectionHandler::listen_protocol`]
 /// and the outbound substream protocol(s) by [`ConnectionHandlerEvent::OutboundSubstreamRequest`].
+///
+/// The order of the protocols returned by the upgrade's `protocol_info` is honored during
+/// negotiation: the dialer proposes them in that order and the first one mutually supported is
+/// chosen. Use [`FallbackUpgrade`](libp2p_core::upgrade::FallbackUpgrade) to offer several
+/// versions of a protocol, most preferred first.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct SubstreamProtocol<TUpgrade, TInfo> {
     upgrade: TUpgrade,
@@ -114,6 +114,13 @@ where
         }
     }
 
+    fn accepts_inbound_streams(&self) -> bool {
+        match self {
+            Either::Left(handler) => handler.accepts_inbound_streams(),
+            Either::Right(handler) => handler.accepts_inbound_streams(),
+        }
+    }
+
     fn poll(
         &mut self,
         cx: &mut Context<'_>,
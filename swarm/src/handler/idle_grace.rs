@@ -0,0 +1,105 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::FutureExt;
+use futures_timer::Delay;
+use std::fmt;
+use std::task::Context;
+use std::time::Duration;
+
+/// Keeps a connection alive for a grace period after a [`ConnectionHandler`](crate::ConnectionHandler)
+/// ran out of work.
+///
+/// Bursty workloads tend to go idle for short moments only. Without a grace period, the
+/// connection would be closed as soon as the idle connection timeout (see
+/// [`Config::with_idle_connection_timeout`](crate::Config::with_idle_connection_timeout))
+/// elapses, just to be re-established shortly after.
+///
+/// Report whether the handler is busy on every call to [`ConnectionHandler::poll`](crate::ConnectionHandler::poll)
+/// via [`IdleGracePeriod::poll`] and return [`IdleGracePeriod::keep_alive`] from
+/// [`ConnectionHandler::connection_keep_alive`](crate::ConnectionHandler::connection_keep_alive).
+/// The grace period starts once the handler is no longer busy and restarts whenever it becomes
+/// busy again. Note that the idle connection timeout only starts once the grace period is over,
+/// i.e. an idle connection is closed after the sum of both.
+pub struct IdleGracePeriod {
+    grace: Duration,
+    state: State,
+}
+
+enum State {
+    Busy,
+    Grace(Delay),
+    Idle,
+}
+
+impl IdleGracePeriod {
+    /// Creates a new [`IdleGracePeriod`] of the given duration.
+    ///
+    /// The grace period starts on the first call to [`IdleGracePeriod::poll`] reporting the
+    /// handler as not busy.
+    pub fn new(grace: Duration) -> Self {
+        Self {
+            grace,
+            state: State::Busy,
+        }
+    }
+
+    /// Records whether the handler is currently busy.
+    ///
+    /// Must be called from [`ConnectionHandler::poll`](crate::ConnectionHandler::poll), so that
+    /// the connection task is woken up once the grace period is over.
+    pub fn poll(&mut self, cx: &mut Context<'_>, busy: bool) {
+        if busy {
+            self.state = State::Busy;
+            return;
+        }
+
+        if let State::Busy = self.state {
+            self.state = State::Grace(Delay::new(self.grace));
+        }
+
+        if let State::Grace(delay) = &mut self.state {
+            if delay.poll_unpin(cx).is_ready() {
+                self.state = State::Idle;
+            }
+        }
+    }
+
+    /// Returns whether the connection should be kept alive, i.e. whether the handler is busy
+    /// or the grace period is not yet over.
+    pub fn keep_alive(&self) -> bool {
+        !matches!(self.state, State::Idle)
+    }
+}
+
+impl fmt::Debug for IdleGracePeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            State::Busy => "Busy",
+            State::Grace(_) => "Grace",
+            State::Idle => "Idle",
+        };
+
+        f.debug_struct("IdleGracePeriod")
+            .field("grace", &self.grace)
+            .field("state", &state)
+            .finish()
+    }
+}
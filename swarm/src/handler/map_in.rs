@@ -71,6 +71,10 @@ where
         self.inner.connection_keep_alive()
     }
 
+    fn accepts_inbound_streams(&self) -> bool {
+        self.inner.accepts_inbound_streams()
+    }
+
     fn poll(
         &mut self,
         cx: &mut Context<'_>,
@@ -31,7 +31,7 @@ use futures::{future::BoxFuture, prelude::*, ready};
 use rand::Rng;
 use std::{
     cmp,
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     error,
     fmt::{self, Debug},
     hash::Hash,
@@ -41,19 +41,28 @@ use std::{
 };
 
 /// A [`ConnectionHandler`] for multiple [`ConnectionHandler`]s of the same type.
-#[derive(Clone)]
-pub struct MultiHandler<K, H> {
+pub struct MultiHandler<K, H>
+where
+    H: ConnectionHandler,
+{
     handlers: HashMap<K, H>,
+    /// Inbound streams negotiated for a handler that defers inbound streams, in the order they
+    /// were negotiated. They are passed to the handler once it accepts inbound streams again.
+    held_inbound: VecDeque<(
+        K,
+        FullyNegotiatedInbound<H::InboundProtocol, H::InboundOpenInfo>,
+    )>,
 }
 
 impl<K, H> fmt::Debug for MultiHandler<K, H>
 where
     K: fmt::Debug + Eq + Hash,
-    H: fmt::Debug,
+    H: ConnectionHandler + fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("MultiHandler")
             .field("handlers", &self.handlers)
+            .field("held_inbound", &self.held_inbound.len())
             .finish()
     }
 }
@@ -72,6 +81,7 @@ where
     {
         let m = MultiHandler {
             handlers: HashMap::from_iter(iter),
+            held_inbound: VecDeque::new(),
         };
         uniq_proto_names(
             m.handlers
@@ -81,6 +91,36 @@ where
         Ok(m)
     }
 
+    /// Create and populate a `MultiHandler` from the given handler iterator, allowing handlers to
+    /// share protocol names.
+    ///
+    /// Inbound streams of a shared protocol are passed to only one of the handlers supporting it.
+    pub(crate) fn from_iter_unchecked<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, H)>,
+    {
+        MultiHandler {
+            handlers: HashMap::from_iter(iter),
+            held_inbound: VecDeque::new(),
+        }
+    }
+
+    /// Passes the held inbound streams to their handlers, as far as these accept them again.
+    fn release_held_inbound(&mut self) {
+        for _ in 0..self.held_inbound.len() {
+            let Some((key, stream)) = self.held_inbound.pop_front() else {
+                break;
+            };
+            match self.handlers.get_mut(&key) {
+                Some(h) if h.accepts_inbound_streams() => {
+                    h.on_connection_event(ConnectionEvent::FullyNegotiatedInbound(stream))
+                }
+                Some(_) => self.held_inbound.push_back((key, stream)),
+                None => tracing::error!("FullyNegotiatedInbound: no handler for key"),
+            }
+        }
+    }
+
     fn on_listen_upgrade_error(
         &mut self,
         ListenUpgradeError {
@@ -167,17 +207,15 @@ where
                 protocol: (key, arg),
                 mut info,
             }) => {
-                if let Some(h) = self.handlers.get_mut(&key) {
-                    if let Some(i) = info.take(&key) {
-                        h.on_connection_event(ConnectionEvent::FullyNegotiatedInbound(
-                            FullyNegotiatedInbound {
-                                protocol: arg,
-                                info: i,
-                            },
-                        ));
-                    }
-                } else {
-                    tracing::error!("FullyNegotiatedInbound: no handler for key")
+                if let Some(i) = info.take(&key) {
+                    self.held_inbound.push_back((
+                        key,
+                        FullyNegotiatedInbound {
+                            protocol: arg,
+                            info: i,
+                        },
+                    ));
+                    self.release_held_inbound();
                 }
             }
             ConnectionEvent::AddressChange(AddressChange { new_address }) => {
@@ -236,12 +274,21 @@ where
             .unwrap_or(false)
     }
 
+    /// Inbound streams are accepted as long as one of the handlers accepts them. A stream negotiated
+    /// for another handler is held until that handler accepts inbound streams again, with no
+    /// further streams being accepted in the meantime.
+    fn accepts_inbound_streams(&self) -> bool {
+        self.held_inbound.is_empty() && self.handlers.values().any(|h| h.accepts_inbound_streams())
+    }
+
     fn poll(
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<
         ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
     > {
+        self.release_held_inbound();
+
         // Calling `gen_range(0, 0)` (see below) would panic, so we have return early to avoid
         // that situation.
         if self.handlers.is_empty() {
@@ -285,7 +332,10 @@ where
 }
 
 /// Split [`MultiHandler`] into parts.
-impl<K, H> IntoIterator for MultiHandler<K, H> {
+impl<K, H> IntoIterator for MultiHandler<K, H>
+where
+    H: ConnectionHandler,
+{
     type Item = <Self::IntoIter as Iterator>::Item;
     type IntoIter = std::collections::hash_map::IntoIter<K, H>;
 
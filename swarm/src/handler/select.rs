@@ -27,21 +27,70 @@ use crate::upgrade::SendWrapper;
 use either::Either;
 use futures::{future, ready};
 use libp2p_core::upgrade::SelectUpgrade;
-use std::{cmp, task::Context, task::Poll};
+use std::{cmp, collections::VecDeque, fmt, task::Context, task::Poll};
 
 /// Implementation of [`ConnectionHandler`] that combines two protocols into one.
-#[derive(Debug, Clone)]
-pub struct ConnectionHandlerSelect<TProto1, TProto2> {
+pub struct ConnectionHandlerSelect<TProto1, TProto2>
+where
+    TProto1: ConnectionHandler,
+    TProto2: ConnectionHandler,
+{
     /// The first protocol.
     proto1: TProto1,
     /// The second protocol.
     proto2: TProto2,
+    /// Inbound streams negotiated for a handler that defers inbound streams, in the order they
+    /// were negotiated. They are passed to the handler once it accepts inbound streams again.
+    held_inbound: VecDeque<
+        Either<
+            FullyNegotiatedInbound<TProto1::InboundProtocol, TProto1::InboundOpenInfo>,
+            FullyNegotiatedInbound<TProto2::InboundProtocol, TProto2::InboundOpenInfo>,
+        >,
+    >,
+}
+
+impl<TProto1, TProto2> fmt::Debug for ConnectionHandlerSelect<TProto1, TProto2>
+where
+    TProto1: ConnectionHandler + fmt::Debug,
+    TProto2: ConnectionHandler + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionHandlerSelect")
+            .field("proto1", &self.proto1)
+            .field("proto2", &self.proto2)
+            .field("held_inbound", &self.held_inbound.len())
+            .finish()
+    }
 }
 
-impl<TProto1, TProto2> ConnectionHandlerSelect<TProto1, TProto2> {
+impl<TProto1, TProto2> ConnectionHandlerSelect<TProto1, TProto2>
+where
+    TProto1: ConnectionHandler,
+    TProto2: ConnectionHandler,
+{
     /// Builds a [`ConnectionHandlerSelect`].
     pub(crate) fn new(proto1: TProto1, proto2: TProto2) -> Self {
-        ConnectionHandlerSelect { proto1, proto2 }
+        ConnectionHandlerSelect {
+            proto1,
+            proto2,
+            held_inbound: VecDeque::new(),
+        }
+    }
+
+    /// Passes the held inbound streams to their handlers, as far as these accept them again.
+    fn release_held_inbound(&mut self) {
+        for _ in 0..self.held_inbound.len() {
+            match self.held_inbound.pop_front() {
+                Some(Either::Left(f)) if self.proto1.accepts_inbound_streams() => self
+                    .proto1
+                    .on_connection_event(ConnectionEvent::FullyNegotiatedInbound(f)),
+                Some(Either::Right(f)) if self.proto2.accepts_inbound_streams() => self
+                    .proto2
+                    .on_connection_event(ConnectionEvent::FullyNegotiatedInbound(f)),
+                Some(held) => self.held_inbound.push_back(held),
+                None => break,
+            }
+        }
     }
 
     pub fn into_inner(self) -> (TProto1, TProto2) {
@@ -214,12 +263,22 @@ where
         )
     }
 
+    /// Inbound streams are accepted as long as one of the handlers accepts them. A stream negotiated
+    /// for the other handler is held until that handler accepts inbound streams again, with no
+    /// further streams being accepted in the meantime.
+    fn accepts_inbound_streams(&self) -> bool {
+        self.held_inbound.is_empty()
+            && (self.proto1.accepts_inbound_streams() || self.proto2.accepts_inbound_streams())
+    }
+
     fn poll(
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<
         ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
     > {
+        self.release_held_inbound();
+
         match self.proto1.poll(cx) {
             Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event)) => {
                 return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Either::Left(event)));
@@ -292,14 +351,9 @@ where
                 }
             }
             ConnectionEvent::FullyNegotiatedInbound(fully_negotiated_inbound) => {
-                match fully_negotiated_inbound.transpose() {
-                    Either::Left(f) => self
-                        .proto1
-                        .on_connection_event(ConnectionEvent::FullyNegotiatedInbound(f)),
-                    Either::Right(f) => self
-                        .proto2
-                        .on_connection_event(ConnectionEvent::FullyNegotiatedInbound(f)),
-                }
+                self.held_inbound
+                    .push_back(fully_negotiated_inbound.transpose());
+                self.release_held_inbound();
             }
             ConnectionEvent::AddressChange(address) => {
                 self.proto1
@@ -348,3 +402,141 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handler::UpgradeInfoSend;
+    use crate::{Stream, StreamProtocol};
+    use libp2p_core::upgrade::{DeniedUpgrade, InboundUpgrade, UpgradeInfo};
+    use std::iter;
+    use void::Void;
+
+    struct Upgrade(StreamProtocol);
+
+    impl UpgradeInfo for Upgrade {
+        type Info = StreamProtocol;
+        type InfoIter = iter::Once<StreamProtocol>;
+
+        fn protocol_info(&self) -> Self::InfoIter {
+            iter::once(self.0.clone())
+        }
+    }
+
+    impl InboundUpgrade<Stream> for Upgrade {
+        type Output = ();
+        type Error = Void;
+        type Future = future::Ready<Result<(), Void>>;
+
+        fn upgrade_inbound(self, _: Stream, _: Self::Info) -> Self::Future {
+            future::ready(Ok(()))
+        }
+    }
+
+    struct Handler {
+        protocol: StreamProtocol,
+        accepts_inbound_streams: bool,
+        inbound_streams: usize,
+    }
+
+    impl ConnectionHandler for Handler {
+        type FromBehaviour = Void;
+        type ToBehaviour = Void;
+        type InboundProtocol = Upgrade;
+        type OutboundProtocol = DeniedUpgrade;
+        type InboundOpenInfo = ();
+        type OutboundOpenInfo = Void;
+
+        fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, ()> {
+            SubstreamProtocol::new(Upgrade(self.protocol.clone()), ())
+        }
+
+        fn accepts_inbound_streams(&self) -> bool {
+            self.accepts_inbound_streams
+        }
+
+        fn poll(
+            &mut self,
+            _: &mut Context<'_>,
+        ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, Void, Void>> {
+            Poll::Pending
+        }
+
+        fn on_behaviour_event(&mut self, event: Void) {
+            void::unreachable(event)
+        }
+
+        fn on_connection_event(
+            &mut self,
+            event: ConnectionEvent<Self::InboundProtocol, Self::OutboundProtocol, (), Void>,
+        ) {
+            if let ConnectionEvent::FullyNegotiatedInbound(_) = event {
+                self.inbound_streams += 1;
+            }
+        }
+    }
+
+    fn handler(protocol: &'static str, accepts_inbound_streams: bool) -> Handler {
+        Handler {
+            protocol: StreamProtocol::new(protocol),
+            accepts_inbound_streams,
+            inbound_streams: 0,
+        }
+    }
+
+    fn offered_protocols(handler: &impl ConnectionHandler) -> Vec<String> {
+        handler
+            .listen_protocol()
+            .upgrade()
+            .protocol_info()
+            .map(|p| p.as_ref().to_owned())
+            .collect()
+    }
+
+    fn negotiated_inbound(
+        protocol: future::Either<(), ()>,
+    ) -> ConnectionEvent<
+        'static,
+        <ConnectionHandlerSelect<Handler, Handler> as ConnectionHandler>::InboundProtocol,
+        <ConnectionHandlerSelect<Handler, Handler> as ConnectionHandler>::OutboundProtocol,
+        ((), ()),
+        Either<Void, Void>,
+    > {
+        ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+            protocol,
+            info: ((), ()),
+        })
+    }
+
+    #[test]
+    fn deferring_handler_keeps_offering_its_protocols() {
+        let select = ConnectionHandlerSelect::new(handler("/a", false), handler("/b", true));
+        assert!(select.accepts_inbound_streams());
+        assert_eq!(offered_protocols(&select), ["/a", "/b"]);
+
+        let select = ConnectionHandlerSelect::new(handler("/a", false), handler("/b", false));
+        assert!(!select.accepts_inbound_streams());
+        assert_eq!(offered_protocols(&select), ["/a", "/b"]);
+    }
+
+    #[test]
+    fn stream_of_deferring_handler_is_held() {
+        let mut select = ConnectionHandlerSelect::new(handler("/a", false), handler("/b", true));
+
+        select.on_connection_event(negotiated_inbound(future::Either::Right(())));
+        assert_eq!(select.proto2.inbound_streams, 1);
+        assert!(select.accepts_inbound_streams());
+
+        select.on_connection_event(negotiated_inbound(future::Either::Left(())));
+        assert_eq!(select.proto1.inbound_streams, 0);
+        assert!(
+            !select.accepts_inbound_streams(),
+            "Expect no further streams to be accepted whilst one is held"
+        );
+
+        select.proto1.accepts_inbound_streams = true;
+        let _ = select.poll(&mut Context::from_waker(futures::task::noop_waker_ref()));
+        assert_eq!(select.proto1.inbound_streams, 1);
+        assert!(select.accepts_inbound_streams());
+    }
+}
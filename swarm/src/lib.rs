@@ -55,7 +55,10 @@
 
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+mod bandwidth;
 mod connection;
+mod connection_filter;
+mod dial_scores;
 mod executor;
 mod stream;
 mod stream_protocol;
@@ -68,6 +71,7 @@ pub mod dial_opts;
 pub mod dummy;
 pub mod handler;
 mod listen_opts;
+pub mod protocol_registry;
 
 /// Bundles all symbols required for the [`libp2p_swarm_derive::NetworkBehaviour`] macro.
 #[doc(hidden)]
@@ -75,6 +79,7 @@ pub mod derive_prelude {
     pub use crate::behaviour::AddressChange;
     pub use crate::behaviour::ConnectionClosed;
     pub use crate::behaviour::ConnectionEstablished;
+    pub use crate::behaviour::DatagramReceived;
     pub use crate::behaviour::DialFailure;
     pub use crate::behaviour::ExpiredListenAddr;
     pub use crate::behaviour::ExternalAddrConfirmed;
@@ -105,34 +110,43 @@ pub mod derive_prelude {
     pub use libp2p_identity::PeerId;
 }
 
+pub use bandwidth::Bandwidth;
 pub use behaviour::{
-    AddressChange, CloseConnection, ConnectionClosed, DialFailure, ExpiredListenAddr,
-    ExternalAddrExpired, ExternalAddresses, FromSwarm, ListenAddresses, ListenFailure,
-    ListenerClosed, ListenerError, NetworkBehaviour, NewExternalAddrCandidate, NewListenAddr,
-    NotifyHandler, ToSwarm,
+    AddressChange, CloseConnection, ConnectionClosed, DatagramReceived, DialFailure,
+    ExpiredListenAddr, ExternalAddrExpired, ExternalAddresses, FromSwarm, ListenAddresses,
+    ListenFailure, ListenerClosed, ListenerError, NetworkBehaviour, NewExternalAddrCandidate,
+    NewListenAddr, NotifyHandler, ToSwarm,
 };
 pub use connection::pool::ConnectionCounters;
 pub use connection::{ConnectionError, ConnectionId, SupportedProtocols};
+pub use connection_filter::{ConnectionFilter, InboundConnectionFiltered};
 pub use executor::Executor;
 pub use handler::{
-    ConnectionHandler, ConnectionHandlerEvent, ConnectionHandlerSelect, OneShotHandler,
-    OneShotHandlerConfig, StreamUpgradeError, SubstreamProtocol,
+    ConnectionHandler, ConnectionHandlerEvent, ConnectionHandlerSelect, IdleGracePeriod,
+    OneShotHandler, OneShotHandlerConfig, StreamUpgradeError, SubstreamProtocol,
 };
 #[cfg(feature = "macros")]
 pub use libp2p_swarm_derive::NetworkBehaviour;
 pub use listen_opts::ListenOpts;
+pub use protocol_registry::{ProtocolRegistry, StreamHandlerFactory};
 pub use stream::Stream;
 pub use stream_protocol::{InvalidProtocol, StreamProtocol};
 
 use crate::behaviour::ExternalAddrConfirmed;
+use crate::dial_scores::DialScores;
 use crate::handler::UpgradeInfoSend;
+use bytes::Bytes;
 use connection::pool::{EstablishedConnection, Pool, PoolConfig, PoolEvent};
 use connection::IncomingInfo;
 use connection::{
     PendingConnectionError, PendingInboundConnectionError, PendingOutboundConnectionError,
 };
 use dial_opts::{DialOpts, PeerCondition};
-use futures::{prelude::*, stream::FusedStream};
+use futures::{
+    future::BoxFuture,
+    prelude::*,
+    stream::{FusedStream, FuturesUnordered},
+};
 use libp2p_core::{
     connection::ConnectedPoint,
     muxing::StreamMuxerBox,
@@ -143,6 +157,7 @@ use libp2p_identity::PeerId;
 use smallvec::SmallVec;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::num::{NonZeroU32, NonZeroU8, NonZeroUsize};
+use std::sync::Arc;
 use std::time::Duration;
 use std::{
     convert::TryFrom,
@@ -236,6 +251,23 @@ pub enum SwarmEvent<TBehaviourOutEvent> {
         /// The error that happened.
         error: ListenError,
     },
+    /// A dialing attempt failed and will be retried after `delay`.
+    ///
+    /// Only reported for dials configured via [`DialOpts::with_retry`]. The retry is reported as
+    /// [`Dialing`](SwarmEvent::Dialing) with the same [`ConnectionId`], i.e. all attempts of a
+    /// dial share the [`ConnectionId`] of its [`DialOpts`].
+    DialRetryScheduled {
+        /// Identifier of the failed connection attempt.
+        connection_id: ConnectionId,
+        /// If known, [`PeerId`] of the peer we tried to reach.
+        peer_id: Option<PeerId>,
+        /// Error that has been encountered.
+        error: DialError,
+        /// The number of the failed attempt, starting at 1.
+        attempt: u32,
+        /// The time until the next attempt.
+        delay: Duration,
+    },
     /// An error happened on an outbound connection.
     OutgoingConnectionError {
         /// Identifier of the connection.
@@ -271,12 +303,17 @@ pub enum SwarmEvent<TBehaviourOutEvent> {
         /// if the stream produced an error.
         reason: Result<(), io::Error>,
     },
-    /// One of the listeners reported a non-fatal error.
+    /// One of the listeners reported an error or a listener requested via [`ToSwarm::ListenOn`]
+    /// could not be created.
     ListenerError {
         /// The listener that errored.
         listener_id: ListenerId,
+        /// The address the listener was requested to listen on, e.g. via [`Swarm::listen_on`].
+        address: Multiaddr,
         /// The listener error.
         error: io::Error,
+        /// Whether the listener is closed or keeps listening.
+        recovery: ListenerRecovery,
     },
     /// A new dialing attempt has been initiated by the [`NetworkBehaviour`]
     /// implementation.
@@ -298,6 +335,34 @@ pub enum SwarmEvent<TBehaviourOutEvent> {
     ExternalAddrConfirmed { address: Multiaddr },
     /// An external address of the local node expired, i.e. is no-longer confirmed.
     ExternalAddrExpired { address: Multiaddr },
+    /// A stream has been opened on a connection and a protocol has been negotiated on it.
+    ///
+    /// Only reported if enabled via [`Config::with_stream_opened_events`].
+    StreamOpened {
+        /// Identity of the peer the stream is opened with.
+        peer_id: PeerId,
+        /// Identifier of the connection the stream is opened on.
+        connection_id: ConnectionId,
+        /// The protocol negotiated on the stream.
+        protocol: StreamProtocol,
+        /// Whether the stream was opened by us ([`Endpoint::Dialer`]) or by the remote
+        /// ([`Endpoint::Listener`]).
+        direction: Endpoint,
+    },
+    /// The address of an established connection changed, e.g. because the remote migrated a QUIC
+    /// connection to a new network path.
+    ///
+    /// The connection, its [`ConnectionId`] and its streams stay intact.
+    AddressChanged {
+        /// Identity of the peer of the connection.
+        peer_id: PeerId,
+        /// Identifier of the connection.
+        connection_id: ConnectionId,
+        /// Endpoint of the connection before the change.
+        old: ConnectedPoint,
+        /// Endpoint of the connection after the change.
+        new: ConnectedPoint,
+    },
 }
 
 impl<TBehaviourOutEvent> SwarmEvent<TBehaviourOutEvent> {
@@ -309,6 +374,48 @@ impl<TBehaviourOutEvent> SwarmEvent<TBehaviourOutEvent> {
             other => Err(other),
         }
     }
+
+    /// Returns the name of this event's variant, e.g. `"ConnectionEstablished"`.
+    ///
+    /// Useful as a cheap, stable label for logs and metrics as it doesn't depend on the data
+    /// contained in the event.
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            SwarmEvent::Behaviour(_) => "Behaviour",
+            SwarmEvent::ConnectionEstablished { .. } => "ConnectionEstablished",
+            SwarmEvent::ConnectionClosed { .. } => "ConnectionClosed",
+            SwarmEvent::IncomingConnection { .. } => "IncomingConnection",
+            SwarmEvent::IncomingConnectionError { .. } => "IncomingConnectionError",
+            SwarmEvent::DialRetryScheduled { .. } => "DialRetryScheduled",
+            SwarmEvent::OutgoingConnectionError { .. } => "OutgoingConnectionError",
+            SwarmEvent::NewListenAddr { .. } => "NewListenAddr",
+            SwarmEvent::ExpiredListenAddr { .. } => "ExpiredListenAddr",
+            SwarmEvent::ListenerClosed { .. } => "ListenerClosed",
+            SwarmEvent::ListenerError { .. } => "ListenerError",
+            SwarmEvent::Dialing { .. } => "Dialing",
+            SwarmEvent::NewExternalAddrCandidate { .. } => "NewExternalAddrCandidate",
+            SwarmEvent::ExternalAddrConfirmed { .. } => "ExternalAddrConfirmed",
+            SwarmEvent::ExternalAddrExpired { .. } => "ExternalAddrExpired",
+            SwarmEvent::StreamOpened { .. } => "StreamOpened",
+            SwarmEvent::AddressChanged { .. } => "AddressChanged",
+        }
+    }
+}
+
+/// Whether a listener recovers from a [`SwarmEvent::ListenerError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListenerRecovery {
+    /// The listener could not be created, e.g. because its address is already in use.
+    ///
+    /// Only reported for listeners requested via [`ToSwarm::ListenOn`], [`Swarm::listen_on`]
+    /// returns the error instead. Listening again requires a new request, possibly on a different
+    /// address. No [`SwarmEvent::ListenerClosed`] is reported for the listener.
+    Closed,
+    /// The error is not fatal and the listener keeps listening.
+    ///
+    /// Should a listener fail fatally later on, it is closed and reported via
+    /// [`SwarmEvent::ListenerClosed`] with the error as reason.
+    Retrying,
 }
 
 /// Contains the state of the network, plus the way it should behave.
@@ -337,15 +444,50 @@ where
 
     confirmed_external_addr: HashSet<Multiaddr>,
 
+    /// Candidates added via [`Swarm::add_external_address_candidate`], confirmed once their score
+    /// reaches `external_addr_score_threshold`, together with the peers that observed them.
+    external_addr_candidates: HashMap<Multiaddr, (AddressScore, HashSet<PeerId>)>,
+
+    external_addr_score_threshold: AddressScore,
+
     /// Multiaddresses that our listeners are listening on,
     listened_addrs: HashMap<ListenerId, SmallVec<[Multiaddr; 1]>>,
 
+    /// The addresses our listeners were requested to listen on.
+    listener_requested_addrs: HashMap<ListenerId, Multiaddr>,
+
     /// Pending event to be delivered to connection handlers
     /// (or dropped if the peer disconnected) before the `behaviour`
     /// can be polled again.
     pending_handler_event: Option<(PeerId, PendingNotifyHandler, THandlerInEvent<TBehaviour>)>,
 
     pending_swarm_events: VecDeque<SwarmEvent<TBehaviour::ToSwarm>>,
+
+    /// Filter consulted for every inbound connection before it is upgraded.
+    connection_filter: Option<Box<dyn ConnectionFilter>>,
+
+    /// In-flight dials configured with [`DialOpts::with_retry`], together with the options for
+    /// their next attempt and the number of the in-flight attempt.
+    dial_retries: HashMap<ConnectionId, (DialOpts, u32)>,
+
+    /// Retries of failed dials waiting for their backoff to elapse, together with the options and
+    /// the number of their next attempt.
+    scheduled_dial_retries: HashMap<ConnectionId, (DialOpts, u32)>,
+
+    /// Fire once the backoff of the retry with the given ID elapsed.
+    dial_retry_timers: FuturesUnordered<BoxFuture<'static, ConnectionId>>,
+
+    /// Peers banned via [`Swarm::ban_peer_id`].
+    banned_peers: HashSet<PeerId>,
+
+    /// Scores deciding the order in which the addresses of a peer are dialed.
+    dial_scores: DialScores,
+
+    /// Dials aborted via [`Swarm::cancel_dial`] that have not been reported yet.
+    cancelled_dials: HashSet<ConnectionId>,
+
+    /// Byte counters of the connections, if enabled via [`Config::with_bandwidth_accounting`].
+    bandwidth: Option<Arc<Bandwidth>>,
 }
 
 impl<TBehaviour> Unpin for Swarm<TBehaviour> where TBehaviour: NetworkBehaviour {}
@@ -364,6 +506,17 @@ where
     ) -> Self {
         tracing::info!(%local_peer_id);
 
+        let bandwidth = config
+            .bandwidth_accounting
+            .then(|| Arc::new(Bandwidth::default()));
+        let transport = match bandwidth.clone() {
+            Some(bandwidth) => Transport::map(transport, move |(peer, muxer), _| {
+                (peer, bandwidth.wrap(peer, muxer))
+            })
+            .boxed(),
+            None => transport,
+        };
+
         Swarm {
             local_peer_id,
             transport,
@@ -371,9 +524,20 @@ where
             behaviour,
             supported_protocols: Default::default(),
             confirmed_external_addr: Default::default(),
+            external_addr_candidates: HashMap::new(),
+            external_addr_score_threshold: config.external_addr_score_threshold,
             listened_addrs: HashMap::new(),
+            listener_requested_addrs: HashMap::new(),
             pending_handler_event: None,
             pending_swarm_events: VecDeque::default(),
+            connection_filter: config.connection_filter,
+            dial_retries: HashMap::new(),
+            scheduled_dial_retries: HashMap::new(),
+            dial_retry_timers: Default::default(),
+            banned_peers: HashSet::new(),
+            dial_scores: DialScores::default(),
+            cancelled_dials: HashSet::new(),
+            bandwidth,
         }
     }
 
@@ -411,6 +575,12 @@ where
     ///
     /// See also [`DialOpts`].
     ///
+    /// Each dial is identified by the [`ConnectionId`] of its [`DialOpts`], see
+    /// [`DialOpts::connection_id`]. The [`SwarmEvent::ConnectionEstablished`] or
+    /// [`SwarmEvent::OutgoingConnectionError`] reporting the outcome of the dial carries the same
+    /// ID, which allows correlating the event with a particular call, e.g. when dialing the same
+    /// peer several times.
+    ///
     /// ```
     /// # use libp2p_swarm::Swarm;
     /// # use libp2p_swarm::dial_opts::{DialOpts, PeerCondition};
@@ -428,6 +598,13 @@ where
     ///
     /// // Dial an unknown peer.
     /// swarm.dial("/ip6/::1/tcp/12345".parse::<Multiaddr>().unwrap());
+    ///
+    /// // Dial a peer, keeping the ID to correlate the events of this dial.
+    /// let opts = DialOpts::peer_id(PeerId::random())
+    ///     .condition(PeerCondition::Always)
+    ///     .build();
+    /// let connection_id = opts.connection_id();
+    /// swarm.dial(opts);
     /// # }
     ///
     /// # fn build_swarm() -> Swarm<dummy::Behaviour> {
@@ -441,6 +618,21 @@ where
         let condition = dial_opts.peer_condition();
         let connection_id = dial_opts.connection_id();
 
+        if let Some(peer_id) = peer_id.filter(|p| self.banned_peers.contains(p)) {
+            let error = DialError::Denied {
+                cause: ConnectionDenied::new(Banned { peer_id }),
+            };
+
+            self.behaviour
+                .on_swarm_event(FromSwarm::DialFailure(DialFailure {
+                    peer_id: Some(peer_id),
+                    error: &error,
+                    connection_id,
+                }));
+
+            return Err(error);
+        }
+
         let should_dial = match (condition, peer_id) {
             (_, None) => true,
             (PeerCondition::Always, _) => true,
@@ -519,11 +711,41 @@ where
                 return Err(error);
             };
 
+            if let Some(allowed) = dial_opts.allowed_transports() {
+                let (allowed_addresses, disallowed_addresses): (Vec<_>, Vec<_>) =
+                    addresses_from_opts
+                        .into_iter()
+                        .partition(|addr| addr.iter().any(|p| allowed.contains(&p.tag())));
+
+                if allowed_addresses.is_empty() {
+                    let error = DialError::Transport(
+                        disallowed_addresses
+                            .into_iter()
+                            .map(|addr| (addr.clone(), TransportError::MultiaddrNotSupported(addr)))
+                            .collect(),
+                    );
+                    self.behaviour
+                        .on_swarm_event(FromSwarm::DialFailure(DialFailure {
+                            peer_id,
+                            error: &error,
+                            connection_id,
+                        }));
+                    return Err(error);
+                }
+
+                addresses_from_opts = allowed_addresses;
+            }
+
+            if let Some(peer_id) = peer_id {
+                self.dial_scores.sort(&peer_id, &mut addresses_from_opts);
+            }
+
             addresses_from_opts
         };
 
         let dials = addresses
-            .into_iter()
+            .iter()
+            .cloned()
             .map(|a| match peer_id.map_or(Ok(a.clone()), |p| a.with_p2p(p)) {
                 Ok(address) => {
                     let (dial, span) = match dial_opts.role_override() {
@@ -555,6 +777,7 @@ where
             .collect();
 
         self.pool.add_outgoing(
+            addresses,
             dials,
             peer_id,
             dial_opts.role_override(),
@@ -562,14 +785,83 @@ where
             connection_id,
         );
 
+        if dial_opts.retry().is_some() {
+            self.dial_retries
+                .insert(connection_id, (dial_opts.next_attempt(), 1));
+        }
+
         Ok(())
     }
 
+    /// Performs a scheduled retry of a dial, unless the peer got connected in the meantime.
+    ///
+    /// A retry that isn't performed ends the dial with [`DialError::DialPeerConditionFalse`].
+    fn retry_dial(&mut self, opts: DialOpts, attempt: u32) {
+        let peer_id = opts.get_peer_id();
+        let connection_id = opts.connection_id();
+
+        if let Some(peer) = peer_id.filter(|p| self.pool.is_connected(*p)) {
+            tracing::debug!(%peer, "Cancelling dial retry, peer is connected");
+
+            let error = DialError::DialPeerConditionFalse(PeerCondition::Disconnected);
+            self.behaviour
+                .on_swarm_event(FromSwarm::DialFailure(DialFailure {
+                    peer_id,
+                    error: &error,
+                    connection_id,
+                }));
+            self.pending_swarm_events
+                .push_back(SwarmEvent::OutgoingConnectionError {
+                    peer_id,
+                    connection_id,
+                    error,
+                });
+            return;
+        }
+
+        match self.dial(opts) {
+            Ok(()) => {
+                if let Some(retry) = self.dial_retries.get_mut(&connection_id) {
+                    retry.1 = attempt;
+                }
+                self.pending_swarm_events.push_back(SwarmEvent::Dialing {
+                    peer_id,
+                    connection_id,
+                });
+            }
+            Err(error) => {
+                self.pending_swarm_events
+                    .push_back(SwarmEvent::OutgoingConnectionError {
+                        peer_id,
+                        connection_id,
+                        error,
+                    });
+            }
+        }
+    }
+
     /// Returns an iterator that produces the list of addresses we're listening on.
     pub fn listeners(&self) -> impl Iterator<Item = &Multiaddr> {
         self.listened_addrs.values().flatten()
     }
 
+    /// Returns an iterator over the addresses we're listening on, together with the
+    /// [`ListenerId`] of the listener that reported them.
+    ///
+    /// Listeners that have not reported an address yet are not included.
+    pub fn listeners_with_ids(&self) -> impl Iterator<Item = (&ListenerId, &Multiaddr)> {
+        self.listened_addrs
+            .iter()
+            .flat_map(|(id, addrs)| addrs.iter().map(move |addr| (id, addr)))
+    }
+
+    /// Returns an iterator over the addresses reported by the listener with the given [`ListenerId`].
+    ///
+    /// The iterator is empty if the listener is unknown or has not reported any address yet.
+    pub fn listener_addresses(&self, id: ListenerId) -> impl Iterator<Item = &Multiaddr> {
+        self.listened_addrs.get(&id).into_iter().flatten()
+    }
+
     /// Returns the peer ID of the swarm passed as parameter.
     pub fn local_peer_id(&self) -> &PeerId {
         &self.local_peer_id
@@ -593,6 +885,8 @@ where
 
             return Err(e);
         }
+        self.listener_requested_addrs
+            .insert(listener_id, addr.clone());
 
         self.behaviour
             .on_swarm_event(FromSwarm::NewListener(behaviour::NewListener {
@@ -617,12 +911,84 @@ where
     /// Remove an external address for the local node.
     ///
     /// The address is broadcast to all [`NetworkBehaviour`]s via [`FromSwarm::ExternalAddrExpired`].
+    /// This also stops tracking the address as a candidate, see
+    /// [`Swarm::add_external_address_candidate`].
     pub fn remove_external_address(&mut self, addr: &Multiaddr) {
+        self.external_addr_candidates.remove(addr);
         self.behaviour
             .on_swarm_event(FromSwarm::ExternalAddrExpired(ExternalAddrExpired { addr }));
         self.confirmed_external_addr.remove(addr);
     }
 
+    /// Add a candidate for an external address of the local node with an initial score.
+    ///
+    /// Every time a [`NetworkBehaviour`] reports another peer observing the address via
+    /// [`ToSwarm::ObservedExternalAddr`], e.g. `identify`, its score increases by one. Repeated
+    /// observations by the same peer and reports via [`ToSwarm::NewExternalAddrCandidate`], which
+    /// don't name an observer, leave the score unchanged. Once the score reaches
+    /// the threshold configured via [`Config::with_external_address_score_threshold`], the
+    /// address is confirmed as if added via [`Swarm::add_external_address`] and reported as
+    /// [`SwarmEvent::ExternalAddrConfirmed`], after the [`SwarmEvent::NewExternalAddrCandidate`]
+    /// of the report that confirmed it. Until then, the address is not advertised to other peers.
+    ///
+    /// The candidate is broadcast to all [`NetworkBehaviour`]s via
+    /// [`FromSwarm::NewExternalAddrCandidate`]. Adding an address that is already a candidate
+    /// keeps the higher of both scores.
+    pub fn add_external_address_candidate(&mut self, address: Multiaddr, score: AddressScore) {
+        if self.confirmed_external_addr.contains(&address) {
+            return;
+        }
+
+        self.behaviour
+            .on_swarm_event(FromSwarm::NewExternalAddrCandidate(
+                NewExternalAddrCandidate { addr: &address },
+            ));
+
+        let (current, _) = self
+            .external_addr_candidates
+            .entry(address.clone())
+            .or_default();
+        *current = (*current).max(score);
+
+        self.maybe_confirm_external_address_candidate(address);
+    }
+
+    /// Lists the candidates added via [`Swarm::add_external_address_candidate`] that are not
+    /// confirmed yet, together with their current score.
+    pub fn external_address_candidates(&self) -> impl Iterator<Item = (&Multiaddr, AddressScore)> {
+        self.external_addr_candidates
+            .iter()
+            .map(|(address, (score, _))| (address, *score))
+    }
+
+    /// Increases the score of the given address if it is a candidate added via
+    /// [`Swarm::add_external_address_candidate`] that was not observed by `observer` before.
+    fn bump_external_address_candidate(&mut self, address: &Multiaddr, observer: PeerId) {
+        let Some((score, observers)) = self.external_addr_candidates.get_mut(address) else {
+            return;
+        };
+        if !observers.insert(observer) {
+            return;
+        }
+        score.0 = score.0.saturating_add(1);
+
+        self.maybe_confirm_external_address_candidate(address.clone());
+    }
+
+    fn maybe_confirm_external_address_candidate(&mut self, address: Multiaddr) {
+        match self.external_addr_candidates.get(&address) {
+            Some((score, _)) if *score >= self.external_addr_score_threshold => {}
+            _ => return,
+        }
+        self.external_addr_candidates.remove(&address);
+
+        tracing::debug!(%address, "External address candidate reached score threshold");
+
+        self.add_external_address(address.clone());
+        self.pending_swarm_events
+            .push_back(SwarmEvent::ExternalAddrConfirmed { address });
+    }
+
     /// Disconnects a peer by its peer ID, closing all connections to said peer.
     ///
     /// Returns `Ok(())` if there was one or more established connections to the peer.
@@ -641,6 +1007,97 @@ where
         }
     }
 
+    /// Sends an unreliable datagram to the given peer.
+    ///
+    /// The datagram is sent on the established connection to the peer that allows for the
+    /// largest datagrams, e.g. a QUIC connection with datagrams enabled. The remote reports it
+    /// to its [`NetworkBehaviour`] as [`FromSwarm::DatagramReceived`].
+    ///
+    /// Fails right away if there is no such connection or if `data` exceeds the maximum size of
+    /// datagrams on that connection at the time it was established. Delivery is not guaranteed:
+    /// datagrams may be lost, arrive out of order, and are dropped if the connection is busy.
+    pub fn send_datagram(&mut self, peer_id: PeerId, data: Bytes) -> Result<(), SendDatagramError> {
+        self.pool.send_datagram(peer_id, data)
+    }
+
+    /// Returns the addresses tried by all pending dials, together with the [`ConnectionId`] of
+    /// the dial.
+    ///
+    /// A dial is pending until it is reported as [`SwarmEvent::ConnectionEstablished`] or
+    /// [`SwarmEvent::OutgoingConnectionError`]. All addresses of a dial are listed until then,
+    /// including those whose attempts already failed. Dials waiting to be retried, see
+    /// [`DialOpts::with_retry`], are listed with the addresses of their [`DialOpts`].
+    pub fn pending_dials(&self) -> impl Iterator<Item = (ConnectionId, &Multiaddr)> {
+        self.pool
+            .iter_pending_dials()
+            .chain(
+                self.scheduled_dial_retries
+                    .iter()
+                    .flat_map(|(id, (opts, _))| {
+                        opts.addresses_ref()
+                            .iter()
+                            .map(move |address| (*id, address))
+                    }),
+            )
+    }
+
+    /// Cancels a pending dial, aborting all of its connection attempts.
+    ///
+    /// The dial is reported as [`SwarmEvent::OutgoingConnectionError`] and to the
+    /// [`NetworkBehaviour`] as [`FromSwarm::DialFailure`], both with [`DialError::Cancelled`], and
+    /// is not retried, even if configured via [`DialOpts::with_retry`]. This includes dials waiting
+    /// for their next attempt.
+    ///
+    /// Returns `false` if there is no such dial, e.g. because it already completed.
+    pub fn cancel_dial(&mut self, connection_id: ConnectionId) -> bool {
+        if let Some((opts, _)) = self.scheduled_dial_retries.remove(&connection_id) {
+            let peer_id = opts.get_peer_id();
+            let error = DialError::Cancelled;
+            self.behaviour
+                .on_swarm_event(FromSwarm::DialFailure(DialFailure {
+                    peer_id,
+                    error: &error,
+                    connection_id,
+                }));
+            self.pending_swarm_events
+                .push_back(SwarmEvent::OutgoingConnectionError {
+                    peer_id,
+                    connection_id,
+                    error,
+                });
+            return true;
+        }
+
+        if !self.pool.abort_dial(connection_id) {
+            return false;
+        }
+
+        self.cancelled_dials.insert(connection_id);
+        true
+    }
+
+    /// Bans a peer by its peer ID.
+    ///
+    /// All connections to the peer are closed and pending dials to it are aborted. Until the peer
+    /// is unbanned via [`Swarm::unban_peer_id`], dialing it fails with [`DialError::Denied`] and
+    /// connections established with it, inbound or outbound, are dropped right away. The cause of
+    /// these errors downcasts to [`Banned`].
+    pub fn ban_peer_id(&mut self, peer_id: PeerId) {
+        if self.banned_peers.insert(peer_id) {
+            self.pool.disconnect(peer_id);
+        }
+    }
+
+    /// Unbans a peer previously banned via [`Swarm::ban_peer_id`].
+    pub fn unban_peer_id(&mut self, peer_id: PeerId) {
+        self.banned_peers.remove(&peer_id);
+    }
+
+    /// Checks whether a peer is banned via [`Swarm::ban_peer_id`].
+    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        self.banned_peers.contains(peer_id)
+    }
+
     /// Attempt to gracefully close a connection.
     ///
     /// Closing a connection is asynchronous but this function will return immediately.
@@ -659,6 +1116,84 @@ where
         false
     }
 
+    /// Gracefully shuts down the [`Swarm`].
+    ///
+    /// All listeners are removed and all connections are closed, allowing their
+    /// [`ConnectionHandler`]s to finish their work via [`ConnectionHandler::poll_close`] and the
+    /// muxers to signal the close to the remote. Connections that are established or dialed by the
+    /// [`NetworkBehaviour`] in the meantime are closed as well.
+    ///
+    /// The returned future resolves once all connections are closed, or after `timeout`, in which
+    /// case the remaining connections are dropped. Events emitted by the [`Swarm`] during the
+    /// shutdown are discarded.
+    pub async fn shutdown(mut self, timeout: Duration) {
+        let listeners = self.listened_addrs.keys().copied().collect::<Vec<_>>();
+        for listener in listeners {
+            self.remove_listener(listener);
+        }
+
+        self.pool.disconnect_all();
+
+        let drain = async {
+            loop {
+                let counters = self.pool.counters();
+                if counters.num_established() == 0 && counters.num_pending() == 0 {
+                    return;
+                }
+
+                match self.select_next_some().await {
+                    SwarmEvent::Dialing { connection_id, .. } => {
+                        self.pool.abort_dial(connection_id);
+                    }
+                    SwarmEvent::ConnectionEstablished { connection_id, .. } => {
+                        self.close_connection(connection_id);
+                    }
+                    _ => {}
+                }
+            }
+        };
+
+        match future::select(futures_timer::Delay::new(timeout), std::pin::pin!(drain)).await {
+            future::Either::Left(_) => {
+                tracing::debug!("Timed out waiting for connections to close, dropping them")
+            }
+            future::Either::Right(_) => {}
+        }
+    }
+
+    /// Returns the number of bytes received from and sent to the given peer, across all
+    /// connections since the [`Swarm`] was created.
+    ///
+    /// Returns `(0, 0)` unless enabled via [`Config::with_bandwidth_accounting`]. The counters
+    /// of disconnected peers may be forgotten, see [`Bandwidth`].
+    ///
+    /// > **Note**: The counters are updated concurrently by the connection tasks. The returned
+    /// >           values should only ever be used for statistics purposes.
+    pub fn bandwidth_for(&self, peer_id: &PeerId) -> (u64, u64) {
+        self.bandwidth
+            .as_ref()
+            .map_or((0, 0), |bandwidth| bandwidth.peer(peer_id))
+    }
+
+    /// Returns the number of bytes received and sent across all connections since the [`Swarm`]
+    /// was created.
+    ///
+    /// Returns `(0, 0)` unless enabled via [`Config::with_bandwidth_accounting`].
+    pub fn total_bandwidth(&self) -> (u64, u64) {
+        self.bandwidth
+            .as_ref()
+            .map_or((0, 0), |bandwidth| bandwidth.total())
+    }
+
+    /// Returns a handle to the byte counters of all connections, if enabled via
+    /// [`Config::with_bandwidth_accounting`].
+    ///
+    /// Unlike [`Swarm::total_bandwidth`] and [`Swarm::bandwidth_for`], the handle can be moved
+    /// to another task, e.g. to log the throughput periodically.
+    pub fn bandwidth(&self) -> Option<Arc<Bandwidth>> {
+        self.bandwidth.clone()
+    }
+
     /// Checks whether there is an established connection to a peer.
     pub fn is_connected(&self, peer_id: &PeerId) -> bool {
         self.pool.is_connected(*peer_id)
@@ -669,12 +1204,53 @@ where
         self.pool.iter_connected()
     }
 
+    /// Records whether connecting to a peer via the given address succeeded.
+    ///
+    /// When dialing a peer, its addresses with the highest score are tried first. The score of an
+    /// address increases with every success and decreases with every failure, with the most
+    /// recent outcome deciding whether it is tried before or after addresses without any recorded
+    /// outcome, e.g. those just discovered via `identify`. The [`Swarm`] records the outcome of
+    /// its own dials. This function allows feeding in other observations, e.g. marking an address
+    /// with a high round-trip time as failed.
+    pub fn record_address_result(&mut self, peer_id: PeerId, address: &Multiaddr, success: bool) {
+        self.dial_scores.record(peer_id, address, success);
+    }
+
+    /// Returns the score of an address of a peer, see [`Swarm::record_address_result`].
+    ///
+    /// Addresses without any recorded outcome have a neutral score of zero.
+    pub fn address_score(&self, peer_id: &PeerId, address: &Multiaddr) -> i32 {
+        self.dial_scores.get(peer_id, address)
+    }
+
+    /// Returns the number of events emitted by connection handlers that have not been delivered
+    /// to the [`NetworkBehaviour`] yet.
+    ///
+    /// A growing number indicates that the [`NetworkBehaviour`] doesn't keep up with its
+    /// connections. Connections are back-pressured once their event buffer is full, see
+    /// [`Config::with_per_connection_event_buffer_size`].
+    pub fn pending_events_len(&self) -> usize {
+        self.pool.queued_connection_events()
+    }
+
     /// Returns a reference to the provided [`NetworkBehaviour`].
     pub fn behaviour(&self) -> &TBehaviour {
         &self.behaviour
     }
 
     /// Returns a mutable reference to the provided [`NetworkBehaviour`].
+    ///
+    /// The [`Swarm`] is never polled while the reference is alive, thus calls into the
+    /// [`NetworkBehaviour`], e.g. from a [`SwarmEvent::Behaviour`] handler, cannot re-enter it.
+    /// Work queued by such calls is picked up on the next poll, once the [`SwarmEvent`]s queued
+    /// by the [`Swarm`] itself, e.g. a [`SwarmEvent::Dialing`], have been reported. The
+    /// [`NetworkBehaviour`] is then polled before connections and listeners, so its events are
+    /// reported in the order it emits them, ahead of connection and listener events that occur
+    /// after the call.
+    ///
+    /// The one exception is an event for a connection handler that the connection is not ready
+    /// to receive yet: until it is delivered, the [`NetworkBehaviour`] is not polled while
+    /// connections and listeners keep being processed.
     pub fn behaviour_mut(&mut self) -> &mut TBehaviour {
         &mut self.behaviour
     }
@@ -689,17 +1265,35 @@ where
                 concurrent_dial_errors,
                 established_in,
             } => {
+                self.dial_retries.remove(&id);
+                self.cancelled_dials.remove(&id);
+
+                if let ConnectedPoint::Dialer { address, .. } = &endpoint {
+                    self.dial_scores.record(peer_id, address, true);
+                }
+                for (address, _) in concurrent_dial_errors.iter().flatten() {
+                    self.dial_scores.record(peer_id, address, false);
+                }
+
+                let banned = self.banned_peers.contains(&peer_id);
+
                 let handler = match endpoint.clone() {
                     ConnectedPoint::Dialer {
                         address,
                         role_override,
                     } => {
-                        match self.behaviour.handle_established_outbound_connection(
-                            id,
-                            peer_id,
-                            &address,
-                            role_override,
-                        ) {
+                        let handler = if banned {
+                            Err(ConnectionDenied::new(Banned { peer_id }))
+                        } else {
+                            self.behaviour.handle_established_outbound_connection(
+                                id,
+                                peer_id,
+                                &address,
+                                role_override,
+                            )
+                        };
+
+                        match handler {
                             Ok(handler) => handler,
                             Err(cause) => {
                                 let dial_error = DialError::Denied { cause };
@@ -726,12 +1320,18 @@ where
                         local_addr,
                         send_back_addr,
                     } => {
-                        match self.behaviour.handle_established_inbound_connection(
-                            id,
-                            peer_id,
-                            &local_addr,
-                            &send_back_addr,
-                        ) {
+                        let handler = if banned {
+                            Err(ConnectionDenied::new(Banned { peer_id }))
+                        } else {
+                            self.behaviour.handle_established_inbound_connection(
+                                id,
+                                peer_id,
+                                &local_addr,
+                                &send_back_addr,
+                            )
+                        };
+
+                        match handler {
                             Ok(handler) => handler,
                             Err(cause) => {
                                 let listen_error = ListenError::Denied { cause };
@@ -817,7 +1417,19 @@ where
                 error,
                 peer,
             } => {
-                let error = error.into();
+                let cancelled = self.cancelled_dials.remove(&connection_id);
+                let error = match error.into() {
+                    DialError::Aborted if cancelled => DialError::Cancelled,
+                    error => error,
+                };
+
+                if let (Some(peer), DialError::Transport(errors) | DialError::Dns(errors)) =
+                    (peer, &error)
+                {
+                    for (address, _) in errors {
+                        self.dial_scores.record(peer, address, false);
+                    }
+                }
 
                 self.behaviour
                     .on_swarm_event(FromSwarm::DialFailure(DialFailure {
@@ -832,6 +1444,32 @@ where
                     tracing::debug!("Connection attempt to unknown peer failed with {:?}", error);
                 }
 
+                if let Some((next, attempt)) = self.dial_retries.remove(&connection_id) {
+                    let retry = *next.retry().expect("retries to be configured");
+
+                    if attempt < retry.max_attempts
+                        && !matches!(error, DialError::Aborted | DialError::Cancelled)
+                    {
+                        let delay = retry.delay(attempt);
+                        self.scheduled_dial_retries
+                            .insert(connection_id, (next, attempt + 1));
+                        self.dial_retry_timers.push(
+                            futures_timer::Delay::new(delay)
+                                .map(move |()| connection_id)
+                                .boxed(),
+                        );
+                        self.pending_swarm_events
+                            .push_back(SwarmEvent::DialRetryScheduled {
+                                peer_id: peer,
+                                connection_id,
+                                error,
+                                attempt,
+                                delay,
+                            });
+                        return;
+                    }
+                }
+
                 self.pending_swarm_events
                     .push_back(SwarmEvent::OutgoingConnectionError {
                         peer_id: peer,
@@ -922,6 +1560,35 @@ where
                         old: &old_endpoint,
                         new: &new_endpoint,
                     }));
+                self.pending_swarm_events
+                    .push_back(SwarmEvent::AddressChanged {
+                        peer_id,
+                        connection_id: id,
+                        old: old_endpoint,
+                        new: new_endpoint,
+                    });
+            }
+            PoolEvent::StreamOpened {
+                id,
+                peer_id,
+                protocol,
+                direction,
+            } => {
+                self.pending_swarm_events
+                    .push_back(SwarmEvent::StreamOpened {
+                        peer_id,
+                        connection_id: id,
+                        protocol,
+                        direction,
+                    });
+            }
+            PoolEvent::DatagramReceived { id, peer_id, data } => {
+                self.behaviour
+                    .on_swarm_event(FromSwarm::DatagramReceived(DatagramReceived {
+                        peer_id,
+                        connection_id: id,
+                        data: &data,
+                    }));
             }
         }
     }
@@ -942,11 +1609,18 @@ where
             } => {
                 let connection_id = ConnectionId::next();
 
-                match self.behaviour.handle_pending_inbound_connection(
-                    connection_id,
-                    &local_addr,
-                    &send_back_addr,
-                ) {
+                let allowed = match &self.connection_filter {
+                    Some(filter) if !filter.allow_inbound(&send_back_addr) => {
+                        Err(ConnectionDenied::new(InboundConnectionFiltered))
+                    }
+                    _ => self.behaviour.handle_pending_inbound_connection(
+                        connection_id,
+                        &local_addr,
+                        &send_back_addr,
+                    ),
+                };
+
+                match allowed {
                     Ok(()) => {}
                     Err(cause) => {
                         let listen_error = ListenError::Denied { cause };
@@ -1043,6 +1717,7 @@ where
                     "Listener closed"
                 );
                 let addrs = self.listened_addrs.remove(&listener_id).unwrap_or_default();
+                self.listener_requested_addrs.remove(&listener_id);
                 for addr in addrs.iter() {
                     self.behaviour.on_swarm_event(FromSwarm::ExpiredListenAddr(
                         ExpiredListenAddr { listener_id, addr },
@@ -1066,8 +1741,21 @@ where
                         listener_id,
                         err: &error,
                     }));
+                // Listeners are only forgotten once they are closed, after which the transport
+                // doesn't report errors for them anymore.
+                let Some(address) = self.listener_requested_addrs.get(&listener_id).cloned() else {
+                    tracing::debug!(listener=?listener_id, "Error of unknown listener");
+                    return;
+                };
+                // The transport keeps polling a listener after reporting an error, a fatal
+                // error closes it via `TransportEvent::ListenerClosed` instead.
                 self.pending_swarm_events
-                    .push_back(SwarmEvent::ListenerError { listener_id, error })
+                    .push_back(SwarmEvent::ListenerError {
+                        listener_id,
+                        address,
+                        error,
+                        recovery: ListenerRecovery::Retrying,
+                    })
             }
         }
     }
@@ -1092,8 +1780,24 @@ where
                 }
             }
             ToSwarm::ListenOn { opts } => {
-                // Error is dispatched internally, safe to ignore.
-                let _ = self.add_listener(opts);
+                let listener_id = opts.listener_id();
+                let address = opts.address().clone();
+                // There is no caller to return the error to, thus report it as event.
+                if let Err(e) = self.add_listener(opts) {
+                    let error = match e {
+                        TransportError::Other(e) => e,
+                        e @ TransportError::MultiaddrNotSupported(_) => {
+                            io::Error::new(io::ErrorKind::Unsupported, e)
+                        }
+                    };
+                    self.pending_swarm_events
+                        .push_back(SwarmEvent::ListenerError {
+                            listener_id,
+                            address,
+                            error,
+                            recovery: ListenerRecovery::Closed,
+                        });
+                }
             }
             ToSwarm::RemoveListener { id } => {
                 self.remove_listener(id);
@@ -1118,40 +1822,10 @@ where
                 self.pending_handler_event = Some((peer_id, handler, event));
             }
             ToSwarm::NewExternalAddrCandidate(addr) => {
-                // Apply address translation to the candidate address.
-                // For TCP without port-reuse, the observed address contains an ephemeral port which needs to be replaced by the port of a listen address.
-                let translated_addresses = {
-                    let mut addrs: Vec<_> = self
-                        .listened_addrs
-                        .values()
-                        .flatten()
-                        .filter_map(|server| self.transport.address_translation(server, &addr))
-                        .collect();
-
-                    // remove duplicates
-                    addrs.sort_unstable();
-                    addrs.dedup();
-                    addrs
-                };
-
-                // If address translation yielded nothing, broacast the original candidate address.
-                if translated_addresses.is_empty() {
-                    self.behaviour
-                        .on_swarm_event(FromSwarm::NewExternalAddrCandidate(
-                            NewExternalAddrCandidate { addr: &addr },
-                        ));
-                    self.pending_swarm_events
-                        .push_back(SwarmEvent::NewExternalAddrCandidate { address: addr });
-                } else {
-                    for addr in translated_addresses {
-                        self.behaviour
-                            .on_swarm_event(FromSwarm::NewExternalAddrCandidate(
-                                NewExternalAddrCandidate { addr: &addr },
-                            ));
-                        self.pending_swarm_events
-                            .push_back(SwarmEvent::NewExternalAddrCandidate { address: addr });
-                    }
-                }
+                self.handle_external_addr_candidate(addr, None);
+            }
+            ToSwarm::ObservedExternalAddr { address, observer } => {
+                self.handle_external_addr_candidate(address, Some(observer));
             }
             ToSwarm::ExternalAddrConfirmed(addr) => {
                 self.add_external_address(addr.clone());
@@ -1179,6 +1853,75 @@ where
         }
     }
 
+    /// Broadcasts a candidate for an external address reported via
+    /// [`ToSwarm::NewExternalAddrCandidate`] or [`ToSwarm::ObservedExternalAddr`].
+    fn handle_external_addr_candidate(&mut self, addr: Multiaddr, observer: Option<PeerId>) {
+        // Apply address translation to the candidate address.
+        // For TCP without port-reuse, the observed address contains an ephemeral port which needs to be replaced by the port of a listen address.
+        let translated_addresses = {
+            let mut addrs: Vec<_> = self
+                .listened_addrs
+                .values()
+                .flatten()
+                .filter_map(|server| self.transport.address_translation(server, &addr))
+                .collect();
+
+            // remove duplicates
+            addrs.sort_unstable();
+            addrs.dedup();
+            addrs
+        };
+
+        // If address translation yielded nothing, broacast the original candidate address.
+        let addresses = if translated_addresses.is_empty() {
+            vec![addr]
+        } else {
+            translated_addresses
+        };
+
+        for addr in addresses {
+            self.behaviour
+                .on_swarm_event(FromSwarm::NewExternalAddrCandidate(
+                    NewExternalAddrCandidate { addr: &addr },
+                ));
+            self.pending_swarm_events
+                .push_back(SwarmEvent::NewExternalAddrCandidate {
+                    address: addr.clone(),
+                });
+            if let Some(observer) = observer {
+                self.bump_external_address_candidate(&addr, observer);
+            }
+        }
+    }
+
+    /// Polls the `Swarm` for up to `max` events that are ready, without waiting for further
+    /// events.
+    ///
+    /// This allows integrating the `Swarm` into a custom scheduler that bounds the amount of work
+    /// done per tick. Polling stops once `max` events have been collected or the `Swarm` has no
+    /// more events ready. In the latter case, the waker of `cx` is registered and the task is
+    /// woken once the `Swarm` can make progress again. If `max` events were returned, more events
+    /// may be ready and the caller is expected to poll again, e.g. on its next tick.
+    ///
+    /// No event is lost if the caller stops polling in between calls: events that are not yet
+    /// returned remain queued in the `Swarm`.
+    pub fn poll_events_batched(
+        &mut self,
+        cx: &mut Context<'_>,
+        max: usize,
+    ) -> Vec<SwarmEvent<TBehaviour::ToSwarm>> {
+        let mut events = Vec::new();
+
+        while events.len() < max {
+            match Pin::new(&mut *self).poll_next_event(cx) {
+                Poll::Ready(event) => events.push(event),
+                Poll::Pending => break,
+            }
+        }
+
+        events
+    }
+
     /// Internal function used by everything event-related.
     ///
     /// Polls the `Swarm` for the next event.
@@ -1242,6 +1985,14 @@ where
             }
 
             // Poll the known peers.
+            if let Poll::Ready(Some(connection_id)) = this.dial_retry_timers.poll_next_unpin(cx) {
+                // Retries cancelled during their backoff are already removed.
+                if let Some((opts, attempt)) = this.scheduled_dial_retries.remove(&connection_id) {
+                    this.retry_dial(opts, attempt);
+                }
+                continue;
+            }
+
             match this.pool.poll(cx) {
                 Poll::Pending => {}
                 Poll::Ready(pool_event) => {
@@ -1380,6 +2131,9 @@ where
 
 pub struct Config {
     pool_config: PoolConfig,
+    connection_filter: Option<Box<dyn ConnectionFilter>>,
+    external_addr_score_threshold: AddressScore,
+    bandwidth_accounting: bool,
 }
 
 impl Config {
@@ -1388,6 +2142,9 @@ impl Config {
     pub fn with_executor(executor: impl Executor + Send + 'static) -> Self {
         Self {
             pool_config: PoolConfig::new(Some(Box::new(executor))),
+            connection_filter: None,
+            external_addr_score_threshold: AddressScore(3),
+            bandwidth_accounting: false,
         }
     }
 
@@ -1493,13 +2250,87 @@ impl Config {
 
     /// How long to keep a connection alive once it is idling.
     ///
+    /// A connection is idle once it has no active or negotiating streams and its
+    /// [`ConnectionHandler`] no longer asks for it to be kept alive, see
+    /// [`ConnectionHandler::connection_keep_alive`]. The timer starts over whenever the
+    /// connection stops being idle. A handler that keeps asking for the connection to be kept
+    /// alive thus takes precedence over this timeout.
+    ///
+    /// Handlers using an [`IdleGracePeriod`] keep the connection alive for their grace period
+    /// first, i.e. such a connection is only closed after the grace period and this timeout
+    /// elapsed.
+    ///
     /// Defaults to 0.
     pub fn with_idle_connection_timeout(mut self, timeout: Duration) -> Self {
         self.pool_config.idle_connection_timeout = timeout;
         self
     }
+
+    /// Whether to report a [`SwarmEvent::StreamOpened`] for every stream opened on a connection,
+    /// carrying the protocol negotiated on it.
+    ///
+    /// Defaults to `false`.
+    pub fn with_stream_opened_events(mut self, enabled: bool) -> Self {
+        self.pool_config.report_opened_streams = enabled;
+        self
+    }
+
+    /// Whether to count the bytes read from and written to the streams of each connection, see
+    /// [`Swarm::bandwidth_for`], [`Swarm::total_bandwidth`] and [`Swarm::bandwidth`].
+    ///
+    /// Bytes are counted on the multiplexed streams, i.e. they do not include the overhead of
+    /// the security and multiplexing protocols.
+    ///
+    /// Defaults to `false`.
+    pub fn with_bandwidth_accounting(mut self, enabled: bool) -> Self {
+        self.bandwidth_accounting = enabled;
+        self
+    }
+
+    /// Assigns a priority to outbound streams of the given protocol.
+    ///
+    /// Outbound streams requested by a [`ConnectionHandler`] are queued until the muxer is
+    /// ready to open a new stream. Whenever that happens, the waiting request with the highest
+    /// priority is served first, across all protocols offered by the request. Protocols without
+    /// an explicit priority default to 0.
+    ///
+    /// Note: This only affects the order in which outbound streams are opened on a connection.
+    /// Inbound streams are negotiated in the order the remote opens them and bytes on already
+    /// open streams are scheduled by the [`StreamMuxerBox`] itself.
+    pub fn with_stream_priority(mut self, protocol: StreamProtocol, priority: u8) -> Self {
+        self.pool_config
+            .stream_priorities
+            .insert(protocol, priority);
+        self
+    }
+
+    /// Sets a [`ConnectionFilter`] to be consulted for every inbound connection.
+    ///
+    /// The filter runs before any upgrade is applied to the connection, thus rejected connections
+    /// don't incur the cost of a security handshake.
+    pub fn with_connection_filter(mut self, filter: impl ConnectionFilter) -> Self {
+        self.connection_filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Sets the score at which candidates added via [`Swarm::add_external_address_candidate`]
+    /// are confirmed.
+    ///
+    /// Defaults to 3.
+    pub fn with_external_address_score_threshold(mut self, threshold: AddressScore) -> Self {
+        self.external_addr_score_threshold = threshold;
+        self
+    }
 }
 
+/// The score of a candidate for an external address, see
+/// [`Swarm::add_external_address_candidate`].
+///
+/// The score counts the distinct peers that observed the address, see
+/// [`ToSwarm::ObservedExternalAddr`], on top of the initial score of the candidate.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AddressScore(pub u32);
+
 /// Possible errors when trying to establish or upgrade an outbound connection.
 #[derive(Debug)]
 pub enum DialError {
@@ -1514,6 +2345,8 @@ pub enum DialError {
     DialPeerConditionFalse(dial_opts::PeerCondition),
     /// Pending connection attempt has been aborted.
     Aborted,
+    /// Pending connection attempt has been cancelled via [`Swarm::cancel_dial`].
+    Cancelled,
     /// The peer identity obtained on the connection did not match the one that was expected.
     WrongPeerId {
         obtained: PeerId,
@@ -1524,6 +2357,11 @@ pub enum DialError {
     },
     /// An error occurred while negotiating the transport protocol(s) on a connection.
     Transport(Vec<(Multiaddr, TransportError<io::Error>)>),
+    /// Resolving the names of all addresses failed, e.g. the domain of a `/dns4` address.
+    ///
+    /// Reported instead of [`DialError::Transport`] if every error carries a
+    /// [`transport::NameResolutionError`], as reported by `libp2p-dns`.
+    Dns(Vec<(Multiaddr, TransportError<io::Error>)>),
 }
 
 impl From<PendingOutboundConnectionError> for DialError {
@@ -1534,6 +2372,14 @@ impl From<PendingOutboundConnectionError> for DialError {
                 DialError::WrongPeerId { obtained, endpoint }
             }
             PendingConnectionError::LocalPeerId { endpoint } => DialError::LocalPeerId { endpoint },
+            PendingConnectionError::Transport(e)
+                if !e.is_empty()
+                    && e.iter().all(|(_, e)| {
+                        transport::find_error::<transport::NameResolutionError>(e).is_some()
+                    }) =>
+            {
+                DialError::Dns(e)
+            }
             PendingConnectionError::Transport(e) => DialError::Transport(e),
         }
     }
@@ -1555,21 +2401,21 @@ impl fmt::Display for DialError {
                 f,
                 "Dial error: Pending connection attempt has been aborted."
             ),
+            DialError::Cancelled => write!(
+                f,
+                "Dial error: Pending connection attempt has been cancelled."
+            ),
             DialError::WrongPeerId { obtained, endpoint } => write!(
                 f,
                 "Dial error: Unexpected peer ID {obtained} at {endpoint:?}."
             ),
             DialError::Transport(errors) => {
-                write!(f, "Failed to negotiate transport protocol(s): [")?;
-
-                for (addr, error) in errors {
-                    write!(f, "({addr}")?;
-                    print_error_chain(f, error)?;
-                    write!(f, ")")?;
-                }
-                write!(f, "]")?;
-
-                Ok(())
+                write!(f, "Failed to negotiate transport protocol(s): ")?;
+                print_address_errors(f, errors)
+            }
+            DialError::Dns(errors) => {
+                write!(f, "Failed to resolve address(es): ")?;
+                print_address_errors(f, errors)
             }
             DialError::Denied { .. } => {
                 write!(f, "Dial error")
@@ -1578,6 +2424,22 @@ impl fmt::Display for DialError {
     }
 }
 
+fn print_address_errors(
+    f: &mut fmt::Formatter<'_>,
+    errors: &[(Multiaddr, TransportError<io::Error>)],
+) -> fmt::Result {
+    write!(f, "[")?;
+
+    for (addr, error) in errors {
+        write!(f, "({addr}")?;
+        print_error_chain(f, error)?;
+        write!(f, ")")?;
+    }
+    write!(f, "]")?;
+
+    Ok(())
+}
+
 fn print_error_chain(f: &mut fmt::Formatter<'_>, e: &dyn error::Error) -> fmt::Result {
     write!(f, ": {e}")?;
 
@@ -1595,13 +2457,42 @@ impl error::Error for DialError {
             DialError::NoAddresses => None,
             DialError::DialPeerConditionFalse(_) => None,
             DialError::Aborted => None,
+            DialError::Cancelled => None,
             DialError::WrongPeerId { .. } => None,
             DialError::Transport(_) => None,
+            DialError::Dns(_) => None,
             DialError::Denied { cause } => Some(cause),
         }
     }
 }
 
+/// Error of [`Swarm::send_datagram`].
+#[derive(Debug)]
+pub enum SendDatagramError {
+    /// There is no established connection to the peer.
+    NotConnected,
+    /// None of the connections to the peer supports datagrams.
+    Unsupported,
+    /// The datagram is larger than `max_size`, the maximum size of datagrams on the connection.
+    TooLarge { max_size: usize },
+}
+
+impl fmt::Display for SendDatagramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendDatagramError::NotConnected => write!(f, "Not connected to the peer"),
+            SendDatagramError::Unsupported => {
+                write!(f, "No connection to the peer supports datagrams")
+            }
+            SendDatagramError::TooLarge { max_size } => {
+                write!(f, "Datagram exceeds the maximum size of {max_size} bytes")
+            }
+        }
+    }
+}
+
+impl error::Error for SendDatagramError {}
+
 /// Possible errors when upgrading an inbound connection.
 #[derive(Debug)]
 pub enum ListenError {
@@ -1711,6 +2602,20 @@ impl ConnectionDenied {
     }
 }
 
+/// A connection was denied because the remote is banned via [`Swarm::ban_peer_id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Banned {
+    pub peer_id: PeerId,
+}
+
+impl fmt::Display for Banned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "peer {} is banned", self.peer_id)
+    }
+}
+
+impl error::Error for Banned {}
+
 impl fmt::Display for ConnectionDenied {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "connection denied")
@@ -2157,6 +3062,78 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dial_without_allowed_transport_fails_immediately() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+
+        let error = swarm
+            .dial(
+                DialOpts::peer_id(PeerId::random())
+                    .addresses(vec![
+                        "/ip4/127.0.0.1/tcp/80".parse().unwrap(),
+                        "/memory/1234".parse().unwrap(),
+                    ])
+                    .allowed_transports(["quic-v1"])
+                    .build(),
+            )
+            .unwrap_err();
+
+        match error {
+            DialError::Transport(errors) => {
+                assert_eq!(errors.len(), 2);
+                assert!(errors
+                    .iter()
+                    .all(|(_, e)| matches!(e, TransportError::MultiaddrNotSupported(_))));
+            }
+            e => panic!("Unexpected error: {e:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn dial_only_uses_allowed_transports() {
+        let mut swarm1 = new_test_swarm(Config::with_tokio_executor());
+        let mut swarm2 = new_test_swarm(Config::with_tokio_executor());
+
+        swarm1.listen_on("/memory/0".parse().unwrap()).unwrap();
+
+        let address = future::poll_fn(|cx| match swarm1.poll_next_unpin(cx) {
+            Poll::Ready(Some(SwarmEvent::NewListenAddr { address, .. })) => Poll::Ready(address),
+            Poll::Pending => Poll::Pending,
+            _ => panic!("Was expecting the listen address to be reported"),
+        })
+        .await;
+
+        swarm2
+            .dial(
+                DialOpts::peer_id(*swarm1.local_peer_id())
+                    .addresses(vec![
+                        "/ip4/127.0.0.1/tcp/80".parse().unwrap(),
+                        address.clone(),
+                    ])
+                    .allowed_transports(["memory"])
+                    .build(),
+            )
+            .unwrap();
+
+        let endpoint = future::poll_fn(|cx| {
+            let _ = swarm1.poll_next_unpin(cx);
+
+            match swarm2.poll_next_unpin(cx) {
+                Poll::Ready(Some(SwarmEvent::ConnectionEstablished { endpoint, .. })) => {
+                    Poll::Ready(endpoint)
+                }
+                Poll::Ready(x) => panic!("unexpected {x:?}"),
+                Poll::Pending => Poll::Pending,
+            }
+        })
+        .await;
+
+        assert_eq!(
+            endpoint.get_remote_address(),
+            &address.with(multiaddr::Protocol::P2p(*swarm1.local_peer_id()))
+        );
+    }
+
     #[tokio::test]
     async fn dial_self() {
         // Check whether dialing ourselves correctly fails.
@@ -2318,6 +3295,234 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn connection_filter_denies_inbound_connection() {
+        let mut listener = new_test_swarm(
+            Config::with_tokio_executor().with_connection_filter(|_: &Multiaddr| false),
+        );
+        let mut dialer = new_test_swarm(Config::with_tokio_executor());
+
+        listener.listen_on("/memory/0".parse().unwrap()).unwrap();
+
+        let address = future::poll_fn(|cx| match listener.poll_next_unpin(cx) {
+            Poll::Ready(Some(SwarmEvent::NewListenAddr { address, .. })) => Poll::Ready(address),
+            Poll::Pending => Poll::Pending,
+            _ => panic!("Was expecting the listen address to be reported"),
+        })
+        .await;
+
+        dialer.dial(address).unwrap();
+
+        let error = future::poll_fn(|cx| {
+            let _ = dialer.poll_next_unpin(cx);
+
+            match listener.poll_next_unpin(cx) {
+                Poll::Ready(Some(SwarmEvent::IncomingConnectionError { error, .. })) => {
+                    Poll::Ready(error)
+                }
+                Poll::Ready(x) => panic!("unexpected {x:?}"),
+                Poll::Pending => Poll::Pending,
+            }
+        })
+        .await;
+
+        match error {
+            ListenError::Denied { cause } => {
+                assert!(cause.downcast_ref::<InboundConnectionFiltered>().is_some())
+            }
+            x => panic!("wrong error {x:?}"),
+        }
+        assert!(listener
+            .behaviour()
+            .handle_pending_inbound_connection
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn listeners_are_reported_with_their_id() {
+        let mut swarm = new_test_swarm(Config::with_tokio_executor());
+
+        let id1 = swarm.listen_on("/memory/0".parse().unwrap()).unwrap();
+        let id2 = swarm.listen_on("/memory/0".parse().unwrap()).unwrap();
+
+        let mut reported = HashMap::new();
+        while reported.len() < 2 {
+            if let SwarmEvent::NewListenAddr {
+                listener_id,
+                address,
+            } = swarm.select_next_some().await
+            {
+                reported.insert(listener_id, address);
+            }
+        }
+
+        let listeners = swarm
+            .listeners_with_ids()
+            .map(|(id, addr)| (*id, addr.clone()))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(listeners, reported);
+        assert_eq!(
+            swarm.listener_addresses(id1).collect::<Vec<_>>(),
+            vec![&reported[&id1]]
+        );
+
+        assert!(swarm.remove_listener(id2));
+        while !matches!(
+            swarm.select_next_some().await,
+            SwarmEvent::ListenerClosed { listener_id, .. } if listener_id == id2
+        ) {}
+
+        assert_eq!(swarm.listener_addresses(id2).count(), 0);
+        assert_eq!(swarm.listeners_with_ids().count(), 1);
+    }
+
+    #[test]
+    fn swarm_event_variant_names_are_stable() {
+        let address: Multiaddr = "/ip4/127.0.0.1/tcp/80".parse().unwrap();
+        let listener_id = ListenerId::next();
+        let connection_id = ConnectionId::new_unchecked(0);
+        let endpoint = ConnectedPoint::Dialer {
+            address: address.clone(),
+            role_override: Endpoint::Dialer,
+        };
+
+        let events: Vec<(SwarmEvent<()>, &str)> = vec![
+            (SwarmEvent::Behaviour(()), "Behaviour"),
+            (
+                SwarmEvent::ConnectionEstablished {
+                    peer_id: PeerId::random(),
+                    connection_id,
+                    endpoint: endpoint.clone(),
+                    num_established: NonZeroU32::new(1).unwrap(),
+                    concurrent_dial_errors: None,
+                    established_in: Duration::ZERO,
+                },
+                "ConnectionEstablished",
+            ),
+            (
+                SwarmEvent::ConnectionClosed {
+                    peer_id: PeerId::random(),
+                    connection_id,
+                    endpoint: endpoint.clone(),
+                    num_established: 0,
+                    cause: None,
+                },
+                "ConnectionClosed",
+            ),
+            (
+                SwarmEvent::IncomingConnection {
+                    connection_id,
+                    local_addr: address.clone(),
+                    send_back_addr: address.clone(),
+                },
+                "IncomingConnection",
+            ),
+            (
+                SwarmEvent::IncomingConnectionError {
+                    connection_id,
+                    local_addr: address.clone(),
+                    send_back_addr: address.clone(),
+                    error: ListenError::Aborted,
+                },
+                "IncomingConnectionError",
+            ),
+            (
+                SwarmEvent::DialRetryScheduled {
+                    connection_id,
+                    peer_id: None,
+                    error: DialError::Aborted,
+                    attempt: 1,
+                    delay: Duration::from_secs(1),
+                },
+                "DialRetryScheduled",
+            ),
+            (
+                SwarmEvent::OutgoingConnectionError {
+                    connection_id,
+                    peer_id: None,
+                    error: DialError::Aborted,
+                },
+                "OutgoingConnectionError",
+            ),
+            (
+                SwarmEvent::NewListenAddr {
+                    listener_id,
+                    address: address.clone(),
+                },
+                "NewListenAddr",
+            ),
+            (
+                SwarmEvent::ExpiredListenAddr {
+                    listener_id,
+                    address: address.clone(),
+                },
+                "ExpiredListenAddr",
+            ),
+            (
+                SwarmEvent::ListenerClosed {
+                    listener_id,
+                    addresses: vec![],
+                    reason: Ok(()),
+                },
+                "ListenerClosed",
+            ),
+            (
+                SwarmEvent::ListenerError {
+                    listener_id,
+                    address: Multiaddr::empty(),
+                    error: io::Error::new(io::ErrorKind::Other, "error"),
+                    recovery: ListenerRecovery::Retrying,
+                },
+                "ListenerError",
+            ),
+            (
+                SwarmEvent::Dialing {
+                    peer_id: None,
+                    connection_id,
+                },
+                "Dialing",
+            ),
+            (
+                SwarmEvent::NewExternalAddrCandidate {
+                    address: address.clone(),
+                },
+                "NewExternalAddrCandidate",
+            ),
+            (
+                SwarmEvent::ExternalAddrConfirmed {
+                    address: address.clone(),
+                },
+                "ExternalAddrConfirmed",
+            ),
+            (
+                SwarmEvent::ExternalAddrExpired { address },
+                "ExternalAddrExpired",
+            ),
+            (
+                SwarmEvent::StreamOpened {
+                    peer_id: PeerId::random(),
+                    connection_id,
+                    protocol: StreamProtocol::new("/ipfs/ping/1.0.0"),
+                    direction: Endpoint::Dialer,
+                },
+                "StreamOpened",
+            ),
+            (
+                SwarmEvent::AddressChanged {
+                    peer_id: PeerId::random(),
+                    connection_id,
+                    old: endpoint.clone(),
+                    new: endpoint,
+                },
+                "AddressChanged",
+            ),
+        ];
+
+        for (event, name) in events {
+            assert_eq!(event.variant_name(), name);
+        }
+    }
+
     #[test]
     fn dial_error_prints_sources() {
         // This constitutes a fairly typical error for chained transports.
@@ -2334,4 +3539,34 @@ mod tests {
         // Unfortunately, we have some "empty" errors that lead to multiple colons without text but that is the best we can do.
         assert_eq!("Failed to negotiate transport protocol(s): [(/ip4/127.0.0.1/tcp/80: : No listener on the given port.)]", string)
     }
+
+    #[test]
+    fn failed_name_resolution_is_reported_as_dns_error() {
+        let resolution_failure = || {
+            TransportError::Other(io::Error::new(
+                io::ErrorKind::Other,
+                transport::NameResolutionError::new(MemoryTransportError::Unreachable),
+            ))
+        };
+        let other_failure = || {
+            TransportError::Other(io::Error::new(
+                io::ErrorKind::Other,
+                MemoryTransportError::Unreachable,
+            ))
+        };
+        let address: Multiaddr = "/dns4/example.com/tcp/80".parse().unwrap();
+
+        let error = DialError::from(PendingConnectionError::Transport(vec![
+            (address.clone(), resolution_failure()),
+            (address.clone(), resolution_failure()),
+        ]));
+        assert!(matches!(error, DialError::Dns(errors) if errors.len() == 2));
+
+        // Other failures of any of the addresses are reported as they were before.
+        let error = DialError::from(PendingConnectionError::Transport(vec![
+            (address.clone(), resolution_failure()),
+            (address, other_failure()),
+        ]));
+        assert!(matches!(error, DialError::Transport(errors) if errors.len() == 2));
+    }
 }
@@ -0,0 +1,308 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+//! Dispatching of inbound streams to handlers registered at runtime.
+//!
+//! A [`ProtocolRegistry`] maps [`StreamProtocol`]s to [`StreamHandlerFactory`]s. The [`Behaviour`]
+//! of this module accepts inbound streams for the protocols registered at the time the stream is
+//! negotiated and passes each stream to the factory of its protocol. Streams for any other
+//! protocol are rejected during negotiation, so the remote sees them as unsupported.
+//!
+//! Since the registry is shared, protocols can be registered and deregistered while the
+//! [`Swarm`](crate::Swarm) is running, e.g. when loading and unloading plugins.
+//!
+//! ```
+//! # use libp2p_swarm::{protocol_registry, ProtocolRegistry, StreamProtocol};
+//! # use futures::AsyncWriteExt;
+//! let registry = ProtocolRegistry::new();
+//! registry.register(
+//!     StreamProtocol::new("/hello/1.0.0"),
+//!     Box::new(|_peer, mut stream: libp2p_swarm::Stream| async move {
+//!         let _ = stream.write_all(b"hello").await;
+//!     }),
+//! );
+//!
+//! let behaviour = protocol_registry::Behaviour::new(registry.clone());
+//! ```
+
+use crate::behaviour::{FromSwarm, NetworkBehaviour, ToSwarm};
+use crate::connection::ConnectionId;
+use crate::handler::{
+    ConnectionEvent, DialUpgradeError, FullyNegotiatedInbound, FullyNegotiatedOutbound,
+};
+use crate::{
+    ConnectionDenied, ConnectionHandlerEvent, Stream, StreamProtocol, SubstreamProtocol, THandler,
+    THandlerInEvent, THandlerOutEvent,
+};
+use futures::future::{self, BoxFuture, FutureExt};
+use futures::stream::{FuturesUnordered, StreamExt};
+use libp2p_core::upgrade::{DeniedUpgrade, InboundUpgrade, UpgradeInfo};
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use void::Void;
+
+/// Handles inbound streams of a protocol registered with a [`ProtocolRegistry`].
+///
+/// The trait is implemented for all closures of type `Fn(PeerId, Stream) -> impl Future`.
+pub trait StreamHandlerFactory: Send + Sync + 'static {
+    /// Creates the future handling an inbound `stream` from `peer`.
+    ///
+    /// The future is polled by the connection the stream belongs to.
+    fn new_handler(&self, peer: PeerId, stream: Stream) -> BoxFuture<'static, ()>;
+}
+
+impl<F, Fut> StreamHandlerFactory for F
+where
+    F: Fn(PeerId, Stream) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    fn new_handler(&self, peer: PeerId, stream: Stream) -> BoxFuture<'static, ()> {
+        self(peer, stream).boxed()
+    }
+}
+
+/// A runtime registry of the [`StreamHandlerFactory`] for each supported [`StreamProtocol`].
+///
+/// Clones share the same registry.
+#[derive(Clone, Default)]
+pub struct ProtocolRegistry {
+    factories: Arc<RwLock<HashMap<StreamProtocol, Arc<dyn StreamHandlerFactory>>>>,
+}
+
+impl ProtocolRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the factory handling inbound streams of the given protocol.
+    ///
+    /// Returns `false` if a factory was already registered for the protocol, in which case it is
+    /// replaced.
+    pub fn register(
+        &self,
+        protocol: StreamProtocol,
+        factory: Box<dyn StreamHandlerFactory>,
+    ) -> bool {
+        self.factories
+            .write()
+            .expect("poisoned lock")
+            .insert(protocol, Arc::from(factory))
+            .is_none()
+    }
+
+    /// Removes the factory of the given protocol.
+    ///
+    /// Streams that are already being handled are not affected. Returns `false` if the protocol
+    /// was not registered.
+    pub fn deregister(&self, protocol: &StreamProtocol) -> bool {
+        self.factories
+            .write()
+            .expect("poisoned lock")
+            .remove(protocol)
+            .is_some()
+    }
+
+    /// Returns the factory registered for the given protocol.
+    pub fn lookup(&self, protocol: &StreamProtocol) -> Option<Arc<dyn StreamHandlerFactory>> {
+        self.factories
+            .read()
+            .expect("poisoned lock")
+            .get(protocol)
+            .cloned()
+    }
+
+    /// Returns the currently registered protocols.
+    pub fn protocols(&self) -> Vec<StreamProtocol> {
+        self.factories
+            .read()
+            .expect("poisoned lock")
+            .keys()
+            .cloned()
+            .collect()
+    }
+}
+
+/// [`NetworkBehaviour`] dispatching inbound streams to the factories of a [`ProtocolRegistry`].
+pub struct Behaviour {
+    registry: ProtocolRegistry,
+}
+
+impl Behaviour {
+    /// Creates a new [`Behaviour`] dispatching to the given registry.
+    pub fn new(registry: ProtocolRegistry) -> Self {
+        Self { registry }
+    }
+
+    /// Returns the registry of this behaviour.
+    pub fn registry(&self) -> &ProtocolRegistry {
+        &self.registry
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = Void;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        peer: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(peer, self.registry.clone()))
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        peer: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(peer, self.registry.clone()))
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+
+    fn on_swarm_event(&mut self, _event: FromSwarm) {}
+}
+
+/// The [`ConnectionHandler`](crate::ConnectionHandler) of [`Behaviour`].
+pub struct Handler {
+    peer: PeerId,
+    registry: ProtocolRegistry,
+    streams: FuturesUnordered<BoxFuture<'static, ()>>,
+}
+
+impl Handler {
+    fn new(peer: PeerId, registry: ProtocolRegistry) -> Self {
+        Self {
+            peer,
+            registry,
+            streams: FuturesUnordered::new(),
+        }
+    }
+}
+
+impl crate::handler::ConnectionHandler for Handler {
+    type FromBehaviour = Void;
+    type ToBehaviour = Void;
+    type InboundProtocol = Upgrade;
+    type OutboundProtocol = DeniedUpgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = Void;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(
+            Upgrade {
+                protocols: self.registry.protocols(),
+            },
+            (),
+        )
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        void::unreachable(event)
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        while let Poll::Ready(Some(())) = self.streams.poll_next_unpin(cx) {}
+
+        Poll::Pending
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+                protocol: (stream, protocol),
+                ..
+            }) => match self.registry.lookup(&protocol) {
+                Some(factory) => self.streams.push(factory.new_handler(self.peer, stream)),
+                None => {
+                    tracing::debug!(%protocol, "Dropping inbound stream of deregistered protocol")
+                }
+            },
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol, ..
+            }) => void::unreachable(protocol),
+            ConnectionEvent::DialUpgradeError(DialUpgradeError { info, .. }) => {
+                void::unreachable(info)
+            }
+            ConnectionEvent::ListenUpgradeError(error) => void::unreachable(error.error),
+            ConnectionEvent::AddressChange(_)
+            | ConnectionEvent::LocalProtocolsChange(_)
+            | ConnectionEvent::RemoteProtocolsChange(_) => {}
+        }
+    }
+}
+
+/// Inbound upgrade accepting streams for the protocols registered when the stream is negotiated.
+pub struct Upgrade {
+    protocols: Vec<StreamProtocol>,
+}
+
+impl UpgradeInfo for Upgrade {
+    type Info = StreamProtocol;
+    type InfoIter = std::vec::IntoIter<StreamProtocol>;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        self.protocols.clone().into_iter()
+    }
+}
+
+impl InboundUpgrade<Stream> for Upgrade {
+    type Output = (Stream, StreamProtocol);
+    type Error = Void;
+    type Future = future::Ready<Result<Self::Output, Self::Error>>;
+
+    fn upgrade_inbound(self, stream: Stream, protocol: Self::Info) -> Self::Future {
+        future::ready(Ok((stream, protocol)))
+    }
+}
@@ -1,11 +1,14 @@
-use futures::{AsyncRead, AsyncWrite};
+use futures::future::{self, Either};
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use futures_timer::Delay;
 use libp2p_core::muxing::SubstreamBox;
 use libp2p_core::Negotiated;
 use std::{
-    io::{IoSlice, IoSliceMut},
+    io::{self, IoSlice, IoSliceMut},
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
 /// Counter for the number of active streams on a connection.
@@ -51,6 +54,41 @@ impl Stream {
     pub fn ignore_for_keep_alive(&mut self) {
         self.counter.take();
     }
+
+    /// Reads some bytes from the stream into `buf`, like [`AsyncReadExt::read`], failing with
+    /// [`io::ErrorKind::TimedOut`] if no bytes arrive within `timeout`.
+    ///
+    /// The deadline is tracked by a timer that does not require spawning a task on the
+    /// [Swarm](crate::Swarm)'s executor.
+    ///
+    /// This method is cancellation safe: if the returned future is dropped or times out, no
+    /// bytes have been read from the stream and the read can be retried.
+    pub async fn read_with_timeout(
+        &mut self,
+        buf: &mut [u8],
+        timeout: Duration,
+    ) -> io::Result<usize> {
+        match future::select(self.read(buf), Delay::new(timeout)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "read timed out")),
+        }
+    }
+
+    /// Writes some bytes from `buf` to the stream, like [`AsyncWriteExt::write`], failing with
+    /// [`io::ErrorKind::TimedOut`] if the stream does not accept any bytes within `timeout`.
+    ///
+    /// The deadline is tracked by a timer that does not require spawning a task on the
+    /// [Swarm](crate::Swarm)'s executor.
+    ///
+    /// This method is cancellation safe: if the returned future is dropped or times out, no
+    /// bytes have been written to the stream and the write can be retried. Use the returned
+    /// number of bytes to write the remainder of `buf`.
+    pub async fn write_with_timeout(&mut self, buf: &[u8], timeout: Duration) -> io::Result<usize> {
+        match future::select(self.write(buf), Delay::new(timeout)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "write timed out")),
+        }
+    }
 }
 
 impl AsyncRead for Stream {
@@ -1,6 +1,7 @@
 use either::Either;
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 use std::sync::Arc;
 
 /// Identifies a protocol for a stream.
@@ -18,10 +19,10 @@ impl StreamProtocol {
     /// # Panics
     ///
     /// This function panics if the protocol does not start with a forward slash: `/`.
+    /// Use [`StreamProtocol::try_new`] to handle this case gracefully.
     pub const fn new(s: &'static str) -> Self {
-        match s.as_bytes() {
-            [b'/', ..] => {}
-            _ => panic!("Protocols should start with a /"),
+        if !starts_with_forward_slash(s) {
+            panic!("Protocols should start with a /")
         }
 
         StreamProtocol {
@@ -29,6 +30,20 @@ impl StreamProtocol {
         }
     }
 
+    /// Attempt to construct a protocol from a static string slice.
+    ///
+    /// This function will fail if the protocol does not start with a forward slash: `/`.
+    /// Like [`StreamProtocol::new`], it does not allocate and can be used in const contexts.
+    pub const fn try_new(s: &'static str) -> Result<Self, InvalidProtocol> {
+        if !starts_with_forward_slash(s) {
+            return Err(InvalidProtocol::missing_forward_slash());
+        }
+
+        Ok(StreamProtocol {
+            inner: Either::Left(s),
+        })
+    }
+
     /// Attempt to construct a protocol from an owned string.
     ///
     /// This function will fail if the protocol does not start with a forward slash: `/`.
@@ -42,6 +57,48 @@ impl StreamProtocol {
             inner: Either::Right(Arc::from(protocol)), // FIXME: Can we somehow reuse the allocation from the owned string?
         })
     }
+
+    /// Attempt to construct a protocol from a shared string.
+    ///
+    /// This function will fail if the protocol does not start with a forward slash: `/`.
+    /// The [`Arc`] is stored as is, i.e. cloning the resulting [`StreamProtocol`] only bumps the reference count.
+    pub fn from_arc(protocol: Arc<str>) -> Result<Self, InvalidProtocol> {
+        if !protocol.starts_with('/') {
+            return Err(InvalidProtocol::missing_forward_slash());
+        }
+
+        Ok(StreamProtocol {
+            inner: Either::Right(protocol),
+        })
+    }
+
+    /// Returns whether this protocol belongs to the protocol family identified by `prefix`.
+    ///
+    /// The comparison is done on whole path segments, i.e. `/myapp/sync` matches `/myapp/sync/1.0.0`
+    /// but not `/myapp/syncer/1.0.0`. A `prefix` that does not start with a forward slash never matches.
+    pub fn matches_prefix(&self, prefix: &str) -> bool {
+        if !prefix.starts_with('/') {
+            return false;
+        }
+
+        let prefix = prefix.trim_end_matches('/');
+
+        match self.as_ref().strip_prefix(prefix) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        }
+    }
+
+    /// Returns an iterator over the `/`-separated segments of this protocol.
+    ///
+    /// For example, `/myapp/sync/1.0.0` yields `myapp`, `sync` and `1.0.0`.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.as_ref().split('/').skip(1)
+    }
+}
+
+const fn starts_with_forward_slash(s: &str) -> bool {
+    matches!(s.as_bytes(), [b'/', ..])
 }
 
 impl AsRef<str> for StreamProtocol {
@@ -62,6 +119,29 @@ impl fmt::Display for StreamProtocol {
     }
 }
 
+impl FromStr for StreamProtocol {
+    type Err = InvalidProtocol;
+
+    /// Parses a protocol from a borrowed string, copying it into a new allocation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+impl TryFrom<&str> for StreamProtocol {
+    type Error = InvalidProtocol;
+
+    fn try_from(protocol: &str) -> Result<Self, Self::Error> {
+        if !protocol.starts_with('/') {
+            return Err(InvalidProtocol::missing_forward_slash());
+        }
+
+        Ok(StreamProtocol {
+            inner: Either::Right(Arc::from(protocol)),
+        })
+    }
+}
+
 impl PartialEq<&str> for StreamProtocol {
     fn eq(&self, other: &&str) -> bool {
         self.as_ref() == *other
@@ -93,7 +173,7 @@ pub struct InvalidProtocol {
 }
 
 impl InvalidProtocol {
-    pub(crate) fn missing_forward_slash() -> Self {
+    pub(crate) const fn missing_forward_slash() -> Self {
         InvalidProtocol { _private: () }
     }
 }
@@ -129,4 +209,70 @@ mod tests {
             "protocol to display print as string without quotes"
         );
     }
+
+    #[test]
+    fn matches_prefix_compares_whole_segments() {
+        let protocol = StreamProtocol::new("/myapp/sync/1.0.0");
+
+        assert!(protocol.matches_prefix("/myapp/sync"));
+        assert!(protocol.matches_prefix("/myapp/sync/"));
+        assert!(protocol.matches_prefix("/myapp/sync/1.0.0"));
+        assert!(protocol.matches_prefix("/"));
+        assert!(!protocol.matches_prefix("/myapp/syn"));
+        assert!(!protocol.matches_prefix("/myapp/sync/1.0"));
+        assert!(!protocol.matches_prefix("myapp/sync"));
+        assert!(!protocol.matches_prefix(""));
+        assert!(!StreamProtocol::new("/myapp/syncer/1.0.0").matches_prefix("/myapp/sync"));
+    }
+
+    #[test]
+    fn segments_skip_leading_slash() {
+        let protocol = StreamProtocol::new("/myapp/sync/1.0.0");
+
+        assert_eq!(
+            protocol.segments().collect::<Vec<_>>(),
+            vec!["myapp", "sync", "1.0.0"]
+        );
+    }
+
+    #[test]
+    fn from_arc_reuses_allocation() {
+        let shared = Arc::<str>::from("/foo/bar/1.0.0");
+
+        let protocol = StreamProtocol::from_arc(shared.clone()).unwrap();
+
+        assert_eq!(protocol, "/foo/bar/1.0.0");
+        assert_eq!(Arc::strong_count(&shared), 2);
+        drop(protocol);
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+
+    #[test]
+    fn from_arc_requires_forward_slash() {
+        assert!(StreamProtocol::from_arc(Arc::from("foo/bar")).is_err());
+    }
+
+    #[test]
+    fn try_new_is_usable_in_const_context() {
+        const VALID: Result<StreamProtocol, InvalidProtocol> =
+            StreamProtocol::try_new("/foo/1.0.0");
+        const INVALID: Result<StreamProtocol, InvalidProtocol> =
+            StreamProtocol::try_new("foo/1.0.0");
+
+        assert_eq!(VALID.unwrap(), "/foo/1.0.0");
+        assert!(INVALID.is_err());
+    }
+
+    #[test]
+    fn parse_from_borrowed_str() {
+        let input = String::from("/foo/bar/1.0.0");
+
+        let parsed = input.parse::<StreamProtocol>().unwrap();
+        let converted = StreamProtocol::try_from(input.as_str()).unwrap();
+
+        assert_eq!(parsed, "/foo/bar/1.0.0");
+        assert_eq!(converted, parsed);
+        assert!("foo/bar".parse::<StreamProtocol>().is_err());
+        assert!(StreamProtocol::try_from("").is_err());
+    }
 }
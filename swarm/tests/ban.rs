@@ -0,0 +1,76 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_swarm::{dial_opts::DialOpts, dummy, Banned, DialError, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+
+#[async_std::test]
+async fn banning_peer_closes_active_connection() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+
+    swarm2.listen().with_memory_addr_external().await;
+    swarm1.connect(&mut swarm2).await;
+
+    let peer2 = *swarm2.local_peer_id();
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    swarm1.ban_peer_id(peer2);
+
+    let closed_peer = swarm1
+        .wait(|event| match event {
+            SwarmEvent::ConnectionClosed { peer_id, .. } => Some(peer_id),
+            _ => None,
+        })
+        .await;
+
+    assert_eq!(closed_peer, peer2);
+    assert!(!swarm1.is_connected(&peer2));
+}
+
+#[async_std::test]
+async fn dialing_banned_peer_fails_until_unbanned() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+
+    swarm2.listen().with_memory_addr_external().await;
+    let peer2 = *swarm2.local_peer_id();
+    let addr2 = swarm2.external_addresses().next().cloned().unwrap();
+
+    swarm1.ban_peer_id(peer2);
+
+    match swarm1
+        .dial(DialOpts::peer_id(peer2).addresses(vec![addr2]).build())
+        .unwrap_err()
+    {
+        DialError::Denied { cause } => {
+            assert_eq!(
+                cause.downcast::<Banned>().unwrap(),
+                Banned { peer_id: peer2 }
+            )
+        }
+        e => panic!("unexpected error {e:?}"),
+    }
+
+    swarm1.unban_peer_id(peer2);
+
+    swarm1.connect(&mut swarm2).await;
+    assert!(swarm1.is_connected(&peer2));
+}
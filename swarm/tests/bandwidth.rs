@@ -0,0 +1,76 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::StreamExt;
+use libp2p_swarm::{Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use std::time::Duration;
+
+#[async_std::test]
+async fn counts_bytes_per_peer() {
+    let mut swarm1 = new_swarm(true);
+    let mut swarm2 = new_swarm(false);
+    let peer1 = *swarm1.local_peer_id();
+    let peer2 = *swarm2.local_peer_id();
+
+    swarm2.listen().with_memory_addr_external().await;
+    swarm1.connect(&mut swarm2).await;
+
+    let mut pongs = 0;
+    while pongs < 2 {
+        futures::select! {
+            event = swarm1.select_next_some() => {
+                if let SwarmEvent::Behaviour(libp2p_ping::Event { result: Ok(_), .. }) = event {
+                    pongs += 1;
+                }
+            }
+            _ = swarm2.select_next_some() => {}
+        }
+    }
+
+    let (inbound, outbound) = swarm1.bandwidth_for(&peer2);
+    assert!(inbound > 0);
+    assert!(outbound > 0);
+    let (total_inbound, total_outbound) = swarm1.total_bandwidth();
+    assert!(total_inbound >= inbound);
+    assert!(total_outbound >= outbound);
+    assert_eq!(swarm1.bandwidth_for(&peer1), (0, 0));
+
+    let handle = swarm1
+        .bandwidth()
+        .expect("bandwidth accounting to be enabled");
+    assert_eq!(handle.peer(&peer2), swarm1.bandwidth_for(&peer2));
+    assert_eq!(handle.total(), swarm1.total_bandwidth());
+    assert!(swarm2.bandwidth().is_none());
+
+    assert_eq!(swarm2.bandwidth_for(&peer1), (0, 0));
+    assert_eq!(swarm2.total_bandwidth(), (0, 0));
+}
+
+fn new_swarm(bandwidth_accounting: bool) -> Swarm<libp2p_ping::Behaviour> {
+    Swarm::new_ephemeral_with_config(
+        |_| {
+            libp2p_ping::Behaviour::new(
+                libp2p_ping::Config::new().with_interval(Duration::from_millis(10)),
+            )
+        },
+        |config| config.with_bandwidth_accounting(bandwidth_accounting),
+    )
+}
@@ -0,0 +1,45 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_swarm::{dummy, Swarm};
+use libp2p_swarm_test::SwarmExt;
+
+#[async_std::test]
+async fn counts_established_connections_per_transport() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+
+    swarm2.listen().with_memory_addr_external().await;
+    swarm1.connect(&mut swarm2).await;
+
+    for (swarm, outgoing, incoming) in [(&swarm1, 1, 0), (&swarm2, 0, 1)] {
+        let info = swarm.network_info();
+        let counters = info.connection_counters();
+
+        assert_eq!(counters.num_established_outgoing(), outgoing);
+        assert_eq!(counters.num_established_incoming(), incoming);
+        assert_eq!(counters.num_established_direct(), 1);
+        assert_eq!(counters.num_established_relayed(), 0);
+        assert_eq!(
+            counters.established_per_transport().collect::<Vec<_>>(),
+            vec![("/memory", 1)]
+        );
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use bytes::Bytes;
+use futures::channel::mpsc;
+use futures::StreamExt;
+use libp2p_core::multiaddr::Protocol;
+use libp2p_core::muxing::{self, StreamMuxer, StreamMuxerBox, StreamMuxerEvent};
+use libp2p_core::transport::MemoryTransport;
+use libp2p_core::upgrade::Version;
+use libp2p_core::{Endpoint, Multiaddr, Transport};
+use libp2p_identity::{Keypair, PeerId};
+use libp2p_swarm::{
+    dummy, Config, ConnectionDenied, ConnectionId, DatagramReceived, FromSwarm, NetworkBehaviour,
+    SendDatagramError, Swarm, SwarmEvent, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+const MAX_DATAGRAM_SIZE: usize = 16;
+
+#[async_std::test]
+async fn datagrams_are_delivered_to_the_behaviour_of_the_remote() {
+    let (a_to_b, b_from_a) = mpsc::unbounded();
+    let (b_to_a, a_from_b) = mpsc::unbounded();
+    let mut swarm1 = new_swarm(a_to_b, a_from_b);
+    let mut swarm2 = new_swarm(b_to_a, b_from_a);
+    let peer1 = *swarm1.local_peer_id();
+    let peer2 = *swarm2.local_peer_id();
+
+    assert!(matches!(
+        swarm1.send_datagram(peer2, Bytes::from_static(b"hello")),
+        Err(SendDatagramError::NotConnected)
+    ));
+
+    connect(&mut swarm1, &mut swarm2).await;
+
+    assert!(matches!(
+        swarm1.send_datagram(peer2, vec![0; MAX_DATAGRAM_SIZE + 1].into()),
+        Err(SendDatagramError::TooLarge {
+            max_size: MAX_DATAGRAM_SIZE
+        })
+    ));
+    swarm1
+        .send_datagram(peer2, Bytes::from_static(b"hello"))
+        .unwrap();
+
+    let (peer_id, data) = swarm2
+        .wait(|event| match event {
+            SwarmEvent::Behaviour(received) => Some(received),
+            _ => None,
+        })
+        .await;
+    assert_eq!(peer_id, peer1);
+    assert_eq!(data, Bytes::from_static(b"hello"));
+}
+
+#[async_std::test]
+async fn sending_datagram_fails_if_muxer_does_not_support_them() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| Behaviour::default());
+    let mut swarm2 = Swarm::new_ephemeral(|_| Behaviour::default());
+    swarm2.listen().with_memory_addr_external().await;
+    swarm1.connect(&mut swarm2).await;
+
+    assert!(matches!(
+        swarm1.send_datagram(*swarm2.local_peer_id(), Bytes::from_static(b"hello")),
+        Err(SendDatagramError::Unsupported)
+    ));
+}
+
+/// Creates a [`Swarm`] whose single connection exchanges datagrams over the given channels.
+fn new_swarm(
+    tx: mpsc::UnboundedSender<Bytes>,
+    rx: mpsc::UnboundedReceiver<Bytes>,
+) -> Swarm<Behaviour> {
+    let identity = Keypair::generate_ed25519();
+    let peer_id = PeerId::from(identity.public());
+    let channels = Arc::new(Mutex::new(Some((tx, rx))));
+
+    let transport = MemoryTransport::default()
+        .upgrade(Version::V1)
+        .authenticate(libp2p_plaintext::Config::new(&identity))
+        .multiplex(libp2p_yamux::Config::default())
+        .map(move |(peer_id, inner), _| {
+            let (tx, rx) = channels
+                .lock()
+                .unwrap()
+                .take()
+                .expect("a single connection per swarm");
+            (
+                peer_id,
+                StreamMuxerBox::new(DatagramMuxer {
+                    inner: StreamMuxerBox::new(inner),
+                    tx,
+                    rx,
+                }),
+            )
+        })
+        .boxed();
+
+    Swarm::new(
+        transport,
+        Behaviour::default(),
+        peer_id,
+        Config::with_async_std_executor().with_idle_connection_timeout(Duration::from_secs(5)),
+    )
+}
+
+async fn connect(swarm1: &mut Swarm<Behaviour>, swarm2: &mut Swarm<Behaviour>) {
+    let address = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+    swarm2.listen_on(address.clone()).unwrap();
+    swarm2
+        .wait(|event| matches!(event, SwarmEvent::NewListenAddr { .. }).then_some(()))
+        .await;
+
+    swarm1.dial(address).unwrap();
+    futures::join!(
+        swarm1
+            .wait(|event| matches!(event, SwarmEvent::ConnectionEstablished { .. }).then_some(())),
+        swarm2
+            .wait(|event| matches!(event, SwarmEvent::ConnectionEstablished { .. }).then_some(())),
+    );
+}
+
+/// Adds unreliable datagrams, sent and received over channels, to an existing muxer.
+struct DatagramMuxer {
+    inner: StreamMuxerBox,
+    tx: mpsc::UnboundedSender<Bytes>,
+    rx: mpsc::UnboundedReceiver<Bytes>,
+}
+
+impl StreamMuxer for DatagramMuxer {
+    type Substream = muxing::SubstreamBox;
+    type Error = std::io::Error;
+
+    fn poll_inbound(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_inbound(cx)
+    }
+
+    fn poll_outbound(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_outbound(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<StreamMuxerEvent, Self::Error>> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(Some(data)) = this.rx.poll_next_unpin(cx) {
+            return Poll::Ready(Ok(StreamMuxerEvent::DatagramReceived(data)));
+        }
+
+        Pin::new(&mut this.inner).poll(cx)
+    }
+
+    fn send_datagram(self: Pin<&mut Self>, data: Bytes) -> Result<(), muxing::SendDatagramError> {
+        if data.len() > MAX_DATAGRAM_SIZE {
+            return Err(muxing::SendDatagramError::TooLarge);
+        }
+
+        self.tx
+            .unbounded_send(data)
+            .map_err(|_| muxing::SendDatagramError::Io(std::io::ErrorKind::BrokenPipe.into()))
+    }
+
+    fn max_datagram_size(&self) -> Option<usize> {
+        Some(MAX_DATAGRAM_SIZE)
+    }
+}
+
+#[derive(Default)]
+struct Behaviour {
+    received: VecDeque<(PeerId, Bytes)>,
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = (PeerId, Bytes);
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        _: THandlerOutEvent<Self>,
+    ) {
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::DatagramReceived(DatagramReceived { peer_id, data, .. }) = event {
+            self.received.push_back((peer_id, data.clone()));
+        }
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(received) = self.received.pop_front() {
+            return Poll::Ready(ToSwarm::GenerateEvent(received));
+        }
+
+        Poll::Pending
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_core::multiaddr::Protocol;
+use libp2p_core::Multiaddr;
+use libp2p_swarm::dial_opts::PeerCondition;
+use libp2p_swarm::dial_opts::{DialOpts, RetryConfig};
+use libp2p_swarm::{dummy, DialError, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use std::time::Duration;
+
+const RETRY: RetryConfig = RetryConfig {
+    max_attempts: 3,
+    base: Duration::from_millis(10),
+    max: Duration::from_millis(50),
+    jitter: Duration::ZERO,
+};
+
+#[async_std::test]
+async fn reports_final_failure_after_all_attempts() {
+    let mut swarm = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let unreachable = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+
+    swarm
+        .dial(
+            DialOpts::unknown_peer_id()
+                .address(unreachable)
+                .build()
+                .with_retry(RETRY),
+        )
+        .unwrap();
+
+    let mut events = Vec::new();
+    loop {
+        match swarm.next_swarm_event().await {
+            SwarmEvent::DialRetryScheduled { attempt, delay, .. } => {
+                events.push(format!("retry {attempt} after {}ms", delay.as_millis()))
+            }
+            SwarmEvent::Dialing { .. } => events.push("dialing".to_owned()),
+            SwarmEvent::OutgoingConnectionError { .. } => break,
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+
+    assert_eq!(
+        events,
+        [
+            "retry 1 after 10ms",
+            "dialing",
+            "retry 2 after 20ms",
+            "dialing"
+        ]
+    );
+}
+
+#[async_std::test]
+async fn stops_retrying_once_connected() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let address = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+    let peer_id = *swarm2.local_peer_id();
+
+    swarm1
+        .dial(
+            DialOpts::peer_id(peer_id)
+                .addresses(vec![address.clone()])
+                .build()
+                .with_retry(RetryConfig {
+                    max_attempts: 5,
+                    ..RETRY
+                }),
+        )
+        .unwrap();
+
+    // The first attempt fails because nobody listens on the address yet.
+    assert!(matches!(
+        swarm1.next_swarm_event().await,
+        SwarmEvent::DialRetryScheduled { attempt: 1, .. }
+    ));
+
+    swarm2.listen_on(address).unwrap();
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    loop {
+        match swarm1.next_swarm_event().await {
+            SwarmEvent::Dialing { .. } | SwarmEvent::DialRetryScheduled { .. } => {}
+            SwarmEvent::ConnectionEstablished { peer_id: peer, .. } => {
+                assert_eq!(peer, peer_id);
+                break;
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+
+    let no_further_dials = async {
+        loop {
+            if let event @ (SwarmEvent::Dialing { .. }
+            | SwarmEvent::DialRetryScheduled { .. }
+            | SwarmEvent::OutgoingConnectionError { .. }) = swarm1.next_swarm_event().await
+            {
+                panic!("Unexpected dial event after connecting: {event:?}")
+            }
+        }
+    };
+    let _ = async_std::future::timeout(Duration::from_millis(200), no_further_dials).await;
+}
+
+#[async_std::test]
+async fn retry_is_reported_as_failed_once_connected_otherwise() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let reachable = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+    let unreachable = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+    let peer_id = *swarm2.local_peer_id();
+
+    swarm2.listen_on(reachable.clone()).unwrap();
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    let retried = DialOpts::peer_id(peer_id)
+        .addresses(vec![unreachable])
+        .build()
+        .with_retry(RetryConfig {
+            base: Duration::from_millis(100),
+            ..RETRY
+        });
+    let retried_id = retried.connection_id();
+    swarm1.dial(retried).unwrap();
+
+    assert!(matches!(
+        swarm1.next_swarm_event().await,
+        SwarmEvent::DialRetryScheduled { attempt: 1, .. }
+    ));
+
+    swarm1
+        .dial(
+            DialOpts::peer_id(peer_id)
+                .addresses(vec![reachable])
+                .condition(PeerCondition::Always)
+                .build(),
+        )
+        .unwrap();
+
+    loop {
+        match swarm1.next_swarm_event().await {
+            SwarmEvent::ConnectionEstablished { connection_id, .. } => {
+                assert_ne!(connection_id, retried_id)
+            }
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                error: DialError::DialPeerConditionFalse(PeerCondition::Disconnected),
+                ..
+            } => {
+                assert_eq!(connection_id, retried_id);
+                break;
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+    assert_eq!(swarm1.pending_dials().count(), 0);
+}
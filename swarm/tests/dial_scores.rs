@@ -0,0 +1,94 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_core::multiaddr::Protocol;
+use libp2p_core::{ConnectedPoint, Multiaddr};
+use libp2p_swarm::dial_opts::DialOpts;
+use libp2p_swarm::{dummy, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use std::num::NonZeroU8;
+
+#[async_std::test]
+async fn dials_previously_successful_address_first() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let peer_id = *swarm2.local_peer_id();
+    let reachable = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+    let unreachable = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+
+    swarm2.listen_on(reachable.clone()).unwrap();
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    let dial = || {
+        DialOpts::peer_id(peer_id)
+            .addresses(vec![unreachable.clone(), reachable.clone()])
+            .override_dial_concurrency_factor(NonZeroU8::new(1).unwrap())
+            .build()
+    };
+
+    swarm1.dial(dial()).unwrap();
+    let (endpoint, failed) = connection_established(&mut swarm1).await;
+    assert_eq!(
+        endpoint.get_remote_address(),
+        &reachable.clone().with_p2p(peer_id).unwrap()
+    );
+    assert_eq!(failed, [unreachable.clone().with_p2p(peer_id).unwrap()]);
+    assert_eq!(swarm1.address_score(&peer_id, &reachable), 1);
+    assert_eq!(swarm1.address_score(&peer_id, &unreachable), -1);
+
+    swarm1.disconnect_peer_id(peer_id).unwrap();
+    loop {
+        if let SwarmEvent::ConnectionClosed { .. } = swarm1.next_swarm_event().await {
+            break;
+        }
+    }
+
+    // The address that worked before is dialed first, the unreachable one isn't tried at all.
+    swarm1.dial(dial()).unwrap();
+    let (_, failed) = connection_established(&mut swarm1).await;
+    assert!(failed.is_empty());
+    assert_eq!(swarm1.address_score(&peer_id, &reachable), 2);
+
+    swarm1.record_address_result(peer_id, &reachable, false);
+    assert_eq!(swarm1.address_score(&peer_id, &reachable), -1);
+}
+
+async fn connection_established(
+    swarm: &mut Swarm<dummy::Behaviour>,
+) -> (ConnectedPoint, Vec<Multiaddr>) {
+    loop {
+        match swarm.next_swarm_event().await {
+            SwarmEvent::Dialing { .. } => {}
+            SwarmEvent::ConnectionEstablished {
+                endpoint,
+                concurrent_dial_errors,
+                ..
+            } => {
+                let failed = concurrent_dial_errors
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(address, _)| address)
+                    .collect();
+                return (endpoint, failed);
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+}
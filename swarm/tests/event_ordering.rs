@@ -0,0 +1,114 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::{
+    dummy, ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, Swarm, SwarmEvent,
+    THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+
+#[async_std::test]
+async fn behaviour_calls_from_event_handler_are_processed_on_next_poll() {
+    let mut swarm = Swarm::new_ephemeral(|_| Behaviour::default());
+
+    swarm.behaviour_mut().push(1);
+    match swarm.next_swarm_event().await {
+        SwarmEvent::Behaviour(1) => {
+            swarm.behaviour_mut().push(2);
+            swarm.behaviour_mut().push(3);
+            swarm
+                .dial("/memory/1234".parse::<Multiaddr>().unwrap())
+                .unwrap();
+        }
+        e => panic!("Unexpected event: {e:?}"),
+    }
+
+    assert!(matches!(
+        swarm.next_swarm_event().await,
+        SwarmEvent::Behaviour(2)
+    ));
+    assert!(matches!(
+        swarm.next_swarm_event().await,
+        SwarmEvent::Behaviour(3)
+    ));
+    assert!(matches!(
+        swarm.next_swarm_event().await,
+        SwarmEvent::OutgoingConnectionError { .. }
+    ));
+}
+
+/// Behaviour emitting the numbers pushed into it, in order.
+#[derive(Default)]
+struct Behaviour {
+    events: VecDeque<u32>,
+}
+
+impl Behaviour {
+    fn push(&mut self, n: u32) {
+        self.events.push_back(n);
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = u32;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _peer_id: PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.events.pop_front() {
+            Some(n) => Poll::Ready(ToSwarm::GenerateEvent(n)),
+            None => Poll::Pending,
+        }
+    }
+}
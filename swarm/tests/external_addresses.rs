@@ -0,0 +1,187 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use std::{
+    collections::VecDeque,
+    task::{Context, Poll},
+};
+
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::{
+    dummy, AddressScore, ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, Swarm,
+    SwarmEvent, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+
+#[async_std::test]
+async fn candidate_is_confirmed_once_score_reaches_threshold() {
+    let mut swarm = Swarm::new_ephemeral(|_| Behaviour::default());
+    let address: Multiaddr = "/ip4/198.51.100.1/tcp/4001".parse().unwrap();
+
+    swarm.add_external_address_candidate(address.clone(), AddressScore(1));
+    assert_eq!(swarm.external_addresses().count(), 0);
+    assert_eq!(
+        swarm.external_address_candidates().collect::<Vec<_>>(),
+        vec![(&address, AddressScore(1))]
+    );
+
+    // Two more peers observing the address reach the default threshold of 3.
+    swarm
+        .behaviour_mut()
+        .report(address.clone(), PeerId::random());
+    swarm
+        .behaviour_mut()
+        .report(address.clone(), PeerId::random());
+
+    // Each report is announced as a candidate before the last one confirms the address.
+    let mut events = Vec::new();
+    while events.len() < 3 {
+        match swarm.next_swarm_event().await {
+            SwarmEvent::NewExternalAddrCandidate { address } => events.push(("candidate", address)),
+            SwarmEvent::ExternalAddrConfirmed { address } => events.push(("confirmed", address)),
+            _ => {}
+        }
+    }
+    assert_eq!(
+        events,
+        vec![
+            ("candidate", address.clone()),
+            ("candidate", address.clone()),
+            ("confirmed", address.clone()),
+        ]
+    );
+    assert_eq!(
+        swarm.external_addresses().collect::<Vec<_>>(),
+        vec![&address]
+    );
+    assert_eq!(swarm.external_address_candidates().count(), 0);
+    assert_eq!(swarm.behaviour().confirmed, vec![address]);
+}
+
+#[async_std::test]
+async fn repeated_observations_of_a_peer_are_scored_once() {
+    let mut swarm = Swarm::new_ephemeral(|_| Behaviour::default());
+    let address: Multiaddr = "/ip4/198.51.100.1/tcp/4001".parse().unwrap();
+    let observer = PeerId::random();
+
+    swarm.add_external_address_candidate(address.clone(), AddressScore(1));
+    swarm.behaviour_mut().report(address.clone(), observer);
+    swarm.behaviour_mut().report(address.clone(), observer);
+
+    for _ in 0..2 {
+        swarm
+            .wait(|e| match e {
+                SwarmEvent::NewExternalAddrCandidate { address } => Some(address),
+                _ => None,
+            })
+            .await;
+    }
+
+    assert_eq!(
+        swarm.external_address_candidates().collect::<Vec<_>>(),
+        vec![(&address, AddressScore(2))]
+    );
+    assert_eq!(swarm.external_addresses().count(), 0);
+}
+
+#[async_std::test]
+async fn removing_candidate_stops_tracking_it() {
+    let mut swarm = Swarm::new_ephemeral(|_| Behaviour::default());
+    let address: Multiaddr = "/ip4/198.51.100.1/tcp/4001".parse().unwrap();
+
+    swarm.add_external_address_candidate(address.clone(), AddressScore(2));
+    swarm.remove_external_address(&address);
+    assert_eq!(swarm.external_address_candidates().count(), 0);
+
+    // A confirmation of an address that is no longer a candidate has no effect.
+    swarm
+        .behaviour_mut()
+        .report(address.clone(), PeerId::random());
+    swarm
+        .wait(|e| match e {
+            SwarmEvent::NewExternalAddrCandidate { address } => Some(address),
+            _ => None,
+        })
+        .await;
+
+    assert_eq!(swarm.external_addresses().count(), 0);
+    assert!(swarm.behaviour().confirmed.is_empty());
+}
+
+/// Reports addresses observed by other peers, like `identify` does.
+#[derive(Default)]
+struct Behaviour {
+    reports: VecDeque<(Multiaddr, PeerId)>,
+    confirmed: Vec<Multiaddr>,
+}
+
+impl Behaviour {
+    fn report(&mut self, address: Multiaddr, observer: PeerId) {
+        self.reports.push_back((address, observer));
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = void::Void;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        _: THandlerOutEvent<Self>,
+    ) {
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::ExternalAddrConfirmed(e) = event {
+            self.confirmed.push(e.addr.clone());
+        }
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some((address, observer)) = self.reports.pop_front() {
+            return Poll::Ready(ToSwarm::ObservedExternalAddr { address, observer });
+        }
+
+        Poll::Pending
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::FutureExt;
+use libp2p_core::upgrade::FallbackUpgrade;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::handler::{ConnectionEvent, FullyNegotiatedInbound, FullyNegotiatedOutbound};
+use libp2p_swarm::{
+    ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, StreamProtocol, SubstreamProtocol, Swarm, SwarmEvent, THandler,
+    THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+
+const V1: StreamProtocol = StreamProtocol::new("/fallback/1.0.0");
+const V2: StreamProtocol = StreamProtocol::new("/fallback/2.0.0");
+
+#[async_std::test]
+async fn peers_supporting_both_versions_negotiate_the_preferred_one() {
+    let negotiated = negotiate(vec![V2, V1], vec![V1, V2]).await;
+
+    assert_eq!(negotiated, (V2, V2));
+}
+
+#[async_std::test]
+async fn falls_back_to_older_version() {
+    let negotiated = negotiate(vec![V2, V1], vec![V1]).await;
+
+    assert_eq!(negotiated, (V1, V1));
+}
+
+/// Connects a dialer and a listener supporting the given protocols and opens a stream from the
+/// dialer, returning the protocol negotiated by the dialer and the listener.
+async fn negotiate(
+    dialer: Vec<StreamProtocol>,
+    listener: Vec<StreamProtocol>,
+) -> (StreamProtocol, StreamProtocol) {
+    let mut dialer = Swarm::new_ephemeral(|_| Behaviour::new(dialer));
+    let mut listener = Swarm::new_ephemeral(|_| Behaviour::new(listener));
+
+    listener.listen().with_memory_addr_external().await;
+    dialer.connect(&mut listener).await;
+
+    let mut outbound = None;
+    let mut inbound = None;
+    while outbound.is_none() || inbound.is_none() {
+        futures::select! {
+            event = dialer.next_swarm_event().fuse() => {
+                if let SwarmEvent::Behaviour((Endpoint::Dialer, protocol)) = event {
+                    outbound = Some(protocol);
+                }
+            }
+            event = listener.next_swarm_event().fuse() => {
+                if let SwarmEvent::Behaviour((Endpoint::Listener, protocol)) = event {
+                    inbound = Some(protocol);
+                }
+            }
+        }
+    }
+
+    (outbound.unwrap(), inbound.unwrap())
+}
+
+/// Opens a stream to every peer it dialed, supporting its protocols in order of preference, and
+/// reports the protocols negotiated on its streams.
+struct Behaviour {
+    protocols: Vec<StreamProtocol>,
+    events: VecDeque<(Endpoint, StreamProtocol)>,
+}
+
+impl Behaviour {
+    fn new(protocols: Vec<StreamProtocol>) -> Self {
+        Self {
+            protocols,
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = (Endpoint, StreamProtocol);
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler {
+            protocols: self.protocols.clone(),
+            open_stream: false,
+            events: VecDeque::new(),
+        })
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler {
+            protocols: self.protocols.clone(),
+            open_stream: true,
+            events: VecDeque::new(),
+        })
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.events.push_back(event);
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.events.pop_front() {
+            Some(event) => Poll::Ready(ToSwarm::GenerateEvent(event)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+struct Handler {
+    protocols: Vec<StreamProtocol>,
+    open_stream: bool,
+    events: VecDeque<(Endpoint, StreamProtocol)>,
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = void::Void;
+    type ToBehaviour = (Endpoint, StreamProtocol);
+    type InboundProtocol = FallbackUpgrade<StreamProtocol>;
+    type OutboundProtocol = FallbackUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, ()> {
+        SubstreamProtocol::new(FallbackUpgrade::new(self.protocols.clone()), ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, (), Self::ToBehaviour>> {
+        if std::mem::take(&mut self.open_stream) {
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(FallbackUpgrade::new(self.protocols.clone()), ()),
+            });
+        }
+
+        match self.events.pop_front() {
+            Some(event) => Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event)),
+            None => Poll::Pending,
+        }
+    }
+
+    fn on_behaviour_event(&mut self, event: void::Void) {
+        void::unreachable(event)
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<Self::InboundProtocol, Self::OutboundProtocol, (), ()>,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+                protocol: (_, protocol),
+                ..
+            }) => self.events.push_back((Endpoint::Listener, protocol)),
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol: (_, protocol),
+                ..
+            }) => self.events.push_back((Endpoint::Dialer, protocol)),
+            _ => {}
+        }
+    }
+}
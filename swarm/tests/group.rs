@@ -0,0 +1,128 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::StreamExt;
+use libp2p_ping as ping;
+use libp2p_swarm::behaviour::Group;
+use libp2p_swarm::{Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use std::time::Duration;
+
+#[async_std::test]
+async fn events_are_reported_with_index_until_removed() {
+    let cfg = ping::Config::new().with_interval(Duration::from_millis(500));
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| Group::from(vec![ping::Behaviour::new(cfg.clone())]));
+    let mut swarm2 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    let ([(index, e1)], [e2]): ([(usize, ping::Event); 1], [ping::Event; 1]) =
+        libp2p_swarm_test::drive(&mut swarm1, &mut swarm2).await;
+    assert_eq!(index, 0);
+    assert!(e1.result.is_ok());
+    assert!(e2.result.is_ok());
+
+    assert!(swarm1.behaviour_mut().remove(0).is_some());
+    assert!(swarm1.behaviour().is_empty());
+
+    let peer1 = *swarm1.local_peer_id();
+    loop {
+        futures::select! {
+            event = swarm1.select_next_some() => {
+                if let SwarmEvent::Behaviour(event) = event {
+                    panic!("Unexpected event of removed behaviour: {event:?}");
+                }
+            }
+            event = swarm2.select_next_some() => match event {
+                SwarmEvent::Behaviour(ping::Event {
+                    result: Err(ping::Failure::Unsupported),
+                    ..
+                }) => break,
+                SwarmEvent::ConnectionClosed { .. } => panic!("Connection should stay open"),
+                _ => {}
+            }
+        }
+    }
+
+    assert!(swarm2.is_connected(&peer1));
+}
+
+#[async_std::test]
+async fn added_behaviour_takes_part_in_new_connections() {
+    let cfg = ping::Config::new().with_interval(Duration::from_millis(500));
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| Group::new());
+    let mut swarm2 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+
+    assert_eq!(
+        swarm1
+            .behaviour_mut()
+            .push(ping::Behaviour::new(cfg.clone())),
+        0
+    );
+    assert!(swarm1.behaviour_mut().remove(0).is_some());
+    assert_eq!(
+        swarm1
+            .behaviour_mut()
+            .push(ping::Behaviour::new(cfg.clone())),
+        1
+    );
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    let ([(index, e1)], [e2]): ([(usize, ping::Event); 1], [ping::Event; 1]) =
+        libp2p_swarm_test::drive(&mut swarm1, &mut swarm2).await;
+    assert_eq!(index, 1);
+    assert!(e1.result.is_ok());
+    assert!(e2.result.is_ok());
+}
+
+#[async_std::test]
+async fn behaviours_of_same_type_share_connection() {
+    let cfg = ping::Config::new().with_interval(Duration::from_millis(100));
+
+    let mut swarm1 = Swarm::new_ephemeral(|_| {
+        Group::from(vec![
+            ping::Behaviour::new(cfg.clone()),
+            ping::Behaviour::new(cfg.clone().with_interval(Duration::from_millis(170))),
+        ])
+    });
+    let mut swarm2 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    // Concurrent pings of both behaviours may collide on the remote, which handles a single
+    // inbound ping at a time. Each behaviour eventually succeeds nonetheless.
+    let mut succeeded = [false; 2];
+    while succeeded != [true; 2] {
+        match swarm1.next_swarm_event().await {
+            SwarmEvent::Behaviour((index, ping::Event { result: Ok(_), .. })) => {
+                succeeded[index] = true
+            }
+            SwarmEvent::ConnectionClosed { .. } => panic!("Connection should stay open"),
+            _ => {}
+        }
+    }
+}
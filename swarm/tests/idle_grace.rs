@@ -0,0 +1,221 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::StreamExt;
+use libp2p_core::{upgrade::ReadyUpgrade, Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::{
+    handler::{ConnectionEvent, FullyNegotiatedInbound, FullyNegotiatedOutbound},
+    ConnectionDenied, ConnectionError, ConnectionHandler, ConnectionHandlerEvent, ConnectionId,
+    FromSwarm, IdleGracePeriod, NetworkBehaviour, StreamProtocol, SubstreamProtocol, Swarm,
+    SwarmEvent, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+const PROTOCOL: StreamProtocol = StreamProtocol::new("/test/grace/1.0.0");
+
+#[async_std::test]
+async fn connection_is_kept_alive_for_grace_period_after_last_stream() {
+    let grace = Duration::from_millis(300);
+    let mut dialer = new_swarm(grace, true);
+    let mut listener = new_swarm(Duration::from_secs(60), false);
+
+    listener.listen_on("/memory/0".parse().unwrap()).unwrap();
+    let addr = loop {
+        if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await {
+            break address;
+        }
+    };
+    dialer.dial(addr).unwrap();
+
+    let stream_closed = loop {
+        futures::select! {
+            event = dialer.select_next_some() => {
+                if let SwarmEvent::Behaviour(closed_at) = event {
+                    break closed_at;
+                }
+            }
+            _ = listener.select_next_some() => {}
+        }
+    };
+
+    let cause = loop {
+        futures::select! {
+            event = dialer.select_next_some() => {
+                if let SwarmEvent::ConnectionClosed { cause, .. } = event {
+                    break cause;
+                }
+            }
+            _ = listener.select_next_some() => {}
+        }
+    };
+
+    assert!(matches!(cause, Some(ConnectionError::KeepAliveTimeout)));
+    assert!(stream_closed.elapsed() >= grace);
+}
+
+fn new_swarm(grace: Duration, open_stream: bool) -> Swarm<Behaviour> {
+    Swarm::new_ephemeral_with_config(
+        |_| Behaviour {
+            grace,
+            open_stream,
+            events: VecDeque::new(),
+        },
+        |config| config.with_idle_connection_timeout(Duration::ZERO),
+    )
+}
+
+/// Reports the point in time at which the stream of a connection was closed.
+struct Behaviour {
+    grace: Duration,
+    open_stream: bool,
+    events: VecDeque<Instant>,
+}
+
+impl Behaviour {
+    fn new_handler(&self) -> Handler {
+        Handler {
+            grace: IdleGracePeriod::new(self.grace),
+            open_stream: self.open_stream,
+            busy: true,
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = Instant;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(self.new_handler())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(self.new_handler())
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        closed_at: THandlerOutEvent<Self>,
+    ) {
+        self.events.push_back(closed_at);
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.events.pop_front() {
+            Some(closed_at) => Poll::Ready(ToSwarm::GenerateEvent(closed_at)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Handles a single stream and drops it right away.
+struct Handler {
+    grace: IdleGracePeriod,
+    open_stream: bool,
+    busy: bool,
+    events: VecDeque<Instant>,
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = void::Void;
+    type ToBehaviour = Instant;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL), ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        self.grace.keep_alive()
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, (), Self::ToBehaviour>> {
+        if std::mem::take(&mut self.open_stream) {
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(ReadyUpgrade::new(PROTOCOL), ()),
+            });
+        }
+
+        if let Some(closed_at) = self.events.pop_front() {
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(closed_at));
+        }
+
+        self.grace.poll(cx, self.busy);
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        void::unreachable(event)
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound {
+                protocol: stream,
+                ..
+            })
+            | ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound {
+                protocol: stream,
+                ..
+            }) => {
+                drop(stream);
+                self.busy = false;
+                self.events.push_back(Instant::now());
+            }
+            _ => {}
+        }
+    }
+}
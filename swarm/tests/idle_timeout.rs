@@ -0,0 +1,72 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::StreamExt;
+use libp2p_swarm::{dummy, ConnectionError, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use std::time::{Duration, Instant};
+
+#[async_std::test]
+async fn idle_connection_is_closed_after_timeout() {
+    let idle_timeout = Duration::from_millis(200);
+    let mut swarm1 = new_swarm(idle_timeout);
+    // Only `swarm1` is expected to time out, `swarm2` must not close the connection first.
+    let mut swarm2 = new_swarm(Duration::from_secs(60));
+
+    swarm2.listen_on("/memory/0".parse().unwrap()).unwrap();
+    let addr = loop {
+        if let SwarmEvent::NewListenAddr { address, .. } = swarm2.select_next_some().await {
+            break address;
+        }
+    };
+    swarm1.dial(addr).unwrap();
+
+    let established = loop {
+        futures::select! {
+            event = swarm1.select_next_some() => {
+                if let SwarmEvent::ConnectionEstablished { .. } = event {
+                    break Instant::now();
+                }
+            }
+            _ = swarm2.select_next_some() => {}
+        }
+    };
+
+    let cause = loop {
+        futures::select! {
+            event = swarm1.select_next_some() => {
+                if let SwarmEvent::ConnectionClosed { cause, .. } = event {
+                    break cause;
+                }
+            }
+            _ = swarm2.select_next_some() => {}
+        }
+    };
+
+    assert!(matches!(cause, Some(ConnectionError::KeepAliveTimeout)));
+    assert!(established.elapsed() >= idle_timeout);
+}
+
+fn new_swarm(idle_timeout: Duration) -> Swarm<dummy::Behaviour> {
+    Swarm::new_ephemeral_with_config(
+        |_| dummy::Behaviour,
+        |config| config.with_idle_connection_timeout(idle_timeout),
+    )
+}
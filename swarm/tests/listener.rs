@@ -7,8 +7,8 @@ use libp2p_core::{multiaddr::Protocol, transport::ListenerId, Endpoint, Multiadd
 use libp2p_identity::PeerId;
 use libp2p_swarm::{
     derive_prelude::NewListener, dummy, ConnectionDenied, ConnectionId, FromSwarm, ListenOpts,
-    ListenerClosed, ListenerError, NetworkBehaviour, NewListenAddr, Swarm, SwarmEvent, THandler,
-    THandlerInEvent, THandlerOutEvent, ToSwarm,
+    ListenerClosed, ListenerError, ListenerRecovery, NetworkBehaviour, NewListenAddr, Swarm,
+    SwarmEvent, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
 };
 
 use libp2p_swarm_test::SwarmExt;
@@ -51,6 +51,47 @@ async fn behaviour_listener() {
         .await;
 }
 
+#[async_std::test]
+async fn reports_listener_error_for_occupied_address() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+
+    swarm1.listen_on(Protocol::Memory(0).into()).unwrap();
+    let occupied = swarm1
+        .wait(|e| match e {
+            SwarmEvent::NewListenAddr { address, .. } => Some(address),
+            _ => None,
+        })
+        .await;
+
+    // The error is returned to the caller only.
+    assert!(swarm2.listen_on(occupied.clone()).is_err());
+    assert_eq!(swarm2.listeners().count(), 0);
+
+    let mut swarm3 = Swarm::new_ephemeral(|_| Behaviour::default());
+    let id = swarm3.behaviour_mut().listen(occupied.clone());
+
+    swarm3
+        .wait(|e| match e {
+            SwarmEvent::ListenerError {
+                listener_id,
+                address,
+                error,
+                recovery,
+            } => {
+                assert_eq!(listener_id, id);
+                assert_eq!(address, occupied);
+                // The original transport error is kept rather than its message.
+                assert!(format!("{error:?}").contains("Unreachable"));
+                assert_eq!(recovery, ListenerRecovery::Closed);
+                Some(())
+            }
+            _ => None,
+        })
+        .await;
+    assert_eq!(swarm3.listeners().count(), 0);
+}
+
 #[derive(Default)]
 struct Behaviour {
     events: VecDeque<ToSwarm<<Self as NetworkBehaviour>::ToSwarm, THandlerInEvent<Self>>>,
@@ -114,7 +155,10 @@ impl NetworkBehaviour for Behaviour {
                 assert!(self.listeners.contains(&listener_id));
             }
             FromSwarm::ListenerError(ListenerError { listener_id, err }) => {
-                panic!("Error for listener {listener_id:?}: {err}");
+                assert!(
+                    self.listeners.contains(&listener_id),
+                    "Error for unknown listener {listener_id:?}: {err}"
+                );
             }
             FromSwarm::ListenerClosed(ListenerClosed {
                 listener_id,
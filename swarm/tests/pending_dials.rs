@@ -0,0 +1,189 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_core::multiaddr::Protocol;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::dial_opts::{DialOpts, RetryConfig};
+use libp2p_swarm::{
+    dummy, ConnectionDenied, ConnectionId, DialError, DialFailure, FromSwarm, NetworkBehaviour,
+    Swarm, SwarmEvent, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[async_std::test]
+async fn cancelled_dial_is_reported_and_not_retried() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let address = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+
+    // `swarm2` is never polled, thus the connection is never upgraded and the dial stays pending.
+    swarm2.listen_on(address.clone()).unwrap();
+
+    let opts = DialOpts::peer_id(*swarm2.local_peer_id())
+        .addresses(vec![address.clone()])
+        .build()
+        .with_retry(RetryConfig {
+            max_attempts: 3,
+            base: Duration::from_millis(10),
+            max: Duration::from_millis(10),
+            jitter: Duration::ZERO,
+        });
+    let id = opts.connection_id();
+    swarm1.dial(opts).unwrap();
+
+    assert_eq!(swarm1.pending_dials().collect::<Vec<_>>(), [(id, &address)]);
+    assert!(swarm1.cancel_dial(id));
+    assert!(!swarm1.cancel_dial(id));
+
+    loop {
+        match swarm1.next_swarm_event().await {
+            SwarmEvent::Dialing { .. } => {}
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                error: DialError::Cancelled,
+                ..
+            } => {
+                assert_eq!(connection_id, id);
+                break;
+            }
+            other => panic!("Unexpected event: {other:?}"),
+        }
+    }
+
+    assert_eq!(swarm1.pending_dials().count(), 0);
+
+    let no_retry = async {
+        loop {
+            let event = swarm1.next_swarm_event().await;
+            panic!("Unexpected event after cancelling: {event:?}")
+        }
+    };
+    let _ = async_std::future::timeout(Duration::from_millis(100), no_retry).await;
+}
+
+#[async_std::test]
+async fn dial_can_be_cancelled_while_waiting_for_retry() {
+    let mut swarm = Swarm::new_ephemeral(|_| RecordDialFailures::default());
+    let unreachable = Multiaddr::empty().with(Protocol::Memory(rand::random::<u64>()));
+
+    let opts = DialOpts::unknown_peer_id()
+        .address(unreachable.clone())
+        .build()
+        .with_retry(RetryConfig {
+            max_attempts: 3,
+            base: Duration::from_millis(100),
+            max: Duration::from_millis(100),
+            jitter: Duration::ZERO,
+        });
+    let id = opts.connection_id();
+    swarm.dial(opts).unwrap();
+
+    match swarm.next_swarm_event().await {
+        SwarmEvent::DialRetryScheduled { connection_id, .. } => assert_eq!(connection_id, id),
+        other => panic!("Unexpected event: {other:?}"),
+    }
+
+    // The retry keeps the ID of the dial while waiting for its backoff.
+    assert_eq!(
+        swarm.pending_dials().collect::<Vec<_>>(),
+        [(id, &unreachable)]
+    );
+    assert!(swarm.cancel_dial(id));
+    assert_eq!(swarm.pending_dials().count(), 0);
+
+    match swarm.next_swarm_event().await {
+        SwarmEvent::OutgoingConnectionError {
+            connection_id,
+            error: DialError::Cancelled,
+            ..
+        } => assert_eq!(connection_id, id),
+        other => panic!("Unexpected event: {other:?}"),
+    }
+    // The behaviour learns about the cancellation, after the failure of the first attempt.
+    assert_eq!(swarm.behaviour().failures, [(id, false), (id, true)]);
+
+    let no_retry = async {
+        loop {
+            let event = swarm.next_swarm_event().await;
+            panic!("Unexpected event after cancelling: {event:?}")
+        }
+    };
+    let _ = async_std::future::timeout(Duration::from_millis(200), no_retry).await;
+}
+
+/// Records the [`ConnectionId`] of every failed dial and whether it failed because it was
+/// cancelled.
+#[derive(Default)]
+struct RecordDialFailures {
+    failures: Vec<(ConnectionId, bool)>,
+}
+
+impl NetworkBehaviour for RecordDialFailures {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = void::Void;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::DialFailure(DialFailure {
+            connection_id,
+            error,
+            ..
+        }) = event
+        {
+            self.failures
+                .push((connection_id, matches!(error, DialError::Cancelled)));
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::StreamExt;
+use libp2p_core::upgrade::DeniedUpgrade;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::handler::ConnectionEvent;
+use libp2p_swarm::{
+    ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, SubstreamProtocol, Swarm, SwarmEvent, THandler, THandlerInEvent,
+    THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use void::Void;
+
+const NUM_EVENTS: usize = 50;
+
+#[async_std::test]
+async fn counts_events_not_yet_delivered_to_behaviour() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| Behaviour::default());
+    let mut swarm2 = Swarm::new_ephemeral(|_| Behaviour::default());
+
+    swarm2.listen().with_memory_addr_external().await;
+    swarm1.connect(&mut swarm2).await;
+
+    // The connection task keeps running while `swarm1` isn't polled, filling the event buffer.
+    async_std::task::sleep(Duration::from_millis(500)).await;
+    assert!(swarm1.pending_events_len() > 0);
+
+    async_std::task::spawn(swarm2.loop_on_next());
+    while swarm1.behaviour().received < NUM_EVENTS {
+        swarm1.select_next_some().await;
+    }
+
+    assert_eq!(swarm1.pending_events_len(), 0);
+}
+
+#[async_std::test]
+async fn count_drops_to_zero_when_connection_is_closed_with_events_pending() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| Behaviour::default());
+    let mut swarm2 = Swarm::new_ephemeral(|_| Behaviour::default());
+    let peer2 = *swarm2.local_peer_id();
+
+    swarm2.listen().with_memory_addr_external().await;
+    swarm1.connect(&mut swarm2).await;
+
+    async_std::task::sleep(Duration::from_millis(500)).await;
+    assert!(swarm1.pending_events_len() > 0);
+
+    swarm1.disconnect_peer_id(peer2).unwrap();
+    loop {
+        if let SwarmEvent::ConnectionClosed { .. } = swarm1.select_next_some().await {
+            break;
+        }
+    }
+
+    assert_eq!(swarm1.pending_events_len(), 0);
+}
+
+/// Behaviour whose handlers emit [`NUM_EVENTS`] events right away.
+#[derive(Default)]
+struct Behaviour {
+    received: usize,
+    events: VecDeque<()>,
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = ();
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler {
+            remaining: NUM_EVENTS,
+        })
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler {
+            remaining: NUM_EVENTS,
+        })
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _peer_id: PeerId,
+        _connection_id: ConnectionId,
+        _event: THandlerOutEvent<Self>,
+    ) {
+        self.received += 1;
+        self.events.push_back(());
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(ToSwarm::GenerateEvent(event));
+        }
+
+        Poll::Pending
+    }
+}
+
+struct Handler {
+    remaining: usize,
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = Void;
+    type ToBehaviour = ();
+    type InboundProtocol = DeniedUpgrade;
+    type OutboundProtocol = DeniedUpgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(DeniedUpgrade, ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(()));
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        void::unreachable(event)
+    }
+
+    fn on_connection_event(
+        &mut self,
+        _: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::{AsyncReadExt, AsyncWriteExt, StreamExt};
+use libp2p_ping as ping;
+use libp2p_swarm::{protocol_registry, ProtocolRegistry, StreamProtocol, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use std::time::Duration;
+
+const PING_PROTOCOL: StreamProtocol = StreamProtocol::new("/ipfs/ping/1.0.0");
+
+#[async_std::test]
+async fn dispatches_inbound_streams_to_registered_factory() {
+    let registry = ProtocolRegistry::new();
+    assert!(registry.register(PING_PROTOCOL, Box::new(|_, stream| echo(stream))));
+
+    let (mut registry_swarm, mut ping_swarm) = connect(registry).await;
+
+    loop {
+        futures::select! {
+            event = registry_swarm.select_next_some() => {
+                if let SwarmEvent::Behaviour(event) = event {
+                    void::unreachable(event)
+                }
+            }
+            event = ping_swarm.select_next_some() => match event {
+                SwarmEvent::Behaviour(ping::Event { result: Ok(_), .. }) => break,
+                SwarmEvent::Behaviour(ping::Event { result: Err(e), .. }) => {
+                    panic!("Unexpected ping failure: {e:?}")
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[async_std::test]
+async fn rejects_streams_of_unregistered_protocols() {
+    let registry = ProtocolRegistry::new();
+    registry.register(PING_PROTOCOL, Box::new(|_, stream| echo(stream)));
+    assert!(registry.deregister(&PING_PROTOCOL));
+    assert!(registry.lookup(&PING_PROTOCOL).is_none());
+
+    let (mut registry_swarm, mut ping_swarm) = connect(registry).await;
+
+    loop {
+        futures::select! {
+            event = registry_swarm.select_next_some() => {
+                if let SwarmEvent::Behaviour(event) = event {
+                    void::unreachable(event)
+                }
+            }
+            event = ping_swarm.select_next_some() => match event {
+                SwarmEvent::Behaviour(ping::Event {
+                    result: Err(ping::Failure::Unsupported),
+                    ..
+                }) => break,
+                SwarmEvent::Behaviour(e) => panic!("Unexpected ping event: {e:?}"),
+                _ => {}
+            }
+        }
+    }
+}
+
+async fn connect(
+    registry: ProtocolRegistry,
+) -> (Swarm<protocol_registry::Behaviour>, Swarm<ping::Behaviour>) {
+    let mut registry_swarm = Swarm::new_ephemeral(|_| protocol_registry::Behaviour::new(registry));
+    let mut ping_swarm = Swarm::new_ephemeral(|_| {
+        ping::Behaviour::new(ping::Config::new().with_interval(Duration::from_millis(100)))
+    });
+
+    registry_swarm.listen().with_memory_addr_external().await;
+    ping_swarm.connect(&mut registry_swarm).await;
+
+    (registry_swarm, ping_swarm)
+}
+
+/// Echoes a single ping payload.
+async fn echo(mut stream: libp2p_swarm::Stream) {
+    let mut payload = [0u8; 32];
+    if stream.read_exact(&mut payload).await.is_ok() {
+        let _ = stream.write_all(&payload).await;
+        let _ = stream.flush().await;
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use libp2p_core::upgrade::DeniedUpgrade;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_swarm::dial_opts::DialOpts;
+use libp2p_swarm::handler::ConnectionEvent;
+use libp2p_swarm::{
+    dummy, ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, SubstreamProtocol, Swarm, THandler, THandlerInEvent, THandlerOutEvent,
+    ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use void::Void;
+
+#[async_std::test]
+async fn shutdown_resolves_once_connections_are_closed() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+
+    swarm2.listen().with_memory_addr_external().await;
+    swarm1.connect(&mut swarm2).await;
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    let start = Instant::now();
+    swarm1.shutdown(Duration::from_secs(30)).await;
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[async_std::test]
+async fn shutdown_drops_connections_after_timeout() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| Behaviour);
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+
+    swarm2.listen().with_memory_addr_external().await;
+    swarm1.connect(&mut swarm2).await;
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    let start = Instant::now();
+    swarm1.shutdown(Duration::from_millis(500)).await;
+
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(500));
+    assert!(elapsed < Duration::from_secs(10));
+}
+
+#[async_std::test]
+async fn shutdown_aborts_dials_made_in_the_meantime() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| RedialBehaviour::default());
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut swarm3 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+    let mut unresponsive = Swarm::new_ephemeral(|_| dummy::Behaviour);
+
+    // `unresponsive` is never polled, thus dials to it stay pending until they are aborted.
+    let (address, _) = unresponsive.listen().await;
+    swarm1.behaviour_mut().address = Some(address);
+
+    swarm2.listen().with_memory_addr_external().await;
+    swarm3.listen().with_memory_addr_external().await;
+    swarm1.connect(&mut swarm2).await;
+    swarm1.connect(&mut swarm3).await;
+    async_std::task::spawn(swarm2.loop_on_next());
+    async_std::task::spawn(swarm3.loop_on_next());
+
+    let start = Instant::now();
+    swarm1.shutdown(Duration::from_secs(30)).await;
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+/// Behaviour whose handlers never finish closing.
+struct Behaviour;
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = Void;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler)
+    }
+
+    fn on_swarm_event(&mut self, _: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _peer_id: PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
+
+struct Handler;
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = Void;
+    type ToBehaviour = Void;
+    type InboundProtocol = DeniedUpgrade;
+    type OutboundProtocol = DeniedUpgrade;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(DeniedUpgrade, ())
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        true
+    }
+
+    fn poll(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<
+        ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>,
+    > {
+        Poll::Pending
+    }
+
+    fn poll_close(&mut self, _: &mut Context<'_>) -> Poll<Option<Self::ToBehaviour>> {
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        void::unreachable(event)
+    }
+
+    fn on_connection_event(
+        &mut self,
+        _: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+    }
+}
+
+/// Behaviour dialing `address` whenever a connection closes.
+#[derive(Default)]
+struct RedialBehaviour {
+    address: Option<Multiaddr>,
+    redial: bool,
+}
+
+impl NetworkBehaviour for RedialBehaviour {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = Void;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::ConnectionClosed(_) = event {
+            self.redial = true;
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _peer_id: PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.address.clone() {
+            Some(address) if std::mem::take(&mut self.redial) => Poll::Ready(ToSwarm::Dial {
+                opts: DialOpts::unknown_peer_id().address(address).build(),
+            }),
+            _ => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::StreamExt;
+use libp2p_core::Endpoint;
+use libp2p_swarm::{StreamProtocol, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+
+#[async_std::test]
+async fn reports_negotiated_protocol_of_ping_stream() {
+    let mut swarm1 = new_swarm();
+    let mut swarm2 = new_swarm();
+
+    swarm2.listen_on("/memory/0".parse().unwrap()).unwrap();
+    let addr = loop {
+        if let SwarmEvent::NewListenAddr { address, .. } = swarm2.select_next_some().await {
+            break address;
+        }
+    };
+    swarm1.dial(addr).unwrap();
+
+    let mut outbound = None;
+    let mut inbound = None;
+    while outbound.is_none() || inbound.is_none() {
+        futures::select! {
+            event = swarm1.select_next_some() => {
+                if let SwarmEvent::StreamOpened { protocol, direction: Endpoint::Dialer, .. } = event {
+                    outbound = Some(protocol);
+                }
+            }
+            event = swarm2.select_next_some() => {
+                if let SwarmEvent::StreamOpened { protocol, direction: Endpoint::Listener, .. } = event {
+                    inbound = Some(protocol);
+                }
+            }
+        }
+    }
+
+    let ping = StreamProtocol::new("/ipfs/ping/1.0.0");
+    assert_eq!(outbound, Some(ping.clone()));
+    assert_eq!(inbound, Some(ping));
+}
+
+fn new_swarm() -> Swarm<libp2p_ping::Behaviour> {
+    Swarm::new_ephemeral_with_config(
+        |_| libp2p_ping::Behaviour::default(),
+        |config| config.with_stream_opened_events(true),
+    )
+}
@@ -0,0 +1,79 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use libp2p_ping as ping;
+use libp2p_swarm::{protocol_registry, ProtocolRegistry, StreamProtocol, Swarm, SwarmEvent};
+use libp2p_swarm_test::SwarmExt;
+use std::{io, time::Duration};
+
+const PING_PROTOCOL: StreamProtocol = StreamProtocol::new("/ipfs/ping/1.0.0");
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+#[async_std::test]
+async fn read_fails_on_deadline_and_write_succeeds() {
+    let (results_tx, mut results_rx) = mpsc::channel(1);
+
+    let registry = ProtocolRegistry::new();
+    registry.register(
+        PING_PROTOCOL,
+        Box::new(move |_, mut stream: libp2p_swarm::Stream| {
+            let mut results_tx = results_tx.clone();
+            async move {
+                let mut payload = [0u8; 32];
+                let mut read = 0;
+                while read < payload.len() {
+                    read += stream
+                        .read_with_timeout(&mut payload[read..], TIMEOUT)
+                        .await
+                        .unwrap();
+                }
+
+                // The remote waits for the echo of its ping, so nothing more arrives.
+                let timed_out = stream.read_with_timeout(&mut [0u8; 1], TIMEOUT).await;
+                let written = stream.write_with_timeout(&payload, TIMEOUT).await;
+
+                let _ = results_tx.send((timed_out, written)).await;
+            }
+        }),
+    );
+
+    let mut registry_swarm = Swarm::new_ephemeral(|_| protocol_registry::Behaviour::new(registry));
+    let mut ping_swarm = Swarm::new_ephemeral(|_| {
+        ping::Behaviour::new(ping::Config::new().with_interval(Duration::from_secs(60)))
+    });
+    registry_swarm.listen().with_memory_addr_external().await;
+    ping_swarm.connect(&mut registry_swarm).await;
+
+    let (timed_out, written) = loop {
+        futures::select! {
+            event = registry_swarm.select_next_some() => {
+                if let SwarmEvent::Behaviour(event) = event {
+                    void::unreachable(event)
+                }
+            }
+            _ = ping_swarm.select_next_some() => {}
+            results = results_rx.next() => break results.unwrap(),
+        }
+    };
+
+    assert_eq!(timed_out.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    assert!(written.unwrap() > 0);
+}
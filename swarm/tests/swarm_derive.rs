@@ -155,6 +155,33 @@ fn custom_event() {
     }
 }
 
+#[test]
+fn generated_event_with_custom_name() {
+    #[allow(dead_code)]
+    #[derive(NetworkBehaviour)]
+    #[behaviour(event_name = "MyEvent", prelude = "libp2p_swarm::derive_prelude")]
+    struct Foo {
+        ping: ping::Behaviour,
+        identify: identify::Behaviour,
+    }
+
+    #[allow(
+        dead_code,
+        unreachable_code,
+        clippy::diverging_sub_expression,
+        clippy::used_underscore_binding
+    )]
+    fn foo() {
+        let _out_event: <Foo as NetworkBehaviour>::ToSwarm = unimplemented!();
+        match _out_event {
+            MyEvent::Ping(ping::Event { .. }) => {}
+            MyEvent::Identify(event) => {
+                let _: identify::Event = event;
+            }
+        }
+    }
+}
+
 #[test]
 fn custom_event_mismatching_field_names() {
     #[allow(dead_code)]
@@ -319,6 +346,24 @@ fn with_toggle() {
     }
 }
 
+#[test]
+fn with_group() {
+    use libp2p_swarm::behaviour::Group;
+
+    #[allow(dead_code)]
+    #[derive(NetworkBehaviour)]
+    #[behaviour(prelude = "libp2p_swarm::derive_prelude")]
+    struct Foo {
+        identify: identify::Behaviour,
+        pings: Group<ping::Behaviour>,
+    }
+
+    #[allow(dead_code)]
+    fn foo() {
+        require_net_behaviour::<Foo>();
+    }
+}
+
 #[test]
 fn with_either() {
     use either::Either;
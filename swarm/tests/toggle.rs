@@ -0,0 +1,148 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::StreamExt;
+use libp2p_core::{Endpoint, Multiaddr};
+use libp2p_identity::PeerId;
+use libp2p_ping as ping;
+use libp2p_swarm::behaviour::toggle::Toggle;
+use libp2p_swarm::behaviour::{ConnectionClosed, ConnectionEstablished};
+use libp2p_swarm::{
+    dummy, ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, Swarm, SwarmEvent,
+    THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p_swarm_test::SwarmExt;
+use std::collections::HashSet;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[async_std::test]
+async fn disabling_toggle_closes_inner_handlers() {
+    let cfg = ping::Config::new().with_interval(Duration::from_millis(500));
+
+    let mut swarm1 =
+        Swarm::new_ephemeral(|_| Toggle::from(Some(ping::Behaviour::new(cfg.clone()))));
+    let mut swarm2 = Swarm::new_ephemeral(|_| ping::Behaviour::new(cfg.clone()));
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    let ([e1], [e2]): ([ping::Event; 1], [ping::Event; 1]) =
+        libp2p_swarm_test::drive(&mut swarm1, &mut swarm2).await;
+    assert!(e1.result.is_ok());
+    assert!(e2.result.is_ok());
+
+    assert!(swarm1.behaviour_mut().disable().is_some());
+    assert!(!swarm1.behaviour().is_enabled());
+
+    let peer1 = *swarm1.local_peer_id();
+    loop {
+        futures::select! {
+            event = swarm1.select_next_some() => {
+                if let SwarmEvent::Behaviour(event) = event {
+                    panic!("Unexpected event of disabled behaviour: {event:?}");
+                }
+            }
+            event = swarm2.select_next_some() => match event {
+                SwarmEvent::Behaviour(ping::Event {
+                    result: Err(ping::Failure::Unsupported),
+                    ..
+                }) => break,
+                SwarmEvent::ConnectionClosed { .. } => panic!("Connection should stay open"),
+                _ => {}
+            }
+        }
+    }
+
+    assert!(swarm2.is_connected(&peer1));
+}
+
+#[async_std::test]
+async fn enabled_behaviour_is_not_told_about_earlier_connections() {
+    let mut swarm1 = Swarm::new_ephemeral(|_| Toggle::<KnownConnections>::from(None));
+    let mut swarm2 = Swarm::new_ephemeral(|_| dummy::Behaviour);
+
+    swarm1.listen().with_memory_addr_external().await;
+    swarm2.connect(&mut swarm1).await;
+
+    swarm1.behaviour_mut().enable(KnownConnections::default());
+    swarm1.disconnect_peer_id(*swarm2.local_peer_id()).unwrap();
+    async_std::task::spawn(swarm2.loop_on_next());
+
+    loop {
+        if let SwarmEvent::ConnectionClosed { .. } = swarm1.next_swarm_event().await {
+            break;
+        }
+    }
+}
+
+/// Panics when told about the closing of a connection it was not told about before.
+#[derive(Default)]
+struct KnownConnections(HashSet<ConnectionId>);
+
+impl NetworkBehaviour for KnownConnections {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = ();
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        match event {
+            FromSwarm::ConnectionEstablished(ConnectionEstablished { connection_id, .. }) => {
+                self.0.insert(connection_id);
+            }
+            FromSwarm::ConnectionClosed(ConnectionClosed { connection_id, .. }) => {
+                assert!(self.0.remove(&connection_id), "Unknown connection closed");
+            }
+            _ => {}
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<(), THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
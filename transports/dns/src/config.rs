@@ -0,0 +1,119 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use hickory_resolver::config::{
+    NameServerConfig, NameServerConfigGroup, Protocol, ResolverConfig, ResolverOpts,
+};
+use hickory_resolver::system_conf;
+use std::{io, net::SocketAddr, time::Duration};
+
+/// Configuration of the DNS resolver used by a [`Transport`](crate::Transport).
+///
+/// Resolved records are cached for their TTL, which can be clamped with
+/// [`Config::with_positive_ttl`]. Failed lookups (e.g. `NXDOMAIN`) are cached as well,
+/// see [`Config::with_negative_ttl`].
+///
+/// The defaults are those of [`ResolverConfig::default`] and [`ResolverOpts::default`].
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    resolver: ResolverConfig,
+    opts: ResolverOpts,
+}
+
+impl Config {
+    /// Creates a configuration from the OS's DNS configuration.
+    pub fn system() -> Result<Self, io::Error> {
+        let (resolver, opts) = system_conf::read_system_conf()?;
+        Ok(Self { resolver, opts })
+    }
+
+    /// Creates a configuration from a [`ResolverConfig`] and [`ResolverOpts`].
+    pub fn custom(resolver: ResolverConfig, opts: ResolverOpts) -> Self {
+        Self { resolver, opts }
+    }
+
+    /// Replaces the nameservers to query, reachable over UDP and TCP at the given addresses.
+    pub fn with_nameservers(mut self, nameservers: impl IntoIterator<Item = SocketAddr>) -> Self {
+        let nameservers = nameservers
+            .into_iter()
+            .flat_map(|addr| {
+                [
+                    NameServerConfig::new(addr, Protocol::Udp),
+                    NameServerConfig::new(addr, Protocol::Tcp),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        self.resolver = ResolverConfig::from_parts(
+            self.resolver.domain().cloned(),
+            self.resolver.search().to_vec(),
+            NameServerConfigGroup::from(nameservers),
+        );
+        self
+    }
+
+    /// Sets the timeout of a single request to a nameserver.
+    ///
+    /// Defaults to 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.opts.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of retries of a failed request before resolution fails.
+    ///
+    /// Defaults to 2.
+    pub fn with_attempts(mut self, attempts: usize) -> Self {
+        self.opts.attempts = attempts;
+        self
+    }
+
+    /// Sets the number of records that are cached.
+    ///
+    /// A size of 0 disables caching. Defaults to 32.
+    pub fn with_cache_size(mut self, cache_size: usize) -> Self {
+        self.opts.cache_size = cache_size;
+        self
+    }
+
+    /// Clamps how long successfully resolved records are cached.
+    ///
+    /// Records are cached for their TTL, raised to `min` and lowered to `max` if given.
+    pub fn with_positive_ttl(mut self, min: Option<Duration>, max: Option<Duration>) -> Self {
+        self.opts.positive_min_ttl = min;
+        self.opts.positive_max_ttl = max;
+        self
+    }
+
+    /// Clamps how long negative responses, i.e. names without records, are cached.
+    ///
+    /// Negative responses are cached for the TTL of the zone's `SOA` record, raised to `min`
+    /// and lowered to `max` if given.
+    pub fn with_negative_ttl(mut self, min: Option<Duration>, max: Option<Duration>) -> Self {
+        self.opts.negative_min_ttl = min;
+        self.opts.negative_max_ttl = max;
+        self
+    }
+
+    /// Returns the resolver configuration and options.
+    pub fn into_parts(self) -> (ResolverConfig, ResolverOpts) {
+        (self.resolver, self.opts)
+    }
+}
@@ -87,6 +87,12 @@ pub mod async_std {
             }
         }
 
+        /// Creates a [`Transport`] with the given [`Config`](crate::Config).
+        pub async fn with_config(inner: T, config: crate::Config) -> Transport<T> {
+            let (cfg, opts) = config.into_parts();
+            Self::custom(inner, cfg, opts).await
+        }
+
         // TODO: Replace `system` implementation with this
         #[doc(hidden)]
         pub fn system2(inner: T) -> Result<Transport<T>, io::Error> {
@@ -142,15 +148,23 @@ pub mod tokio {
                 resolver: TokioAsyncResolver::tokio(cfg, opts),
             }
         }
+
+        /// Creates a [`Transport`] with the given [`Config`](crate::Config).
+        pub fn with_config(inner: T, config: crate::Config) -> Transport<T> {
+            let (cfg, opts) = config.into_parts();
+            Self::custom(inner, cfg, opts)
+        }
     }
 }
 
+mod config;
+
 use async_trait::async_trait;
 use futures::{future::BoxFuture, prelude::*};
 use libp2p_core::{
     connection::Endpoint,
     multiaddr::{Multiaddr, Protocol},
-    transport::{ListenerId, TransportError, TransportEvent},
+    transport::{find_error, ListenerId, NameResolutionError, TransportError, TransportEvent},
 };
 use parking_lot::Mutex;
 use smallvec::SmallVec;
@@ -166,6 +180,7 @@ use std::{
     task::{Context, Poll},
 };
 
+pub use config::Config;
 pub use hickory_resolver::config::{ResolverConfig, ResolverOpts};
 pub use hickory_resolver::error::{ResolveError, ResolveErrorKind};
 use hickory_resolver::lookup::{Ipv4Lookup, Ipv6Lookup, TxtLookup};
@@ -401,7 +416,9 @@ where
             // for the given address to begin with (i.e. DNS lookups succeeded but
             // produced no records relevant for the given `addr`).
             Err(last_err.unwrap_or_else(|| {
-                Error::ResolveError(ResolveErrorKind::Message("No matching records found.").into())
+                Error::ResolveError(NameResolutionError::new(ResolveError::from(
+                    ResolveErrorKind::Message("No matching records found."),
+                )))
             }))
         }
         .boxed()
@@ -416,7 +433,9 @@ pub enum Error<TErr> {
     /// The underlying transport encountered an error.
     Transport(TErr),
     /// DNS resolution failed.
-    ResolveError(ResolveError),
+    ///
+    /// Carries the [`ResolveError`] of the resolver, see [`find_resolve_error`].
+    ResolveError(NameResolutionError),
     /// DNS resolution was successful, but the underlying transport refused the resolved address.
     MultiaddrNotSupported(Multiaddr),
     /// DNS resolution involved too many lookups.
@@ -435,7 +454,7 @@ where
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::Transport(err) => write!(f, "{err}"),
-            Error::ResolveError(err) => write!(f, "{err}"),
+            Error::ResolveError(_) => write!(f, "DNS resolution failed"),
             Error::MultiaddrNotSupported(a) => write!(f, "Unsupported resolved address: {a}"),
             Error::TooManyLookups => write!(f, "Too many DNS lookups"),
         }
@@ -456,6 +475,15 @@ where
     }
 }
 
+/// Searches the given error and its sources for a failed DNS resolution.
+///
+/// This allows telling DNS failures apart from other dialing errors once the
+/// [`Error`] of a [`Transport`] has been type-erased, e.g. in the `io::Error`s
+/// of a boxed transport reported by a `Swarm`.
+pub fn find_resolve_error<'a>(error: &'a (dyn error::Error + 'static)) -> Option<&'a ResolveError> {
+    find_error(error)
+}
+
 /// The successful outcome of [`resolve`] for a given [`Protocol`].
 enum Resolved<'a> {
     /// The given `Protocol` has been resolved to a single `Protocol`,
@@ -499,7 +527,7 @@ fn resolve<'a, E: 'a + Send, R: Resolver>(
                         Ok(Resolved::One(Protocol::from(one)))
                     }
                 }
-                Err(e) => Err(Error::ResolveError(e)),
+                Err(e) => Err(Error::ResolveError(NameResolutionError::new(e))),
             })
             .boxed(),
         Protocol::Dns4(ref name) => resolver
@@ -523,7 +551,7 @@ fn resolve<'a, E: 'a + Send, R: Resolver>(
                         Ok(Resolved::One(Protocol::from(Ipv4Addr::from(one))))
                     }
                 }
-                Err(e) => Err(Error::ResolveError(e)),
+                Err(e) => Err(Error::ResolveError(NameResolutionError::new(e))),
             })
             .boxed(),
         Protocol::Dns6(ref name) => resolver
@@ -547,7 +575,7 @@ fn resolve<'a, E: 'a + Send, R: Resolver>(
                         Ok(Resolved::One(Protocol::from(Ipv6Addr::from(one))))
                     }
                 }
-                Err(e) => Err(Error::ResolveError(e)),
+                Err(e) => Err(Error::ResolveError(NameResolutionError::new(e))),
             })
             .boxed(),
         Protocol::Dnsaddr(ref name) => {
@@ -572,7 +600,7 @@ fn resolve<'a, E: 'a + Send, R: Resolver>(
                         }
                         Ok(Resolved::Addrs(addrs))
                     }
-                    Err(e) => Err(Error::ResolveError(e)),
+                    Err(e) => Err(Error::ResolveError(NameResolutionError::new(e))),
                 })
                 .boxed()
         }
@@ -756,8 +784,8 @@ mod tests {
                 .unwrap()
                 .await
             {
-                Err(Error::ResolveError(e)) => match e.kind() {
-                    ResolveErrorKind::NoRecordsFound { .. } => {}
+                Err(Error::ResolveError(e)) => match e.downcast_ref().map(ResolveError::kind) {
+                    Some(ResolveErrorKind::NoRecordsFound { .. }) => {}
                     _ => panic!("Unexpected DNS error: {e:?}"),
                 },
                 Err(e) => panic!("Unexpected error: {e:?}"),
@@ -791,4 +819,219 @@ mod tests {
             rt.block_on(run(tokio::Transport::custom(CustomTransport, config, opts)));
         }
     }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn unresponsive_nameserver_times_out() {
+        use libp2p_core::transport::dummy::DummyTransport;
+        use libp2p_core::transport::upgrade::{UpgradeStepError, UpgradedTransportError};
+        use std::time::{Duration, Instant};
+
+        // A nameserver that accepts requests but never answers them.
+        let udp = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let nameserver = udp.local_addr().unwrap();
+        let _tcp = std::net::TcpListener::bind(nameserver).unwrap();
+
+        let config = Config::default()
+            .with_nameservers([nameserver])
+            .with_timeout(Duration::from_millis(200))
+            .with_attempts(0);
+
+        let rt = tokio_crate::runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let mut transport = tokio::Transport::with_config(DummyTransport::<()>::new(), config);
+
+            let started = Instant::now();
+            let error = transport
+                .dial("/dns4/example.com/tcp/20000".parse().unwrap())
+                .unwrap()
+                .await
+                .unwrap_err();
+            assert!(started.elapsed() < Duration::from_secs(5));
+
+            match &error {
+                Error::ResolveError(e) => match e.downcast_ref().map(ResolveError::kind) {
+                    Some(ResolveErrorKind::Timeout) => {}
+                    _ => panic!("Unexpected DNS error: {e:?}"),
+                },
+                e => panic!("Unexpected error: {e:?}"),
+            }
+
+            // As reported for a boxed transport upgraded on top of the DNS transport.
+            let boxed = std::io::Error::new(
+                std::io::ErrorKind::Other,
+                UpgradedTransportError::Transport(error),
+            );
+            assert!(find_resolve_error(&boxed).is_some());
+            assert!(UpgradeStepError::find(&boxed).is_none());
+            assert!(find_resolve_error(&std::io::Error::from(std::io::ErrorKind::Other)).is_none());
+        });
+    }
+
+    /// A nameserver answering every query for an `A` record with `127.0.0.1` and a TTL of 0, or
+    /// with `NXDOMAIN` and an SOA record with a TTL of 0 if `nxdomain` is set, counting the
+    /// queries it received.
+    #[cfg(feature = "tokio")]
+    fn nameserver(nxdomain: bool) -> (std::net::SocketAddr, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = socket.local_addr().unwrap();
+        let queries = Arc::new(AtomicUsize::new(0));
+
+        let counter = queries.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0; 512];
+            loop {
+                let Ok((len, peer)) = socket.recv_from(&mut buf) else {
+                    return;
+                };
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                // The header and question, skipping the additional records of the query.
+                let question_end = 12 + buf[12..len].iter().position(|b| *b == 0).unwrap() + 5;
+                let mut response = buf[..question_end].to_vec();
+                response[2..4].copy_from_slice(if nxdomain {
+                    &[0x81, 0x83]
+                } else {
+                    &[0x81, 0x80]
+                });
+                response[6..12].copy_from_slice(&[
+                    0,
+                    u8::from(!nxdomain),
+                    0,
+                    u8::from(nxdomain),
+                    0,
+                    0,
+                ]);
+                if nxdomain {
+                    // SOA record of the queried name with a TTL of 0, in the authority section.
+                    response.extend_from_slice(&[0xc0, 12, 0, 6, 0, 1, 0, 0, 0, 0, 0, 22, 0, 0]);
+                    response.extend_from_slice(&[0; 20]);
+                } else {
+                    // Pointer to the queried name, type A, class IN, TTL 0, 4 bytes of data.
+                    response.extend_from_slice(&[0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 0, 0, 4]);
+                    response.extend_from_slice(&[127, 0, 0, 1]);
+                }
+                let _ = socket.send_to(&response, peer);
+            }
+        });
+
+        (address, queries)
+    }
+
+    /// Looks up the `A` record of `example.com` twice via a resolver built from `config`,
+    /// returning the number of queries the nameserver received.
+    #[cfg(feature = "tokio")]
+    fn queries_for_two_lookups(config: Config, nxdomain: bool) -> usize {
+        let (address, queries) = nameserver(nxdomain);
+        let config = config.with_nameservers([address]);
+
+        let rt = tokio_crate::runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let transport = tokio::Transport::with_config((), config);
+            for _ in 0..2 {
+                let result = transport
+                    .resolver
+                    .ipv4_lookup("example.com.".to_owned())
+                    .await;
+                assert_eq!(result.is_err(), nxdomain);
+            }
+        });
+
+        queries.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn resolver_queries_configured_nameservers() {
+        assert_eq!(queries_for_two_lookups(Config::default(), false), 2);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn resolver_applies_positive_ttl() {
+        use std::time::Duration;
+
+        let config = Config::default().with_positive_ttl(Some(Duration::from_secs(60)), None);
+        assert_eq!(queries_for_two_lookups(config, false), 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn resolver_applies_negative_ttl() {
+        use std::time::Duration;
+
+        assert_eq!(queries_for_two_lookups(Config::default(), true), 2);
+
+        let config = Config::default().with_negative_ttl(Some(Duration::from_secs(60)), None);
+        assert_eq!(queries_for_two_lookups(config, true), 1);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn resolver_applies_cache_size() {
+        use std::time::Duration;
+
+        let config = Config::default()
+            .with_positive_ttl(Some(Duration::from_secs(60)), None)
+            .with_cache_size(0);
+        assert_eq!(queries_for_two_lookups(config, false), 2);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn resolver_applies_timeout_and_attempts() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        for attempts in [0, 2] {
+            // A nameserver that counts queries but never answers them.
+            let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            let nameserver = socket.local_addr().unwrap();
+            let queries = Arc::new(AtomicUsize::new(0));
+            let counter = queries.clone();
+            std::thread::spawn(move || {
+                let mut buf = [0; 512];
+                while socket.recv_from(&mut buf).is_ok() {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+
+            let config = Config::default()
+                .with_nameservers([nameserver])
+                .with_timeout(Duration::from_millis(100))
+                .with_attempts(attempts);
+
+            let rt = tokio_crate::runtime::Builder::new_current_thread()
+                .enable_io()
+                .enable_time()
+                .build()
+                .unwrap();
+
+            rt.block_on(async {
+                let transport = tokio::Transport::with_config((), config);
+                let error = transport
+                    .resolver
+                    .ipv4_lookup("example.com.".to_owned())
+                    .await
+                    .unwrap_err();
+                assert!(matches!(error.kind(), ResolveErrorKind::Timeout));
+            });
+
+            // Give the nameserver thread time to count the last query.
+            std::thread::sleep(Duration::from_millis(100));
+            assert_eq!(queries.load(Ordering::SeqCst), attempts + 1);
+        }
+    }
 }
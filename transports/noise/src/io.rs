@@ -22,6 +22,7 @@
 
 mod framed;
 pub(crate) mod handshake;
+use crate::protocol::PublicKey;
 use asynchronous_codec::Framed;
 use bytes::Bytes;
 use framed::{Codec, MAX_FRAME_LEN};
@@ -39,6 +40,7 @@ use std::{
 /// `T` is the type of the underlying I/O resource.
 pub struct Output<T> {
     io: Framed<T, Codec<snow::TransportState>>,
+    remote_static_key: PublicKey,
     recv_buffer: Bytes,
     recv_offset: usize,
     send_buffer: Vec<u8>,
@@ -52,15 +54,24 @@ impl<T> fmt::Debug for Output<T> {
 }
 
 impl<T> Output<T> {
-    fn new(io: Framed<T, Codec<snow::TransportState>>) -> Self {
+    fn new(io: Framed<T, Codec<snow::TransportState>>, remote_static_key: PublicKey) -> Self {
         Output {
             io,
+            remote_static_key,
             recv_buffer: Bytes::new(),
             recv_offset: 0,
             send_buffer: Vec::new(),
             send_offset: 0,
         }
     }
+
+    /// The static X25519 DH public key of the remote, as authenticated during the handshake.
+    ///
+    /// The remote signed this key with the identity key its [`PeerId`](libp2p_identity::PeerId)
+    /// is derived from.
+    pub fn remote_static_public_key(&self) -> &[u8] {
+        self.remote_static_key.as_ref()
+    }
 }
 
 impl<T: AsyncRead + Unpin> AsyncRead for Output<T> {
@@ -139,7 +139,7 @@ where
             }
         }
 
-        Ok((id_pk, Output::new(framed)))
+        Ok((id_pk, Output::new(framed, pubkey)))
     }
 }
 
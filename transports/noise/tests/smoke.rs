@@ -108,6 +108,37 @@ fn xx() {
         .quickcheck(prop as fn(Vec<Message>) -> bool)
 }
 
+#[test]
+fn remote_static_public_key_is_reported() {
+    let server = noise::Config::new(&identity::Keypair::generate_ed25519()).unwrap();
+
+    let handshake = |server: noise::Config| {
+        let (client_io, server_io) = futures_ringbuf::Endpoint::pair(100, 100);
+        let client = noise::Config::new(&identity::Keypair::generate_ed25519()).unwrap();
+
+        futures::executor::block_on(futures::future::try_join(
+            server.upgrade_inbound(server_io, ""),
+            client.upgrade_outbound(client_io, ""),
+        ))
+        .unwrap()
+    };
+
+    let ((_, server_session1), (_, client_session1)) = handshake(server.clone());
+    let ((_, server_session2), (_, client_session2)) = handshake(server);
+
+    // Both clients see the same static key of the server, ...
+    assert_eq!(client_session1.remote_static_public_key().len(), 32);
+    assert_eq!(
+        client_session1.remote_static_public_key(),
+        client_session2.remote_static_public_key()
+    );
+    // ... whereas the server sees a different key for each client.
+    assert_ne!(
+        server_session1.remote_static_public_key(),
+        server_session2.remote_static_public_key()
+    );
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Message(Vec<u8>);
 
@@ -57,6 +57,24 @@ pub struct Config {
     /// As client the version is chosen based on the remote's address.
     pub support_draft_29: bool,
 
+    /// Size in bytes of the buffer for incoming unreliable datagrams, or `None` to disable them.
+    ///
+    /// Datagrams are disabled by default. When enabled, datagrams can be sent via
+    /// [`StreamMuxer::send_datagram`](libp2p_core::muxing::StreamMuxer::send_datagram) and are
+    /// received as [`StreamMuxerEvent::DatagramReceived`](libp2p_core::muxing::StreamMuxerEvent::DatagramReceived),
+    /// provided that the remote enabled them as well.
+    pub datagram_receive_buffer_size: Option<usize>,
+
+    /// Whether to allow remotes to migrate connections to a new address, e.g. when switching from
+    /// WiFi to a cellular network.
+    ///
+    /// A migrated connection keeps its [`ConnectionId`](https://docs.rs/libp2p-swarm/latest/libp2p_swarm/struct.ConnectionId.html)
+    /// and streams, and the new address is reported as an address change of the connection within
+    /// a second of the migration.
+    ///
+    /// Disabled by default.
+    pub connection_migration: bool,
+
     /// TLS client config for the inner [`quinn::ClientConfig`].
     client_tls_config: Arc<rustls::ClientConfig>,
     /// TLS server config for the inner [`quinn::ServerConfig`].
@@ -77,6 +95,8 @@ impl Config {
             client_tls_config,
             server_tls_config,
             support_draft_29: false,
+            datagram_receive_buffer_size: None,
+            connection_migration: false,
             handshake_timeout: Duration::from_secs(5),
             max_idle_timeout: 30 * 1000,
             max_concurrent_stream_limit: 256,
@@ -116,6 +136,8 @@ impl From<Config> for QuinnConfig {
             max_connection_data,
             max_stream_data,
             support_draft_29,
+            datagram_receive_buffer_size,
+            connection_migration,
             handshake_timeout: _,
             keypair,
             mtu_discovery_config,
@@ -124,8 +146,7 @@ impl From<Config> for QuinnConfig {
         // Disable uni-directional streams.
         transport.max_concurrent_uni_streams(0u32.into());
         transport.max_concurrent_bidi_streams(max_concurrent_stream_limit.into());
-        // Disable datagrams.
-        transport.datagram_receive_buffer_size(None);
+        transport.datagram_receive_buffer_size(datagram_receive_buffer_size);
         transport.keep_alive_interval(Some(keep_alive_interval));
         transport.max_idle_timeout(Some(VarInt::from_u32(max_idle_timeout).into()));
         transport.allow_spin(false);
@@ -136,10 +157,7 @@ impl From<Config> for QuinnConfig {
 
         let mut server_config = quinn::ServerConfig::with_crypto(server_tls_config);
         server_config.transport = Arc::clone(&transport);
-        // Disables connection migration.
-        // Long-term this should be enabled, however we then need to handle address change
-        // on connections in the `Connection`.
-        server_config.migration(false);
+        server_config.migration(connection_migration);
 
         let mut client_config = quinn::ClientConfig::new(client_tls_config);
         client_config.transport_config(transport);
@@ -24,15 +24,27 @@ mod stream;
 pub use connecting::Connecting;
 pub use stream::Stream;
 
-use crate::{ConnectionError, Error};
+use crate::{
+    transport::{socketaddr_to_multiaddr, ProtocolVersion},
+    ConnectionError, Error,
+};
 
+use bytes::Bytes;
 use futures::{future::BoxFuture, FutureExt};
-use libp2p_core::muxing::{StreamMuxer, StreamMuxerEvent};
+use futures_timer::Delay;
+use libp2p_core::muxing::{SendDatagramError, StreamMuxer, StreamMuxerEvent};
 use std::{
+    io,
+    net::SocketAddr,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 
+/// Interval at which the address of the remote is checked for a change through connection
+/// migration.
+const ADDRESS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 /// State for a single opened QUIC connection.
 pub struct Connection {
     /// Underlying connection.
@@ -47,6 +59,14 @@ pub struct Connection {
     >,
     /// Future to wait for the connection to be closed.
     closing: Option<BoxFuture<'static, quinn::ConnectionError>>,
+    /// Future for receiving the next incoming datagram.
+    datagram: Option<BoxFuture<'static, Result<Bytes, quinn::ConnectionError>>>,
+    /// The last known address of the remote, to detect connection migration.
+    remote_address: SocketAddr,
+    /// Timer for the next check of the address of the remote.
+    address_check: Delay,
+    /// The QUIC version of the connection, for reporting the new address after a migration.
+    version: ProtocolVersion,
 }
 
 impl Connection {
@@ -54,12 +74,16 @@ impl Connection {
     ///
     /// This function assumes that the [`quinn::Connection`] is completely fresh and none of
     /// its methods has ever been called. Failure to comply might lead to logic errors and panics.
-    fn new(connection: quinn::Connection) -> Self {
+    fn new(connection: quinn::Connection, version: ProtocolVersion) -> Self {
         Self {
+            remote_address: connection.remote_address(),
+            address_check: Delay::new(ADDRESS_CHECK_INTERVAL),
             connection,
             incoming: None,
             outgoing: None,
             closing: None,
+            datagram: None,
+            version,
         }
     }
 }
@@ -104,11 +128,56 @@ impl StreamMuxer for Connection {
 
     fn poll(
         self: Pin<&mut Self>,
-        _cx: &mut Context<'_>,
+        cx: &mut Context<'_>,
     ) -> Poll<Result<StreamMuxerEvent, Self::Error>> {
-        // TODO: If connection migration is enabled (currently disabled) address
-        // change on the connection needs to be handled.
-        Poll::Pending
+        let this = self.get_mut();
+
+        // quinn does not notify about a migration, thus the address of the remote is checked
+        // periodically.
+        while this.address_check.poll_unpin(cx).is_ready() {
+            this.address_check.reset(ADDRESS_CHECK_INTERVAL);
+
+            let remote_address = this.connection.remote_address();
+            if remote_address != this.remote_address {
+                tracing::debug!(
+                    old=%this.remote_address,
+                    new=%remote_address,
+                    "Remote migrated QUIC connection",
+                );
+                this.remote_address = remote_address;
+
+                return Poll::Ready(Ok(StreamMuxerEvent::AddressChange(
+                    socketaddr_to_multiaddr(&remote_address, this.version),
+                )));
+            }
+        }
+
+        let datagram = this.datagram.get_or_insert_with(|| {
+            let connection = this.connection.clone();
+            async move { connection.read_datagram().await }.boxed()
+        });
+
+        let data = futures::ready!(datagram.poll_unpin(cx)).map_err(ConnectionError)?;
+        this.datagram.take();
+        Poll::Ready(Ok(StreamMuxerEvent::DatagramReceived(data)))
+    }
+
+    fn send_datagram(self: Pin<&mut Self>, data: Bytes) -> Result<(), SendDatagramError> {
+        self.connection.send_datagram(data).map_err(|e| match e {
+            quinn::SendDatagramError::UnsupportedByPeer | quinn::SendDatagramError::Disabled => {
+                SendDatagramError::Unsupported
+            }
+            quinn::SendDatagramError::TooLarge => SendDatagramError::TooLarge,
+            quinn::SendDatagramError::ConnectionLost(e) => {
+                SendDatagramError::Io(io::Error::new(io::ErrorKind::Other, ConnectionError(e)))
+            }
+        })
+    }
+
+    /// Returns `None` if datagrams are disabled locally or not supported by the remote, see
+    /// [`Config::datagram_receive_buffer_size`](crate::Config::datagram_receive_buffer_size).
+    fn max_datagram_size(&self) -> Option<usize> {
+        self.connection.max_datagram_size()
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
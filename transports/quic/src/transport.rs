@@ -298,7 +298,7 @@ impl<P: Provider> Transport for GenTransport<P> {
             let connecting = endpoint
                 .connect_with(client_config, socket_addr, "l")
                 .map_err(ConnectError)?;
-            Connecting::new(connecting, handshake_timeout).await
+            Connecting::new(connecting, version, handshake_timeout).await
         }))
     }
 
@@ -592,7 +592,7 @@ impl<P: Provider> Stream for Listener<P> {
                     let send_back_addr = socketaddr_to_multiaddr(&remote_addr, self.version);
 
                     let event = TransportEvent::Incoming {
-                        upgrade: Connecting::new(connecting, self.handshake_timeout),
+                        upgrade: Connecting::new(connecting, self.version, self.handshake_timeout),
                         local_addr,
                         send_back_addr,
                         listener_id: self.listener_id,
@@ -740,7 +740,10 @@ fn is_quic_addr(addr: &Multiaddr, support_draft_29: bool) -> bool {
 }
 
 /// Turns an IP address and port into the corresponding QUIC multiaddr.
-fn socketaddr_to_multiaddr(socket_addr: &SocketAddr, version: ProtocolVersion) -> Multiaddr {
+pub(crate) fn socketaddr_to_multiaddr(
+    socket_addr: &SocketAddr,
+    version: ProtocolVersion,
+) -> Multiaddr {
     let quic_proto = match version {
         ProtocolVersion::V1 => Protocol::QuicV1,
         ProtocolVersion::Draft29 => Protocol::Quic,
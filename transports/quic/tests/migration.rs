@@ -0,0 +1,134 @@
+// Copyright 2024 Protocol Labs.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a
+// copy of this software and associated documentation files (the "Software"),
+// to deal in the Software without restriction, including without limitation
+// the rights to use, copy, modify, merge, publish, distribute, sublicense,
+// and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
+// FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
+// DEALINGS IN THE SOFTWARE.
+
+use futures::StreamExt;
+use libp2p_core::{
+    multiaddr::Protocol, muxing::StreamMuxerBox, ConnectedPoint, Multiaddr, Transport,
+};
+use libp2p_identity::Keypair;
+use libp2p_quic as quic;
+use libp2p_swarm::{dummy, Config, Swarm, SwarmEvent};
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn migrated_connection_reports_address_change() {
+    let keypair = Keypair::generate_ed25519();
+    let mut config = quic::Config::new(&keypair);
+    config.connection_migration = true;
+    let mut swarm = new_swarm(&keypair, config);
+
+    swarm
+        .listen_on("/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap())
+        .unwrap();
+    let listen_address = loop {
+        if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
+            break address;
+        }
+    };
+
+    let client_keypair = Keypair::generate_ed25519();
+    let mut endpoint = quinn::Endpoint::client((Ipv4Addr::LOCALHOST, 0).into()).unwrap();
+    let client_tls = libp2p_tls::make_client_config(&client_keypair, None).unwrap();
+    let mut client_config = quinn::ClientConfig::new(Arc::new(client_tls));
+    let mut transport = quinn::TransportConfig::default();
+    transport.keep_alive_interval(Some(Duration::from_millis(100)));
+    client_config.transport_config(Arc::new(transport));
+    endpoint.set_default_client_config(client_config);
+
+    let connecting = endpoint.connect(socket_addr(&listen_address), "l").unwrap();
+    tokio::pin!(connecting);
+    let mut connection = None;
+    let mut established = None;
+    while connection.is_none() || established.is_none() {
+        tokio::select! {
+            result = &mut connecting, if connection.is_none() => {
+                connection = Some(result.unwrap());
+            }
+            event = swarm.select_next_some() => match event {
+                SwarmEvent::ConnectionEstablished {
+                    connection_id,
+                    endpoint: ConnectedPoint::Listener { send_back_addr, .. },
+                    ..
+                } => established = Some((connection_id, send_back_addr)),
+                SwarmEvent::IncomingConnection { .. } => {}
+                other => panic!("Unexpected event: {other:?}"),
+            },
+        }
+    }
+    let (_connection, (connection_id, first_address)) = (connection.unwrap(), established.unwrap());
+
+    let new_socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let new_address = new_socket.local_addr().unwrap();
+    endpoint.rebind(new_socket).unwrap();
+
+    // Only keep-alive packets are sent on the new path, none of which wake the connection on
+    // the listener side.
+
+    let (old, new) = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match swarm.select_next_some().await {
+                SwarmEvent::AddressChanged {
+                    connection_id: id,
+                    old,
+                    new,
+                    ..
+                } => {
+                    assert_eq!(id, connection_id);
+                    break (old, new);
+                }
+                other => panic!("Unexpected event: {other:?}"),
+            }
+        }
+    })
+    .await
+    .expect("Expected the migration to be reported");
+
+    assert_eq!(old.get_remote_address(), &first_address);
+    assert_eq!(
+        new.get_remote_address(),
+        &Multiaddr::empty()
+            .with(Protocol::Ip4(Ipv4Addr::LOCALHOST))
+            .with(Protocol::Udp(new_address.port()))
+            .with(Protocol::QuicV1)
+    );
+}
+
+fn new_swarm(keypair: &Keypair, config: quic::Config) -> Swarm<dummy::Behaviour> {
+    let peer_id = keypair.public().to_peer_id();
+    let transport = quic::tokio::Transport::new(config)
+        .map(|(peer_id, muxer), _| (peer_id, StreamMuxerBox::new(muxer)))
+        .boxed();
+
+    Swarm::new(
+        transport,
+        dummy::Behaviour,
+        peer_id,
+        Config::with_tokio_executor().with_idle_connection_timeout(Duration::from_secs(60)),
+    )
+}
+
+fn socket_addr(address: &Multiaddr) -> SocketAddr {
+    match address.iter().collect::<Vec<_>>()[..] {
+        [Protocol::Ip4(ip), Protocol::Udp(port), Protocol::QuicV1] => (ip, port).into(),
+        _ => panic!("Unexpected listen address: {address}"),
+    }
+}
@@ -6,7 +6,9 @@ use futures::future::{poll_fn, Either};
 use futures::stream::StreamExt;
 use futures::{future, AsyncReadExt, AsyncWriteExt, FutureExt, SinkExt};
 use futures_timer::Delay;
-use libp2p_core::muxing::{StreamMuxerBox, StreamMuxerExt, SubstreamBox};
+use libp2p_core::muxing::{
+    SendDatagramError, StreamMuxer, StreamMuxerBox, StreamMuxerEvent, StreamMuxerExt, SubstreamBox,
+};
 use libp2p_core::transport::{Boxed, OrTransport, TransportEvent};
 use libp2p_core::transport::{ListenerId, TransportError};
 use libp2p_core::{multiaddr::Protocol, upgrade, Multiaddr, Transport};
@@ -67,6 +69,66 @@ async fn endpoint_reuse() {
     assert_eq!(a_send_back_addr, a_addr);
 }
 
+#[cfg(feature = "async-std")]
+#[async_std::test]
+async fn datagrams() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .try_init();
+    let new_transport = || {
+        let mut config = quic::Config::new(&generate_tls_keypair());
+        config.datagram_receive_buffer_size = Some(64 * 1024);
+        quic::async_std::Transport::new(config).boxed()
+    };
+    let mut a_transport = new_transport();
+    let mut b_transport = new_transport();
+
+    a_transport
+        .listen_on(
+            ListenerId::next(),
+            "/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap(),
+        )
+        .unwrap();
+    let a_addr = match a_transport.select_next_some().await {
+        TransportEvent::NewAddress { listen_addr, .. } => listen_addr,
+        e => panic!("{e:?}"),
+    };
+
+    let ((_, mut a_connection), (_, mut b_connection)) = future::join(
+        async {
+            let (upgrade, _) = a_transport
+                .select_next_some()
+                .await
+                .into_incoming()
+                .unwrap();
+            upgrade.await.unwrap()
+        },
+        async {
+            match future::select(b_transport.dial(a_addr).unwrap(), b_transport.next()).await {
+                Either::Left((conn, _)) => conn.unwrap(),
+                Either::Right((event, _)) => panic!("Unexpected event: {event:?}"),
+            }
+        },
+    )
+    .await;
+
+    let max_datagram_size = b_connection.max_datagram_size().unwrap();
+    assert!(matches!(
+        b_connection.send_datagram_unpin(vec![0; max_datagram_size + 1].into()),
+        Err(SendDatagramError::TooLarge)
+    ));
+
+    b_connection
+        .send_datagram_unpin(bytes::Bytes::from_static(b"hello"))
+        .unwrap();
+    let received = poll_fn(|cx| a_connection.poll_unpin(cx)).await.unwrap();
+
+    assert!(matches!(
+        received,
+        StreamMuxerEvent::DatagramReceived(data) if data == b"hello"[..]
+    ));
+}
+
 #[cfg(feature = "async-std")]
 #[async_std::test]
 async fn ipv4_dial_ipv6() {
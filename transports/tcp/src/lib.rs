@@ -71,6 +71,12 @@ pub struct Config {
     backlog: u32,
     /// Whether port reuse should be enabled.
     enable_port_reuse: bool,
+    /// Whether `SO_REUSEPORT` should be set for opened sockets.
+    reuse_port: bool,
+    /// `SO_RCVBUF` to set for opened sockets, or `None` to keep default.
+    recv_buffer_size: Option<usize>,
+    /// `SO_SNDBUF` to set for opened sockets, or `None` to keep default.
+    send_buffer_size: Option<usize>,
 }
 
 type Port = u16;
@@ -162,12 +168,19 @@ impl Config {
     ///     See [`Config::ttl`].
     ///   * The size of the listen backlog for new listening sockets is `1024`.
     ///     See [`Config::listen_backlog`].
+    ///   * `SO_REUSEPORT` is not set, unless port reuse is enabled.
+    ///     See [`Config::reuse_port`].
+    ///   * No custom `SO_RCVBUF` and `SO_SNDBUF` is set. The defaults of the OS TCP stack apply.
+    ///     See [`Config::recv_buffer_size`] and [`Config::send_buffer_size`].
     pub fn new() -> Self {
         Self {
             ttl: None,
             nodelay: None,
             backlog: 1024,
             enable_port_reuse: false,
+            reuse_port: false,
+            recv_buffer_size: None,
+            send_buffer_size: None,
         }
     }
 
@@ -189,6 +202,31 @@ impl Config {
         self
     }
 
+    /// Configures the `SO_REUSEPORT` option for new sockets on unix systems.
+    ///
+    /// This allows multiple listening sockets, e.g. of several [`Transport`]s each driven by
+    /// its own accept loop, to bind the same address and port. The OS then distributes incoming
+    /// connections among them.
+    ///
+    /// Unlike [`Config::port_reuse`], this does not reuse listening ports for outgoing
+    /// connections. The option has no effect on non-unix systems.
+    pub fn reuse_port(mut self, value: bool) -> Self {
+        self.reuse_port = value;
+        self
+    }
+
+    /// Configures the `SO_RCVBUF` option, i.e. the size of the receive buffer, for new sockets.
+    pub fn recv_buffer_size(mut self, size: usize) -> Self {
+        self.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Configures the `SO_SNDBUF` option, i.e. the size of the send buffer, for new sockets.
+    pub fn send_buffer_size(mut self, size: usize) -> Self {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
     /// Configures port reuse for local sockets, which implies
     /// reuse of listening ports for outgoing connections to
     /// enhance NAT traversal capabilities.
@@ -361,9 +399,15 @@ where
         if let Some(nodelay) = self.config.nodelay {
             socket.set_nodelay(nodelay)?;
         }
+        if let Some(size) = self.config.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        if let Some(size) = self.config.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
         socket.set_reuse_address(true)?;
         #[cfg(unix)]
-        if let PortReuse::Enabled { .. } = &self.port_reuse {
+        if self.config.reuse_port || matches!(self.port_reuse, PortReuse::Enabled { .. }) {
             socket.set_reuse_port(true)?;
         }
         Ok(socket)
@@ -1206,6 +1250,62 @@ mod tests {
         test("/ip4/127.0.0.1/tcp/0".parse().unwrap());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn reuse_port_listening() {
+        let _ = tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .try_init();
+
+        async fn listen<T: Provider>(tcp: &mut Transport<T>, addr: Multiaddr) -> Multiaddr {
+            tcp.listen_on(ListenerId::next(), addr).unwrap();
+            match poll_fn(|cx| Pin::new(&mut *tcp).poll(cx)).await {
+                TransportEvent::NewAddress { listen_addr, .. } => listen_addr,
+                e => panic!("Unexpected transport event: {e:?}"),
+            }
+        }
+
+        async fn listen_twice<T: Provider>(addr: Multiaddr) {
+            let config = Config::new()
+                .reuse_port(true)
+                .recv_buffer_size(64 * 1024)
+                .send_buffer_size(64 * 1024);
+            let mut tcp1 = Transport::<T>::new(config.clone());
+            let mut tcp2 = Transport::<T>::new(config);
+
+            let addr1 = listen(&mut tcp1, addr).await;
+            let addr2 = listen(&mut tcp2, addr1.clone()).await;
+            assert_eq!(addr1, addr2);
+
+            // Listening ports are not reused for dialing.
+            let listener1 = tcp1.listeners.iter().next().unwrap();
+            assert!(tcp1
+                .port_reuse
+                .local_dial_addr(&listener1.listen_addr.ip())
+                .is_none());
+        }
+
+        fn test(addr: Multiaddr) {
+            #[cfg(feature = "async-io")]
+            {
+                let listener = listen_twice::<async_io::Tcp>(addr.clone());
+                async_std::task::block_on(listener);
+            }
+
+            #[cfg(feature = "tokio")]
+            {
+                let listener = listen_twice::<tokio::Tcp>(addr);
+                let rt = ::tokio::runtime::Builder::new_current_thread()
+                    .enable_io()
+                    .build()
+                    .unwrap();
+                rt.block_on(listener);
+            }
+        }
+
+        test("/ip4/127.0.0.1/tcp/0".parse().unwrap());
+    }
+
     #[test]
     fn listen_port_0() {
         let _ = tracing_subscriber::fmt()
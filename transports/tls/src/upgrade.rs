@@ -44,10 +44,13 @@ pub enum UpgradeError {
     BadCertificate(#[from] certificate::ParseError),
 }
 
+type PeerCertVerifier = Arc<dyn Fn(&PeerId, &rustls::Certificate) -> bool + Send + Sync>;
+
 #[derive(Clone)]
 pub struct Config {
     server: rustls::ServerConfig,
     client: rustls::ClientConfig,
+    peer_cert_verifier: Option<PeerCertVerifier>,
 }
 
 impl Config {
@@ -55,8 +58,24 @@ impl Config {
         Ok(Self {
             server: crate::make_server_config(identity)?,
             client: crate::make_client_config(identity, None)?,
+            peer_cert_verifier: None,
         })
     }
+
+    /// Additionally verify the certificate of every remote peer with the given function, e.g.
+    /// to only accept peers whose certificate is part of a pinned set.
+    ///
+    /// The function is called with the peer ID and the DER-encoded certificate of the remote
+    /// once its certificate passed the verification required by the libp2p TLS spec. The
+    /// handshake fails if the function returns `false`, both for inbound and outbound
+    /// connections.
+    pub fn with_peer_cert_verifier(
+        mut self,
+        verifier: impl Fn(&PeerId, &rustls::Certificate) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.peer_cert_verifier = Some(Arc::new(verifier));
+        self
+    }
 }
 
 impl UpgradeInfo for Config {
@@ -84,6 +103,12 @@ where
                 .map_err(UpgradeError::ServerUpgrade)?;
 
             let peer_id = extract_single_certificate(stream.get_ref().1)?.peer_id();
+            verify_peer_cert(
+                self.peer_cert_verifier.as_ref(),
+                &peer_id,
+                stream.get_ref().1,
+            )
+            .map_err(UpgradeError::ServerUpgrade)?;
 
             Ok((peer_id, stream.into()))
         }
@@ -111,6 +136,12 @@ where
                 .map_err(UpgradeError::ClientUpgrade)?;
 
             let peer_id = extract_single_certificate(stream.get_ref().1)?.peer_id();
+            verify_peer_cert(
+                self.peer_cert_verifier.as_ref(),
+                &peer_id,
+                stream.get_ref().1,
+            )
+            .map_err(UpgradeError::ClientUpgrade)?;
 
             Ok((peer_id, stream.into()))
         }
@@ -118,6 +149,28 @@ where
     }
 }
 
+fn verify_peer_cert(
+    verifier: Option<&PeerCertVerifier>,
+    peer_id: &PeerId,
+    state: &CommonState,
+) -> Result<(), std::io::Error> {
+    let Some(verifier) = verifier else {
+        return Ok(());
+    };
+    let Some([cert]) = state.peer_certificates() else {
+        panic!("config enforces exactly one certificate");
+    };
+
+    if !verifier(peer_id, cert) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("certificate of peer {peer_id} was rejected"),
+        ));
+    }
+
+    Ok(())
+}
+
 fn extract_single_certificate(
     state: &CommonState,
 ) -> Result<P2pCertificate<'_>, certificate::ParseError> {
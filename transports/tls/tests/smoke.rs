@@ -4,6 +4,7 @@ use libp2p_core::transport::MemoryTransport;
 use libp2p_core::upgrade::Version;
 use libp2p_core::Transport;
 use libp2p_swarm::{dummy, Config, Swarm, SwarmEvent};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 #[tokio::test]
@@ -56,12 +57,94 @@ async fn can_establish_connection() {
     assert_eq!(&outbound_peer_id, swarm1.local_peer_id());
 }
 
+#[tokio::test]
+async fn listener_verifier_rejects_peer() {
+    rejecting_verifier_fails_upgrade(true).await
+}
+
+#[tokio::test]
+async fn dialer_verifier_rejects_peer() {
+    rejecting_verifier_fails_upgrade(false).await
+}
+
+/// Rejects every peer with the verifier of either the listener or the dialer.
+async fn rejecting_verifier_fails_upgrade(reject_on_listener: bool) {
+    let verified = Arc::new(Mutex::new(Vec::new()));
+    let rejecting = {
+        let verified = verified.clone();
+        move |config: libp2p_tls::Config| {
+            config.with_peer_cert_verifier(move |peer_id, _| {
+                verified.lock().unwrap().push(*peer_id);
+                false
+            })
+        }
+    };
+    let (mut swarm1, mut swarm2) = if reject_on_listener {
+        (make_swarm_with_config(rejecting), make_swarm())
+    } else {
+        (make_swarm(), make_swarm_with_config(rejecting))
+    };
+
+    let listen_address = {
+        let expected_listener_id = swarm1.listen_on(Protocol::Memory(0).into()).unwrap();
+
+        loop {
+            match swarm1.next().await.unwrap() {
+                SwarmEvent::NewListenAddr {
+                    address,
+                    listener_id,
+                } if listener_id == expected_listener_id => break address,
+                _ => continue,
+            };
+        }
+    };
+    swarm2.dial(listen_address).unwrap();
+
+    let await_inbound_failure = async {
+        loop {
+            match swarm1.next().await.unwrap() {
+                SwarmEvent::ConnectionEstablished { .. } => {
+                    panic!("Rejected upgrade should not establish a connection")
+                }
+                SwarmEvent::IncomingConnectionError { .. } => break,
+                _ => continue,
+            };
+        }
+    };
+    let await_outbound_failure = async {
+        loop {
+            match swarm2.next().await.unwrap() {
+                SwarmEvent::ConnectionEstablished { .. } => {
+                    panic!("Rejected upgrade should not establish a connection")
+                }
+                SwarmEvent::OutgoingConnectionError { .. } => break,
+                _ => continue,
+            };
+        }
+    };
+
+    future::join(await_inbound_failure, await_outbound_failure).await;
+
+    let remote = if reject_on_listener {
+        *swarm2.local_peer_id()
+    } else {
+        *swarm1.local_peer_id()
+    };
+    assert_eq!(*verified.lock().unwrap(), [remote]);
+}
+
 fn make_swarm() -> Swarm<dummy::Behaviour> {
+    make_swarm_with_config(|config| config)
+}
+
+fn make_swarm_with_config(
+    configure: impl FnOnce(libp2p_tls::Config) -> libp2p_tls::Config,
+) -> Swarm<dummy::Behaviour> {
     let identity = libp2p_identity::Keypair::generate_ed25519();
 
     let transport = MemoryTransport::default()
         .upgrade(Version::V1)
-        .authenticate(libp2p_tls::Config::new(&identity).unwrap())
+        .authenticate(configure(libp2p_tls::Config::new(&identity).unwrap()))
         .multiplex(libp2p_yamux::Config::default())
         .boxed();
 
@@ -119,7 +119,7 @@ macro_rules! codegen {
                                             Ok((stream, _)) => {
                                                 tracing::debug!(address=%addr, "incoming connection on address");
                                                 TransportEvent::Incoming {
-                                                    upgrade: future::ok(stream),
+                                                    upgrade: future::ok(<$unix_stream>::from(stream)),
                                                     local_addr: addr.clone(),
                                                     send_back_addr: addr.clone(),
                                                     listener_id: id,
@@ -222,16 +222,84 @@ codegen!(
     "async-std",
     UdsConfig,
     |addr| async move { async_std::os::unix::net::UnixListener::bind(&addr).await },
-    async_std::os::unix::net::UnixStream,
+    UnixStream,
 );
 #[cfg(feature = "tokio")]
 codegen!(
     "tokio",
     TokioUdsConfig,
     |addr| async move { tokio::net::UnixListener::bind(&addr) },
-    tokio::net::UnixStream,
+    TokioUnixStream,
 );
 
+/// The connection type of [`UdsConfig`].
+#[cfg(feature = "async-std")]
+pub type UnixStream = async_std::os::unix::net::UnixStream;
+
+/// A [`tokio::net::UnixStream`] implementing the [`AsyncRead`] and [`AsyncWrite`] traits of
+/// `futures`, as required by the libp2p upgrades.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct TokioUnixStream(pub tokio::net::UnixStream);
+
+#[cfg(feature = "tokio")]
+impl TokioUnixStream {
+    async fn connect(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        tokio::net::UnixStream::connect(path).await.map(Self)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl From<tokio::net::UnixStream> for TokioUnixStream {
+    fn from(stream: tokio::net::UnixStream) -> Self {
+        Self(stream)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncRead for TokioUnixStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut read_buf = tokio::io::ReadBuf::new(buf);
+        futures::ready!(tokio::io::AsyncRead::poll_read(
+            Pin::new(&mut self.0),
+            cx,
+            &mut read_buf
+        ))?;
+        Poll::Ready(Ok(read_buf.filled().len()))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncWrite for TokioUnixStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        tokio::io::AsyncWrite::poll_write(Pin::new(&mut self.0), cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        tokio::io::AsyncWrite::poll_flush(Pin::new(&mut self.0), cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        tokio::io::AsyncWrite::poll_shutdown(Pin::new(&mut self.0), cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        tokio::io::AsyncWrite::poll_write_vectored(Pin::new(&mut self.0), cx, bufs)
+    }
+}
+
 /// Turns a `Multiaddr` containing a single `Unix` component into a path.
 ///
 /// Also returns an error if the path is not absolute, as we don't want to dial/listen on relative